@@ -272,7 +272,7 @@ fn setup_after_reload_game_log(
 
     for evt in &tick_zero.simulation_events {
         match evt {
-            SimulationEvent::ArenaObjectPositionUpdate { object_type_id, position, scale, rotation } => {
+            SimulationEvent::ArenaObjectPositionUpdate { feature_index: _, object_type_id, position, scale, rotation } => {
                 let z_index = match object_type_id {
                     1 => -10.0,
                     2 => -9.0,
@@ -25,6 +25,9 @@ pub fn satisfiable_impl(input: TokenStream) -> TokenStream {
     let mut builder_struct_accessors_from_parent = vec![];
 
     let cloneable_test_trait_ident = format_ident!("{}SatisfiabilityTest", concrete_ident);
+    let test_kind_ident = format_ident!("{}TestKind", concrete_ident);
+    let mut test_kind_variants = vec![];
+    let mut test_kind_to_tests = vec![];
 
     for variant in &mut variants {
         let variant_ident = &variant.ident;
@@ -56,7 +59,7 @@ pub fn satisfiable_impl(input: TokenStream) -> TokenStream {
         };
 
         builder_structs.push(quote! {
-            #[derive(Clone, Debug, Default)]
+            #[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
             pub struct #builder_struct_name {
                 #(#builder_struct_fields),*
             }
@@ -82,9 +85,16 @@ pub fn satisfiable_impl(input: TokenStream) -> TokenStream {
                 }
             }
 
-            impl #cloneable_test_trait_ident for #builder_struct_name {}
+            impl #cloneable_test_trait_ident for #builder_struct_name {
+                fn test_kind(&self) -> #test_kind_ident {
+                    #test_kind_ident::#variant_ident(self.clone())
+                }
+            }
         });
 
+        test_kind_variants.push(quote! { #variant_ident(#builder_struct_name) });
+        test_kind_to_tests.push(quote! { #test_kind_ident::#variant_ident(test) => Self::new(test) });
+
         builder_struct_accessors_from_parent.push(quote! {
            pub fn #variant_ident() -> #builder_struct_name { #builder_struct_name::default() }
         });
@@ -96,10 +106,18 @@ pub fn satisfiable_impl(input: TokenStream) -> TokenStream {
     let generated = quote! {
         use dyn_clone::DynClone;
 
-        pub trait #cloneable_test_trait_ident: DynClone + std::fmt::Debug + SatisfiabilityTest<ConcreteT=#concrete_ident> {}
+        pub trait #cloneable_test_trait_ident: DynClone + std::fmt::Debug + SatisfiabilityTest<ConcreteT=#concrete_ident> {
+            /// The test as one of the tests for each variant, which unlike a boxed test can be serialized.
+            fn test_kind(&self) -> #test_kind_ident;
+        }
 
         dyn_clone::clone_trait_object!(#cloneable_test_trait_ident);
 
+        #[derive(serde::Serialize, serde::Deserialize)]
+        pub enum #test_kind_ident {
+            #(#test_kind_variants),*
+        }
+
         #[derive(Clone, Debug)]
         pub struct #tester_struct_ident(Box<dyn #cloneable_test_trait_ident>);
         impl #tester_struct_ident {
@@ -116,6 +134,20 @@ pub fn satisfiable_impl(input: TokenStream) -> TokenStream {
             }
         }
 
+        impl serde::Serialize for #tester_struct_ident {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.0.test_kind(), serializer)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for #tester_struct_ident {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+                Ok(match <#test_kind_ident as serde::Deserialize>::deserialize(deserializer)? {
+                    #(#test_kind_to_tests),*
+                })
+            }
+        }
+
         impl SatisfiabilityTest for #tester_struct_ident {
             type ConcreteT = #concrete_ident;
 
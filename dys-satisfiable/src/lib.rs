@@ -1,13 +1,56 @@
+use std::cmp::Ordering;
 use std::fmt::{Debug, Formatter, Result};
 use std::rc::Rc;
+use serde::{Deserialize, Serialize};
 pub use dyn_clone;
 pub use ahash;
 
+/// A value that can be tested by a [`SatisfiableField`].
+/// Every value can be tested for equality. Values may also be ordered, or measured by how far apart they are,
+/// which allows for range and distance tests. Values that can't be ordered or measured never pass those tests.
+pub trait SatisfiableValue: Clone + PartialEq {
+    /// How this value orders against another, if values of this type have an order.
+    fn compare_to(&self, _other: &Self) -> Option<Ordering> {
+        None
+    }
+
+    /// How far this value is from another, if values of this type have a distance between them.
+    fn distance_to(&self, _other: &Self) -> Option<f32> {
+        None
+    }
+}
+
+macro_rules! ordered_satisfiable_value {
+    ($($value_type:ty),*) => {
+        $(
+            impl SatisfiableValue for $value_type {
+                fn compare_to(&self, other: &Self) -> Option<Ordering> {
+                    self.partial_cmp(other)
+                }
+            }
+        )*
+    };
+}
+
+ordered_satisfiable_value!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
+
+impl SatisfiableValue for bool {}
+impl SatisfiableValue for char {}
+impl SatisfiableValue for String {}
+impl<T: SatisfiableValue> SatisfiableValue for Option<T> {}
+
+impl SatisfiableValue for glam::Vec3 {
+    fn distance_to(&self, other: &Self) -> Option<f32> {
+        Some((*self - *other).length())
+    }
+}
+
 /// A `SatisfiableField` represents an abstract test that will be performed on a concrete value.
 ///
-#[derive(Clone, Default)]
+/// Tests can be serialized, except for lambdas.
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub enum SatisfiableField<
-    ConcreteT: Clone + PartialEq
+    ConcreteT: SatisfiableValue
 > {
     /// The concrete value may have any value for this field.
     /// Ignored fields will always pass satisfiability tests.
@@ -73,6 +116,43 @@ pub enum SatisfiableField<
     /// ```
     NotIn(Vec<ConcreteT>),
 
+    /// The concrete value must be less than the value of type `ConcreteT`
+    /// ```
+    /// # use dys_satisfiable::SatisfiableField;
+    /// let less_than_three = SatisfiableField::LessThan(3.0f32);
+    /// assert_eq!(true, less_than_three.satisfied_by(&2.5f32));
+    /// assert_eq!(false, less_than_three.satisfied_by(&3.0f32));
+    /// ```
+    LessThan(ConcreteT),
+
+    /// The concrete value must be at least the value of type `ConcreteT`
+    /// ```
+    /// # use dys_satisfiable::SatisfiableField;
+    /// let at_least_three = SatisfiableField::AtLeast(3u32);
+    /// assert_eq!(false, at_least_three.satisfied_by(&2u32));
+    /// assert_eq!(true, at_least_three.satisfied_by(&3u32));
+    /// assert_eq!(true, at_least_three.satisfied_by(&4u32));
+    /// ```
+    AtLeast(ConcreteT),
+
+    /// The concrete value must be closer than a distance to the value of type `ConcreteT`
+    /// ```
+    /// # use dys_satisfiable::SatisfiableField;
+    /// let near_origin = SatisfiableField::CloserThan(glam::Vec3::ZERO, 5.0);
+    /// assert_eq!(true, near_origin.satisfied_by(&glam::Vec3::new(3.0, 0.0, 0.0)));
+    /// assert_eq!(false, near_origin.satisfied_by(&glam::Vec3::new(0.0, 5.0, 0.0)));
+    /// ```
+    CloserThan(ConcreteT, f32),
+
+    /// The concrete value must be at least a distance from the value of type `ConcreteT`
+    /// ```
+    /// # use dys_satisfiable::SatisfiableField;
+    /// let away_from_origin = SatisfiableField::NotCloserThan(glam::Vec3::ZERO, 5.0);
+    /// assert_eq!(false, away_from_origin.satisfied_by(&glam::Vec3::new(3.0, 0.0, 0.0)));
+    /// assert_eq!(true, away_from_origin.satisfied_by(&glam::Vec3::new(0.0, 5.0, 0.0)));
+    /// ```
+    NotCloserThan(ConcreteT, f32),
+
     /// The concrete value must pass a provided lambda.
     /// See [`SatisfiableField::lambda_from`] for a convenience method of constructing a Lambda.
     /// ```
@@ -87,10 +167,13 @@ pub enum SatisfiableField<
     /// assert_eq!(false, is_power_of_two.satisfied_by(&5u32));
     /// assert_eq!(true, is_power_of_two.satisfied_by(&8u32));
     /// ```
+    ///
+    /// Lambdas can't be serialized, so prefer the other tests where possible.
+    #[serde(skip)]
     Lambda(Rc<dyn Fn(ConcreteT) -> bool>)
 }
 
-impl<ConcreteT: Clone + PartialEq + Debug> Debug for SatisfiableField<ConcreteT> {
+impl<ConcreteT: SatisfiableValue + Debug> Debug for SatisfiableField<ConcreteT> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match self {
             SatisfiableField::Lambda(_) => write!(f, "<lambda fn>"),
@@ -99,12 +182,16 @@ impl<ConcreteT: Clone + PartialEq + Debug> Debug for SatisfiableField<ConcreteT>
             SatisfiableField::NotExactly(val) => write!(f, "NotExactly({val:?})"),
             SatisfiableField::In(vals) => write!(f, "In({vals:?})"),
             SatisfiableField::NotIn(vals) => write!(f, "NotIn({vals:?})"),
+            SatisfiableField::LessThan(val) => write!(f, "LessThan({val:?})"),
+            SatisfiableField::AtLeast(val) => write!(f, "AtLeast({val:?})"),
+            SatisfiableField::CloserThan(val, distance) => write!(f, "CloserThan({val:?}, {distance})"),
+            SatisfiableField::NotCloserThan(val, distance) => write!(f, "NotCloserThan({val:?}, {distance})"),
         }
     }
 }
 
 impl<
-    ConcreteT: Clone + PartialEq,
+    ConcreteT: SatisfiableValue,
 > SatisfiableField<ConcreteT> {
     pub fn lambda_from(lambda_fn: impl Fn(ConcreteT) -> bool + 'static) -> Self {
         SatisfiableField::Lambda(Rc::new(lambda_fn))
@@ -117,6 +204,10 @@ impl<
             SatisfiableField::NotExactly(self_val) => self_val != value,
             SatisfiableField::In(self_iter) => self_iter.contains(value),
             SatisfiableField::NotIn(self_iter) => !self_iter.contains(value),
+            SatisfiableField::LessThan(self_val) => value.compare_to(self_val) == Some(Ordering::Less),
+            SatisfiableField::AtLeast(self_val) => matches!(value.compare_to(self_val), Some(Ordering::Greater | Ordering::Equal)),
+            SatisfiableField::CloserThan(self_val, distance) => value.distance_to(self_val).is_some_and(|value_distance| value_distance < *distance),
+            SatisfiableField::NotCloserThan(self_val, distance) => value.distance_to(self_val).is_some_and(|value_distance| value_distance >= *distance),
             SatisfiableField::Lambda(lambda_fn) => lambda_fn(value.to_owned())
         }
     }
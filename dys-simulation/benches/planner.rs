@@ -0,0 +1,57 @@
+use std::sync::{Arc, Mutex};
+use criterion::{criterion_group, criterion_main, Criterion};
+use dys_simulation::planning::{plan_with_heuristic, CheapestSatisfierHeuristic, ZeroHeuristic};
+use dys_simulation::game::Game;
+use dys_simulation::game_state::GameState;
+use dys_simulation::simulation::simulate_tick;
+use dys_world::{schedule::{calendar::{Date, Month}}, generator::Generator};
+use dys_world::arena::Arena;
+use dys_world::games::instance::GameInstance;
+
+// Enough ticks for combatants to have sensed their surroundings and started moving
+const WARMUP_TICKS: u32 = 20;
+
+#[allow(clippy::arc_with_non_send_sync)]
+fn planner_benchmark(c: &mut Criterion) {
+    let world = Generator::new().generate_world(&mut rand::rng());
+    let mut teams = world.teams.values();
+    let game = Game {
+        game_instance: GameInstance {
+            game_id: 0,
+            away_team: teams.next().unwrap().clone(),
+            home_team: teams.next().unwrap().clone(),
+            arena: Arc::new(Mutex::new(Arena::new_with_testing_defaults())),
+            arena_id: 0,
+            date: Date::new(Month::Arguscorp, 1, 10000),
+        },
+    };
+
+    let game_state = Arc::new(Mutex::new(GameState::from_game_seeded(game, &[0; 32])));
+    for _ in 0..WARMUP_TICKS {
+        simulate_tick(game_state.clone());
+    }
+
+    let combatants = game_state.lock().unwrap().combatants.values().cloned().collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("planner");
+    for combatant in &combatants {
+        group.bench_function(format!("plan_combatant_{}", combatant.id), |b| b.iter(|| {
+            plan_with_heuristic(combatant, game_state.clone(), &CheapestSatisfierHeuristic)
+        }));
+    }
+
+    group.bench_function("plan_all_combatants_zero_heuristic", |b| b.iter(|| {
+        for combatant in &combatants {
+            plan_with_heuristic(combatant, game_state.clone(), &ZeroHeuristic);
+        }
+    }));
+    group.bench_function("plan_all_combatants_cheapest_satisfier_heuristic", |b| b.iter(|| {
+        for combatant in &combatants {
+            plan_with_heuristic(combatant, game_state.clone(), &CheapestSatisfierHeuristic);
+        }
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, planner_benchmark);
+criterion_main!(benches);
@@ -1,6 +1,8 @@
+use std::sync::{Arc, Mutex};
 use criterion::{criterion_group, criterion_main, Criterion};
 use dys_simulation::game::Game;
 use dys_world::{schedule::{calendar::{Date, Month}}, generator::Generator};
+use dys_world::arena::Arena;
 use dys_world::games::instance::GameInstance;
 
 fn game_simulation_benchmark(c: &mut Criterion) {
@@ -16,7 +18,7 @@ fn game_simulation_benchmark(c: &mut Criterion) {
             game_id: 0,
             away_team: teams.next().unwrap().clone(),
             home_team: teams.next().unwrap().clone(),
-            // arena: Arc::new(Mutex::new(Arena::new_with_testing_defaults())),
+            arena: Arc::new(Mutex::new(Arena::new_with_testing_defaults())),
             arena_id: 0,
             date: Date::new(Month::Arguscorp, 1, 10000),
         },
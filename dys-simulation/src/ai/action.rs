@@ -1,6 +1,7 @@
 use std::fmt::Debug;
 use std::sync::{Arc, Mutex};
 use dys_satisfiable::SatisfiabilityTest;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use crate::ai::beliefs::belief_set::BeliefSet;
 use crate::game_state::GameState;
 use crate::simulation::simulation_event::PendingSimulationEvent;
@@ -8,11 +9,11 @@ use crate::simulation::simulation_event::PendingSimulationEvent;
 use super::agent::Agent;
 use super::belief::{Belief, BeliefSatisfiabilityTest, BeliefTest};
 use super::strategies::noop::NoopStrategy;
-use super::strategy::Strategy;
+use super::strategy::{Strategy, StrategySnapshot};
 
 pub type StrategyT = Arc<Mutex<dyn Strategy>>;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Action {
     /// Name of the action
     name: String,
@@ -21,6 +22,7 @@ pub struct Action {
     /// Arbitrary float value
     cost: f32,
 
+    #[serde(serialize_with = "serialize_strategy", deserialize_with = "deserialize_strategy")]
     strategy: StrategyT,
 
     /// Belief tests required for the action to be taken
@@ -43,6 +45,14 @@ pub struct Action {
     consumed_beliefs: Vec<BeliefTest>,
 }
 
+fn serialize_strategy<S: Serializer>(strategy: &StrategyT, serializer: S) -> Result<S::Ok, S::Error> {
+    strategy.lock().unwrap().snapshot().serialize(serializer)
+}
+
+fn deserialize_strategy<'de, D: Deserializer<'de>>(deserializer: D) -> Result<StrategyT, D::Error> {
+    Ok(StrategySnapshot::deserialize(deserializer)?.restore())
+}
+
 impl Action {
     pub fn name(&self) -> String {
         self.name.clone()
@@ -515,7 +525,7 @@ mod tests {
         use std::sync::{Arc, Mutex};
         use crate::ai::agent::Agent;
         use crate::ai::beliefs::belief_set::BeliefSet;
-        use crate::ai::strategy::Strategy;
+        use crate::ai::strategy::{Strategy, StrategySnapshot};
         use crate::game_state::GameState;
         use crate::simulation::simulation_event::PendingSimulationEvent;
         use super::*;
@@ -524,6 +534,8 @@ mod tests {
         impl Strategy for StrategyAlwaysIsComplete {
             fn name(&self) -> String { String::from("StrategyAlwaysIsComplete") }
 
+            fn snapshot(&self) -> StrategySnapshot { StrategySnapshot::Noop }
+
             fn can_perform(&self, _: &BeliefSet) -> bool { true }
 
             fn should_interrupt(&self, _: &BeliefSet) -> bool {
@@ -541,6 +553,8 @@ mod tests {
         impl Strategy for StrategyNeverIsComplete {
             fn name(&self) -> String { String::from("StrategyNeverIsComplete") }
 
+            fn snapshot(&self) -> StrategySnapshot { StrategySnapshot::Noop }
+
             fn can_perform(&self, _: &BeliefSet) -> bool { true }
 
             fn should_interrupt(&self, _: &BeliefSet) -> bool {
@@ -4,15 +4,31 @@ use rapier3d::prelude::Vec3;
 use dys_satisfiable::SatisfiableField;
 use crate::{ai::{action::ActionBuilder, belief::Belief, strategies::move_to_location::MoveToLocationStrategy}, game_objects::{combatant::CombatantObject, game_object::GameObject}, game_state::GameState};
 use crate::ai::belief::SatisfiableBelief;
+use crate::ai::agent::Agent;
+use crate::ai::personality::Personality;
+use crate::ai::strategies::body_block::{blocking_point, BlockedObject, BlockingPosition, BodyBlockStrategy};
 use crate::ai::strategies::shove_combatant::ShoveCombatantStrategy;
+use crate::game_objects::combatant::{SHOVE_STAMINA_COST, THROW_STAMINA_COST};
 use crate::game_objects::game_object_type::GameObjectType;
 use super::{action::Action, strategies::{pick_up_ball::PickUpBallStrategy, throw_ball_at_target_location::ThrowBallAtTargetStrategy}};
 
-/// ZJ-TODO: HACK: this value should be passed in through simulation settings.
-/// This value allows us to make all movement actions cheaper/more expensive,
-/// as other actions may have lower/higher absolute costs.
-const MOVE_TO_LOCATION_WEIGHT_HARDCODE_HACK: f32 = 0.8_f32;
-const MOVE_TO_BALL_WEIGHT_HARDCODE_HACK: f32 = 0.4_f32;
+/// How far a combatant moves away from the closest enemy when retreating.
+const RETREAT_DISTANCE: f32 = 10.0;
+
+/// How far from a plate a guarding combatant stands, towards the closest enemy.
+const GUARD_PLATE_DISTANCE: f32 = 3.0;
+
+/// How far from a teammate a screening combatant stands, towards the closest enemy.
+const SCREEN_DISTANCE: f32 = 2.0;
+
+/// How far in front of a runner an intercepting combatant stands, towards the runner's closest plate.
+const INTERCEPT_DISTANCE: f32 = 2.0;
+
+/// How many ticks a combatant will spend getting into a blocking position before giving up.
+const BODY_BLOCK_MAX_TICKS: u16 = 20;
+
+/// Combatants won't throw at anyone closer than this, nor while enemies are this close.
+const MIN_THROW_DISTANCE: f32 = 5.0;
 
 #[tracing::instrument(fields(combatant_id = combatant.id), skip_all, level = "trace")]
 pub fn actions(
@@ -32,24 +48,48 @@ pub fn actions(
             .to_owned()
     };
 
-    let combatant_move_speed = combatant.combatant.lock().unwrap().move_speed();
+    let (combatant_move_speed, personality) = {
+        let combatant_instance = combatant.combatant.lock().unwrap();
+        (combatant_instance.move_speed(), Personality::from_combatant(&combatant_instance))
+    };
+    // Tired combatants move slower, making far away destinations more expensive
+    let combatant_move_speed = combatant_move_speed * combatant.fatigue_modifier();
+
+    // Combatants won't plan to throw or shove if they believe they're too tired to
+    let too_tired_to = |stamina_cost: f32| {
+        SatisfiableBelief::CombatantStamina()
+            .combatant_id(SatisfiableField::Exactly(combatant.id))
+            .stamina(SatisfiableField::LessThan(stamina_cost))
+    };
+
+    // Cost multipliers allow teams to make some actions cheaper/more expensive,
+    // as other actions may have lower/higher absolute costs.
+    let cost_multipliers = game_state.lock().unwrap().team_tactics(combatant.team).action_cost_multipliers;
 
     let (plates, balls, combatants) = {
         let game_state = game_state.lock().unwrap();
         (game_state.plates.clone(), game_state.balls.clone(), game_state.combatants.clone())
     };
 
-    for (plate_id, plate_object) in plates {
-        let plate_location = {
-            let game_state = game_state.lock().unwrap();
-            let (_, collider_set) = game_state.physics_sim.sets();
-            collider_set
-                .get(plate_object.collider_handle().unwrap())
-                .unwrap()
-                .translation()
-                .to_owned()
-        };
+    let plate_locations = {
+        let game_state = game_state.lock().unwrap();
+        let (_, collider_set) = game_state.physics_sim.sets();
+        // There's no point heading for (or guarding) plates that can't be scored on right now
+        plates
+            .iter()
+            .filter(|(_, plate_object)| plate_object.is_active())
+            .map(|(plate_id, plate_object)| {
+                let plate_location = collider_set
+                    .get(plate_object.collider_handle().unwrap())
+                    .unwrap()
+                    .translation()
+                    .to_owned();
+                (*plate_id, plate_location)
+            })
+            .collect::<Vec<_>>()
+    };
 
+    for (plate_id, plate_location) in plate_locations.iter().copied() {
         actions.push(
             ActionBuilder::new()
                 .name(format!("Move to Plate {plate_id}"))
@@ -58,18 +98,230 @@ pub fn actions(
                     plate_location.into(),
                     4)
                 )
-                .cost(MOVE_TO_LOCATION_WEIGHT_HARDCODE_HACK * (plate_location - combatant_pos).length() / combatant_move_speed)
+                .cost(cost_multipliers.move_to_location * (plate_location - combatant_pos).length() / combatant_move_speed)
                 .promises(Belief::OnPlate { combatant_id: combatant.id, plate_id })
                 .build()
         );
     }
 
+    let closest_enemy_pos = {
+        let game_state = game_state.lock().unwrap();
+        let (rigid_body_set, _) = game_state.physics_sim.sets();
+        combatants
+            .values()
+            .filter(|other_combatant_object| other_combatant_object.team != combatant.team)
+            .filter_map(|other_combatant_object| rigid_body_set.get(other_combatant_object.rigid_body_handle))
+            .map(|rigid_body| rigid_body.translation())
+            .min_by(|a, b| (*a - combatant_pos).length().total_cmp(&(*b - combatant_pos).length()))
+    };
+
+    if let Some(closest_enemy_pos) = closest_enemy_pos {
+        let mut retreat_direction = combatant_pos - closest_enemy_pos;
+        retreat_direction.y = 0.0;
+        let retreat_location = combatant_pos + retreat_direction.normalize_or_zero() * RETREAT_DISTANCE;
+
+        actions.push(
+            ActionBuilder::new()
+                .name("Retreat")
+                .strategy(MoveToLocationStrategy::new(
+                    combatant.id,
+                    retreat_location,
+                    4)
+                )
+                .cost(cost_multipliers.move_to_location * RETREAT_DISTANCE / combatant_move_speed)
+                .completion(vec![
+                    Belief::Retreated { combatant_id: combatant.id, tick: current_tick },
+                ])
+                .promises(Belief::Retreated { combatant_id: combatant.id, tick: current_tick })
+                .build()
+        );
+    }
+
+    let role_assignment = game_state.lock().unwrap().role_assignment(combatant);
+    if let Some(role_assignment) = role_assignment {
+        actions.push(
+            ActionBuilder::new()
+                .name("Move to Formation Position")
+                .strategy(MoveToLocationStrategy::new(
+                    combatant.id,
+                    role_assignment.formation_position,
+                    4)
+                )
+                .cost(cost_multipliers.move_to_location * (role_assignment.formation_position - combatant_pos).length() / combatant_move_speed)
+                .completion(vec![
+                    Belief::InFormation { combatant_id: combatant.id, tick: current_tick },
+                ])
+                .promises(Belief::InFormation { combatant_id: combatant.id, tick: current_tick })
+                .build()
+        );
+    }
+
+    // Defensive actions react to where we believe enemies are, which may not be where they actually are
+    let believed_enemy_positions = combatant
+        .beliefs()
+        .beliefs()
+        .into_iter()
+        .filter_map(|belief| match belief {
+            Belief::CombatantPosition { combatant_id, position } => Some((combatant_id, position)),
+            _ => None,
+        })
+        .filter(|(combatant_id, _)| combatants.get(combatant_id).is_some_and(|other| other.team != combatant.team))
+        .collect::<Vec<_>>();
+
+    let closest_believed_enemy = |location: Vec3| {
+        believed_enemy_positions
+            .iter()
+            .copied()
+            .min_by(|(_, a), (_, b)| (*a - location).length().total_cmp(&(*b - location).length()))
+    };
+
+    let combatant_positions = {
+        let game_state = game_state.lock().unwrap();
+        let (rigid_body_set, _) = game_state.physics_sim.sets();
+        combatants
+            .iter()
+            .filter_map(|(combatant_id, combatant_object)| {
+                rigid_body_set
+                    .get(combatant_object.rigid_body_handle)
+                    .map(|rigid_body| (*combatant_id, rigid_body.translation()))
+            })
+            .collect::<IndexMap<_, _>>()
+    };
+
+    for (plate_id, plate_location) in plate_locations.iter().copied() {
+        // Only guard plates that a teammate is scoring on
+        let plate_occupied_by_teammate = combatants
+            .values()
+            .any(|other| other.id != combatant.id && other.team == combatant.team && other.plate() == Some(plate_id));
+
+        if !plate_occupied_by_teammate {
+            continue;
+        }
+
+        let Some((threat_id, threat_pos)) = closest_believed_enemy(plate_location) else {
+            continue;
+        };
+
+        let blocking_position = BlockingPosition::FromBlocked(GUARD_PLATE_DISTANCE);
+        let guard_location = blocking_point(threat_pos, plate_location, blocking_position);
+
+        actions.push(
+            ActionBuilder::new()
+                .name(format!("Guard Plate {plate_id}"))
+                .strategy(BodyBlockStrategy::new(
+                    combatant.id,
+                    threat_id,
+                    BlockedObject::Location(plate_location),
+                    blocking_position,
+                    BODY_BLOCK_MAX_TICKS)
+                )
+                .cost(cost_multipliers.move_to_location * (guard_location - combatant_pos).length() / combatant_move_speed)
+                .requires(
+                    SatisfiableBelief::CombatantPosition()
+                        .combatant_id(SatisfiableField::Exactly(threat_id))
+                )
+                .completion(vec![
+                    Belief::GuardingPlate { combatant_id: combatant.id, plate_id, tick: current_tick },
+                ])
+                .promises(Belief::GuardingPlate { combatant_id: combatant.id, plate_id, tick: current_tick })
+                .build()
+        );
+    }
+
+    for (teammate_id, teammate_object) in &combatants {
+        // Screen teammates carrying a ball from the closest enemy
+        if teammate_object.id == combatant.id || teammate_object.team != combatant.team || teammate_object.ball().is_none() {
+            continue;
+        }
+
+        let Some(teammate_pos) = combatant_positions.get(teammate_id).copied() else {
+            continue;
+        };
+
+        let Some((threat_id, threat_pos)) = closest_believed_enemy(teammate_pos) else {
+            continue;
+        };
+
+        let blocking_position = BlockingPosition::FromBlocked(SCREEN_DISTANCE);
+        let screen_location = blocking_point(threat_pos, teammate_pos, blocking_position);
+
+        actions.push(
+            ActionBuilder::new()
+                .name(format!("Screen Combatant {teammate_id}"))
+                .strategy(BodyBlockStrategy::new(
+                    combatant.id,
+                    threat_id,
+                    BlockedObject::Combatant(*teammate_id),
+                    blocking_position,
+                    BODY_BLOCK_MAX_TICKS)
+                )
+                .cost(cost_multipliers.move_to_location * (screen_location - combatant_pos).length() / combatant_move_speed)
+                .requires(
+                    SatisfiableBelief::CombatantPosition()
+                        .combatant_id(SatisfiableField::Exactly(threat_id))
+                )
+                .completion(vec![
+                    Belief::ScreeningCombatant { self_combatant_id: combatant.id, teammate_combatant_id: *teammate_id, tick: current_tick },
+                ])
+                .promises(Belief::ScreeningCombatant { self_combatant_id: combatant.id, teammate_combatant_id: *teammate_id, tick: current_tick })
+                .build()
+        );
+    }
+
+    for (enemy_id, enemy_pos) in believed_enemy_positions.iter().copied() {
+        // Runners already on a plate are for the shovers to deal with
+        if combatants.get(&enemy_id).is_none_or(|enemy| enemy.plate().is_some()) {
+            continue;
+        }
+
+        let Some((_, runner_destination)) = plate_locations
+            .iter()
+            .copied()
+            .min_by(|(_, a), (_, b)| (*a - enemy_pos).length().total_cmp(&(*b - enemy_pos).length()))
+        else {
+            continue;
+        };
+
+        let blocking_position = BlockingPosition::FromThreat(INTERCEPT_DISTANCE);
+        let intercept_location = blocking_point(enemy_pos, runner_destination, blocking_position);
+
+        actions.push(
+            ActionBuilder::new()
+                .name(format!("Intercept Combatant {enemy_id}"))
+                .strategy(BodyBlockStrategy::new(
+                    combatant.id,
+                    enemy_id,
+                    BlockedObject::Location(runner_destination),
+                    blocking_position,
+                    BODY_BLOCK_MAX_TICKS)
+                )
+                .cost(cost_multipliers.move_to_location * (intercept_location - combatant_pos).length() / combatant_move_speed)
+                .requires(
+                    SatisfiableBelief::CombatantPosition()
+                        .combatant_id(SatisfiableField::Exactly(enemy_id))
+                )
+                .completion(vec![
+                    Belief::InterceptingCombatant { self_combatant_id: combatant.id, target_combatant_id: enemy_id, tick: current_tick },
+                ])
+                .promises(Belief::InterceptingCombatant { self_combatant_id: combatant.id, target_combatant_id: enemy_id, tick: current_tick })
+                .build()
+        );
+    }
+
     for (other_combatant_id, other_combatant_object) in &combatants {
         // Don't add actions that refer to ourselves
         if combatant.id == *other_combatant_id {
             continue;
         }
 
+        // High-presence enemies draw attention, making them more attractive targets
+        let targeting_cost_multiplier = if other_combatant_object.team == combatant.team {
+            1.0
+        } else {
+            let other_personality = Personality::from_combatant(&other_combatant_object.combatant.lock().unwrap());
+            personality.targeting_cost_multiplier(&other_personality)
+        };
+
         let target_pos = {
             let game_state = game_state.lock().unwrap();
             let (rigid_body_set, _) = game_state.physics_sim.sets();
@@ -88,7 +340,7 @@ pub fn actions(
                     GameObjectType::Combatant(*other_combatant_id),
                     4)
                 )
-                .cost(MOVE_TO_LOCATION_WEIGHT_HARDCODE_HACK * (target_pos - combatant_pos).length() / combatant_move_speed)
+                .cost(cost_multipliers.move_to_location * (target_pos - combatant_pos).length() / combatant_move_speed)
                 .completion(vec![
                     Belief::ScannedEnvironment { tick: current_tick },
                 ])
@@ -107,7 +359,7 @@ pub fn actions(
                     combatant.id,
                     GameObjectType::Combatant(*other_combatant_id),
                 ))
-                .cost(MOVE_TO_LOCATION_WEIGHT_HARDCODE_HACK * (target_pos - combatant_pos).length() / combatant_move_speed)
+                .cost(cost_multipliers.move_to_location * (target_pos - combatant_pos).length() / combatant_move_speed)
                 .promises(Belief::CanReachCombatant {
                     self_combatant_id: combatant.id,
                     target_combatant_id: *other_combatant_id,
@@ -122,12 +374,13 @@ pub fn actions(
                     combatant.id,
                     *other_combatant_id
                 ))
-                .cost(15.0) // ZJ-TODO
+                .cost(targeting_cost_multiplier * cost_multipliers.shove * 15.0) // ZJ-TODO
                 .requires(
                     SatisfiableBelief::CanReachCombatant()
                         .self_combatant_id(SatisfiableField::Exactly(combatant.id))
                         .target_combatant_id(SatisfiableField::Exactly(*other_combatant_id)),
                 )
+                .prohibits(too_tired_to(SHOVE_STAMINA_COST))
                 .promises(Belief::CombatantShoved {
                     combatant_id: *other_combatant_id,
                     on_plate: other_combatant_object.plate(),
@@ -158,7 +411,7 @@ pub fn actions(
                     ball_location.into(),
                     4)
                 )
-                .cost(MOVE_TO_BALL_WEIGHT_HARDCODE_HACK * (ball_location - combatant_pos).length() / combatant_move_speed)
+                .cost(cost_multipliers.move_to_ball * (ball_location - combatant_pos).length() / combatant_move_speed)
                 .requires(
                     SatisfiableBelief::BallPosition()
                         .ball_id(SatisfiableField::Exactly(ball_id))
@@ -175,7 +428,7 @@ pub fn actions(
             ActionBuilder::new()
                 .name(format!("Pick Up Ball {ball_id}"))
                 .strategy(PickUpBallStrategy::new(combatant.id, ball_id, ball_location))
-                .cost(1.0)
+                .cost(cost_multipliers.pick_up_ball)
                 .requires(
                     SatisfiableBelief::InBallPickupRange()
                         .combatant_id(SatisfiableField::Exactly(combatant.id))
@@ -223,7 +476,7 @@ pub fn actions(
                 ActionBuilder::new()
                     .name(format!("Catch Ball {ball_id}"))
                     .strategy(PickUpBallStrategy::new(combatant.id, ball_id, ball_location.to_owned()))
-                    .cost(1.0) // ZJ-TODO
+                    .cost(cost_multipliers.catch_ball) // ZJ-TODO
                     .requires(
                         SatisfiableBelief::InBallPickupRange()
                             .combatant_id(SatisfiableField::Exactly(combatant.id))
@@ -287,7 +540,7 @@ pub fn actions(
                     .strategy(ThrowBallAtTargetStrategy::new(combatant.id, teammate_combatant_id))
                     // ZJ-TODO: ideally this is an inverse bell curve
                     //          for now, just penalize close throws and reward far throws
-                    .cost(10.0 + 5.0 / (target_pos - combatant_pos).length())
+                    .cost(cost_multipliers.pass_ball * (10.0 + 5.0 / (target_pos - combatant_pos).length()))
                     .requires(
                         SatisfiableBelief::HeldBall()
                             .combatant_id(SatisfiableField::Exactly(combatant.id))
@@ -305,10 +558,7 @@ pub fn actions(
                     .prohibits(
                         SatisfiableBelief::CombatantPosition()
                             .combatant_id(SatisfiableField::In(enemy_combatant_ids.clone()))
-                            .position(SatisfiableField::lambda_from(move |target_pos: Vec3| {
-                                const MIN_THROW_DISTANCE: f32 = 5.0;
-                                (target_pos - combatant_pos).length() < MIN_THROW_DISTANCE
-                            }))
+                            .position(SatisfiableField::CloserThan(combatant_pos, MIN_THROW_DISTANCE))
                     )
                     .prohibits(
                         SatisfiableBelief::CombatantIsStunned()
@@ -317,15 +567,13 @@ pub fn actions(
                     .requires(
                         SatisfiableBelief::CombatantPosition()
                             .combatant_id(SatisfiableField::Exactly(teammate_combatant_id))
-                            .position(SatisfiableField::lambda_from(move |target_pos: Vec3| {
-                                const MIN_THROW_DISTANCE: f32 = 5.0;
-                                (target_pos - combatant_pos).length() >= MIN_THROW_DISTANCE
-                            }))
+                            .position(SatisfiableField::NotCloserThan(combatant_pos, MIN_THROW_DISTANCE))
                     )
                     .prohibits(
                         SatisfiableBelief::HeldBall()
                             .combatant_id(SatisfiableField::Exactly(teammate_combatant_id))
                     )
+                    .prohibits(too_tired_to(THROW_STAMINA_COST))
                     .completion(vec![
                         Belief::BallThrownAtCombatant {
                             ball_id,
@@ -366,6 +614,11 @@ pub fn actions(
             .collect::<IndexMap<_, _>>();
 
         for (enemy_combatant_id, enemy_combatant_object) in enemy_combatants {
+            let targeting_cost_multiplier = {
+                let enemy_personality = Personality::from_combatant(&enemy_combatant_object.combatant.lock().unwrap());
+                personality.targeting_cost_multiplier(&enemy_personality)
+            };
+
             let target_pos = {
                 let game_state = game_state.lock().unwrap();
                 let (rigid_body_set, _) = game_state.physics_sim.sets();
@@ -382,7 +635,7 @@ pub fn actions(
                     .strategy(ThrowBallAtTargetStrategy::new(combatant.id, enemy_combatant_id))
                     // ZJ-TODO: ideally this is an inverse bell curve
                     //          for now, just penalize close throws and reward far throws
-                    .cost(10.0 + 5.0 / (target_pos - combatant_pos).length())
+                    .cost(targeting_cost_multiplier * cost_multipliers.throw_ball * (10.0 + 5.0 / (target_pos - combatant_pos).length()))
                     .requires(
                         SatisfiableBelief::HeldBall()
                             .combatant_id(SatisfiableField::Exactly(combatant.id))
@@ -400,11 +653,9 @@ pub fn actions(
                     .prohibits(
                         SatisfiableBelief::CombatantPosition()
                             .combatant_id(SatisfiableField::In(enemy_combatant_ids.clone()))
-                            .position(SatisfiableField::lambda_from(move |target_pos: Vec3| {
-                                const MIN_THROW_DISTANCE: f32 = 5.0;
-                                (target_pos - combatant_pos).length() < MIN_THROW_DISTANCE
-                            }))
+                            .position(SatisfiableField::CloserThan(combatant_pos, MIN_THROW_DISTANCE))
                     )
+                    .prohibits(too_tired_to(THROW_STAMINA_COST))
                     .completion(vec![
                         Belief::BallThrownAtCombatant {
                             ball_id,
@@ -98,10 +98,82 @@ pub enum Belief {
     CombatantIsStunned {
         #[unique]
         combatant_id: CombatantInstanceId,
+    },
+    Retreated {
+        #[unique]
+        combatant_id: CombatantInstanceId,
+        tick: GameTickNumber,
+    },
+    CombatantStamina {
+        #[unique]
+        combatant_id: CombatantInstanceId,
+        stamina: f32,
+    },
+    GuardingPlate {
+        #[unique]
+        combatant_id: CombatantInstanceId,
+        #[unique]
+        plate_id: PlateId,
+        tick: GameTickNumber,
+    },
+    ScreeningCombatant {
+        #[unique]
+        self_combatant_id: CombatantInstanceId,
+        #[unique]
+        teammate_combatant_id: CombatantInstanceId,
+        tick: GameTickNumber,
+    },
+    InterceptingCombatant {
+        #[unique]
+        self_combatant_id: CombatantInstanceId,
+        #[unique]
+        target_combatant_id: CombatantInstanceId,
+        tick: GameTickNumber,
+    },
+    InFormation {
+        #[unique]
+        combatant_id: CombatantInstanceId,
+        tick: GameTickNumber,
+    },
+}
+
+impl Belief {
+    /// Is this belief about the given combatant, whether as its subject or its target?
+    pub fn concerns_combatant(&self, id: CombatantInstanceId) -> bool {
+        match *self {
+            Belief::ScannedEnvironment { .. }
+            | Belief::BallPosition { .. }
+            | Belief::PlatePosition { .. }
+            | Belief::BallIsFlying { .. } => false,
+            Belief::CombatantPosition { combatant_id, .. }
+            | Belief::OnPlate { combatant_id, .. }
+            | Belief::HeldBall { combatant_id, .. }
+            | Belief::InBallPickupRange { combatant_id, .. }
+            | Belief::CombatantShoved { combatant_id, .. }
+            | Belief::CombatantIsStunned { combatant_id }
+            | Belief::Retreated { combatant_id, .. }
+            | Belief::CombatantStamina { combatant_id, .. }
+            | Belief::GuardingPlate { combatant_id, .. }
+            | Belief::InFormation { combatant_id, .. } => combatant_id == id,
+            Belief::CanReachCombatant { self_combatant_id, target_combatant_id }
+            | Belief::InterceptingCombatant { self_combatant_id, target_combatant_id, .. } => {
+                self_combatant_id == id || target_combatant_id == id
+            },
+            Belief::BallThrownAtCombatant { thrower_combatant_id, target_combatant_id, .. } => {
+                thrower_combatant_id == id || target_combatant_id == id
+            },
+            Belief::DirectLineOfSightToCombatant { self_combatant_id, other_combatant_id } => {
+                self_combatant_id == id || other_combatant_id == id
+            },
+            Belief::BallCaught { combatant_id, thrower_id, .. } => combatant_id == id || thrower_id == id,
+            Belief::ScreeningCombatant { self_combatant_id, teammate_combatant_id, .. } => {
+                self_combatant_id == id || teammate_combatant_id == id
+            },
+        }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ExpiringBelief {
     pub belief: Belief,
     pub expires_on_tick: Option<GameTickNumber>,
@@ -1,15 +1,17 @@
-use std::collections::hash_map::Entry;
-use std::collections::HashMap;
 use std::fmt::Debug;
+use indexmap::map::Entry;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
 use dys_satisfiable::{SatisfiabilityTest, Uniqueness};
+use dys_world::combatant::instance::CombatantInstanceId;
 use crate::ai::belief::{Belief, ExpiringBelief};
 use crate::game_tick::GameTickNumber;
 
 /// BeliefSets are collections of beliefs that allow for tests against existing beliefs.
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct BeliefSet {
     unsourced_beliefs: Vec<ExpiringBelief>,
-    sourced_beliefs: HashMap<u32, Vec<ExpiringBelief>>,
+    sourced_beliefs: IndexMap<u32, Vec<ExpiringBelief>>,
 }
 
 impl BeliefSet {
@@ -20,7 +22,7 @@ impl BeliefSet {
     pub fn from(beliefs: &[Belief]) -> BeliefSet {
         BeliefSet {
             unsourced_beliefs: ExpiringBelief::from_beliefs(beliefs, None),
-            sourced_beliefs: HashMap::new(),
+            sourced_beliefs: IndexMap::new(),
         }
     }
 
@@ -99,6 +101,14 @@ impl BeliefSet {
         self.sourced_beliefs.iter_mut().for_each(|(_, beliefs)| beliefs.retain(retain_fn));
     }
 
+    /// Forgets everything believed about a combatant, eg because they've left the game.
+    pub fn forget_combatant(&mut self, combatant_id: CombatantInstanceId) {
+        let retain_fn = |expiring_belief: &ExpiringBelief| !expiring_belief.belief.concerns_combatant(combatant_id);
+
+        self.unsourced_beliefs.retain(retain_fn);
+        self.sourced_beliefs.iter_mut().for_each(|(_, beliefs)| beliefs.retain(retain_fn));
+    }
+
     pub fn beliefs(&self) -> Vec<Belief> {
         let sourced_beliefs = self.sourced_beliefs.values().flatten();
         self
@@ -110,7 +120,7 @@ impl BeliefSet {
             .collect::<Vec<Belief>>()
     }
 
-    pub fn sourced_beliefs(&self) -> HashMap<u32, Vec<ExpiringBelief>> {
+    pub fn sourced_beliefs(&self) -> IndexMap<u32, Vec<ExpiringBelief>> {
         let mut sourced_expiring_beliefs = self.sourced_beliefs.clone();
         sourced_expiring_beliefs.insert(0, self.unsourced_beliefs.clone());
         sourced_expiring_beliefs
@@ -0,0 +1,255 @@
+use serde::{Deserialize, Serialize};
+use indexmap::IndexMap;
+use rapier3d::prelude::Vec3;
+use dys_world::combatant::instance::CombatantInstanceId;
+use dys_world::team::formation::{CombatantRole, Formation};
+use crate::game_objects::combatant::CombatantObject;
+use crate::game_state::CombatantsMapT;
+use crate::simulation::simulation_event::SimulationEvent;
+
+/// A role a combatant has been given by their team, and where they line up while playing it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RoleAssignment {
+    pub role: CombatantRole,
+    pub formation_position: Vec3,
+}
+
+/// Assigns roles to a team's combatants at kickoff, and reassigns them as the game unfolds.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TeamCoordinator {
+    assignments: IndexMap<CombatantInstanceId, RoleAssignment>,
+}
+
+impl TeamCoordinator {
+    /// Fills the team's formation with the best suited combatants, most important roles first.
+    /// `combatant_starts` are the positions of the team's combatant starts, in arena order,
+    /// and `forward` is the direction towards the enemy side of the arena.
+    pub fn kickoff(
+        formation: &Formation,
+        combatant_starts: &[Vec3],
+        combatants: &[CombatantObject],
+        forward: Vec3,
+    ) -> TeamCoordinator {
+        let right = forward.cross(Vec3::Y);
+
+        let mut slots = combatant_starts
+            .iter()
+            .enumerate()
+            .map(|(start_index, start)| {
+                let slot = formation.slot(start_index);
+                RoleAssignment {
+                    role: slot.role,
+                    formation_position: *start + forward * slot.forward + right * slot.lateral,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Stable sort, so that slots of equal importance are filled in start order
+        slots.sort_by_key(|slot| slot.role.importance());
+
+        let mut unassigned = combatants
+            .iter()
+            .map(|combatant_object| (combatant_object.id, combatant_object.combatant.clone()))
+            .collect::<Vec<_>>();
+
+        let mut assignments = IndexMap::new();
+        for slot in slots {
+            let best_suited = unassigned
+                .iter()
+                .enumerate()
+                .rev() // max_by returns the last maximum, so reverse to prefer earlier combatants on ties
+                .max_by(|(_, (_, a)), (_, (_, b))| {
+                    let a_suitability = slot.role.suitability(&a.lock().unwrap());
+                    let b_suitability = slot.role.suitability(&b.lock().unwrap());
+                    a_suitability.total_cmp(&b_suitability)
+                })
+                .map(|(index, _)| index);
+
+            let Some(index) = best_suited else {
+                break;
+            };
+
+            let (combatant_id, _) = unassigned.remove(index);
+            tracing::debug!(combatant_id, role = ?slot.role, "assigned role at kickoff");
+            assignments.insert(combatant_id, slot);
+        }
+
+        TeamCoordinator { assignments }
+    }
+
+    pub fn assignment(&self, combatant_id: CombatantInstanceId) -> Option<&RoleAssignment> {
+        self.assignments.get(&combatant_id)
+    }
+
+    pub fn role(&self, combatant_id: CombatantInstanceId) -> Option<CombatantRole> {
+        self.assignment(combatant_id).map(|assignment| assignment.role)
+    }
+
+    /// Reassigns roles in response to this tick's events.
+    /// Returns whether any role changed hands.
+    pub fn handle_events(&mut self, events: &[SimulationEvent], combatants: &CombatantsMapT) -> bool {
+        let availability_changed = events.iter().any(|event| matches!(
+            event,
+            SimulationEvent::CombatantStunned { combatant_id, .. } if self.assignments.contains_key(combatant_id)
+        ));
+
+        if !availability_changed {
+            return false;
+        }
+
+        self.cover_unavailable_roles(|combatant_id| {
+            combatants
+                .get(&combatant_id)
+                .is_some_and(|combatant_object| !combatant_object.is_stunned())
+        })
+    }
+
+    /// Takes a combatant out of the team for good, handing their role to a combatant playing a less important one.
+    /// Returns whether any role changed hands.
+    pub fn release_combatant(&mut self, combatant_id: CombatantInstanceId) -> bool {
+        if !self.assignments.contains_key(&combatant_id) {
+            return false;
+        }
+
+        let any_reassigned = self.cover_unavailable_roles(|other_id| other_id != combatant_id);
+        self.assignments.shift_remove(&combatant_id);
+        any_reassigned
+    }
+
+    /// Hands a combatant's role to a substitute coming on in their place.
+    /// Returns false if the combatant has no role to hand over.
+    pub fn substitute_combatant(&mut self, combatant_id: CombatantInstanceId, substitute_id: CombatantInstanceId) -> bool {
+        let Some((index, _, assignment)) = self.assignments.shift_remove_full(&combatant_id) else {
+            return false;
+        };
+
+        self.assignments.shift_insert(index, substitute_id, assignment);
+        true
+    }
+
+    /// Hands the roles of unavailable combatants to available combatants playing less important roles.
+    /// Roles aren't handed back when a combatant becomes available again, to avoid combatants thrashing between roles.
+    fn cover_unavailable_roles(&mut self, is_available: impl Fn(CombatantInstanceId) -> bool) -> bool {
+        let mut combatant_ids = self.assignments.keys().copied().collect::<Vec<_>>();
+        combatant_ids.sort_by_key(|combatant_id| self.assignments[combatant_id].role.importance());
+
+        let mut any_reassigned = false;
+        for combatant_id in combatant_ids {
+            if is_available(combatant_id) {
+                continue;
+            }
+
+            let importance = self.assignments[&combatant_id].role.importance();
+            let replacement_id = self.assignments
+                .iter()
+                .filter(|(other_id, other)| is_available(**other_id) && other.role.importance() > importance)
+                .max_by_key(|(_, other)| other.role.importance())
+                .map(|(other_id, _)| *other_id);
+
+            let Some(replacement_id) = replacement_id else {
+                continue;
+            };
+
+            let unavailable_assignment = self.assignments[&combatant_id].clone();
+            let replacement_assignment = self.assignments.insert(replacement_id, unavailable_assignment).unwrap();
+            self.assignments.insert(combatant_id, replacement_assignment);
+
+            tracing::debug!(combatant_id, replacement_id, "reassigned role of unavailable combatant");
+            any_reassigned = true;
+        }
+
+        any_reassigned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rapier3d::glamx::vec3;
+    use dys_world::attribute::attribute_type::AttributeType;
+    use dys_world::attribute::instance::AttributeInstance;
+    use dys_world::team::formation::{CombatantRole, Formation, FormationSlot};
+    use crate::ai::agent::Agent;
+    use crate::ai::coordinator::TeamCoordinator;
+    use crate::ai::test_utils::TestAgent;
+    use crate::game_objects::combatant::CombatantObject;
+
+    fn make_combatant(id: u32, attributes: &[AttributeInstance]) -> CombatantObject {
+        let mut combatant_object = TestAgent::new_with_attributes(attributes).combatant().clone();
+        combatant_object.id = id;
+        combatant_object
+    }
+
+    fn make_coordinator() -> TeamCoordinator {
+        let formation = Formation {
+            slots: vec![
+                FormationSlot { role: CombatantRole::Support, forward: 5.0, lateral: 0.0 },
+                FormationSlot { role: CombatantRole::PlateHolder, forward: 20.0, lateral: 2.0 },
+            ],
+        };
+
+        let combatants = [
+            make_combatant(1, &[AttributeInstance::new(AttributeType::Communication, 20.0)]),
+            make_combatant(2, &[AttributeInstance::new(AttributeType::Constitution, 20.0)]),
+        ];
+
+        TeamCoordinator::kickoff(
+            &formation,
+            &[vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, 10.0)],
+            &combatants,
+            vec3(1.0, 0.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn test_kickoff_assigns_best_suited_combatants() {
+        let coordinator = make_coordinator();
+
+        assert_eq!(coordinator.role(1), Some(CombatantRole::Support));
+        assert_eq!(coordinator.role(2), Some(CombatantRole::PlateHolder));
+    }
+
+    #[test]
+    fn test_formation_positions_are_relative_to_starts() {
+        let coordinator = make_coordinator();
+
+        let plate_holder_position = coordinator.assignment(2).unwrap().formation_position;
+        assert!((plate_holder_position - vec3(20.0, 0.0, 12.0)).length() < 0.0001);
+    }
+
+    #[test]
+    fn test_unavailable_combatants_hand_over_important_roles() {
+        let mut coordinator = make_coordinator();
+
+        assert!(coordinator.cover_unavailable_roles(|combatant_id| combatant_id != 2));
+        assert_eq!(coordinator.role(1), Some(CombatantRole::PlateHolder));
+        assert_eq!(coordinator.role(2), Some(CombatantRole::Support));
+
+        // Roles aren't handed back once the combatant is available again
+        assert!(!coordinator.cover_unavailable_roles(|_| true));
+        assert_eq!(coordinator.role(1), Some(CombatantRole::PlateHolder));
+    }
+
+    #[test]
+    fn test_released_combatants_hand_over_their_role_for_good() {
+        let mut coordinator = make_coordinator();
+
+        assert!(coordinator.release_combatant(2));
+        assert_eq!(coordinator.role(1), Some(CombatantRole::PlateHolder));
+        assert_eq!(coordinator.role(2), None);
+
+        assert!(!coordinator.release_combatant(2));
+    }
+
+    #[test]
+    fn test_substitutes_take_over_their_combatants_role() {
+        let mut coordinator = make_coordinator();
+        let plate_holder_assignment = coordinator.assignment(2).cloned();
+
+        assert!(coordinator.substitute_combatant(2, 7));
+        assert_eq!(coordinator.assignment(7).cloned(), plate_holder_assignment);
+        assert_eq!(coordinator.role(1), Some(CombatantRole::Support));
+        assert_eq!(coordinator.role(2), None);
+
+        assert!(!coordinator.substitute_combatant(2, 8));
+    }
+}
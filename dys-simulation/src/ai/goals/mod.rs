@@ -3,11 +3,24 @@ use dys_satisfiable::SatisfiableField;
 use dys_world::attribute::attribute_type::AttributeType;
 use crate::{ai::goal::GoalBuilder, game_objects::combatant::CombatantObject, game_state::GameState};
 use crate::ai::belief::SatisfiableBelief;
+use crate::ai::personality::Personality;
+use crate::ai::utility::{GoalUtility, UtilityContext};
 use super::goal::Goal;
 
-// ZJ-TODO: move to config
-const ON_PLATE_PRIORITY_MULTIPLIER: f32 = 4.5;
-const TEAMMATE_PASS_PRIORITY_MULTIPLIER: f32 = 1.5;
+/// How many ticks a combatant remembers retreating for.
+/// Once forgotten, the combatant may decide to retreat again.
+const RETREAT_MEMORY_TICKS: u32 = 50;
+
+/// How many ticks a combatant remembers guarding, screening or intercepting for.
+/// Defensive positions go stale quickly as enemies move, so this is much shorter than retreating.
+const DEFENSE_MEMORY_TICKS: u32 = 20;
+
+/// How many ticks a combatant remembers lining up in formation for.
+const FORMATION_MEMORY_TICKS: u32 = 100;
+
+/// Priority of lining up in formation.
+/// Low enough that combatants only hold their position when there's nothing better to do.
+const FORMATION_PRIORITY: f32 = 10.0;
 
 pub fn idle_goal() -> Goal {
     GoalBuilder::new()
@@ -22,16 +35,43 @@ pub fn goals(
     combatant_object: &CombatantObject,
     game_state: Arc<Mutex<GameState>>,
 ) -> Vec<Goal> {
+    let (current_tick, teammate_ids, tactics, role_assignment, utility_profile, utility_context) = {
+        let game_state = game_state.lock().unwrap();
+        let teammate_ids = game_state.team_combatants(combatant_object.team)
+            .iter()
+            .map(|combatant_object| combatant_object.id)
+            .collect::<Vec<_>>();
+
+        (
+            game_state.current_tick,
+            teammate_ids,
+            game_state.team_tactics(combatant_object.team),
+            game_state.role_assignment(combatant_object),
+            game_state.simulation_config.utility_profile().clone(),
+            UtilityContext::from_game_state(combatant_object, &game_state),
+        )
+    };
+
+    // Note: the utility context locks the combatant, so it must be built before we take the lock below
     let combatant_instance = combatant_object.combatant.lock().unwrap();
 
     let attr = |attribute_type: AttributeType| {
         combatant_instance.get_attribute_value(&attribute_type).unwrap_or_default()
     };
+    let personality = Personality::from_combatant(&combatant_instance);
+    let self_serving = personality.self_serving_multiplier();
+    let objective = personality.objective_multiplier();
+    let risk = personality.risk_multiplier(utility_context.own_health);
+    let utility = |goal_utility: &GoalUtility| goal_utility.score(&utility_context);
 
-    let teammate_ids = game_state.lock().unwrap().team_combatants(combatant_object.team)
-        .iter()
-        .map(|combatant_object| combatant_object.id)
-        .collect::<Vec<_>>();
+    // Roles shift the team's goal weights towards the combatant's job
+    let goal_weights = &match &role_assignment {
+        Some(role_assignment) => tactics.goal_weights.scaled_by(&role_assignment.role.goal_weight_multipliers()),
+        None => tactics.goal_weights.clone(),
+    };
+    let aggression = tactics.aggression_multiplier();
+    let plate_focus = tactics.plate_focus_multiplier();
+    let enemies_on_plate = goal_weights.enemies_on_plate * plate_focus;
 
     // ZJ-TODO: refactor, goodness
     vec![
@@ -41,7 +81,7 @@ pub fn goals(
                 SatisfiableBelief::OnPlate()
                     .combatant_id(SatisfiableField::Exactly(combatant_object.id))
             )
-            .priority(0.75 * attr(AttributeType::Dexterity))
+            .priority(goal_weights.score_points * plate_focus * self_serving * objective * 0.75 * attr(AttributeType::Dexterity) * utility(&utility_profile.score_points))
             .repeatable(true)
             .build(),
         GoalBuilder::new()
@@ -50,7 +90,7 @@ pub fn goals(
                 SatisfiableBelief::BallThrownAtCombatant()
                     .target_combatant_id(SatisfiableField::In(teammate_ids.clone()))
             )
-            .priority(TEAMMATE_PASS_PRIORITY_MULTIPLIER * (75.0 + attr(AttributeType::Communication) - attr(AttributeType::Ego)))
+            .priority(goal_weights.pass_ball * objective * (75.0 + attr(AttributeType::Communication) - attr(AttributeType::Ego)) * utility(&utility_profile.pass_ball))
             .build(),
         GoalBuilder::new()
             .name("Throw Ball At Enemies On Plates")
@@ -59,7 +99,7 @@ pub fn goals(
                     .target_combatant_id(SatisfiableField::NotIn(teammate_ids.clone()))
                     .target_on_plate(SatisfiableField::NotExactly(None))
             )
-            .priority(ON_PLATE_PRIORITY_MULTIPLIER * (attr(AttributeType::Coordination) + attr(AttributeType::Strength)))
+            .priority(goal_weights.throw_at_enemies * self_serving * aggression * enemies_on_plate * (attr(AttributeType::Coordination) + attr(AttributeType::Strength)) * utility(&utility_profile.throw_at_enemies_on_plates))
             .build(),
         GoalBuilder::new()
             .name("Throw Ball At Enemies Off Plates")
@@ -68,7 +108,7 @@ pub fn goals(
                     .target_combatant_id(SatisfiableField::NotIn(teammate_ids.clone()))
                     .target_on_plate(SatisfiableField::Exactly(None))
             )
-            .priority(attr(AttributeType::Coordination) + attr(AttributeType::Strength))
+            .priority(goal_weights.throw_at_enemies * self_serving * aggression * (attr(AttributeType::Coordination) + attr(AttributeType::Strength)) * utility(&utility_profile.throw_at_enemies_off_plates))
             .build(),
         GoalBuilder::new()
             .name("Shove Combatants On Plates")
@@ -77,7 +117,7 @@ pub fn goals(
                     .combatant_id(SatisfiableField::NotIn(teammate_ids.clone()))
                     .on_plate(SatisfiableField::NotExactly(None))
             )
-            .priority(ON_PLATE_PRIORITY_MULTIPLIER * (attr(AttributeType::Constitution) + attr(AttributeType::Presence)))
+            .priority(goal_weights.shove_enemies * risk * aggression * enemies_on_plate * (attr(AttributeType::Constitution) + attr(AttributeType::Presence)) * utility(&utility_profile.shove_enemies_on_plates))
             .build(),
         GoalBuilder::new()
             .name("Shove Combatants Off Plates")
@@ -86,7 +126,34 @@ pub fn goals(
                     .combatant_id(SatisfiableField::NotIn(teammate_ids.clone()))
                     .on_plate(SatisfiableField::Exactly(None))
             )
-            .priority(attr(AttributeType::Constitution) + attr(AttributeType::Presence))
+            .priority(goal_weights.shove_enemies * risk * aggression * (attr(AttributeType::Constitution) + attr(AttributeType::Presence)) * utility(&utility_profile.shove_enemies_off_plates))
+            .build(),
+        GoalBuilder::new()
+            .name("Guard Plate")
+            .desired_belief(
+                SatisfiableBelief::GuardingPlate()
+                    .combatant_id(SatisfiableField::Exactly(combatant_object.id))
+                    .tick(SatisfiableField::lambda_from(move |tick| tick + DEFENSE_MEMORY_TICKS >= current_tick))
+            )
+            .priority(goal_weights.defend_plates * plate_focus * objective * (attr(AttributeType::Constitution) + attr(AttributeType::Stability)) * utility(&utility_profile.guard_plate))
+            .build(),
+        GoalBuilder::new()
+            .name("Screen Teammate")
+            .desired_belief(
+                SatisfiableBelief::ScreeningCombatant()
+                    .self_combatant_id(SatisfiableField::Exactly(combatant_object.id))
+                    .tick(SatisfiableField::lambda_from(move |tick| tick + DEFENSE_MEMORY_TICKS >= current_tick))
+            )
+            .priority(goal_weights.screen_teammates * objective * (attr(AttributeType::Communication) + attr(AttributeType::Stability)) * utility(&utility_profile.screen_teammate))
+            .build(),
+        GoalBuilder::new()
+            .name("Intercept Runner")
+            .desired_belief(
+                SatisfiableBelief::InterceptingCombatant()
+                    .self_combatant_id(SatisfiableField::Exactly(combatant_object.id))
+                    .tick(SatisfiableField::lambda_from(move |tick| tick + DEFENSE_MEMORY_TICKS >= current_tick))
+            )
+            .priority(goal_weights.defend_plates * plate_focus * aggression * objective * attr(AttributeType::Dexterity) * utility(&utility_profile.intercept_runner))
             .build(),
         GoalBuilder::new()
             .name("Catch Ball")
@@ -94,8 +161,207 @@ pub fn goals(
                 SatisfiableBelief::BallCaught()
                     .combatant_id(SatisfiableField::Exactly(combatant_object.id))
             )
-            .priority(100.0)
+            .priority(goal_weights.catch_ball * 100.0 * utility(&utility_profile.catch_ball))
+            .build(),
+        GoalBuilder::new()
+            .name("Retreat")
+            .desired_belief(
+                SatisfiableBelief::Retreated()
+                    .combatant_id(SatisfiableField::Exactly(combatant_object.id))
+                    .tick(SatisfiableField::lambda_from(move |tick| tick + RETREAT_MEMORY_TICKS >= current_tick))
+            )
+            .priority(personality.retreat_priority(utility_context.own_health))
+            .build(),
+        GoalBuilder::new()
+            .name("Hold Formation")
+            .desired_belief(
+                SatisfiableBelief::InFormation()
+                    .combatant_id(SatisfiableField::Exactly(combatant_object.id))
+                    .tick(SatisfiableField::lambda_from(move |tick| tick + FORMATION_MEMORY_TICKS >= current_tick))
+            )
+            .priority(if role_assignment.is_some() { FORMATION_PRIORITY * objective } else { 0.0 })
             .build(),
         idle_goal()
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use dys_world::attribute::attribute_type::AttributeType;
+    use dys_world::attribute::instance::AttributeInstance;
+    use dys_world::team::formation::{CombatantRole, Formation, FormationSlot};
+    use dys_world::team::tactics::TeamTactics;
+    use rapier3d::prelude::Vec3;
+    use crate::ai::agent::Agent;
+    use crate::ai::coordinator::TeamCoordinator;
+    use crate::ai::goals::goals;
+    use crate::ai::test_utils::{make_test_game_state, TestAgent};
+    use crate::ai::utility::{Consideration, ConsiderationInput, GoalUtility, ResponseCurve, UtilityProfile};
+
+    fn goal_priority(tactics: TeamTactics, goal_name: &str) -> f32 {
+        goal_priority_with_profile(tactics, UtilityProfile::default(), goal_name)
+    }
+
+    fn goal_priority_with_profile(tactics: TeamTactics, utility_profile: UtilityProfile, goal_name: &str) -> f32 {
+        let agent = TestAgent::new_with_attributes(&[
+            AttributeInstance::new(AttributeType::Coordination, 10.0),
+            AttributeInstance::new(AttributeType::Strength, 10.0),
+            AttributeInstance::new(AttributeType::Dexterity, 10.0),
+        ]);
+
+        agent_goal_priority(&agent, tactics, utility_profile, goal_name)
+    }
+
+    fn personality_goal_priority(personality: &[AttributeInstance], damage: f32, goal_name: &str) -> f32 {
+        let mut attributes = vec![
+            AttributeInstance::new(AttributeType::Coordination, 10.0),
+            AttributeInstance::new(AttributeType::Strength, 10.0),
+            AttributeInstance::new(AttributeType::Dexterity, 10.0),
+            AttributeInstance::new(AttributeType::Constitution, 10.0),
+        ];
+        attributes.extend_from_slice(personality);
+
+        let agent = TestAgent::new_with_attributes(&attributes);
+        agent.combatant().combatant_state.lock().unwrap().damage = damage;
+
+        agent_goal_priority(&agent, TeamTactics::default(), UtilityProfile::default(), goal_name)
+    }
+
+    fn agent_goal_priority(agent: &TestAgent, tactics: TeamTactics, utility_profile: UtilityProfile, goal_name: &str) -> f32 {
+        let game_state = make_test_game_state(None);
+        {
+            let mut game_state = game_state.lock().unwrap();
+            game_state.game.game_instance.home_team.lock().unwrap().tactics = tactics;
+            game_state.simulation_config = game_state.simulation_config.clone().with_utility_profile(utility_profile);
+        }
+
+        goals(agent.combatant(), game_state)
+            .into_iter()
+            .find(|goal| goal.name() == goal_name)
+            .unwrap()
+            .priority()
+    }
+
+    fn role_goal_priority(role: Option<CombatantRole>, goal_name: &str) -> f32 {
+        let agent = TestAgent::new_with_attributes(&[
+            AttributeInstance::new(AttributeType::Coordination, 10.0),
+            AttributeInstance::new(AttributeType::Strength, 10.0),
+            AttributeInstance::new(AttributeType::Dexterity, 10.0),
+        ]);
+
+        let game_state = make_test_game_state(None);
+        if let Some(role) = role {
+            let formation = Formation { slots: vec![FormationSlot { role, ..FormationSlot::default() }] };
+            let coordinator = TeamCoordinator::kickoff(&formation, &[Vec3::ZERO], &[agent.combatant().clone()], Vec3::X);
+            game_state.lock().unwrap().team_coordinators.insert(agent.combatant().team, coordinator);
+        }
+
+        goals(agent.combatant(), game_state)
+            .into_iter()
+            .find(|goal| goal.name() == goal_name)
+            .unwrap()
+            .priority()
+    }
+
+    #[test]
+    fn test_aggressive_teams_prefer_throwing_at_enemies() {
+        let passive = TeamTactics { aggression: 0.0, ..TeamTactics::default() };
+        let aggressive = TeamTactics { aggression: 1.0, ..TeamTactics::default() };
+
+        assert!(
+            goal_priority(aggressive, "Throw Ball At Enemies Off Plates")
+                > goal_priority(passive, "Throw Ball At Enemies Off Plates")
+        );
+    }
+
+    #[test]
+    fn test_plate_focused_teams_prefer_scoring() {
+        let unfocused = TeamTactics { plate_focus: 0.0, ..TeamTactics::default() };
+        let focused = TeamTactics { plate_focus: 1.0, ..TeamTactics::default() };
+
+        assert!(goal_priority(focused, "Score Points") > goal_priority(unfocused, "Score Points"));
+    }
+
+    #[test]
+    fn test_goal_weights_scale_priority() {
+        let mut tactics = TeamTactics::default();
+        let default_priority = goal_priority(tactics.clone(), "Pass Ball to Teammate");
+
+        tactics.goal_weights.pass_ball *= 2.0;
+        let doubled_priority = goal_priority(tactics, "Pass Ball to Teammate");
+
+        assert!((doubled_priority - 2.0 * default_priority).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_defend_weight_scales_interception_priority() {
+        let mut tactics = TeamTactics::default();
+        let default_priority = goal_priority(tactics.clone(), "Intercept Runner");
+
+        tactics.goal_weights.defend_plates = 0.0;
+        assert!(goal_priority(tactics, "Intercept Runner").abs() < 0.0001);
+        assert!(default_priority > 0.0);
+    }
+
+    #[test]
+    fn test_roles_shift_goal_priorities() {
+        let unassigned = role_goal_priority(None, "Throw Ball At Enemies Off Plates");
+        let striker = role_goal_priority(Some(CombatantRole::Striker), "Throw Ball At Enemies Off Plates");
+        let plate_holder = role_goal_priority(Some(CombatantRole::PlateHolder), "Throw Ball At Enemies Off Plates");
+
+        assert!(striker > unassigned);
+        assert!(plate_holder < unassigned);
+        assert!(role_goal_priority(None, "Hold Formation").abs() < 0.0001);
+        assert!(role_goal_priority(Some(CombatantRole::Support), "Hold Formation") > 0.0);
+    }
+
+    #[test]
+    fn test_utility_profile_scales_priority() {
+        let mut utility_profile = UtilityProfile::default();
+        let default_priority = goal_priority_with_profile(TeamTactics::default(), utility_profile.clone(), "Catch Ball");
+
+        utility_profile.catch_ball = GoalUtility::new(vec![
+            Consideration::new(ConsiderationInput::OwnHealth, ResponseCurve::Constant { value: 0.5 }),
+        ]);
+        let halved_priority = goal_priority_with_profile(TeamTactics::default(), utility_profile, "Catch Ball");
+
+        assert!((halved_priority - 0.5 * default_priority).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_egotistical_combatants_prefer_self_serving_goals() {
+        let humble = [AttributeInstance::new(AttributeType::Ego, 0.0)];
+        let egotistical = [AttributeInstance::new(AttributeType::Ego, 30.0)];
+
+        assert!(personality_goal_priority(&egotistical, 0.0, "Score Points") > personality_goal_priority(&humble, 0.0, "Score Points"));
+        assert!(
+            personality_goal_priority(&egotistical, 0.0, "Throw Ball At Enemies Off Plates")
+                > personality_goal_priority(&humble, 0.0, "Throw Ball At Enemies Off Plates")
+        );
+        assert!(personality_goal_priority(&egotistical, 0.0, "Pass Ball to Teammate") < personality_goal_priority(&humble, 0.0, "Pass Ball to Teammate"));
+    }
+
+    #[test]
+    fn test_committed_combatants_prefer_objective_goals() {
+        let uncommitted = [AttributeInstance::new(AttributeType::Commitment, 0.0)];
+        let committed = [AttributeInstance::new(AttributeType::Commitment, 30.0)];
+
+        assert!(personality_goal_priority(&committed, 0.0, "Score Points") > personality_goal_priority(&uncommitted, 0.0, "Score Points"));
+        assert!(personality_goal_priority(&committed, 0.0, "Pass Ball to Teammate") > personality_goal_priority(&uncommitted, 0.0, "Pass Ball to Teammate"));
+    }
+
+    #[test]
+    fn test_self_preserving_combatants_retreat_instead_of_shoving_when_hurt() {
+        let fearless = [AttributeInstance::new(AttributeType::SelfPreservation, 0.0)];
+        let self_preserving = [AttributeInstance::new(AttributeType::SelfPreservation, 30.0)];
+
+        assert!(personality_goal_priority(&self_preserving, 0.0, "Retreat").abs() < 0.0001);
+        assert!(personality_goal_priority(&fearless, 500.0, "Retreat").abs() < 0.0001);
+        assert!(personality_goal_priority(&self_preserving, 500.0, "Retreat") > 0.0);
+
+        assert!(
+            personality_goal_priority(&self_preserving, 500.0, "Shove Combatants Off Plates")
+                < personality_goal_priority(&fearless, 500.0, "Shove Combatants Off Plates")
+        );
+    }
+}
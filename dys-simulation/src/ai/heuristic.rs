@@ -0,0 +1,17 @@
+use std::fmt::Debug;
+use super::action::Action;
+use super::belief::BeliefTest;
+
+/// Heuristics estimate the remaining cost of a regressive planner state.
+///
+/// The planner asks for an estimate for each unsatisfied belief test independently,
+/// and uses the largest estimate as the heuristic value for the state.
+/// To guarantee the planner returns the cheapest plan, estimates must never exceed
+/// the real cost of satisfying the belief test (eg the heuristic must be admissible).
+pub trait PlannerHeuristic: Debug {
+    fn name(&self) -> String;
+
+    /// Estimated cost of satisfying the desired belief test using the provided actions.
+    /// Returning [`f32::INFINITY`] marks the belief test as unsatisfiable, and prunes any state that requires it.
+    fn estimate(&self, desired_belief: &BeliefTest, actions: &[Action]) -> f32;
+}
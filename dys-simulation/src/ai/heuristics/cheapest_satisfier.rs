@@ -0,0 +1,71 @@
+use crate::ai::action::Action;
+use crate::ai::belief::BeliefTest;
+use crate::ai::heuristic::PlannerHeuristic;
+
+/// Estimates the cost of a belief test as the cost of the cheapest action that could satisfy it.
+/// Belief tests that no action can satisfy are considered unsatisfiable.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CheapestSatisfierHeuristic;
+
+impl PlannerHeuristic for CheapestSatisfierHeuristic {
+    fn name(&self) -> String {
+        String::from("CheapestSatisfier")
+    }
+
+    fn estimate(&self, desired_belief: &BeliefTest, actions: &[Action]) -> f32 {
+        actions
+            .iter()
+            .filter(|action| action.can_satisfy(desired_belief.to_owned()))
+            .map(Action::cost)
+            .min_by(f32::total_cmp)
+            .unwrap_or(f32::INFINITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dys_satisfiable::SatisfiableField;
+    use crate::ai::action::ActionBuilder;
+    use crate::ai::belief::{Belief, SatisfiableBelief};
+    use crate::ai::heuristic::PlannerHeuristic;
+    use super::CheapestSatisfierHeuristic;
+
+    #[test]
+    fn test_cheapest_satisfier_is_estimated() {
+        let actions = vec![
+            ActionBuilder::new()
+                .cost(2.5)
+                .completion(vec![Belief::HeldBall { ball_id: 1, combatant_id: 1 }])
+                .build(),
+            ActionBuilder::new()
+                .cost(1.25)
+                .completion(vec![Belief::HeldBall { ball_id: 1, combatant_id: 1 }])
+                .build(),
+            ActionBuilder::new()
+                .cost(0.5)
+                .completion(vec![Belief::HeldBall { ball_id: 2, combatant_id: 1 }])
+                .build(),
+        ];
+
+        let desired_belief = SatisfiableBelief::HeldBall()
+            .ball_id(SatisfiableField::Exactly(1))
+            .into();
+
+        let estimate = CheapestSatisfierHeuristic.estimate(&desired_belief, &actions);
+        assert!((estimate - 1.25).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_unsatisfiable_is_infinite() {
+        let actions = vec![
+            ActionBuilder::new()
+                .cost(1.0)
+                .completion(vec![Belief::HeldBall { ball_id: 2, combatant_id: 1 }])
+                .build(),
+        ];
+
+        let desired_belief = SatisfiableBelief::OnPlate().into();
+
+        assert!(CheapestSatisfierHeuristic.estimate(&desired_belief, &actions).is_infinite());
+    }
+}
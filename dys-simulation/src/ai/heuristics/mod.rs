@@ -0,0 +1,2 @@
+pub mod cheapest_satisfier;
+pub mod zero;
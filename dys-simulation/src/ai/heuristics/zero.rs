@@ -0,0 +1,18 @@
+use crate::ai::action::Action;
+use crate::ai::belief::BeliefTest;
+use crate::ai::heuristic::PlannerHeuristic;
+
+/// Estimates zero cost for every belief test.
+/// This reduces the planner to a uniform cost search.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ZeroHeuristic;
+
+impl PlannerHeuristic for ZeroHeuristic {
+    fn name(&self) -> String {
+        String::from("Zero")
+    }
+
+    fn estimate(&self, _: &BeliefTest, _: &[Action]) -> f32 {
+        0.0
+    }
+}
@@ -4,14 +4,20 @@ pub mod agent;
 pub mod action;
 pub mod belief;
 pub mod beliefs;
+pub mod coordinator;
 pub mod goal;
+pub mod heuristic;
+pub mod personality;
 pub mod planner;
+pub mod planner_trace;
 pub mod sensor;
 pub mod strategy;
+pub mod utility;
 
 pub mod actions;
 pub mod goals;
+pub mod heuristics;
 pub mod strategies;
 pub mod sensors;
 
-mod test_utils;
\ No newline at end of file
+pub(crate) mod test_utils;
\ No newline at end of file
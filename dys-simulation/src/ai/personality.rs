@@ -0,0 +1,181 @@
+use dys_world::attribute::attribute_type::AttributeType;
+use dys_world::combatant::instance::CombatantInstance;
+
+/// Personality attribute value of an unremarkable combatant.
+/// Combatants without a personality attribute are treated as having this value.
+const AVERAGE_PERSONALITY_VALUE: f32 = 10.0;
+
+/// How strongly a target's presence (beyond the chooser's stoicism) discounts the cost of targeting them.
+const PRESENCE_ATTENTION_WEIGHT: f32 = 0.5;
+
+/// How much damage an average combatant will take before abandoning their current plan.
+const PLAN_ABANDONMENT_DAMAGE: f32 = 10.0;
+
+/// Priority of retreating for an average combatant that has lost all of their health.
+const MAX_RETREAT_PRIORITY: f32 = 100.0;
+
+/// The personality of a combatant, derived from their mental attributes.
+/// Each trait is normalized such that an unremarkable combatant has a value of 1.0.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Personality {
+    /// Preference for self-serving plays (scoring, throwing) over team-oriented ones (passing).
+    pub ego: f32,
+
+    /// How much this combatant draws the attention of adversaries.
+    pub presence: f32,
+
+    /// Resistance to the presence of adversaries.
+    pub stoicism: f32,
+
+    /// Willingness to see plans through and to keep doing the job when hurt.
+    pub commitment: f32,
+
+    /// Willingness to abandon the job to avoid getting hurt.
+    pub self_preservation: f32,
+}
+
+impl Default for Personality {
+    fn default() -> Self {
+        Personality {
+            ego: 1.0,
+            presence: 1.0,
+            stoicism: 1.0,
+            commitment: 1.0,
+            self_preservation: 1.0,
+        }
+    }
+}
+
+impl Personality {
+    pub fn from_combatant(combatant_instance: &CombatantInstance) -> Personality {
+        let normalized = |attribute_type: AttributeType| {
+            combatant_instance
+                .get_attribute_value(&attribute_type)
+                .unwrap_or(AVERAGE_PERSONALITY_VALUE)
+                .max(0.0) / AVERAGE_PERSONALITY_VALUE
+        };
+
+        Personality {
+            ego: normalized(AttributeType::Ego),
+            presence: normalized(AttributeType::Presence),
+            stoicism: normalized(AttributeType::Stoicism),
+            commitment: normalized(AttributeType::Commitment),
+            self_preservation: normalized(AttributeType::SelfPreservation),
+        }
+    }
+
+    /// Multiplier for the priority of self-serving goals, such as scoring points or throwing at enemies.
+    pub fn self_serving_multiplier(&self) -> f32 {
+        f32::midpoint(1.0, self.ego)
+    }
+
+    /// Multiplier for the priority of goals that further the team's objective, such as scoring points or passing.
+    pub fn objective_multiplier(&self) -> f32 {
+        f32::midpoint(1.0, self.commitment)
+    }
+
+    /// Multiplier for the priority of goals that put this combatant in harm's way, such as shoving.
+    /// Combatants that value their own safety shy away from danger as they get hurt.
+    /// `own_health` is 1.0 when undamaged, approaching 0.0 as the combatant takes damage.
+    pub fn risk_multiplier(&self, own_health: f32) -> f32 {
+        1.0 / (1.0 + self.fear(own_health))
+    }
+
+    /// Priority of retreating from adversaries.
+    pub fn retreat_priority(&self, own_health: f32) -> f32 {
+        MAX_RETREAT_PRIORITY * self.fear(own_health)
+    }
+
+    /// Multiplier for the cost of targeting a combatant (throwing at or shoving them) with the given personality.
+    /// High-presence targets are cheaper to target, unless this combatant is stoic enough to ignore them.
+    pub fn targeting_cost_multiplier(&self, target: &Personality) -> f32 {
+        let distraction = (target.presence - self.stoicism).max(0.0);
+        1.0 / (1.0 + PRESENCE_ATTENTION_WEIGHT * distraction)
+    }
+
+    /// Should this combatant abandon their current plan after taking the given damage while executing it?
+    pub fn should_abandon_plan(&self, damage_since_planning: f32) -> bool {
+        if damage_since_planning <= 0.0 {
+            return false;
+        }
+
+        let damage_tolerance = PLAN_ABANDONMENT_DAMAGE * self.commitment / self.self_preservation.max(0.01);
+        damage_since_planning > damage_tolerance
+    }
+
+    fn fear(&self, own_health: f32) -> f32 {
+        let injury = 1.0 - own_health.clamp(0.0, 1.0);
+        self.self_preservation * injury / self.commitment.max(0.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dys_world::attribute::attribute_type::AttributeType;
+    use dys_world::attribute::instance::AttributeInstance;
+    use dys_world::combatant::instance::CombatantInstance;
+    use dys_world::combatant::limb::{Limb, LimbModifier, LimbType};
+    use super::Personality;
+
+    fn make_combatant(attributes: &[AttributeInstance]) -> CombatantInstance {
+        CombatantInstance {
+            id: 1,
+            name: String::from("TestCombatant"),
+            limbs: vec![
+                Limb {
+                    limb_type: LimbType::Head,
+                    modifiers: vec![LimbModifier::default_with_attributes(attributes)],
+                    child_limbs: vec![],
+                }
+            ],
+            effect_modifiers: vec![],
+        }
+    }
+
+    #[test]
+    fn test_missing_attributes_are_average() {
+        assert_eq!(Personality::from_combatant(&make_combatant(&[])), Personality::default());
+    }
+
+    #[test]
+    fn test_attributes_are_normalized() {
+        let personality = Personality::from_combatant(&make_combatant(&[
+            AttributeInstance::new(AttributeType::Ego, 20.0),
+            AttributeInstance::new(AttributeType::Stoicism, 5.0),
+        ]));
+
+        assert!((personality.ego - 2.0).abs() < 0.0001);
+        assert!((personality.stoicism - 0.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_stoic_combatants_ignore_high_presence_targets() {
+        let loud_target = Personality { presence: 3.0, ..Personality::default() };
+        let quiet_target = Personality { presence: 0.0, ..Personality::default() };
+        let distractible = Personality { stoicism: 0.0, ..Personality::default() };
+        let stoic = Personality { stoicism: 3.0, ..Personality::default() };
+
+        assert!(distractible.targeting_cost_multiplier(&loud_target) < distractible.targeting_cost_multiplier(&quiet_target));
+        assert!((stoic.targeting_cost_multiplier(&loud_target) - stoic.targeting_cost_multiplier(&quiet_target)).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_self_preserving_combatants_retreat_when_hurt() {
+        let cowardly = Personality { self_preservation: 3.0, commitment: 0.5, ..Personality::default() };
+        let committed = Personality { self_preservation: 0.5, commitment: 3.0, ..Personality::default() };
+
+        assert!(cowardly.retreat_priority(1.0).abs() < 0.0001);
+        assert!(cowardly.retreat_priority(0.5) > committed.retreat_priority(0.5));
+        assert!(cowardly.risk_multiplier(0.5) < committed.risk_multiplier(0.5));
+    }
+
+    #[test]
+    fn test_committed_combatants_tolerate_more_damage_before_abandoning_plans() {
+        let cowardly = Personality { self_preservation: 3.0, commitment: 0.5, ..Personality::default() };
+        let committed = Personality { self_preservation: 0.5, commitment: 3.0, ..Personality::default() };
+
+        assert!(!cowardly.should_abandon_plan(0.0));
+        assert!(cowardly.should_abandon_plan(5.0));
+        assert!(!committed.should_abandon_plan(5.0));
+    }
+}
@@ -1,47 +1,62 @@
 use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::fmt::Debug;
 use std::sync::{Arc, Mutex};
 use dys_satisfiable::SatisfiabilityTest;
 use crate::{ai::goals::goals, game_state::GameState};
 use crate::ai::belief::BeliefTest;
 use crate::ai::beliefs::belief_set::BeliefSet;
+use crate::ai::heuristic::PlannerHeuristic;
+use crate::ai::heuristics::cheapest_satisfier::CheapestSatisfierHeuristic;
+use crate::ai::planner_trace::{CandidatePlanTrace, ChosenPlanTrace, GoalOutcome, GoalTrace, PlanRejectionReason, PlannerTrace};
 use super::{action::Action, actions::actions, agent::Agent, goal::Goal};
 
+/// Goals' priorities are clamped to at least this when weighing up their plans' costs,
+/// so that a goal with no priority is only ever planned for as a last resort (rather than dividing by zero).
+const MIN_GOAL_PRIORITY: f32 = 0.001;
+
 #[tracing::instrument(skip_all, level = "trace")]
 pub fn plan(
     agent: &impl Agent,
     game_state: Arc<Mutex<GameState>>,
 ) -> Vec<Action> {
+    plan_with_heuristic(agent, game_state, &CheapestSatisfierHeuristic)
+}
+
+/// Plans as normal, also returning a trace of the decisions the planner made.
+#[tracing::instrument(skip_all, level = "trace")]
+pub fn plan_with_trace(
+    agent: &impl Agent,
+    game_state: Arc<Mutex<GameState>>,
+) -> (Vec<Action>, PlannerTrace) {
+    let mut trace = PlannerTrace::default();
     let goals = goals(agent.combatant(), game_state.clone());
     let actions = actions(agent.combatant(), game_state.clone());
-    make_plan(agent, game_state, goals, actions).plan()
+    let plan = make_plan(agent, game_state, goals, &actions, &CheapestSatisfierHeuristic, Some(&mut trace)).plan();
+
+    (plan, trace)
+}
+
+/// Plans using the provided heuristic to guide the search.
+#[tracing::instrument(skip_all, level = "trace")]
+pub fn plan_with_heuristic(
+    agent: &impl Agent,
+    game_state: Arc<Mutex<GameState>>,
+    heuristic: &dyn PlannerHeuristic,
+) -> Vec<Action> {
+    let goals = goals(agent.combatant(), game_state.clone());
+    let actions = actions(agent.combatant(), game_state.clone());
+    make_plan(agent, game_state, goals, &actions, heuristic, None).plan()
 }
 
 #[derive(Clone)]
 struct Plan {
     actions: Vec<Action>,
-    dbg_str: String,
 }
 
 impl Plan {
     pub fn new() -> Self {
-        Plan { actions: vec![], dbg_str: String::new() }
-    }
-
-    pub fn from(actions: &[Action]) -> Self {
-        let mut new_plan = Plan { actions: actions.to_vec(), dbg_str: String::new() };
-        new_plan.dbg_str = format!("{new_plan:?}");
-        new_plan
-    }
-
-    pub fn push(&mut self, action: Action) {
-        self.actions.push(action);
-        self.dbg_str = format!("{self:?}");
-    }
-
-    pub fn extend(&mut self, actions: &[Action]) {
-        self.actions.extend(actions.to_vec());
-        self.dbg_str = format!("{self:?}");
+        Plan { actions: vec![] }
     }
 
     pub fn plan(&self) -> Vec<Action> {
@@ -50,16 +65,21 @@ impl Plan {
 
     pub fn cost(&self) -> f32 {
         self
-            .plan()
+            .actions
             .iter()
-            .map(|action| action.cost())
+            .map(Action::cost)
             .sum()
     }
+
+    /// Names of the actions in execution order.
+    pub fn action_names(&self) -> Vec<String> {
+        self.actions.iter().rev().map(Action::name).collect()
+    }
 }
 
 impl Debug for Plan {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[{}] ", self.actions.iter().map(|action| action.cost()).sum::<f32>())?;
+        write!(f, "[{}] ", self.cost())?;
 
         for action in &self.actions {
             write!(f, "({})<-", action.name())?;
@@ -69,210 +89,452 @@ impl Debug for Plan {
     }
 }
 
-#[derive(Clone, Debug)]
+/// Where a desired belief test of a planner state came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum DesiredBeliefSource {
+    /// The nth desired belief of a goal.
+    Goal { goal: usize, index: usize },
+    /// The nth prerequisite belief of an action.
+    Prerequisite { action: usize, index: usize },
+}
+
+/// Everything a single call to the planner is allowed to consider.
+struct PlanningProblem<'a> {
+    actions: &'a [Action],
+    goal_beliefs: Vec<Vec<BeliefTest>>,
+}
+
+impl PlanningProblem<'_> {
+    fn desired_belief(&self, source: DesiredBeliefSource) -> &BeliefTest {
+        match source {
+            DesiredBeliefSource::Goal { goal, index } => &self.goal_beliefs[goal][index],
+            DesiredBeliefSource::Prerequisite { action, index } => &self.actions[action].prerequisite_beliefs()[index],
+        }
+    }
+}
+
+/// Planner states with the same key are interchangeable for the remainder of the search.
+/// Both are kept in order, as the desired beliefs are a stack, and plans are validated in the order their actions are performed.
+type PlannerStateKey = (Vec<DesiredBeliefSource>, Vec<usize>);
+
+/// A node of the regressive search.
+/// The search starts from a goal's desired beliefs and works backwards,
+/// applying actions that satisfy the most recently desired belief until no desired beliefs remain.
+#[derive(Clone)]
 struct PlannerState<'a> {
+    problem: &'a PlanningProblem<'a>,
     lifetime: u8,
-    pub actions: &'a Vec<Action>,
-    pub action_plan: Plan,
-    pub belief_set: BeliefSet,
-    pub current_desired_belief: BeliefTest,
-    pub remaining_desired_beliefs: Vec<BeliefTest>,
-    pub prohibited_beliefs: Vec<BeliefTest>,
+    /// Indices of applied actions, ordered from the last action to execute to the first.
+    applied_actions: Vec<usize>,
+    cost: f32,
+    belief_set: BeliefSet,
+    remaining_desired_beliefs: Vec<DesiredBeliefSource>,
 }
 
 impl<'a> PlannerState<'a> {
-    pub fn new(
+    fn new(
+        problem: &'a PlanningProblem<'a>,
         lifetime: u8,
-        actions: &'a Vec<Action>,
         belief_set: BeliefSet,
-        current_desired_belief: BeliefTest,
-        remaining_desired_beliefs: Vec<BeliefTest>,
+        goal: usize,
     ) -> Self {
+        let remaining_desired_beliefs = (0..problem.goal_beliefs[goal].len())
+            .map(|index| DesiredBeliefSource::Goal { goal, index })
+            .collect();
+
         PlannerState {
+            problem,
             lifetime,
-            actions,
-            action_plan: Plan::new(),
+            applied_actions: vec![],
+            cost: 0.0,
             belief_set,
-            current_desired_belief,
             remaining_desired_beliefs,
-            prohibited_beliefs: vec![],
         }
     }
 
-    pub fn new_after_completing(&self, action: &Action) -> Option<PlannerState<'_>> {
+    fn is_complete(&self) -> bool {
+        self.remaining_desired_beliefs.is_empty()
+    }
+
+    fn current_desired_belief(&self) -> Option<&'a BeliefTest> {
+        let source = self.remaining_desired_beliefs.last()?;
+        Some(self.problem.desired_belief(*source))
+    }
+
+    fn prohibited_beliefs(&self) -> impl Iterator<Item = &'a BeliefTest> {
+        let actions = self.problem.actions;
+        self
+            .applied_actions
+            .iter()
+            .flat_map(move |action_index| actions[*action_index].prohibited_beliefs())
+    }
+
+    fn key(&self) -> PlannerStateKey {
+        (self.remaining_desired_beliefs.clone(), self.applied_actions.clone())
+    }
+
+    fn new_after_completing(&self, action_index: usize) -> Option<PlannerState<'a>> {
         // If our lifetime is zero, we've reached our maximum action plan length
         if self.lifetime == 0 {
             return None;
         }
 
+        let action = &self.problem.actions[action_index];
+
         // If we had prohibited beliefs from previously planned actions, abort
-        for prohibited_belief in &self.prohibited_beliefs {
-            for action_completion_belief in action.completion_beliefs() {
-                if prohibited_belief.satisfied_by(action_completion_belief.to_owned()) {
-                    return None;
-                }
-            }
+        for prohibited_belief in self.prohibited_beliefs() {
+            let is_prohibited = action
+                .completion_beliefs()
+                .iter()
+                .chain(action.promised_beliefs())
+                .any(|belief| prohibited_belief.satisfied_by(*belief));
 
-            for action_promised_belief in action.promised_beliefs() {
-                if prohibited_belief.satisfied_by(action_promised_belief.to_owned()) {
-                    return None;
-                }
+            if is_prohibited {
+                return None;
             }
         }
 
         let mut new_planner_state = self.clone();
         new_planner_state.lifetime = self.lifetime - 1;
+        new_planner_state.remaining_desired_beliefs.pop();
 
-        for prerequisite_belief in action.prerequisite_beliefs() {
+        for (index, prerequisite_belief) in action.prerequisite_beliefs().iter().enumerate() {
             if !new_planner_state.belief_set.can_satisfy(prerequisite_belief) {
-                new_planner_state.remaining_desired_beliefs.push(prerequisite_belief.to_owned());
+                new_planner_state
+                    .remaining_desired_beliefs
+                    .push(DesiredBeliefSource::Prerequisite { action: action_index, index });
             }
         }
 
-        for prohibited_belief in action.prohibited_beliefs() {
-            new_planner_state.prohibited_beliefs.push(prohibited_belief.to_owned());
-        }
-
         for consumed_belief in action.consumed_beliefs() {
             new_planner_state.belief_set.remove_beliefs_by_test(consumed_belief);
         }
 
         new_planner_state.belief_set.add_beliefs(action.completion_beliefs());
         new_planner_state.belief_set.add_beliefs(action.promised_beliefs());
-        new_planner_state.action_plan.push(action.to_owned());
+        new_planner_state.applied_actions.push(action_index);
+        new_planner_state.cost += action.cost();
 
         Some(new_planner_state)
     }
+
+    fn plan(&self) -> Plan {
+        Plan {
+            actions: self
+                .applied_actions
+                .iter()
+                .map(|action_index| self.problem.actions[*action_index].clone())
+                .collect(),
+        }
+    }
 }
 
-fn make_plan(
-    agent: &impl Agent,
-    game_state: Arc<Mutex<GameState>>,
-    goals: Vec<Goal>,
-    actions: Vec<Action>,
-) -> Plan {
-    let mut plans_scored_by_priority: Vec<(Plan, f32)> = vec![];
+/// Planner states waiting to be expanded, ordered such that the cheapest estimated total cost is expanded first.
+struct OpenPlannerState<'a> {
+    estimated_total_cost: f32,
+    /// Breaks ties between equally estimated states, preferring the state discovered first.
+    sequence: u32,
+    state: PlannerState<'a>,
+}
 
-    for goal in next_best_goal(agent, game_state, goals) {
-        let mut desired_beliefs_remaining = goal.desired_beliefs();
-        let Some(next_desired_belief) = desired_beliefs_remaining.pop() else {
-            tracing::error!("Considering goal {} where we already have all desired beliefs!", goal.name());
-            continue;
-        };
+impl PartialEq for OpenPlannerState<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
 
-        let planner_state = PlannerState::new(5, &actions, agent.beliefs(), next_desired_belief, desired_beliefs_remaining);
-        let potential_plans = make_potential_plans(planner_state);
+impl Eq for OpenPlannerState<'_> {}
 
-        // Validate our potential plans from front to back
-        let retain_fn = |plan: &Plan| -> bool {
-            let mut plan = plan.plan();
-            plan.reverse();
+impl PartialOrd for OpenPlannerState<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-            let mut beliefs = agent.beliefs();
-            for action in plan {
-                for prohibited_belief in action.prohibited_beliefs() {
-                    if beliefs.can_satisfy(prohibited_belief) {
-                        return false;
-                    }
-                }
+impl Ord for OpenPlannerState<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Note: comparing other to self (instead of self to other) as BinaryHeap is a max-heap
+        other
+            .estimated_total_cost
+            .total_cmp(&self.estimated_total_cost)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
 
-                for prerequisite_belief in action.prerequisite_beliefs() {
-                    if !beliefs.can_satisfy(prerequisite_belief) {
-                        return false;
-                    }
+/// Why a goal search did not yield a plan.
+enum SearchFailure {
+    /// No valid plan exists within the maximum plan length.
+    NoPlanFound,
+    /// Every remaining plan costs at least the cost bound.
+    OverCostBound,
+    NodeBudgetExhausted,
+}
+
+/// A* search over planner states, sharing a node budget and heuristic estimates across goals.
+struct PlannerSearch<'a> {
+    problem: &'a PlanningProblem<'a>,
+    heuristic: &'a dyn PlannerHeuristic,
+    estimates: HashMap<DesiredBeliefSource, f32>,
+    max_plan_length: u8,
+    nodes_remaining: u32,
+    nodes_expanded: u32,
+}
+
+impl<'a> PlannerSearch<'a> {
+    fn new(
+        problem: &'a PlanningProblem<'a>,
+        heuristic: &'a dyn PlannerHeuristic,
+        node_budget: u32,
+        max_plan_length: u8,
+    ) -> Self {
+        PlannerSearch {
+            problem,
+            heuristic,
+            estimates: HashMap::new(),
+            max_plan_length,
+            nodes_remaining: node_budget,
+            nodes_expanded: 0,
+        }
+    }
+
+    fn budget_exhausted(&self) -> bool {
+        self.nodes_remaining == 0
+    }
+
+    /// Estimated cost to satisfy all remaining desired beliefs of the planner state.
+    /// An infinite estimate means the state can never be completed.
+    fn estimate(&mut self, state: &PlannerState) -> f32 {
+        let mut estimate = 0.0_f32;
+        for source in &state.remaining_desired_beliefs {
+            let source_estimate = *self
+                .estimates
+                .entry(*source)
+                .or_insert_with(|| self.heuristic.estimate(self.problem.desired_belief(*source), self.problem.actions));
+
+            estimate = estimate.max(source_estimate);
+        }
+
+        estimate
+    }
+
+    /// Finds the cheapest valid plan for the goal that costs less than the cost bound.
+    /// If candidate plans are provided, every complete plan found is recorded in it.
+    fn search_goal(
+        &mut self,
+        goal: usize,
+        agent_beliefs: &BeliefSet,
+        cost_bound: f32,
+        mut candidate_plans: Option<&mut Vec<CandidatePlanTrace>>,
+    ) -> Result<Plan, SearchFailure> {
+        let mut open_states = BinaryHeap::new();
+        let mut closed_states: HashSet<PlannerStateKey> = HashSet::new();
+        let mut sequence = 0;
+
+        let start_state = PlannerState::new(self.problem, self.max_plan_length, agent_beliefs.clone(), goal);
+        let start_estimate = self.estimate(&start_state);
+        if start_estimate.is_infinite() {
+            return Err(SearchFailure::NoPlanFound);
+        }
+
+        open_states.push(OpenPlannerState { estimated_total_cost: start_estimate, sequence, state: start_state });
+
+        while let Some(OpenPlannerState { estimated_total_cost, state, .. }) = open_states.pop() {
+            // Every remaining state costs at least as much as this one
+            if estimated_total_cost.total_cmp(&cost_bound).is_ge() {
+                return Err(SearchFailure::OverCostBound);
+            }
+
+            if state.is_complete() {
+                let plan = state.plan();
+                let validation = validate_plan(agent_beliefs, &plan);
+
+                if let Some(candidate_plans) = candidate_plans.as_deref_mut() {
+                    candidate_plans.push(CandidatePlanTrace {
+                        actions: plan.action_names(),
+                        cost: plan.cost(),
+                        rejection_reason: validation.clone().err(),
+                    });
                 }
 
-                for consumed_belief in action.consumed_beliefs() {
-                    beliefs.remove_beliefs_by_test(consumed_belief);
+                if validation.is_ok() {
+                    return Ok(plan);
                 }
 
-                beliefs.add_beliefs(action.completion_beliefs());
-                beliefs.add_beliefs(action.promised_beliefs());
+                continue;
             }
 
-            true
-        };
+            if !closed_states.insert(state.key()) {
+                continue;
+            }
 
-        let mut potential_plans = potential_plans
-            .into_iter()
-            .filter(retain_fn)
-            .collect::<Vec<_>>();
+            if self.budget_exhausted() {
+                return Err(SearchFailure::NodeBudgetExhausted);
+            }
+            self.nodes_remaining -= 1;
+            self.nodes_expanded += 1;
 
-        if potential_plans.is_empty() {
-            continue;
-        }
-        potential_plans.sort_by_key(|potential_plan| {
-            // Because floats are not Ord, we'll cast to ints
-            // I don't love this, but at worst our chosen plan is <0.5 cost worse than the next best
-            potential_plan
-                .plan()
-                .iter()
-                .map(|action| action.cost().round() as u32)
-                .sum::<u32>()
-        });
+            let Some(desired_belief) = state.current_desired_belief() else {
+                continue;
+            };
 
-        let plan = potential_plans.first().unwrap().to_owned();
-        let prioritized_cost = plan.cost() / goal.priority();
-        plans_scored_by_priority.push((plan, prioritized_cost));
+            for (action_index, action) in self.problem.actions.iter().enumerate() {
+                if !action.can_satisfy(desired_belief.to_owned()) {
+                    continue;
+                }
 
-        // Once we have three goals potentially satisfied, pick the best and hope it's reasonable
-        if plans_scored_by_priority.len() >= 3 {
-            break;
+                let Some(next_state) = state.new_after_completing(action_index) else {
+                    continue;
+                };
+
+                let next_estimate = self.estimate(&next_state);
+                if next_estimate.is_infinite() {
+                    continue;
+                }
+
+                sequence += 1;
+                open_states.push(OpenPlannerState {
+                    estimated_total_cost: next_state.cost + next_estimate,
+                    sequence,
+                    state: next_state,
+                });
+            }
         }
+
+        Err(SearchFailure::NoPlanFound)
     }
+}
+
+/// Validates the plan from front to back, ensuring each action can be performed
+/// given the beliefs of the agent and the actions performed before it.
+fn validate_plan(agent_beliefs: &BeliefSet, plan: &Plan) -> Result<(), PlanRejectionReason> {
+    let mut beliefs = agent_beliefs.clone();
+    for action in plan.actions.iter().rev() {
+        for prohibited_belief in action.prohibited_beliefs() {
+            if beliefs.can_satisfy(prohibited_belief) {
+                return Err(PlanRejectionReason::ProhibitedBelief {
+                    action: action.name(),
+                    belief_test: format!("{prohibited_belief:?}"),
+                });
+            }
+        }
 
-    plans_scored_by_priority.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        for prerequisite_belief in action.prerequisite_beliefs() {
+            if !beliefs.can_satisfy(prerequisite_belief) {
+                return Err(PlanRejectionReason::MissingPrerequisiteBelief {
+                    action: action.name(),
+                    belief_test: format!("{prerequisite_belief:?}"),
+                });
+            }
+        }
 
-    if let Some((best_plan, _)) = plans_scored_by_priority.first() {
-        best_plan.to_owned()
-    } else {
-        tracing::warn!("Failed to construct plan for any goal - this is very bad!");
-        Plan::new()
+        for consumed_belief in action.consumed_beliefs() {
+            beliefs.remove_beliefs_by_test(consumed_belief);
+        }
+
+        beliefs.add_beliefs(action.completion_beliefs());
+        beliefs.add_beliefs(action.promised_beliefs());
     }
-}
 
-fn make_potential_plans(
-    planner_state: PlannerState,
-) -> Vec<Plan> {
-    let mut potential_plans: Vec<Plan> = vec![];
+    Ok(())
+}
 
-    let filtered_actions = planner_state.actions.iter().filter(|action| {
-        let meets_with_completion_belief = action
-            .completion_beliefs()
-            .iter()
-            .any(|belief| planner_state.current_desired_belief.satisfied_by(belief.to_owned()));
+fn make_plan(
+    agent: &impl Agent,
+    game_state: Arc<Mutex<GameState>>,
+    goals: Vec<Goal>,
+    actions: &[Action],
+    heuristic: &dyn PlannerHeuristic,
+    mut trace: Option<&mut PlannerTrace>,
+) -> Plan {
+    let (node_budget, max_plan_length) = {
+        let game_state = game_state.lock().unwrap();
+        let simulation_config = &game_state.simulation_config;
+        (simulation_config.planner_node_budget(), simulation_config.planner_max_plan_length())
+    };
+
+    let goals = next_best_goal(agent, game_state, goals).collect::<Vec<_>>();
+    let problem = PlanningProblem {
+        actions,
+        goal_beliefs: goals.iter().map(Goal::desired_beliefs).collect(),
+    };
 
-        let meets_with_promised_belief = action
-            .promised_beliefs()
-            .iter()
-            .any(|belief| planner_state.current_desired_belief.satisfied_by(belief.to_owned()));
+    let agent_beliefs = agent.beliefs();
+    let mut search = PlannerSearch::new(&problem, heuristic, node_budget, max_plan_length);
+    let mut best_plan: Option<(Plan, f32, &Goal)> = None;
 
-        meets_with_completion_belief || meets_with_promised_belief
-    }).collect::<Vec<_>>();
+    for (goal_index, goal) in goals.iter().enumerate() {
+        let priority = goal.priority().max(MIN_GOAL_PRIORITY);
 
-    for action in filtered_actions {
-        let Some(mut updated_planner_state) = planner_state.new_after_completing(action) else {
-            // This operation only fails if we have reached the maximum lifetime (eg action depth)
-            break;
+        // A plan for this goal must cost less than the bound to beat the best plan found so far
+        let cost_bound = match &best_plan {
+            Some((_, best_prioritized_cost, _)) => best_prioritized_cost * priority,
+            None => f32::INFINITY,
         };
 
-        if let Some(next_needed_belief) = updated_planner_state.remaining_desired_beliefs.pop() {
-            updated_planner_state.current_desired_belief = next_needed_belief;
-            for plan in make_potential_plans(updated_planner_state) {
-                if plan.plan().is_empty() {
-                    break;
+        let mut candidate_plans = trace.is_some().then(Vec::new);
+        let search_result = search.search_goal(goal_index, &agent_beliefs, cost_bound, candidate_plans.as_mut());
+
+        let outcome = match search_result {
+            Ok(plan) => {
+                let prioritized_cost = plan.cost() / priority;
+                let outcome = GoalOutcome::Planned { cost: plan.cost(), prioritized_cost };
+                let is_best = best_plan
+                    .as_ref()
+                    .is_none_or(|(_, best_prioritized_cost, _)| prioritized_cost.total_cmp(best_prioritized_cost).is_lt());
+
+                if is_best {
+                    best_plan = Some((plan, prioritized_cost, goal));
                 }
 
-                let mut new_plan = Plan::from(&[action.to_owned()]);
-                new_plan.extend(&plan.plan());
-                potential_plans.push(new_plan);
-            }
-        } else {
-            potential_plans.push(Plan::from(&[action.to_owned()]));
+                outcome
+            },
+            Err(SearchFailure::NoPlanFound) => GoalOutcome::NoPlanFound,
+            Err(SearchFailure::OverCostBound) => GoalOutcome::OutprioritizedByGoal {
+                goal_name: best_plan.as_ref().map(|(_, _, best_goal)| best_goal.name()).unwrap_or_default(),
+            },
+            Err(SearchFailure::NodeBudgetExhausted) => GoalOutcome::NodeBudgetExhausted,
+        };
+
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.goals.push(GoalTrace {
+                goal_name: goal.name(),
+                priority: goal.priority(),
+                candidate_plans: candidate_plans.unwrap_or_default(),
+                outcome,
+            });
+        }
+
+        if search.budget_exhausted() {
+            tracing::debug!("Exhausted planner node budget while considering goal {}", goal.name());
+            break;
         }
     }
 
-    potential_plans
+    tracing::trace!("Expanded {} planner nodes", search.nodes_expanded);
+
+    if let Some(trace) = trace {
+        trace.nodes_expanded = search.nodes_expanded;
+        trace.node_budget_exhausted = search.budget_exhausted();
+        trace.chosen_plan = best_plan.as_ref().map(|(plan, prioritized_cost, goal)| ChosenPlanTrace {
+            goal_name: goal.name(),
+            actions: plan.action_names(),
+            cost: plan.cost(),
+            prioritized_cost: *prioritized_cost,
+        });
+    }
+
+    if let Some((best_plan, _, goal)) = best_plan {
+        tracing::debug!("Chose plan {best_plan:?} for goal {}", goal.name());
+        best_plan
+    } else {
+        tracing::warn!(
+            "Failed to construct plan for any of {} goals (expanded {} of {} planner nodes) - this is very bad!",
+            goals.len(),
+            search.nodes_expanded,
+            node_budget,
+        );
+        Plan::new()
+    }
 }
 
 /// The best goal is the highest priority goal where the agent doesn't already have all of the desired beliefs.
@@ -304,7 +566,7 @@ fn next_best_goal(
         .collect();
 
     // Note: comparing b's priority to a (instead of comparing a's priority to b) as we want the largest priority goals first
-    goals.sort_by(|a, b| b.priority().partial_cmp(&a.priority()).unwrap());
+    goals.sort_by(|a, b| b.priority().total_cmp(&a.priority()));
 
     tracing::debug!("Prioritized goals: {:?}", goals);
 
@@ -327,9 +589,24 @@ mod tests {
         use crate::ai::action::ActionBuilder;
         use crate::ai::belief::{Belief, SatisfiableBelief};
         use crate::ai::goal::GoalBuilder;
+        use crate::ai::heuristics::cheapest_satisfier::CheapestSatisfierHeuristic;
+        use crate::ai::heuristics::zero::ZeroHeuristic;
         use crate::ai::planner;
         use crate::ai::planner::tests::make_test_agent;
         use crate::ai::test_utils::make_test_game_state;
+        use crate::simulation::config::SimulationConfig;
+
+        fn make_test_goal() -> crate::ai::goal::Goal {
+            GoalBuilder::new()
+                .name("Hold Ball")
+                .priority(1.0)
+                .desired_belief(
+                    SatisfiableBelief::HeldBall()
+                        .ball_id(SatisfiableField::Exactly(1))
+                        .combatant_id(SatisfiableField::Exactly(1))
+                )
+                .build()
+        }
 
         #[test]
         fn big_boi_test() {
@@ -398,9 +675,332 @@ mod tests {
 
             let agent = make_test_agent();
             let game_state = make_test_game_state(None);
-            let plan = planner::make_plan(&agent, game_state, goals, actions);
+            let plan = planner::make_plan(&agent, game_state, goals, &actions, &CheapestSatisfierHeuristic, None);
             assert_eq!(plan.plan().len(), 3);
         }
+
+        #[test]
+        fn test_cheapest_plan_uses_exact_cost() {
+            // Both costs round to the same integer, so only an exact comparison can tell them apart
+            let actions = vec![
+                ActionBuilder::new()
+                    .name("Expensive")
+                    .cost(1.4)
+                    .completion(vec![Belief::HeldBall { ball_id: 1, combatant_id: 1 }])
+                    .build(),
+                ActionBuilder::new()
+                    .name("Cheap")
+                    .cost(1.2)
+                    .completion(vec![Belief::HeldBall { ball_id: 1, combatant_id: 1 }])
+                    .build(),
+            ];
+
+            let agent = make_test_agent();
+            let game_state = make_test_game_state(None);
+            let plan = planner::make_plan(&agent, game_state, vec![make_test_goal()], &actions, &CheapestSatisfierHeuristic, None);
+
+            assert_eq!(plan.plan().len(), 1);
+            assert_eq!(plan.plan()[0].name(), "Cheap");
+        }
+
+        #[test]
+        fn test_heuristic_does_not_change_plan_cost() {
+            let make_actions = || vec![
+                ActionBuilder::new()
+                    .name("Walk To Plate")
+                    .cost(3.0)
+                    .completion(vec![Belief::OnPlate { plate_id: 1, combatant_id: 1 }])
+                    .build(),
+                ActionBuilder::new()
+                    .name("Pick Up On Plate")
+                    .cost(1.0)
+                    .requires(SatisfiableBelief::OnPlate().combatant_id(SatisfiableField::Exactly(1)))
+                    .completion(vec![Belief::HeldBall { ball_id: 1, combatant_id: 1 }])
+                    .build(),
+                ActionBuilder::new()
+                    .name("Dive For Ball")
+                    .cost(4.5)
+                    .completion(vec![Belief::HeldBall { ball_id: 1, combatant_id: 1 }])
+                    .build(),
+            ];
+
+            let agent = make_test_agent();
+            let zero_plan = planner::make_plan(&agent, make_test_game_state(None), vec![make_test_goal()], &make_actions(), &ZeroHeuristic, None);
+            let cheapest_plan = planner::make_plan(&agent, make_test_game_state(None), vec![make_test_goal()], &make_actions(), &CheapestSatisfierHeuristic, None);
+
+            assert!((zero_plan.cost() - 4.0).abs() < f32::EPSILON);
+            assert!((cheapest_plan.cost() - 4.0).abs() < f32::EPSILON);
+            assert_eq!(zero_plan.plan().len(), 2);
+        }
+
+        #[test]
+        fn test_exhausted_node_budget_yields_empty_plan() {
+            let actions = vec![
+                ActionBuilder::new()
+                    .name("Pick Up")
+                    .cost(1.0)
+                    .completion(vec![Belief::HeldBall { ball_id: 1, combatant_id: 1 }])
+                    .build(),
+            ];
+
+            let agent = make_test_agent();
+            let game_state = make_test_game_state(None);
+            game_state.lock().unwrap().simulation_config = SimulationConfig::default().with_planner_node_budget(0);
+
+            let plan = planner::make_plan(&agent, game_state, vec![make_test_goal()], &actions, &CheapestSatisfierHeuristic, None);
+            assert!(plan.plan().is_empty());
+        }
+
+        #[test]
+        fn test_plan_length_is_limited() {
+            let actions = vec![
+                ActionBuilder::new()
+                    .name("Walk To Plate")
+                    .cost(1.0)
+                    .completion(vec![Belief::OnPlate { plate_id: 1, combatant_id: 1 }])
+                    .build(),
+                ActionBuilder::new()
+                    .name("Pick Up On Plate")
+                    .cost(1.0)
+                    .requires(SatisfiableBelief::OnPlate().combatant_id(SatisfiableField::Exactly(1)))
+                    .completion(vec![Belief::HeldBall { ball_id: 1, combatant_id: 1 }])
+                    .build(),
+            ];
+
+            let agent = make_test_agent();
+            let game_state = make_test_game_state(None);
+            game_state.lock().unwrap().simulation_config = SimulationConfig::default().with_planner_max_plan_length(1);
+
+            let plan = planner::make_plan(&agent, game_state, vec![make_test_goal()], &actions, &CheapestSatisfierHeuristic, None);
+            assert!(plan.plan().is_empty());
+        }
+    }
+
+    mod trace {
+        use dys_satisfiable::SatisfiableField;
+        use crate::ai::action::ActionBuilder;
+        use crate::ai::agent::Agent;
+        use crate::ai::belief::{Belief, SatisfiableBelief};
+        use crate::ai::goal::GoalBuilder;
+        use crate::ai::heuristics::cheapest_satisfier::CheapestSatisfierHeuristic;
+        use crate::ai::planner;
+        use crate::ai::planner::tests::make_test_agent;
+        use crate::ai::planner_trace::{GoalOutcome, PlanRejectionReason, PlannerTrace};
+        use crate::ai::test_utils::make_test_game_state;
+
+        fn make_held_ball_goal(name: &str, priority: f32) -> crate::ai::goal::Goal {
+            GoalBuilder::new()
+                .name(name)
+                .priority(priority)
+                .desired_belief(SatisfiableBelief::HeldBall().combatant_id(SatisfiableField::Exactly(1)))
+                .build()
+        }
+
+        #[test]
+        fn test_trace_records_prohibited_rejection_and_chosen_plan() {
+            let actions = vec![
+                ActionBuilder::new()
+                    .name("Pick Up Off Plate")
+                    .cost(1.0)
+                    .prohibits(SatisfiableBelief::OnPlate().combatant_id(SatisfiableField::Exactly(1)))
+                    .completion(vec![Belief::HeldBall { ball_id: 1, combatant_id: 1 }])
+                    .build(),
+                ActionBuilder::new()
+                    .name("Pick Up Anywhere")
+                    .cost(2.0)
+                    .completion(vec![Belief::HeldBall { ball_id: 1, combatant_id: 1 }])
+                    .build(),
+            ];
+
+            let mut agent = make_test_agent();
+            agent.set_beliefs(vec![Belief::OnPlate { plate_id: 1, combatant_id: agent.combatant().id }]);
+
+            let mut trace = PlannerTrace::default();
+            let plan = planner::make_plan(
+                &agent,
+                make_test_game_state(None),
+                vec![make_held_ball_goal("Hold Ball", 2.0)],
+                &actions,
+                &CheapestSatisfierHeuristic,
+                Some(&mut trace),
+            );
+
+            let goal_trace = trace.goal("Hold Ball").unwrap();
+            assert_eq!(goal_trace.candidate_plans.len(), 2);
+            assert!(matches!(
+                &goal_trace.candidate_plans[0].rejection_reason,
+                Some(PlanRejectionReason::ProhibitedBelief { action, .. }) if action == "Pick Up Off Plate"
+            ));
+            assert!(goal_trace.candidate_plans[1].rejection_reason.is_none());
+            assert_eq!(goal_trace.outcome, GoalOutcome::Planned { cost: 2.0, prioritized_cost: 1.0 });
+
+            let chosen_plan = trace.chosen_plan.unwrap();
+            assert_eq!(chosen_plan.goal_name, "Hold Ball");
+            assert_eq!(chosen_plan.actions, vec![String::from("Pick Up Anywhere")]);
+            assert_eq!(plan.action_names(), chosen_plan.actions);
+        }
+
+        #[test]
+        fn test_trace_records_missing_prerequisite_rejection() {
+            let actions = vec![
+                ActionBuilder::new()
+                    .name("Pick Up On Plate")
+                    .cost(1.0)
+                    .requires(SatisfiableBelief::BallIsFlying().ball_id(SatisfiableField::Exactly(1)))
+                    .requires(SatisfiableBelief::OnPlate().combatant_id(SatisfiableField::Exactly(1)))
+                    .completion(vec![Belief::HeldBall { ball_id: 1, combatant_id: 1 }])
+                    .build(),
+                ActionBuilder::new()
+                    .name("Walk To Plate")
+                    .cost(1.0)
+                    .consumes(SatisfiableBelief::BallIsFlying())
+                    .completion(vec![Belief::OnPlate { plate_id: 1, combatant_id: 1 }])
+                    .build(),
+                ActionBuilder::new()
+                    .name("Throw Ball")
+                    .cost(1.0)
+                    .completion(vec![Belief::BallIsFlying { ball_id: 1 }])
+                    .build(),
+            ];
+
+            let mut trace = PlannerTrace::default();
+            let plan = planner::make_plan(
+                &make_test_agent(),
+                make_test_game_state(None),
+                vec![make_held_ball_goal("Hold Ball", 1.0)],
+                &actions,
+                &CheapestSatisfierHeuristic,
+                Some(&mut trace),
+            );
+
+            assert!(plan.plan().is_empty());
+            assert!(trace.chosen_plan.is_none());
+
+            let goal_trace = trace.goal("Hold Ball").unwrap();
+            assert_eq!(goal_trace.outcome, GoalOutcome::NoPlanFound);
+            assert_eq!(goal_trace.candidate_plans.len(), 1);
+            assert_eq!(
+                goal_trace.candidate_plans[0].actions,
+                vec![String::from("Throw Ball"), String::from("Walk To Plate"), String::from("Pick Up On Plate")]
+            );
+            assert!(matches!(
+                &goal_trace.candidate_plans[0].rejection_reason,
+                Some(PlanRejectionReason::MissingPrerequisiteBelief { action, .. }) if action == "Pick Up On Plate"
+            ));
+        }
+
+        #[test]
+        fn test_trace_records_outprioritized_goal() {
+            let actions = vec![
+                ActionBuilder::new()
+                    .name("Pick Up")
+                    .cost(4.0)
+                    .completion(vec![Belief::HeldBall { ball_id: 1, combatant_id: 1 }])
+                    .build(),
+            ];
+
+            let goals = vec![
+                make_held_ball_goal("Important", 4.0),
+                make_held_ball_goal("Unimportant", 1.0),
+            ];
+
+            let mut trace = PlannerTrace::default();
+            planner::make_plan(&make_test_agent(), make_test_game_state(None), goals, &actions, &CheapestSatisfierHeuristic, Some(&mut trace));
+
+            assert_eq!(trace.goals.len(), 2);
+            assert_eq!(trace.goal("Important").unwrap().outcome, GoalOutcome::Planned { cost: 4.0, prioritized_cost: 1.0 });
+            assert_eq!(
+                trace.goal("Unimportant").unwrap().outcome,
+                GoalOutcome::OutprioritizedByGoal { goal_name: String::from("Important") }
+            );
+            assert_eq!(trace.chosen_plan.unwrap().goal_name, "Important");
+            assert!(!trace.node_budget_exhausted);
+        }
+
+        #[test]
+        fn test_zero_priority_goal_is_planned_as_last_resort() {
+            let actions = vec![
+                ActionBuilder::new()
+                    .name("Pick Up")
+                    .cost(0.0)
+                    .completion(vec![Belief::HeldBall { ball_id: 1, combatant_id: 1 }])
+                    .build(),
+            ];
+
+            let mut trace = PlannerTrace::default();
+            let plan = planner::make_plan(
+                &make_test_agent(),
+                make_test_game_state(None),
+                vec![make_held_ball_goal("Pointless", 0.0)],
+                &actions,
+                &CheapestSatisfierHeuristic,
+                Some(&mut trace),
+            );
+
+            assert_eq!(plan.action_names(), vec![String::from("Pick Up")]);
+            assert_eq!(trace.goal("Pointless").unwrap().outcome, GoalOutcome::Planned { cost: 0.0, prioritized_cost: 0.0 });
+        }
+    }
+
+    mod planner_state {
+        use dys_satisfiable::SatisfiableField;
+        use crate::ai::action::ActionBuilder;
+        use crate::ai::belief::{Belief, SatisfiableBelief};
+        use crate::ai::goal::GoalBuilder;
+        use crate::ai::heuristics::cheapest_satisfier::CheapestSatisfierHeuristic;
+        use crate::ai::planner;
+        use crate::ai::planner::tests::make_test_agent;
+        use crate::ai::test_utils::make_test_game_state;
+
+        #[test]
+        fn test_same_actions_in_another_order_are_still_searched() {
+            // Either pick up can be regressed first, and both need the ball the agent believes is flying,
+            // but only catching it before diving keeps it from being in the air while diving
+            let actions = vec![
+                ActionBuilder::new()
+                    .name("Catch Ball")
+                    .cost(1.0)
+                    .requires(SatisfiableBelief::BallIsFlying().ball_id(SatisfiableField::Exactly(1)))
+                    .consumes(SatisfiableBelief::BallIsFlying().ball_id(SatisfiableField::Exactly(1)))
+                    .completion(vec![
+                        Belief::HeldBall { ball_id: 1, combatant_id: 1 },
+                        Belief::OnPlate { plate_id: 1, combatant_id: 1 },
+                    ])
+                    .build(),
+                ActionBuilder::new()
+                    .name("Dive Onto Plate")
+                    .cost(1.0)
+                    .prohibits(SatisfiableBelief::BallIsFlying().ball_id(SatisfiableField::Exactly(1)))
+                    .completion(vec![
+                        Belief::HeldBall { ball_id: 1, combatant_id: 1 },
+                        Belief::OnPlate { plate_id: 1, combatant_id: 1 },
+                    ])
+                    .build(),
+                ActionBuilder::new()
+                    .name("Throw Other Ball")
+                    .cost(1.0)
+                    .completion(vec![Belief::BallIsFlying { ball_id: 2 }])
+                    .build(),
+            ];
+
+            let goal = GoalBuilder::new()
+                .name("Hold Ball On Plate")
+                .priority(1.0)
+                .desired_belief(SatisfiableBelief::BallIsFlying().ball_id(SatisfiableField::Exactly(2)))
+                .desired_belief(SatisfiableBelief::HeldBall().combatant_id(SatisfiableField::Exactly(1)))
+                .desired_belief(SatisfiableBelief::OnPlate().combatant_id(SatisfiableField::Exactly(1)))
+                .build();
+
+            let mut agent = make_test_agent();
+            agent.set_beliefs(vec![Belief::BallIsFlying { ball_id: 1 }]);
+
+            // Diving then catching is searched first, and fails validation, as the ball is still flying during the dive
+            let plan = planner::make_plan(&agent, make_test_game_state(None), vec![goal], &actions, &CheapestSatisfierHeuristic, None);
+            assert_eq!(
+                plan.action_names(),
+                vec![String::from("Throw Other Ball"), String::from("Catch Ball"), String::from("Dive Onto Plate")]
+            );
+        }
     }
 
     mod next_best_goal {
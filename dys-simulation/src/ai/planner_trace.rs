@@ -0,0 +1,105 @@
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+
+/// A record of the decisions the planner made while choosing a plan.
+/// Only produced when planner traces are enabled in the simulation config.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PlannerTrace {
+    /// Goals considered by the planner, in the order they were considered (highest priority first).
+    pub goals: Vec<GoalTrace>,
+
+    /// The plan the planner settled on, if any.
+    pub chosen_plan: Option<ChosenPlanTrace>,
+
+    /// Number of planner states expanded across all goals.
+    pub nodes_expanded: u32,
+
+    /// Did the planner stop considering goals because it ran out of node budget?
+    pub node_budget_exhausted: bool,
+}
+
+impl PlannerTrace {
+    #[must_use]
+    pub fn goal(&self, goal_name: &str) -> Option<&GoalTrace> {
+        self.goals.iter().find(|goal| goal.goal_name == goal_name)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GoalTrace {
+    pub goal_name: String,
+    pub priority: f32,
+
+    /// Complete plans found for this goal, in the order the search found them.
+    pub candidate_plans: Vec<CandidatePlanTrace>,
+
+    pub outcome: GoalOutcome,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GoalOutcome {
+    /// A valid plan was found for the goal.
+    Planned { cost: f32, prioritized_cost: f32 },
+
+    /// No valid plan exists for the goal within the maximum plan length.
+    NoPlanFound,
+
+    /// Any plan for the goal would have a worse prioritized cost than a plan for a previously considered goal.
+    OutprioritizedByGoal { goal_name: String },
+
+    /// The planner ran out of node budget before finding a plan for the goal.
+    NodeBudgetExhausted,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CandidatePlanTrace {
+    /// Names of the actions in the plan, in execution order.
+    pub actions: Vec<String>,
+    pub cost: f32,
+
+    /// Why the plan was rejected, or None if the plan was accepted.
+    pub rejection_reason: Option<PlanRejectionReason>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PlanRejectionReason {
+    /// An action in the plan would be performed while one of its prohibited beliefs is held.
+    ProhibitedBelief { action: String, belief_test: String },
+
+    /// An action in the plan would be performed without one of its prerequisite beliefs held.
+    MissingPrerequisiteBelief { action: String, belief_test: String },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChosenPlanTrace {
+    pub goal_name: String,
+
+    /// Names of the actions in the plan, in execution order.
+    pub actions: Vec<String>,
+    pub cost: f32,
+    pub prioritized_cost: f32,
+}
+
+impl Display for PlanRejectionReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanRejectionReason::ProhibitedBelief { action, belief_test } => {
+                write!(f, "({action}) prohibits {belief_test}")
+            },
+            PlanRejectionReason::MissingPrerequisiteBelief { action, belief_test } => {
+                write!(f, "({action}) requires {belief_test}")
+            },
+        }
+    }
+}
+
+impl Display for GoalOutcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GoalOutcome::Planned { cost, prioritized_cost } => write!(f, "planned (cost {cost}, prioritized cost {prioritized_cost})"),
+            GoalOutcome::NoPlanFound => write!(f, "no plan found"),
+            GoalOutcome::OutprioritizedByGoal { goal_name } => write!(f, "outprioritized by {goal_name}"),
+            GoalOutcome::NodeBudgetExhausted => write!(f, "node budget exhausted"),
+        }
+    }
+}
@@ -2,13 +2,19 @@ use std::fmt::Debug;
 use std::sync::{Arc, Mutex};
 use dyn_clone::DynClone;
 use rapier3d::prelude::Pose3;
+use serde::{Deserialize, Serialize};
 use crate::ai::belief::ExpiringBelief;
+use crate::ai::sensors::field_of_view::FieldOfViewSensor;
+use crate::ai::sensors::proximity::ProximitySensor;
 use crate::game_state::GameState;
 
 pub trait Sensor: DynClone + Debug {
     fn set_enabled(&mut self, enabled: bool);
     fn enabled(&self) -> bool;
     fn set_yields_beliefs(&mut self, _yields_beliefs: bool) {}
+
+    /// Captures the sensor, so that it can be saved and picked up again later.
+    fn snapshot(&self) -> SensorSnapshot;
     fn sense(
         &self,
         combatant_isometry: Pose3,
@@ -16,4 +22,20 @@ pub trait Sensor: DynClone + Debug {
     ) -> (bool, Vec<ExpiringBelief>);
 }
 
-dyn_clone::clone_trait_object!(Sensor);
\ No newline at end of file
+dyn_clone::clone_trait_object!(Sensor);
+
+/// A sensor, which unlike a boxed sensor can be serialized.
+#[derive(Serialize, Deserialize)]
+pub enum SensorSnapshot {
+    FieldOfView(FieldOfViewSensor),
+    Proximity(ProximitySensor),
+}
+
+impl SensorSnapshot {
+    pub fn restore(self) -> Box<dyn Sensor> {
+        match self {
+            SensorSnapshot::FieldOfView(sensor) => Box::new(sensor),
+            SensorSnapshot::Proximity(sensor) => Box::new(sensor),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
 use rapier3d::geometry::{ColliderHandle, Cuboid};
 use rapier3d::glamx::vec3;
 use rapier3d::prelude::*;
@@ -6,13 +7,13 @@ use rapier3d::pipeline::QueryFilter;
 use rapier3d::utils::PoseOps;
 use dys_world::combatant::instance::CombatantInstanceId;
 use crate::ai::belief::{Belief, ExpiringBelief};
-use crate::ai::sensor::Sensor;
+use crate::ai::sensor::{Sensor, SensorSnapshot};
 use crate::game_objects::ball::BallState;
 use crate::game_objects::game_object::GameObject;
 use crate::game_objects::game_object_type::GameObjectType;
 use crate::game_state::GameState;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct FieldOfViewSensor {
     enabled: bool,
     shape: Cuboid,
@@ -52,6 +53,10 @@ impl Sensor for FieldOfViewSensor {
         self.enabled
     }
 
+    fn snapshot(&self) -> SensorSnapshot {
+        SensorSnapshot::FieldOfView(self.clone())
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
     fn sense(
         &self,
@@ -202,6 +207,7 @@ mod tests {
     use rapier3d::prelude::*;
     use dys_satisfiable::{SatisfiabilityTest, SatisfiableField};
     use dys_world::arena::barrier::{ArenaBarrier, BarrierPathing};
+    use dys_world::arena::environment::GameEnvironment;
     use dys_world::arena::feature::ArenaFeature;
     use dys_world::generator::Generator;
     use GameObjectType::Combatant;
@@ -209,7 +215,7 @@ mod tests {
     use crate::ai::sensor::Sensor;
     use crate::ai::sensors::field_of_view::FieldOfViewSensor;
     use crate::ai::test_utils::make_test_game_state;
-    use crate::game_objects::combatant::{CombatantObject, TeamAlignment};
+    use crate::game_objects::combatant::{CombatantObject, CombatantSpawn, TeamAlignment};
     use crate::game_objects::game_object_type::GameObjectType;
     use crate::game_objects::game_object_type::GameObjectType::Barrier;
     use crate::game_state::{CollidersMapT, CombatantsMapT};
@@ -229,7 +235,7 @@ mod tests {
         // Combatant 3 is behind combatant 1
         let combatant_3_position = vec3(1.0, 0.0, -3.0);
 
-        let physics_sim = PhysicsSim::new(10);
+        let physics_sim = PhysicsSim::new(10, &GameEnvironment::default());
         let game_state = make_test_game_state(Some(physics_sim));
         let (combatant_1_collider_handle, combatant_1, active_colliders, combatants) = {
             let mut game_state = game_state.lock().unwrap();
@@ -239,31 +245,40 @@ mod tests {
             ) = game_state.physics_sim.sets_mut();
 
             let combatant_1 = CombatantObject::new(
-                1,
-                combatant_1_instance,
-                combatant_1_position,
-                Vec3::ZERO,
-                TeamAlignment::Home,
+                CombatantSpawn {
+                    id: 1,
+                    combatant: combatant_1_instance,
+                    position: combatant_1_position,
+                    rotation: Vec3::ZERO,
+                    team: TeamAlignment::Home,
+                },
+                &GameEnvironment::default(),
                 rigid_body_set,
                 collider_set,
             );
 
             let combatant_2 = CombatantObject::new(
-                2,
-                combatant_2_instance,
-                combatant_2_position,
-                Vec3::ZERO,
-                TeamAlignment::Home,
+                CombatantSpawn {
+                    id: 2,
+                    combatant: combatant_2_instance,
+                    position: combatant_2_position,
+                    rotation: Vec3::ZERO,
+                    team: TeamAlignment::Home,
+                },
+                &GameEnvironment::default(),
                 rigid_body_set,
                 collider_set,
             );
 
             let combatant_3 = CombatantObject::new(
-                3,
-                combatant_3_instance,
-                combatant_3_position,
-                Vec3::ZERO,
-                TeamAlignment::Away,
+                CombatantSpawn {
+                    id: 3,
+                    combatant: combatant_3_instance,
+                    position: combatant_3_position,
+                    rotation: Vec3::ZERO,
+                    team: TeamAlignment::Away,
+                },
+                &GameEnvironment::default(),
                 rigid_body_set,
                 collider_set,
             );
@@ -392,7 +407,7 @@ mod tests {
         let wall_position = vec3(1.0, 0.0, 1.5);
         let wall_size = vec3(5.0, 5.0, 0.5);
 
-        let physics_sim = PhysicsSim::new(10);
+        let physics_sim = PhysicsSim::new(10, &GameEnvironment::default());
         let game_state = make_test_game_state(Some(physics_sim));
         let (combatant_1_collider_handle, combatant_1, active_colliders, combatants) = {
             let mut game_state = game_state.lock().unwrap();
@@ -402,21 +417,27 @@ mod tests {
             ) = game_state.physics_sim.sets_mut();
 
             let combatant_1 = CombatantObject::new(
-                1,
-                combatant_1_instance,
-                combatant_1_position,
-                Vec3::ZERO,
-                TeamAlignment::Home,
+                CombatantSpawn {
+                    id: 1,
+                    combatant: combatant_1_instance,
+                    position: combatant_1_position,
+                    rotation: Vec3::ZERO,
+                    team: TeamAlignment::Home,
+                },
+                &GameEnvironment::default(),
                 rigid_body_set,
                 collider_set,
             );
 
             let combatant_2 = CombatantObject::new(
-                2,
-                combatant_2_instance,
-                combatant_2_position,
-                Vec3::ZERO,
-                TeamAlignment::Home,
+                CombatantSpawn {
+                    id: 2,
+                    combatant: combatant_2_instance,
+                    position: combatant_2_position,
+                    rotation: Vec3::ZERO,
+                    team: TeamAlignment::Home,
+                },
+                &GameEnvironment::default(),
                 rigid_body_set,
                 collider_set,
             );
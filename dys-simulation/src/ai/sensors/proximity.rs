@@ -1,16 +1,17 @@
 use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
 use rapier3d::geometry::ColliderHandle;
 use rapier3d::pipeline::QueryFilter;
 use rapier3d::prelude::{Cylinder, Pose3};
 use dys_world::combatant::instance::CombatantInstanceId;
 use crate::ai::belief::{Belief, ExpiringBelief};
-use crate::ai::sensor::Sensor;
+use crate::ai::sensor::{Sensor, SensorSnapshot};
 use crate::game_objects::ball::BallState;
 use crate::game_objects::game_object_type::GameObjectType;
 use crate::game_state::GameState;
 
 /// ProximitySensors are used to detect events in a cylindrical area around the agent.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ProximitySensor {
     enabled: bool,
     shape: Cylinder,
@@ -49,6 +50,10 @@ impl Sensor for ProximitySensor {
         self.enabled
     }
 
+    fn snapshot(&self) -> SensorSnapshot {
+        SensorSnapshot::Proximity(self.clone())
+    }
+
     fn set_yields_beliefs(&mut self, yields_beliefs: bool) {
         self.yields_beliefs = yields_beliefs;
     }
@@ -0,0 +1,247 @@
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use rapier3d::prelude::Vec3;
+use dys_world::combatant::instance::CombatantInstanceId;
+use crate::ai::agent::Agent;
+use crate::ai::belief::Belief;
+use crate::ai::beliefs::belief_set::BeliefSet;
+use crate::ai::strategies::move_to_location::MoveToLocationStrategy;
+use crate::ai::strategy::{Strategy, StrategySnapshot};
+use crate::game_state::GameState;
+use crate::simulation::simulation_event::PendingSimulationEvent;
+
+/// What a body-blocking combatant is standing in front of.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum BlockedObject {
+    /// A fixed location, such as a plate.
+    Location(Vec3),
+
+    /// A (typically friendly) combatant.
+    Combatant(CombatantInstanceId),
+}
+
+/// Where along the line between the threat and the blocked object the blocker stands.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum BlockingPosition {
+    /// Stand this far from the blocked object, towards the threat (eg guarding a plate).
+    FromBlocked(f32),
+
+    /// Stand this far from the threat, towards the blocked object (eg cutting off a runner).
+    FromThreat(f32),
+}
+
+/// Moves the combatant between a threatening combatant and whatever they're threatening.
+/// The threat's position is taken from the combatant's beliefs, and the blocking position is
+/// re-evaluated each tick as the threat moves.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BodyBlockStrategy {
+    self_combatant_id: CombatantInstanceId,
+    threat_combatant_id: CombatantInstanceId,
+    blocked: BlockedObject,
+    blocking_position: BlockingPosition,
+    move_strategy: Option<(Vec3, MoveToLocationStrategy)>,
+    max_ticks: u16,
+    is_complete: bool,
+}
+
+impl BodyBlockStrategy {
+    pub fn new(
+        self_combatant_id: CombatantInstanceId,
+        threat_combatant_id: CombatantInstanceId,
+        blocked: BlockedObject,
+        blocking_position: BlockingPosition,
+        max_ticks: u16,
+    ) -> BodyBlockStrategy {
+        BodyBlockStrategy {
+            self_combatant_id,
+            threat_combatant_id,
+            blocked,
+            blocking_position,
+            move_strategy: None,
+            max_ticks,
+            is_complete: false,
+        }
+    }
+
+    fn believed_threat_position(&self, beliefs: &BeliefSet) -> Option<Vec3> {
+        beliefs
+            .beliefs()
+            .into_iter()
+            .find_map(|belief| match belief {
+                Belief::CombatantPosition { combatant_id, position } if combatant_id == self.threat_combatant_id => Some(position),
+                _ => None,
+            })
+    }
+}
+
+/// Returns the point along the line between the threat and the blocked position where a blocker should stand.
+pub(crate) fn blocking_point(threat_position: Vec3, blocked_position: Vec3, blocking_position: BlockingPosition) -> Vec3 {
+    let blocked_to_threat = threat_position - blocked_position;
+    let distance = blocked_to_threat.length();
+    let direction = blocked_to_threat.normalize_or_zero();
+
+    match blocking_position {
+        BlockingPosition::FromBlocked(standoff) => blocked_position + direction * standoff.min(distance),
+        BlockingPosition::FromThreat(standoff) => threat_position - direction * standoff.min(distance),
+    }
+}
+
+impl Strategy for BodyBlockStrategy {
+    fn name(&self) -> String {
+        String::from("Body Block")
+    }
+
+    fn snapshot(&self) -> StrategySnapshot {
+        StrategySnapshot::BodyBlock(self.clone())
+    }
+
+    fn can_perform(&self, owned_beliefs: &BeliefSet) -> bool {
+        self.believed_threat_position(owned_beliefs).is_some()
+    }
+
+    fn should_interrupt(&self, owned_beliefs: &BeliefSet) -> bool {
+        self.max_ticks == 0 || self.believed_threat_position(owned_beliefs).is_none()
+    }
+
+    fn is_complete(&self) -> bool {
+        self.is_complete
+    }
+
+    #[tracing::instrument(
+        name = "body_block::tick",
+        skip_all,
+        level = "trace"
+    )]
+    fn tick(
+        &mut self,
+        agent: &dyn Agent,
+        game_state: Arc<Mutex<GameState>>,
+    ) -> Option<Vec<PendingSimulationEvent>> {
+        self.max_ticks = self.max_ticks.saturating_sub(1);
+
+        let threat_position = self.believed_threat_position(&agent.beliefs())?;
+
+        let (blocked_position, unit_resolution) = {
+            let game_state = game_state.lock().unwrap();
+            let blocked_position = match self.blocked {
+                BlockedObject::Location(location) => location,
+                BlockedObject::Combatant(combatant_id) => {
+                    let (rigid_body_set, _) = game_state.physics_sim.sets();
+                    let combatant_object = game_state.combatants.get(&combatant_id)?;
+                    rigid_body_set.get(combatant_object.rigid_body_handle)?.translation()
+                },
+            };
+
+            (blocked_position, game_state.arena_navmesh.config().unit_resolution)
+        };
+
+        let target = blocking_point(threat_position, blocked_position, self.blocking_position);
+
+        // Only re-path if the threat has moved enough to change where we should be standing
+        let should_repath = self
+            .move_strategy
+            .as_ref()
+            .is_none_or(|(previous_target, _)| (*previous_target - target).length() > unit_resolution);
+
+        if should_repath {
+            self.move_strategy = Some((target, MoveToLocationStrategy::new(self.self_combatant_id, target, self.max_ticks)));
+        }
+
+        let (_, move_strategy) = self.move_strategy.as_mut()?;
+        let events = move_strategy.tick(agent, game_state);
+
+        // Once we're in position, we've done our job - the planner will decide if we should keep blocking
+        if move_strategy.is_complete() {
+            self.is_complete = true;
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use rapier3d::glamx::vec3;
+    use rapier3d::prelude::Vec3;
+    use crate::ai::action::ActionBuilder;
+    use crate::ai::belief::Belief;
+    use crate::ai::test_utils::make_generated_game;
+    use crate::game_objects::game_object::GameObject;
+    use crate::game_state::GameState;
+    use crate::simulation::simulate_tick;
+    use super::{blocking_point, BlockedObject, BlockingPosition, BodyBlockStrategy};
+
+    #[test]
+    fn test_blocking_point_from_blocked() {
+        let point = blocking_point(vec3(10.0, 0.0, 0.0), vec3(0.0, 0.0, 0.0), BlockingPosition::FromBlocked(3.0));
+        assert!((point - vec3(3.0, 0.0, 0.0)).length() < 0.0001);
+    }
+
+    #[test]
+    fn test_blocking_point_from_threat() {
+        let point = blocking_point(vec3(10.0, 0.0, 0.0), vec3(0.0, 0.0, 0.0), BlockingPosition::FromThreat(3.0));
+        assert!((point - vec3(7.0, 0.0, 0.0)).length() < 0.0001);
+    }
+
+    #[test]
+    fn test_blocking_point_never_overshoots() {
+        let from_blocked = blocking_point(vec3(2.0, 0.0, 0.0), vec3(0.0, 0.0, 0.0), BlockingPosition::FromBlocked(5.0));
+        let from_threat = blocking_point(vec3(2.0, 0.0, 0.0), vec3(0.0, 0.0, 0.0), BlockingPosition::FromThreat(5.0));
+
+        assert!((from_blocked - vec3(2.0, 0.0, 0.0)).length() < 0.0001);
+        assert!(from_threat.length() < 0.0001);
+    }
+
+    #[test]
+    fn test_defender_takes_up_blocking_position() {
+        let game_state = Arc::new(Mutex::new(GameState::from_game_seeded(make_generated_game(), &[0; 32])));
+        let (defender, plate_position, defender_position) = {
+            let game_state = game_state.lock().unwrap();
+            let (rigid_body_set, collider_set) = game_state.physics_sim.sets();
+            let defender = game_state.combatants.values().next().unwrap().clone();
+            let plate_collider_handle = game_state.plates.values().next().unwrap().collider_handle().unwrap();
+            let plate_position = collider_set.get(plate_collider_handle).unwrap().translation();
+            let defender_position = rigid_body_set.get(defender.rigid_body_handle).unwrap().translation();
+            (defender, plate_position, defender_position)
+        };
+
+        // An enemy standing where the defender starts, so the defender has to cross towards the plate to block them
+        let threat_id = 999;
+        let blocking_position = BlockingPosition::FromBlocked(3.0);
+        let target = blocking_point(defender_position, plate_position, blocking_position);
+        {
+            let mut combatant_state = defender.combatant_state.lock().unwrap();
+            combatant_state.beliefs.add_belief(Belief::CombatantPosition { combatant_id: threat_id, position: defender_position });
+            combatant_state.current_action = Some(
+                ActionBuilder::new()
+                    .name("Guard Plate")
+                    .strategy(BodyBlockStrategy::new(defender.id, threat_id, BlockedObject::Location(plate_position), blocking_position, 500))
+                    .build()
+            );
+        }
+
+        let horizontal_distance = |a: Vec3, b: Vec3| vec3(a.x - b.x, 0.0, a.z - b.z).length();
+        for _ in 0..500 {
+            simulate_tick(game_state.clone());
+
+            let guarded = defender
+                .combatant_state
+                .lock()
+                .unwrap()
+                .completed_action
+                .as_ref()
+                .is_some_and(|action| action.name() == "Guard Plate");
+
+            if guarded {
+                let game_state = game_state.lock().unwrap();
+                let (rigid_body_set, _) = game_state.physics_sim.sets();
+                let position = rigid_body_set.get(defender.rigid_body_handle).unwrap().translation();
+                assert!(horizontal_distance(position, target) < 1.0, "defender stopped at {position:?}, not {target:?}");
+                return;
+            }
+        }
+
+        panic!("defender never took up the blocking position");
+    }
+}
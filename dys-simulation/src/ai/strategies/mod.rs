@@ -1,3 +1,4 @@
+pub(super) mod body_block;
 pub(super) mod noop;
 pub(super) mod move_to_location;
 pub(super) mod pick_up_ball;
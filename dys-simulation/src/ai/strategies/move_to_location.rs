@@ -1,14 +1,30 @@
 use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
 use rapier3d::glamx::vec3;
-use dys_world::arena::navmesh::{ArenaNavmeshNode, ArenaNavmeshPath};
+use dys_world::arena::feature::NavmeshAreaType;
+use dys_world::arena::navmesh::{ArenaNavmeshNode, ArenaNavmeshPath, NavmeshAreaShape, NavmeshCostArea, NavmeshCostProfile};
 use rapier3d::prelude::*;
 use dys_world::combatant::instance::CombatantInstanceId;
-use crate::{ai::{agent::Agent, strategy::Strategy}, game_state::GameState, simulation::simulation_event::SimulationEvent};
+use crate::{ai::{agent::Agent, strategy::{Strategy, StrategySnapshot}}, game_state::GameState, simulation::simulation_event::SimulationEvent};
 use crate::ai::beliefs::belief_set::BeliefSet;
+use crate::game_objects::ball::BallState;
 use crate::game_objects::game_object::GameObject;
 use crate::game_objects::game_object_type::GameObjectType;
 use crate::simulation::simulation_event::PendingSimulationEvent;
 
+/// How far ahead of themselves combatants look for other combatants to steer around.
+const AVOIDANCE_LOOKAHEAD: f32 = 2.0;
+
+/// Combatants making less than this fraction of their desired progress in a tick are considered blocked.
+const BLOCKED_PROGRESS_RATIO: f32 = 0.25;
+
+/// How many consecutive blocked ticks before a combatant re-paths around whoever is blocking them.
+const REPATH_AFTER_BLOCKED_TICKS: u8 = 3;
+
+/// How close a combatant must end up to a path node to have reached it.
+const NODE_REACHED_DISTANCE: f32 = 0.001;
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct MoveToLocationStrategy {
     is_complete: bool,
     path: ArenaNavmeshPath,
@@ -19,6 +35,7 @@ pub struct MoveToLocationStrategy {
     target_location: Vec3,
     max_ticks: u16,
     dynamic_pathing: bool,
+    blocked_ticks: u8,
 }
 
 impl MoveToLocationStrategy {
@@ -40,6 +57,7 @@ impl MoveToLocationStrategy {
             target_location,
             max_ticks,
             dynamic_pathing: false,
+            blocked_ticks: 0,
         }
     }
 
@@ -58,6 +76,7 @@ impl MoveToLocationStrategy {
             target_location: vec3(0.0, 0.0, 0.0),
             max_ticks,
             dynamic_pathing: false,
+            blocked_ticks: 0,
         }
     }
 
@@ -75,6 +94,7 @@ impl MoveToLocationStrategy {
             target_location: vec3(0.0, 0.0, 0.0),
             max_ticks: u16::MAX,
             dynamic_pathing: true,
+            blocked_ticks: 0,
         }
     }
 }
@@ -84,8 +104,10 @@ impl MoveToLocationStrategy {
     fn compute_path(
         &mut self,
         game_state: Arc<Mutex<GameState>>,
+        obstacles: &[Vec3],
+        obstacle_radius: f32,
     ) -> ArenaNavmeshPath {
-        let game_state = game_state.lock().unwrap();
+        let mut game_state = game_state.lock().unwrap();
 
         if self.start_location.is_none() {
             let start_location = {
@@ -122,11 +144,62 @@ impl MoveToLocationStrategy {
             };
         }
 
+        let mut cost_profile = NavmeshCostProfile::default();
+        cost_profile.areas.extend(game_state.kinematic_navmesh_areas());
+        for obstacle in obstacles {
+            cost_profile.areas.push(NavmeshCostArea {
+                area_type: NavmeshAreaType::Obstacle,
+                center: *obstacle,
+                shape: NavmeshAreaShape::Circle { radius: obstacle_radius },
+            });
+        }
+
+        // Stay out of the blast radius of balls in flight, unless that's the ball we're going after
+        let (rigid_body_set, _) = game_state.physics_sim.sets();
+        for (ball_id, ball_object) in &game_state.balls {
+            if !matches!(ball_object.state, BallState::ThrownAtTarget { .. })
+                || self.target_game_object == Some(GameObjectType::Ball(*ball_id)) {
+                continue;
+            }
+
+            let Some(ball_rigid_body) = ball_object.rigid_body_handle().and_then(|handle| rigid_body_set.get(handle)) else {
+                continue;
+            };
+
+            cost_profile.areas.push(NavmeshCostArea {
+                area_type: NavmeshAreaType::Danger,
+                center: ball_rigid_body.translation(),
+                shape: NavmeshAreaShape::Circle { radius: ball_object.explosion_radius() },
+            });
+        }
+
+        let game_state = &mut *game_state;
         game_state
             .arena_navmesh
-            .create_path(self.start_location.unwrap(), self.target_location)
+            .create_path_with_cache(self.start_location.unwrap(), self.target_location, &cost_profile, &mut game_state.path_cache)
             .unwrap_or(ArenaNavmeshPath::empty())
     }
+
+    /// Moves along the path past the nodes reached this tick, re-pathing from where we ended up if the target may have moved.
+    fn advance_path(&mut self, game_state: Arc<Mutex<GameState>>, combatant_position: Vec3, nodes_reached: usize) {
+        if nodes_reached > 0 && self.dynamic_pathing {
+            self.start_location = Some(combatant_position);
+            self.path = self.compute_path(game_state, &[], 0.0);
+            // The first node is where we're already standing
+            let _ = self.path.next_node();
+            if let Some(next_node) = self.path.next_node() {
+                self.next_node = Some(next_node);
+            }
+            return;
+        }
+
+        for _ in 0..nodes_reached {
+            let Some(next_node) = self.path.next_node() else {
+                break;
+            };
+            self.next_node = Some(next_node);
+        }
+    }
 }
 
 impl Strategy for MoveToLocationStrategy {
@@ -134,6 +207,10 @@ impl Strategy for MoveToLocationStrategy {
         String::from("Move to Location")
     }
 
+    fn snapshot(&self) -> StrategySnapshot {
+        StrategySnapshot::MoveToLocation(self.clone())
+    }
+
     fn can_perform(&self, _: &BeliefSet) -> bool {
         // If we have no path, we are either uninitialized, or have nowhere to go
         // In either case, we can perform this action, in which this is either a no-op
@@ -167,7 +244,7 @@ impl Strategy for MoveToLocationStrategy {
 
         self.max_ticks = self.max_ticks.saturating_sub(1);
 
-        let (combatant_isometry, unit_resolution) = {
+        let (combatant_isometry, unit_resolution, arena_navmesh, neighbors) = {
             let game_state = game_state.lock().unwrap();
 
             let (rigid_body_set, _) = game_state.physics_sim.sets();
@@ -179,65 +256,86 @@ impl Strategy for MoveToLocationStrategy {
 
             let unit_resolution = game_state.arena_navmesh.config().unit_resolution;
 
-            (combatant_pos, unit_resolution)
+            // Other combatants we may need to steer around - the combatant we're moving to isn't in our way
+            let neighbors = game_state
+                .combatants
+                .values()
+                .filter(|other| other.id != agent.combatant().id)
+                .filter(|other| self.target_game_object != Some(GameObjectType::Combatant(other.id)))
+                .filter_map(|other| rigid_body_set.get(other.rigid_body_handle))
+                .map(RigidBody::translation)
+                .collect::<Vec<_>>();
+
+            (combatant_pos, unit_resolution, game_state.arena_navmesh.clone(), neighbors)
         };
 
-        let mut combatant_position = combatant_isometry.translation;
+        let start_position = combatant_isometry.translation;
+        let mut combatant_position = start_position;
 
         if self.dynamic_pathing {
             self.start_location = Some(combatant_position);
             // 1. Always finish path to next node if exists
             if self.next_node.is_none() {
-                self.path = self.compute_path(game_state.clone());
+                self.path = self.compute_path(game_state.clone(), &[], 0.0);
                 self.next_node = self.path.next_node();
             }
         }
         else if self.path.is_empty() && self.next_node.is_none() {
-            self.path = self.compute_path(game_state.clone());
+            self.path = self.compute_path(game_state.clone(), &[], 0.0);
             self.next_node = self.path.next_node();
         }
 
-        let mut total_distance_can_travel_this_tick = agent.combatant().combatant.lock().unwrap().move_speed();
+        let move_speed = agent.combatant().combatant.lock().unwrap().move_speed();
+        let mut total_distance_can_travel_this_tick = move_speed * agent.combatant().fatigue_modifier();
 
-        while total_distance_can_travel_this_tick > 0.0 {
-            let Some(next_node) = self.next_node else {
+        // Look ahead along the path without using up any nodes, as steering may stop us short of them
+        let mut nodes_reached = 0;
+        let upcoming_nodes = self.next_node.iter().chain(self.path.upcoming_nodes());
+        for node in upcoming_nodes {
+            if total_distance_can_travel_this_tick <= 0.0 {
                 break;
-            };
-
-            let difference_vector = next_node.as_vector() - combatant_position;
-            let (updated_position, distance_traveled) = {
-                if total_distance_can_travel_this_tick >= difference_vector.length() {
-                    (next_node.as_vector(), difference_vector.length())
-                } else {
-                    let partial_vector = difference_vector.normalize() * total_distance_can_travel_this_tick;
-
-                    (combatant_position + partial_vector, partial_vector.length())
-                }
-            };
+            }
 
-            if distance_traveled == 0.0 {
-                total_distance_can_travel_this_tick = 0.0;
+            let difference_vector = node.as_vector() - combatant_position;
+            if total_distance_can_travel_this_tick >= difference_vector.length() {
+                combatant_position = node.as_vector();
+                total_distance_can_travel_this_tick -= difference_vector.length();
+                nodes_reached += 1;
             } else {
-                combatant_position = updated_position;
-                total_distance_can_travel_this_tick = (total_distance_can_travel_this_tick - distance_traveled).max(0.0);
+                combatant_position += difference_vector.normalize() * total_distance_can_travel_this_tick;
+                break;
             }
+        }
 
-            let distance_from_node = (next_node.as_vector() - combatant_position).length();
-            if distance_from_node == 0.0 {
-                if self.dynamic_pathing {
-                    self.path = self.compute_path(game_state.clone());
-                    // pop the first node - it's where we're already standing
-                    // ZJ-TODO: fix this
-                    let _ = self.path.next_node();
-                    self.start_location = Some(combatant_position.into());
-                }
-
-                if let Some(next_node) = self.path.next_node() {
-                    self.next_node = Some(next_node);
-                } else {
-                    break;
-                }
-            }
+        // Steer around other combatants rather than walking through them, without sidestepping off the navmesh
+        let desired_movement = combatant_position - start_position;
+        let separation = 2.0 * agent.combatant().radius();
+        let steered_movement = steer_around_neighbors(start_position, desired_movement, &neighbors, separation);
+        if steered_movement != desired_movement {
+            combatant_position = arena_navmesh.clamp_to_navmesh(start_position + steered_movement).unwrap_or(combatant_position);
+        }
+        let movement = combatant_position - start_position;
+
+        // Only move along the path past the nodes we actually reached - once steered off the path, that's at most the next one
+        if steered_movement != desired_movement {
+            let is_at_next_node = self.next_node
+                .is_some_and(|next_node| (next_node.as_vector() - combatant_position).length() <= NODE_REACHED_DISTANCE);
+            nodes_reached = usize::from(is_at_next_node);
+        }
+
+        self.advance_path(game_state.clone(), combatant_position, nodes_reached);
+
+        let desired_distance = desired_movement.length();
+        let is_blocked = desired_distance > 0.0
+            && movement.dot(desired_movement) / desired_distance < BLOCKED_PROGRESS_RATIO * desired_distance;
+        self.blocked_ticks = if is_blocked { self.blocked_ticks.saturating_add(1) } else { 0 };
+
+        if self.blocked_ticks >= REPATH_AFTER_BLOCKED_TICKS {
+            tracing::trace!("Blocked for {} ticks - re-pathing around other combatants", self.blocked_ticks);
+            self.blocked_ticks = 0;
+            self.start_location = Some(combatant_position);
+            self.path = self.compute_path(game_state.clone(), &neighbors, separation);
+            self.next_node = self.path.next_node();
         }
 
         let is_at_target = (self.target_location - combatant_position).length() <= unit_resolution;
@@ -255,4 +353,161 @@ impl Strategy for MoveToLocationStrategy {
 
         Some(events)
     }
-}
\ No newline at end of file
+}
+
+/// Steers a desired movement around nearby combatants, so that combatants walk around each other rather than through each other.
+/// Combatants always sidestep to the same side of whoever is in their way, so that two combatants walking
+/// into each other pass one another rather than mirroring each other's sidestep.
+/// Returns no movement at all if every option would walk into another combatant.
+fn steer_around_neighbors(position: Vec3, desired_movement: Vec3, neighbors: &[Vec3], separation: f32) -> Vec3 {
+    let distance = desired_movement.length();
+    if distance <= f32::EPSILON {
+        return desired_movement;
+    }
+
+    let direction = desired_movement / distance;
+    let awareness = separation + AVOIDANCE_LOOKAHEAD.max(distance);
+
+    // Sum up the steering in the same order however the neighbors were given, so floating point error can't differ
+    let mut neighbors = neighbors.to_vec();
+    neighbors.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.z.total_cmp(&b.z)).then(a.y.total_cmp(&b.y)));
+
+    let mut steering = direction;
+    let mut sidestep = Vec3::ZERO;
+    for neighbor in &neighbors {
+        let mut to_neighbor = *neighbor - position;
+        to_neighbor.y = 0.0;
+
+        let neighbor_distance = to_neighbor.length();
+        if neighbor_distance <= f32::EPSILON || neighbor_distance > awareness {
+            continue;
+        }
+
+        // Only steer around combatants we're heading towards
+        let to_neighbor = to_neighbor / neighbor_distance;
+        let approach = direction.dot(to_neighbor);
+        if approach <= 0.0 {
+            continue;
+        }
+
+        let closeness = 1.0 - (neighbor_distance - separation).max(0.0) / (awareness - separation);
+        let side = Vec3::Y.cross(to_neighbor);
+
+        steering += (side - to_neighbor) * approach * closeness;
+        sidestep += side;
+    }
+
+    let walks_into_neighbor = |movement: Vec3| {
+        neighbors.iter().any(|neighbor| {
+            let mut before = *neighbor - position;
+            before.y = 0.0;
+            let mut after = *neighbor - (position + movement);
+            after.y = 0.0;
+            after.length() < separation && after.length() < before.length()
+        })
+    };
+
+    [steering, sidestep]
+        .into_iter()
+        .map(|candidate| candidate.normalize_or_zero() * distance)
+        .find(|movement| *movement != Vec3::ZERO && !walks_into_neighbor(*movement))
+        .unwrap_or(Vec3::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use rapier3d::glamx::vec3;
+    use rapier3d::prelude::Vec3;
+    use dys_world::arena::navmesh::{ArenaNavmeshNode, ArenaNavmeshPath};
+    use crate::ai::strategy::Strategy;
+    use crate::ai::test_utils::make_generated_game;
+    use crate::game_state::GameState;
+    use super::{steer_around_neighbors, MoveToLocationStrategy};
+
+    const SEPARATION: f32 = 1.0;
+
+    #[test]
+    fn test_no_neighbors_moves_directly() {
+        let movement = steer_around_neighbors(Vec3::ZERO, vec3(0.5, 0.0, 0.0), &[], SEPARATION);
+        assert!((movement - vec3(0.5, 0.0, 0.0)).length() < 0.0001);
+    }
+
+    #[test]
+    fn test_neighbors_behind_are_ignored() {
+        let movement = steer_around_neighbors(Vec3::ZERO, vec3(0.5, 0.0, 0.0), &[vec3(-1.0, 0.0, 0.0)], SEPARATION);
+        assert!((movement - vec3(0.5, 0.0, 0.0)).length() < 0.0001);
+    }
+
+    #[test]
+    fn test_steers_around_neighbor_ahead() {
+        let neighbor = vec3(1.5, 0.0, 0.0);
+        let movement = steer_around_neighbors(Vec3::ZERO, vec3(0.5, 0.0, 0.0), &[neighbor], SEPARATION);
+
+        assert!(movement.z.abs() > 0.0001);
+        assert!((neighbor - movement).length() >= SEPARATION);
+        assert!((movement.length() - 0.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_combatants_walking_into_each_other_pass() {
+        let first = Vec3::ZERO;
+        let second = vec3(2.0, 0.0, 0.0);
+
+        let first_movement = steer_around_neighbors(first, vec3(0.5, 0.0, 0.0), &[second], SEPARATION);
+        let second_movement = steer_around_neighbors(second, vec3(-0.5, 0.0, 0.0), &[first], SEPARATION);
+
+        // Sidestepping to opposite sides of the arena is what lets them pass
+        assert!(first_movement.z * second_movement.z < 0.0);
+    }
+
+    #[test]
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn test_path_is_not_used_up_while_steered_to_a_stop() {
+        let game_state = Arc::new(Mutex::new(GameState::from_game_seeded(make_generated_game(), &[0; 32])));
+        let (combatant, start_position) = {
+            let mut game_state = game_state.lock().unwrap();
+            let combatant = game_state.combatants.values().next().unwrap().clone();
+            let separation = 2.0 * combatant.radius();
+            let other_rigid_body_handles = game_state.combatants
+                .values()
+                .filter(|other| other.id != combatant.id)
+                .map(|other| other.rigid_body_handle)
+                .collect::<Vec<_>>();
+
+            // Surround the combatant, so that every way they could step walks into someone
+            let (rigid_body_set, _) = game_state.physics_sim.sets_mut();
+            let start_position = rigid_body_set.get(combatant.rigid_body_handle).unwrap().translation();
+            let directions = [(1.0, 0.0), (1.0, 1.0), (0.0, 1.0), (-1.0, 1.0), (-1.0, 0.0), (-1.0, -1.0), (0.0, -1.0), (1.0, -1.0)];
+            for ((x, z), rigid_body_handle) in directions.into_iter().zip(other_rigid_body_handles) {
+                let position = start_position + vec3(x, 0.0, z).normalize() * separation * 0.9;
+                rigid_body_set.get_mut(rigid_body_handle).unwrap().set_translation(position, true);
+            }
+
+            (combatant, start_position)
+        };
+
+        // The first node is close enough to reach this tick, if the combatant weren't boxed in
+        let nodes = [0.1, 1.0, 2.0].map(|distance| ArenaNavmeshNode::from_point(start_position + vec3(distance, 0.0, 0.0)));
+        let mut strategy = MoveToLocationStrategy::new(combatant.id, nodes[2].as_vector(), 100);
+        strategy.next_node = Some(nodes[0]);
+        strategy.path = ArenaNavmeshPath::new(nodes[1..].to_vec());
+
+        strategy.tick(&combatant, game_state.clone());
+        assert!(strategy.next_node == Some(nodes[0]));
+        assert_eq!(strategy.path.len(), 2);
+    }
+
+    #[test]
+    fn test_neighbor_order_does_not_change_steering() {
+        // Summing up steering for these neighbors in different orders rounds differently
+        let neighbors = [vec3(1.29, 0.0, 0.493), vec3(1.296, 0.0, 0.499), vec3(1.302, 0.0, -0.495)];
+        let movement = steer_around_neighbors(Vec3::ZERO, vec3(0.5, 0.0, 0.0), &neighbors, SEPARATION);
+        assert_ne!(movement, vec3(0.5, 0.0, 0.0));
+
+        for reordered in [[0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]] {
+            let reordered_neighbors = reordered.map(|index| neighbors[index]);
+            assert_eq!(steer_around_neighbors(Vec3::ZERO, vec3(0.5, 0.0, 0.0), &reordered_neighbors, SEPARATION), movement);
+        }
+    }
+}
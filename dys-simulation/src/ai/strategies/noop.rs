@@ -1,5 +1,5 @@
 use std::sync::{Arc, Mutex};
-use crate::{ai::{agent::Agent, strategy::Strategy}, game_state::GameState};
+use crate::{ai::{agent::Agent, strategy::{Strategy, StrategySnapshot}}, game_state::GameState};
 use crate::ai::beliefs::belief_set::BeliefSet;
 use crate::simulation::simulation_event::PendingSimulationEvent;
 
@@ -10,6 +10,10 @@ impl Strategy for NoopStrategy {
         String::from("Noop")
     }
 
+    fn snapshot(&self) -> StrategySnapshot {
+        StrategySnapshot::Noop
+    }
+
     fn can_perform(&self, _: &BeliefSet) -> bool {
         true
     }
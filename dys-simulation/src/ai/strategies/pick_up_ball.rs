@@ -1,13 +1,15 @@
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
 use rapier3d::prelude::Vec3;
 use dys_satisfiable::SatisfiableField;
 use dys_world::combatant::instance::CombatantInstanceId;
-use crate::{ai::{agent::Agent, strategy::Strategy}, game_objects::ball::BallId, game_state::GameState, simulation::simulation_event::SimulationEvent};
+use crate::{ai::{agent::Agent, strategy::{Strategy, StrategySnapshot}}, game_objects::ball::BallId, game_state::GameState, simulation::simulation_event::SimulationEvent};
 use crate::ai::belief::SatisfiableBelief;
 use crate::ai::beliefs::belief_set::BeliefSet;
 use crate::simulation::simulation_event::PendingSimulationEvent;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct PickUpBallStrategy {
     self_combatant_id: CombatantInstanceId,
     ball_id: BallId,
@@ -35,6 +37,10 @@ impl Strategy for PickUpBallStrategy {
         format!("Pick Up Ball {}", self.ball_id)
     }
 
+    fn snapshot(&self) -> StrategySnapshot {
+        StrategySnapshot::PickUpBall(self.clone())
+    }
+
     #[tracing::instrument(name = "strategy::pick_up_ball::can_perform", skip_all, level = "trace")]
     fn can_perform(&self, owned_beliefs: &BeliefSet) -> bool {
         let self_not_holding_any_ball = owned_beliefs.all_satisfy(
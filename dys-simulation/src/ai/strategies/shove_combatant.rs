@@ -1,16 +1,18 @@
 use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
 use dys_satisfiable::SatisfiableField;
 use dys_world::attribute::attribute_type::AttributeType;
 use dys_world::combatant::instance::CombatantInstanceId;
 use crate::ai::agent::Agent;
 use crate::ai::belief::SatisfiableBelief;
 use crate::ai::beliefs::belief_set::BeliefSet;
-use crate::ai::strategy::Strategy;
+use crate::ai::strategy::{Strategy, StrategySnapshot};
 use crate::game_state::GameState;
 use crate::simulation::simulation_event::{PendingSimulationEvent, SimulationEvent};
 
 const SHOVE_FORCE_MULTIPLIER: f32 = 15000.0;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ShoveCombatantStrategy {
     self_combatant_id: CombatantInstanceId,
     target_combatant_id: CombatantInstanceId,
@@ -32,6 +34,10 @@ impl Strategy for ShoveCombatantStrategy {
         String::from("Shove Combatant")
     }
 
+    fn snapshot(&self) -> StrategySnapshot {
+        StrategySnapshot::ShoveCombatant(self.clone())
+    }
+
     fn can_perform(&self, beliefs: &BeliefSet) -> bool {
         beliefs.can_satisfy(
             &SatisfiableBelief::CanReachCombatant()
@@ -70,6 +76,7 @@ impl Strategy for ShoveCombatantStrategy {
         let target_pos = rigid_body_set.get(target_object.rigid_body_handle).unwrap().translation();
 
         let force_direction = (target_pos - self_pos).normalize();
+        let fatigue_modifier = agent.combatant().fatigue_modifier();
         let force_magnitude = {
             let combatant_instance = agent.combatant().combatant.lock().unwrap();
             let strength = combatant_instance
@@ -79,7 +86,7 @@ impl Strategy for ShoveCombatantStrategy {
 
             let target_weight = target_object.weight();
 
-            strength / target_weight
+            fatigue_modifier * strength / target_weight
         };
 
         events.push(PendingSimulationEvent(
@@ -1,15 +1,21 @@
 use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use rand::RngExt;
 use rand_distr::num_traits::Zero;
 use rapier3d::prelude::*;
-use rapier3d::glamx::vec3;
+use rapier3d::glamx::{vec3, Quat};
 use dys_satisfiable::SatisfiableField;
 use dys_world::combatant::instance::CombatantInstanceId;
-use crate::{ai::{agent::Agent, strategy::Strategy}, game_objects::game_object::GameObject, game_state::{GameState}, simulation::simulation_event::SimulationEvent};
+use crate::{ai::{agent::Agent, strategy::{Strategy, StrategySnapshot}}, game_objects::game_object::GameObject, game_state::{GameState}, simulation::simulation_event::SimulationEvent};
 use crate::ai::belief::SatisfiableBelief;
 use crate::ai::beliefs::belief_set::BeliefSet;
 use crate::game_objects::ball::BallState;
 use crate::simulation::simulation_event::PendingSimulationEvent;
 
+/// The furthest a completely inaccurate throw will stray from its target, in radians.
+const MAX_THROW_DEVIATION_RADIANS: f32 = std::f32::consts::FRAC_PI_4;
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ThrowBallAtTargetStrategy {
     self_id: CombatantInstanceId,
     target: CombatantInstanceId,
@@ -31,6 +37,10 @@ impl Strategy for ThrowBallAtTargetStrategy {
         String::from("Throw Ball at Target")
     }
 
+    fn snapshot(&self) -> StrategySnapshot {
+        StrategySnapshot::ThrowBallAtTarget(self.clone())
+    }
+
     fn can_perform(&self, owned_beliefs: &BeliefSet) -> bool {
         let has_ball = owned_beliefs.can_satisfy(
             &SatisfiableBelief::HeldBall()
@@ -72,8 +82,16 @@ impl Strategy for ThrowBallAtTargetStrategy {
             return None;
         };
 
-        let (target_pos, ball_pos, is_same_team, y_axis_gravity) = {
-            let game_state = game_state.lock().unwrap();
+        // Tired combatants throw less accurately
+        let accuracy = agent.combatant().fatigue_modifier().clamp(0.0, 1.0);
+
+        let (target_pos, ball_pos, is_same_team, y_axis_gravity, deviation) = {
+            let mut game_state = game_state.lock().unwrap();
+            let deviation = if accuracy < 1.0 {
+                game_state.rng.random_range(-1.0..=1.0)
+            } else {
+                0.0
+            };
 
             let (rigid_body_set, collider_set) = game_state.physics_sim.sets();
 
@@ -94,17 +112,17 @@ impl Strategy for ThrowBallAtTargetStrategy {
             let is_same_team = agent.combatant().team == target_object.team;
             let y_axis_gravity = game_state.physics_sim.gravity_y();
 
-            (target_pos, ball_pos, is_same_team, y_axis_gravity)
+            (target_pos, ball_pos, is_same_team, y_axis_gravity, deviation)
         };
 
         let throw_speed_units_per_sec_hack = 30.0_f32;
-        let accuracy_hack = 1.0_f32;
 
         let ball_impulse_vector = get_throw_vector_towards_target(
             target_pos,
             ball_pos,
             throw_speed_units_per_sec_hack,
-            accuracy_hack,
+            accuracy,
+            deviation,
             y_axis_gravity
         );
 
@@ -161,15 +179,18 @@ impl Strategy for ThrowBallAtTargetStrategy {
 /// * `target_pos` - the world position where the throw would land if perfectly accurate
 /// * `start_pos` - the world position where the throw will originate from
 /// * `throw_speed_units_per_sec` - how many in-world non-vertical units the throw will travel per second, ignoring gravity.
-/// * `accuracy` - how accurate the throw is, in range `[0.0, 1.0]`, where 1.0 is perfectly accurate and 0.0 will go in a completely random direction.
+/// * `accuracy` - how accurate the throw is, in range `[0.0, 1.0]`, where 1.0 is perfectly accurate and 0.0 will stray as far as possible from the target.
+/// * `deviation` - which way an inaccurate throw strays, in range `[-1.0, 1.0]`, where negative values stray left and positive values stray right.
 fn get_throw_vector_towards_target(
     target_pos: Vec3,
     start_pos: Vec3,
     throw_speed_units_per_sec: f32,
     accuracy: f32,
+    deviation: f32,
     y_axis_gravity: f32,
 ) -> Vec3 {
     assert!((0.0..=1.0).contains(&accuracy));
+    assert!((-1.0..=1.0).contains(&deviation));
 
     let difference_vector = target_pos - start_pos;
     let difference_distance = difference_vector.length();
@@ -187,9 +208,35 @@ fn get_throw_vector_towards_target(
     let gravity_adjustment_magnitude = (difference_vector.y + (0.5 * -y_axis_gravity * (total_travel_time_sec.powi(2)))) / total_travel_time_sec;
     
     // Our throw direction will ignore the Y direction to get a correct normal vector.
-    let throw_direction = vec3(difference_vector.x, 0.0, difference_vector.z).normalize();
+    // Inaccurate throws are rotated away from the target around the Y axis.
+    let deviation_radians = (1.0 - accuracy) * deviation * MAX_THROW_DEVIATION_RADIANS;
+    let throw_direction = Quat::from_rotation_y(deviation_radians) * vec3(difference_vector.x, 0.0, difference_vector.z).normalize();
 
     // Our overall throw vector is the X and Z components of the throw, and our Y component that we calculated accounting for gravity.
 
     (throw_direction * throw_speed_units_per_sec) + vec3(0.0, gravity_adjustment_magnitude, 0.0)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use rapier3d::glamx::vec3;
+    use super::get_throw_vector_towards_target;
+
+    #[test]
+    fn test_accurate_throws_ignore_deviation() {
+        let straight = get_throw_vector_towards_target(vec3(10.0, 0.0, 0.0), vec3(0.0, 0.0, 0.0), 30.0, 1.0, 0.0, -9.81);
+        let deviated = get_throw_vector_towards_target(vec3(10.0, 0.0, 0.0), vec3(0.0, 0.0, 0.0), 30.0, 1.0, 1.0, -9.81);
+
+        assert!((straight - deviated).length() < 0.0001);
+        assert!(straight.z.abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_inaccurate_throws_stray_from_target() {
+        let slightly_off = get_throw_vector_towards_target(vec3(10.0, 0.0, 0.0), vec3(0.0, 0.0, 0.0), 30.0, 0.75, 1.0, -9.81);
+        let very_off = get_throw_vector_towards_target(vec3(10.0, 0.0, 0.0), vec3(0.0, 0.0, 0.0), 30.0, 0.25, 1.0, -9.81);
+
+        assert!(slightly_off.z.abs() > 0.0001);
+        assert!(very_off.z.abs() > slightly_off.z.abs());
+    }
+}
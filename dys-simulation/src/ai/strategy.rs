@@ -1,5 +1,13 @@
 use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use crate::ai::action::StrategyT;
 use crate::ai::beliefs::belief_set::BeliefSet;
+use crate::ai::strategies::body_block::BodyBlockStrategy;
+use crate::ai::strategies::move_to_location::MoveToLocationStrategy;
+use crate::ai::strategies::noop::NoopStrategy;
+use crate::ai::strategies::pick_up_ball::PickUpBallStrategy;
+use crate::ai::strategies::shove_combatant::ShoveCombatantStrategy;
+use crate::ai::strategies::throw_ball_at_target_location::ThrowBallAtTargetStrategy;
 use crate::game_state::GameState;
 use crate::simulation::simulation_event::PendingSimulationEvent;
 use super::agent::Agent;
@@ -7,6 +15,9 @@ use super::agent::Agent;
 pub trait Strategy {
     fn name(&self) -> String;
 
+    /// Captures the strategy and its progress, so that it can be saved and picked up again later.
+    fn snapshot(&self) -> StrategySnapshot;
+
     /// Can this strategy be performed given our current beliefs about the world?
     fn can_perform(&self, owned_beliefs: &BeliefSet) -> bool;
 
@@ -26,4 +37,29 @@ pub trait Strategy {
         agent: &dyn Agent,
         game_state: Arc<Mutex<GameState>>
     ) -> Option<Vec<PendingSimulationEvent>>;
-}
\ No newline at end of file
+}
+
+/// A strategy and its progress, which unlike a running strategy can be serialized.
+#[derive(Serialize, Deserialize)]
+pub enum StrategySnapshot {
+    Noop,
+    BodyBlock(BodyBlockStrategy),
+    MoveToLocation(MoveToLocationStrategy),
+    PickUpBall(PickUpBallStrategy),
+    ShoveCombatant(ShoveCombatantStrategy),
+    ThrowBallAtTarget(ThrowBallAtTargetStrategy),
+}
+
+impl StrategySnapshot {
+    /// Picks the strategy back up from where it was when the snapshot was taken.
+    pub fn restore(self) -> StrategyT {
+        match self {
+            StrategySnapshot::Noop => Arc::new(Mutex::new(NoopStrategy)),
+            StrategySnapshot::BodyBlock(strategy) => Arc::new(Mutex::new(strategy)),
+            StrategySnapshot::MoveToLocation(strategy) => Arc::new(Mutex::new(strategy)),
+            StrategySnapshot::PickUpBall(strategy) => Arc::new(Mutex::new(strategy)),
+            StrategySnapshot::ShoveCombatant(strategy) => Arc::new(Mutex::new(strategy)),
+            StrategySnapshot::ThrowBallAtTarget(strategy) => Arc::new(Mutex::new(strategy)),
+        }
+    }
+}
@@ -1,19 +1,26 @@
 #![cfg(test)]
 
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
+use indexmap::IndexMap;
+use rand::prelude::StdRng;
 use rand::SeedableRng;
 use rand_pcg::Pcg64;
+use dys_world::arena::environment::GameEnvironment;
+use dys_world::arena::Arena;
+use dys_world::arena::navmesh::{ArenaNavmesh, ArenaNavmeshConfig, NavmeshPathCache};
+use dys_world::attribute::instance::AttributeInstance;
 use dys_world::combatant::instance::{CombatantInstance, CombatantInstanceId};
+use dys_world::combatant::limb::{Limb, LimbModifier, LimbType};
 use rapier3d::prelude::{ColliderHandle, RigidBodyHandle};
-use dys_world::arena::Arena;
-use dys_world::arena::navmesh::{ArenaNavmesh, ArenaNavmeshConfig};
 use dys_world::schedule::calendar::{Date, Month};
 use dys_world::games::instance::GameInstance;
+use dys_world::generator::Generator;
 use dys_world::team::instance::TeamInstance;
+use dys_world::team::tactics::TeamTactics;
 use crate::{game_objects::combatant::{CombatantObject, CombatantState, TeamAlignment}, game_state::GameState};
 use crate::ai::beliefs::belief_set::BeliefSet;
 use crate::game::Game;
-use crate::game_state::{BallsMapT, CollidersMapT, CombatantsMapT, PlatesMapT};
+use crate::game_state::{BallsMapT, CollidersMapT, CombatantsMapT, HazardsMapT, KinematicFeaturesMapT, PlatesMapT};
 use crate::physics_sim::PhysicsSim;
 use crate::simulation::config::SimulationConfig;
 use crate::simulation::simulation_event::PendingSimulationEvent;
@@ -58,6 +65,25 @@ impl TestAgent {
         }
     }
 
+    /// Creates a test agent whose combatant has a single limb with the given attributes.
+    pub fn new_with_attributes(attributes: &[AttributeInstance]) -> TestAgent {
+        TestAgent::new_with_settings(TestAgentSettings {
+            combatant_override: Some(CombatantInstance {
+                id: 1,
+                name: String::from("TestCombatant"),
+                limbs: vec![
+                    Limb {
+                        limb_type: LimbType::Torso,
+                        modifiers: vec![LimbModifier::default_with_attributes(attributes)],
+                        child_limbs: vec![],
+                    }
+                ],
+                effect_modifiers: vec![],
+            }),
+            ..TestAgentSettings::default()
+        })
+    }
+
     pub fn set_beliefs(&mut self, beliefs: Vec<Belief>) {
         self.beliefs = beliefs;
     }
@@ -77,6 +103,23 @@ impl Agent for TestAgent {
     }
 }
 
+/// Navmesh of the testing arena.
+/// Generating a navmesh is expensive, so it's baked on first use and shared by every test.
+pub(crate) fn testing_arena_navmesh() -> Arc<ArenaNavmesh> {
+    static NAVMESH: OnceLock<Arc<ArenaNavmesh>> = OnceLock::new();
+
+    NAVMESH
+        .get_or_init(|| Arc::new(ArenaNavmesh::from_arena(&Arena::new_with_testing_defaults(), ArenaNavmeshConfig::default())))
+        .clone()
+}
+
+/// Makes a game between two teams from a generated world, so that both teams have combatants.
+pub fn make_generated_game() -> Game {
+    let world = Generator::new().generate_world(&mut StdRng::seed_from_u64(0));
+
+    Game::new_with_testing_defaults(world.teams[&1].clone(), world.teams[&2].clone())
+}
+
 pub fn make_test_game_state(with_physics_sim: Option<PhysicsSim>) -> Arc<Mutex<GameState>> {
     let game = Game {
         game_instance: GameInstance {
@@ -85,25 +128,23 @@ pub fn make_test_game_state(with_physics_sim: Option<PhysicsSim>) -> Arc<Mutex<G
                 id: 1,
                 name: String::from("TestAwayTeam"),
                 combatants: vec![],
+                tactics: TeamTactics::default(),
+                home_arena_id: 0,
             })),
             home_team: Arc::new(Mutex::new(TeamInstance {
                 id: 2,
                 name: String::from("TestHomeTeam"),
                 combatants: vec![],
+                tactics: TeamTactics::default(),
+                home_arena_id: 0,
             })),
-            // arena: Arc::new(Mutex::new(Arena::new_with_testing_defaults())), // ZJ-TODO: don't use arena's default values
+            arena: Arc::new(Mutex::new(Arena::new_with_testing_defaults())),
             arena_id: 0,
             date: Date::new(Month::Arguscorp, 1, 10000),
         },
     };
     let simulation_config = SimulationConfig::default();
-    let arena_navmesh = ArenaNavmesh::new_from(
-        Arc::new(Mutex::new(Arena::new_with_testing_defaults())),
-        // game.game_instance.arena.clone(),
-        ArenaNavmeshConfig {
-            unit_resolution: 1.0
-        }
-    );
+    let arena_navmesh = testing_arena_navmesh();
 
     Arc::new(Mutex::new(GameState {
         game,
@@ -112,16 +153,21 @@ pub fn make_test_game_state(with_physics_sim: Option<PhysicsSim>) -> Arc<Mutex<G
         physics_sim: if let Some(physics_sim) = with_physics_sim {
             physics_sim
         } else {
-            PhysicsSim::new(simulation_config.ticks_per_second())
+            PhysicsSim::new(simulation_config.ticks_per_second(), &GameEnvironment::default())
         },
         combatants: CombatantsMapT::new(),
         balls: BallsMapT::new(),
         plates: PlatesMapT::new(),
+        hazards: HazardsMapT::new(),
+        kinematic_features: KinematicFeaturesMapT::new(),
         active_colliders: CollidersMapT::new(),
         home_points: 0,
         away_points: 0,
         current_tick: 0,
         simulation_config,
         arena_navmesh,
+        path_cache: NavmeshPathCache::new(),
+        environment: GameEnvironment::default(),
+        team_coordinators: IndexMap::new(),
     }))
 }
\ No newline at end of file
@@ -0,0 +1,381 @@
+use rapier3d::prelude::RigidBody;
+use serde::{Deserialize, Serialize};
+use dys_world::attribute::attribute_type::AttributeType;
+use crate::game_objects::combatant::{CombatantObject, TeamAlignment};
+use crate::game_objects::game_object::GameObject;
+use crate::game_state::GameState;
+
+/// Distance at which a plate is considered as far away as it can be.
+/// ZJ-TODO: derive this from the arena's size
+const MAX_PLATE_DISTANCE: f32 = 100.0;
+
+/// Damage at which a combatant with 1 constitution is considered to be at half health.
+/// ZJ-TODO: replace with limb health once limbs can be damaged
+const HALF_HEALTH_DAMAGE_PER_CONSTITUTION: f32 = 50.0;
+
+/// Response curves map a normalized consideration input (from 0.0 to 1.0)
+/// to a multiplier for a goal's priority.
+/// Inputs outside of the normalized range are clamped, and negative outputs are treated as zero.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ResponseCurve {
+    /// The same value regardless of input.
+    Constant { value: f32 },
+
+    /// `slope * x + intercept`
+    Linear { slope: f32, intercept: f32 },
+
+    /// `scale * x^exponent + intercept`
+    Polynomial { exponent: f32, scale: f32, intercept: f32 },
+
+    /// `scale / (1 + e^(-steepness * (x - midpoint))) + intercept`
+    Logistic { steepness: f32, midpoint: f32, scale: f32, intercept: f32 },
+
+    /// `below` if the input is less than the threshold, otherwise `above`.
+    Step { threshold: f32, below: f32, above: f32 },
+}
+
+impl ResponseCurve {
+    pub fn evaluate(&self, input: f32) -> f32 {
+        let x = input.clamp(0.0, 1.0);
+        let output = match self {
+            ResponseCurve::Constant { value } => *value,
+            ResponseCurve::Linear { slope, intercept } => slope * x + intercept,
+            ResponseCurve::Polynomial { exponent, scale, intercept } => scale * x.powf(*exponent) + intercept,
+            ResponseCurve::Logistic { steepness, midpoint, scale, intercept } => {
+                scale / (1.0 + (-steepness * (x - midpoint)).exp()) + intercept
+            },
+            ResponseCurve::Step { threshold, below, above } => if x < *threshold { *below } else { *above },
+        };
+
+        output.max(0.0)
+    }
+}
+
+/// Contextual inputs a consideration can respond to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConsiderationInput {
+    /// 0.0 when losing by the game conclusion score (or more), 0.5 when tied, and 1.0 when winning by the game conclusion score (or more).
+    ScoreDifferential,
+
+    /// 1.0 at the start of the game, 0.0 once time expires.
+    SecondsRemaining,
+
+    /// 0.0 when standing on a plate's origin, 1.0 when the closest plate is far away.
+    DistanceToPlate,
+
+    /// Fraction of the combatant's teammates that are standing on a plate.
+    TeammatesOnPlate,
+
+    /// 1.0 when undamaged, approaching 0.0 as the combatant takes damage.
+    OwnHealth,
+}
+
+/// A single contextual factor of a goal's priority.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Consideration {
+    pub input: ConsiderationInput,
+    pub curve: ResponseCurve,
+}
+
+impl Consideration {
+    pub fn new(input: ConsiderationInput, curve: ResponseCurve) -> Consideration {
+        Consideration { input, curve }
+    }
+
+    pub fn score(&self, context: &UtilityContext) -> f32 {
+        self.curve.evaluate(context.input(self.input))
+    }
+}
+
+/// The considerations that affect a single goal's priority.
+/// A goal's utility is the product of its considerations, or 1.0 if it has none.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GoalUtility {
+    pub considerations: Vec<Consideration>,
+}
+
+impl GoalUtility {
+    pub fn new(considerations: Vec<Consideration>) -> GoalUtility {
+        GoalUtility { considerations }
+    }
+
+    pub fn score(&self, context: &UtilityContext) -> f32 {
+        self
+            .considerations
+            .iter()
+            .map(|consideration| consideration.score(context))
+            .product()
+    }
+}
+
+/// Utilities for each goal a combatant may consider.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UtilityProfile {
+    pub score_points: GoalUtility,
+    pub pass_ball: GoalUtility,
+    pub throw_at_enemies_on_plates: GoalUtility,
+    pub throw_at_enemies_off_plates: GoalUtility,
+    pub shove_enemies_on_plates: GoalUtility,
+    pub shove_enemies_off_plates: GoalUtility,
+    pub catch_ball: GoalUtility,
+    pub guard_plate: GoalUtility,
+    pub screen_teammate: GoalUtility,
+    pub intercept_runner: GoalUtility,
+}
+
+impl Default for UtilityProfile {
+    fn default() -> Self {
+        use ConsiderationInput::{DistanceToPlate, OwnHealth, ScoreDifferential, SecondsRemaining, TeammatesOnPlate};
+
+        // Losing teams get more urgent as time runs out
+        let urgency = || Consideration::new(SecondsRemaining, ResponseCurve::Logistic {
+            steepness: -12.0,
+            midpoint: 0.15,
+            scale: 0.5,
+            intercept: 1.0,
+        });
+
+        UtilityProfile {
+            score_points: GoalUtility::new(vec![
+                Consideration::new(ScoreDifferential, ResponseCurve::Linear { slope: -0.5, intercept: 1.25 }),
+                Consideration::new(DistanceToPlate, ResponseCurve::Polynomial { exponent: 2.0, scale: -0.5, intercept: 1.25 }),
+                Consideration::new(TeammatesOnPlate, ResponseCurve::Linear { slope: -0.5, intercept: 1.25 }),
+                urgency(),
+            ]),
+            pass_ball: GoalUtility::new(vec![
+                Consideration::new(OwnHealth, ResponseCurve::Linear { slope: -0.5, intercept: 1.25 }),
+            ]),
+            throw_at_enemies_on_plates: GoalUtility::new(vec![
+                Consideration::new(ScoreDifferential, ResponseCurve::Linear { slope: -0.5, intercept: 1.25 }),
+                urgency(),
+            ]),
+            throw_at_enemies_off_plates: GoalUtility::new(vec![
+                Consideration::new(OwnHealth, ResponseCurve::Linear { slope: 0.5, intercept: 0.75 }),
+            ]),
+            shove_enemies_on_plates: GoalUtility::new(vec![
+                Consideration::new(OwnHealth, ResponseCurve::Polynomial { exponent: 2.0, scale: 0.6, intercept: 0.6 }),
+                Consideration::new(DistanceToPlate, ResponseCurve::Linear { slope: -0.5, intercept: 1.25 }),
+            ]),
+            shove_enemies_off_plates: GoalUtility::new(vec![
+                Consideration::new(OwnHealth, ResponseCurve::Polynomial { exponent: 2.0, scale: 0.6, intercept: 0.6 }),
+            ]),
+            catch_ball: GoalUtility::default(),
+            // Defending matters more when protecting a lead
+            guard_plate: GoalUtility::new(vec![
+                Consideration::new(TeammatesOnPlate, ResponseCurve::Linear { slope: 1.0, intercept: 0.25 }),
+                Consideration::new(ScoreDifferential, ResponseCurve::Linear { slope: 0.5, intercept: 0.75 }),
+            ]),
+            screen_teammate: GoalUtility::new(vec![
+                Consideration::new(OwnHealth, ResponseCurve::Linear { slope: 0.5, intercept: 0.75 }),
+            ]),
+            intercept_runner: GoalUtility::new(vec![
+                Consideration::new(ScoreDifferential, ResponseCurve::Linear { slope: 0.5, intercept: 0.75 }),
+                Consideration::new(DistanceToPlate, ResponseCurve::Linear { slope: -0.5, intercept: 1.25 }),
+            ]),
+        }
+    }
+}
+
+/// Normalized game context that considerations are evaluated against.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UtilityContext {
+    pub score_differential: f32,
+    pub seconds_remaining: f32,
+    pub distance_to_plate: f32,
+    pub teammates_on_plate: f32,
+    pub own_health: f32,
+}
+
+impl Default for UtilityContext {
+    /// A tied game at half time, for a healthy combatant halfway to a plate with no teammates on plates.
+    fn default() -> Self {
+        UtilityContext {
+            score_differential: 0.5,
+            seconds_remaining: 0.5,
+            distance_to_plate: 0.5,
+            teammates_on_plate: 0.0,
+            own_health: 1.0,
+        }
+    }
+}
+
+impl UtilityContext {
+    // Tick counts and team sizes are far too small to lose any precision as floats
+    #[allow(clippy::cast_precision_loss)]
+    pub fn from_game_state(combatant_object: &CombatantObject, game_state: &GameState) -> UtilityContext {
+        let simulation_config = &game_state.simulation_config;
+
+        let (own_points, enemy_points) = match combatant_object.team {
+            TeamAlignment::Home => (game_state.home_points, game_state.away_points),
+            TeamAlignment::Away => (game_state.away_points, game_state.home_points),
+        };
+        let game_conclusion_score = f32::from(simulation_config.game_conclusion_score().max(1));
+        let score_differential = (f32::from(own_points) - f32::from(enemy_points)) / game_conclusion_score;
+
+        let ticks_per_game = simulation_config.ticks_per_game();
+        let seconds_remaining = if ticks_per_game == 0 {
+            1.0
+        } else {
+            1.0 - game_state.current_tick as f32 / ticks_per_game as f32
+        };
+
+        let (rigid_body_set, collider_set) = game_state.physics_sim.sets();
+        let combatant_position = rigid_body_set
+            .get(combatant_object.rigid_body_handle)
+            .map(RigidBody::translation);
+
+        let distance_to_plate = combatant_position.map_or(1.0, |combatant_position| {
+            game_state
+                .plates
+                .values()
+                .filter(|plate_object| plate_object.is_active())
+                .filter_map(|plate_object| collider_set.get(plate_object.collider_handle()?))
+                .map(|plate_collider| (plate_collider.translation() - combatant_position).length())
+                .min_by(f32::total_cmp)
+                .map_or(1.0, |distance| distance / MAX_PLATE_DISTANCE)
+        });
+
+        let teammates = game_state
+            .combatants
+            .values()
+            .filter(|other| other.team == combatant_object.team && other.id != combatant_object.id)
+            .collect::<Vec<_>>();
+        let teammates_on_plate = if teammates.is_empty() {
+            0.0
+        } else {
+            teammates.iter().filter(|teammate| teammate.plate().is_some()).count() as f32 / teammates.len() as f32
+        };
+
+        let constitution = combatant_object
+            .combatant
+            .lock()
+            .unwrap()
+            .get_attribute_value(&AttributeType::Constitution)
+            .unwrap_or_default()
+            .max(1.0);
+        let damage = combatant_object.combatant_state.lock().unwrap().damage;
+        let own_health = 1.0 / (1.0 + damage / (HALF_HEALTH_DAMAGE_PER_CONSTITUTION * constitution));
+
+        UtilityContext {
+            score_differential: f32::midpoint(score_differential, 1.0).clamp(0.0, 1.0),
+            seconds_remaining: seconds_remaining.clamp(0.0, 1.0),
+            distance_to_plate: distance_to_plate.clamp(0.0, 1.0),
+            teammates_on_plate,
+            own_health,
+        }
+    }
+
+    pub fn input(&self, input: ConsiderationInput) -> f32 {
+        match input {
+            ConsiderationInput::ScoreDifferential => self.score_differential,
+            ConsiderationInput::SecondsRemaining => self.seconds_remaining,
+            ConsiderationInput::DistanceToPlate => self.distance_to_plate,
+            ConsiderationInput::TeammatesOnPlate => self.teammates_on_plate,
+            ConsiderationInput::OwnHealth => self.own_health,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Consideration, ConsiderationInput, GoalUtility, ResponseCurve, UtilityContext, UtilityProfile};
+
+    fn assert_near(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 0.0001, "expected {expected}, got {actual}");
+    }
+
+    #[test]
+    fn test_constant_curve() {
+        let curve = ResponseCurve::Constant { value: 0.3 };
+        assert_near(curve.evaluate(0.0), 0.3);
+        assert_near(curve.evaluate(1.0), 0.3);
+    }
+
+    #[test]
+    fn test_linear_curve() {
+        let curve = ResponseCurve::Linear { slope: -0.5, intercept: 1.25 };
+        assert_near(curve.evaluate(0.0), 1.25);
+        assert_near(curve.evaluate(0.5), 1.0);
+        assert_near(curve.evaluate(1.0), 0.75);
+    }
+
+    #[test]
+    fn test_polynomial_curve() {
+        let curve = ResponseCurve::Polynomial { exponent: 2.0, scale: 1.0, intercept: 0.0 };
+        assert_near(curve.evaluate(0.5), 0.25);
+        assert_near(curve.evaluate(1.0), 1.0);
+    }
+
+    #[test]
+    fn test_logistic_curve() {
+        let curve = ResponseCurve::Logistic { steepness: 10.0, midpoint: 0.5, scale: 1.0, intercept: 0.0 };
+        assert_near(curve.evaluate(0.5), 0.5);
+        assert!(curve.evaluate(0.0) < 0.01);
+        assert!(curve.evaluate(1.0) > 0.99);
+    }
+
+    #[test]
+    fn test_step_curve() {
+        let curve = ResponseCurve::Step { threshold: 0.25, below: 2.0, above: 1.0 };
+        assert_near(curve.evaluate(0.2), 2.0);
+        assert_near(curve.evaluate(0.25), 1.0);
+    }
+
+    #[test]
+    fn test_inputs_are_clamped_and_outputs_are_non_negative() {
+        let curve = ResponseCurve::Linear { slope: -2.0, intercept: 1.0 };
+        assert_near(curve.evaluate(-5.0), 1.0);
+        assert_near(curve.evaluate(5.0), 0.0);
+    }
+
+    #[test]
+    fn test_goal_utility_is_product_of_considerations() {
+        let utility = GoalUtility::new(vec![
+            Consideration::new(ConsiderationInput::OwnHealth, ResponseCurve::Linear { slope: 1.0, intercept: 0.0 }),
+            Consideration::new(ConsiderationInput::SecondsRemaining, ResponseCurve::Constant { value: 2.0 }),
+        ]);
+
+        let context = UtilityContext { own_health: 0.25, ..UtilityContext::default() };
+        assert_near(utility.score(&context), 0.5);
+        assert_near(GoalUtility::default().score(&context), 1.0);
+    }
+
+    #[test]
+    fn test_losing_late_makes_scoring_more_urgent() {
+        let profile = UtilityProfile::default();
+        let tied_early = UtilityContext { score_differential: 0.5, seconds_remaining: 0.9, ..UtilityContext::default() };
+        let losing_late = UtilityContext { score_differential: 0.3, seconds_remaining: 0.05, ..UtilityContext::default() };
+
+        assert!(profile.score_points.score(&losing_late) > profile.score_points.score(&tied_early));
+    }
+
+    #[test]
+    fn test_teammates_on_plate_lowers_scoring_utility() {
+        let profile = UtilityProfile::default();
+        let no_teammates = UtilityContext { teammates_on_plate: 0.0, ..UtilityContext::default() };
+        let all_teammates = UtilityContext { teammates_on_plate: 1.0, ..UtilityContext::default() };
+
+        assert!(profile.score_points.score(&no_teammates) > profile.score_points.score(&all_teammates));
+    }
+
+    #[test]
+    fn test_injured_combatants_prefer_passing_over_shoving() {
+        let profile = UtilityProfile::default();
+        let healthy = UtilityContext { own_health: 1.0, ..UtilityContext::default() };
+        let injured = UtilityContext { own_health: 0.2, ..UtilityContext::default() };
+
+        assert!(profile.pass_ball.score(&injured) > profile.pass_ball.score(&healthy));
+        assert!(profile.shove_enemies_off_plates.score(&injured) < profile.shove_enemies_off_plates.score(&healthy));
+    }
+
+    #[test]
+    fn test_profile_deserializes_with_defaults() {
+        let profile: UtilityProfile = serde_json::from_str(
+            r#"{ "catch_ball": { "considerations": [{ "input": "OwnHealth", "curve": { "Constant": { "value": 0.5 } } }] } }"#
+        ).unwrap();
+
+        assert_near(profile.catch_ball.score(&UtilityContext::default()), 0.5);
+        assert_eq!(profile.score_points, UtilityProfile::default().score_points);
+    }
+}
@@ -0,0 +1,433 @@
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+use dys_world::attribute::attribute_type::AttributeType;
+use dys_world::attribute::instance::AttributeValueT;
+use dys_world::combatant::instance::CombatantInstanceId;
+use crate::game::Game;
+use crate::game_checkpoint::GameCheckpointError;
+use crate::game_objects::combatant::TeamAlignment;
+use crate::game_state::{GameState, SeedT};
+use crate::game_tick::GameTickNumber;
+use crate::simulation::event_queue::SimulationEventQueue;
+use crate::simulation::simulation_event::{EventRejectionReason, PendingSimulationEvent, SimulationEvent};
+use crate::simulation::simulate_tick;
+
+/// Something done differently at the point a game is forked, to see how the rest of the game would have gone.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum CounterfactualChange {
+    /// Adjusts one of a combatant's attributes for the rest of the game, eg Strength +20.
+    /// Only the forked game is affected, not the combatant in the world.
+    AttributeChange { combatant_id: CombatantInstanceId, attribute_type: AttributeType, delta: AttributeValueT },
+
+    /// Makes an event happen as though the simulation had committed it, eg a throw hitting its target.
+    /// The event must only concern combatants and balls still in play.
+    InjectEvent(SimulationEvent),
+
+    /// Takes a combatant off the field for the rest of the game.
+    RemoveCombatant(CombatantInstanceId),
+}
+
+/// Why a game couldn't be forked, or a change couldn't be made to a fork.
+#[derive(Debug)]
+pub enum CounterfactualError {
+    /// The forked game couldn't be saved or picked back up.
+    Checkpoint(GameCheckpointError),
+
+    /// The game ended before reaching the tick it was to be forked at.
+    GameOverBeforeFork { fork_tick: GameTickNumber, end_tick: GameTickNumber },
+
+    /// A change was made to a combatant who isn't playing in the game.
+    UnknownCombatant(CombatantInstanceId),
+
+    /// An injected event couldn't happen in the forked game, eg a combatant picking up a ball they're too far from.
+    EventRejected { event: SimulationEvent, reason: EventRejectionReason },
+}
+
+impl Display for CounterfactualError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CounterfactualError::Checkpoint(error) => write!(f, "failed to fork game: {error}"),
+            CounterfactualError::GameOverBeforeFork { fork_tick, end_tick } => write!(f, "game ended on tick {end_tick}, before it could be forked on tick {fork_tick}"),
+            CounterfactualError::UnknownCombatant(combatant_id) => write!(f, "combatant {combatant_id} doesn't play in the game"),
+            CounterfactualError::EventRejected { event, reason } => write!(f, "injected event {event:?} was rejected by the simulation: {reason:?}"),
+        }
+    }
+}
+
+impl std::error::Error for CounterfactualError {}
+
+impl From<GameCheckpointError> for CounterfactualError {
+    fn from(error: GameCheckpointError) -> Self {
+        CounterfactualError::Checkpoint(error)
+    }
+}
+
+/// The final score of a game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameOutcome {
+    pub home_score: u16,
+    pub away_score: u16,
+}
+
+impl GameOutcome {
+    #[must_use]
+    pub fn score(&self, team: TeamAlignment) -> u16 {
+        match team {
+            TeamAlignment::Home => self.home_score,
+            TeamAlignment::Away => self.away_score,
+        }
+    }
+
+    /// The team with the higher score, or None if the game was a draw.
+    #[must_use]
+    pub fn winner(&self) -> Option<TeamAlignment> {
+        match self.home_score.cmp(&self.away_score) {
+            std::cmp::Ordering::Greater => Some(TeamAlignment::Home),
+            std::cmp::Ordering::Less => Some(TeamAlignment::Away),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+/// The outcomes of many playthroughs of the same game.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OutcomeDistribution {
+    outcomes: Vec<GameOutcome>,
+}
+
+impl OutcomeDistribution {
+    #[must_use]
+    pub fn outcomes(&self) -> &[GameOutcome] {
+        &self.outcomes
+    }
+
+    /// Fraction of playthroughs won by the team, from 0 to 1.
+    #[must_use]
+    pub fn win_rate(&self, team: TeamAlignment) -> f32 {
+        self.fraction(|outcome| outcome.winner() == Some(team))
+    }
+
+    /// Fraction of playthroughs neither team won, from 0 to 1.
+    #[must_use]
+    pub fn draw_rate(&self) -> f32 {
+        self.fraction(|outcome| outcome.winner().is_none())
+    }
+
+    #[must_use]
+    // Far fewer playthroughs than it'd take to lose precision as a float
+    #[allow(clippy::cast_precision_loss)]
+    pub fn mean_score(&self, team: TeamAlignment) -> f32 {
+        if self.outcomes.is_empty() {
+            return 0.0;
+        }
+
+        let total_score = self.outcomes.iter().map(|outcome| f32::from(outcome.score(team))).sum::<f32>();
+        total_score / self.outcomes.len() as f32
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn fraction(&self, predicate: impl Fn(&GameOutcome) -> bool) -> f32 {
+        if self.outcomes.is_empty() {
+            return 0.0;
+        }
+
+        self.outcomes.iter().filter(|outcome| predicate(outcome)).count() as f32 / self.outcomes.len() as f32
+    }
+}
+
+/// How a game played out after being forked, with and without the counterfactual changes.
+///
+/// Both distributions are played out with the same seeds, so any difference between them
+/// comes from the changes rather than from luck.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CounterfactualReport {
+    pub fork_tick: GameTickNumber,
+    /// How the game actually ended.
+    pub original: GameOutcome,
+    /// The game replayed from the fork without any changes.
+    pub baseline: OutcomeDistribution,
+    /// The game replayed from the fork with the changes made.
+    pub counterfactual: OutcomeDistribution,
+}
+
+impl CounterfactualReport {
+    /// How much the changes moved the team's chances of winning, from -1 to 1.
+    #[must_use]
+    pub fn win_rate_change(&self, team: TeamAlignment) -> f32 {
+        self.counterfactual.win_rate(team) - self.baseline.win_rate(team)
+    }
+
+    #[must_use]
+    pub fn mean_score_change(&self, team: TeamAlignment) -> f32 {
+        self.counterfactual.mean_score(team) - self.baseline.mean_score(team)
+    }
+}
+
+/// A game paused partway through, from which any number of "what if" branches can be played out.
+pub struct GameFork {
+    game: Game,
+    checkpoint: Vec<u8>,
+    fork_tick: GameTickNumber,
+}
+
+impl GameFork {
+    /// Forks the game where it currently is.
+    ///
+    /// # Errors
+    /// Will error if the game cannot be checkpointed.
+    pub fn from_game_state(game_state: &GameState) -> Result<GameFork, CounterfactualError> {
+        Ok(GameFork {
+            game: game_state.game.clone(),
+            checkpoint: game_state.checkpoint()?,
+            fork_tick: game_state.current_tick,
+        })
+    }
+
+    /// Plays the game on from wherever it is, and forks it once the given tick has been simulated.
+    /// Everything about the game so far carries into the fork, such as the arena it's played in
+    /// and any attributes adjusted during it. Games already past the tick are forked where they are.
+    ///
+    /// # Errors
+    /// Will error if the game ends before the fork tick, or cannot be checkpointed.
+    ///
+    /// # Panics
+    /// Will panic if the game state cannot be locked.
+    // Games are only ever simulated on one thread, but ticks are simulated on shared game state
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub fn at_tick(game_state: GameState, fork_tick: GameTickNumber) -> Result<GameFork, CounterfactualError> {
+        let game_state = Arc::new(Mutex::new(game_state));
+
+        while game_state.lock().unwrap().current_tick < fork_tick {
+            let game_tick = simulate_tick(game_state.clone());
+            if game_tick.is_end_of_game() && game_tick.tick_number < fork_tick {
+                return Err(CounterfactualError::GameOverBeforeFork { fork_tick, end_tick: game_tick.tick_number });
+            }
+        }
+
+        GameFork::from_game_state(&game_state.lock().unwrap())
+    }
+
+    #[must_use]
+    pub fn fork_tick(&self) -> GameTickNumber {
+        self.fork_tick
+    }
+
+    /// Makes the changes to a fresh copy of the forked game, ready to be simulated on.
+    /// If a seed is given, the branch is reseeded so that it plays out differently from the original game.
+    /// Changes are made in order, so later changes see the effects of earlier ones.
+    ///
+    /// # Errors
+    /// Will error if a change concerns a combatant who isn't playing, or an injected event is rejected.
+    ///
+    /// # Panics
+    /// Will panic if the game state or a combatant cannot be locked.
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub fn branch(&self, changes: &[CounterfactualChange], seed: Option<&SeedT>) -> Result<Arc<Mutex<GameState>>, CounterfactualError> {
+        let mut game_state = GameState::from_checkpoint(self.game.clone(), &self.checkpoint)?;
+
+        if let Some(seed) = seed {
+            game_state.seed = *seed;
+            game_state.rng = Pcg64::from_seed(*seed);
+        }
+
+        let game_state = Arc::new(Mutex::new(game_state));
+        for change in changes {
+            apply_change(&game_state, change)?;
+        }
+
+        Ok(game_state)
+    }
+
+    /// Plays out the rest of the game once for each seed, both with and without the changes,
+    /// and compares the outcomes to how the game actually ended.
+    ///
+    /// # Errors
+    /// Will error if the changes cannot be made to the forked game.
+    ///
+    /// # Panics
+    /// Will panic if a branch's game state cannot be locked.
+    pub fn simulate(&self, changes: &[CounterfactualChange], seeds: &[SeedT]) -> Result<CounterfactualReport, CounterfactualError> {
+        let original = play_out(&self.branch(&[], None)?);
+
+        let mut baseline = OutcomeDistribution::default();
+        let mut counterfactual = OutcomeDistribution::default();
+        for seed in seeds {
+            baseline.outcomes.push(play_out(&self.branch(&[], Some(seed))?));
+            counterfactual.outcomes.push(play_out(&self.branch(changes, Some(seed))?));
+        }
+
+        Ok(CounterfactualReport {
+            fork_tick: self.fork_tick,
+            original,
+            baseline,
+            counterfactual,
+        })
+    }
+}
+
+fn apply_change(game_state: &Arc<Mutex<GameState>>, change: &CounterfactualChange) -> Result<(), CounterfactualError> {
+    match change {
+        CounterfactualChange::AttributeChange { combatant_id, attribute_type, delta } => {
+            if !game_state.lock().unwrap().adjust_attribute(*combatant_id, attribute_type, *delta) {
+                return Err(CounterfactualError::UnknownCombatant(*combatant_id));
+            }
+        },
+        CounterfactualChange::InjectEvent(event) => {
+            let event_budget_per_tick = game_state.lock().unwrap().simulation_config.event_budget_per_tick();
+            let mut event_queue = SimulationEventQueue::new(event_budget_per_tick);
+            event_queue.commit(game_state, vec![PendingSimulationEvent(event.clone())]);
+
+            // Nothing is committed only if the injected event itself was rejected
+            let (committed_simulation_events, _, mut rejected_simulation_events) = event_queue.into_parts();
+            if committed_simulation_events.is_empty() {
+                let reason = rejected_simulation_events.remove(0).reason;
+                return Err(CounterfactualError::EventRejected { event: event.clone(), reason });
+            }
+
+            game_state.lock().unwrap().update_team_roles(&committed_simulation_events);
+        },
+        CounterfactualChange::RemoveCombatant(combatant_id) => {
+            if !game_state.lock().unwrap().remove_combatant(*combatant_id) {
+                return Err(CounterfactualError::UnknownCombatant(*combatant_id));
+            }
+        },
+    }
+
+    Ok(())
+}
+
+fn play_out(game_state: &Arc<Mutex<GameState>>) -> GameOutcome {
+    let ticks_per_game = game_state.lock().unwrap().simulation_config.ticks_per_game();
+
+    // Forks taken once the clock has run out have nothing left to play
+    while game_state.lock().unwrap().current_tick < ticks_per_game {
+        if simulate_tick(game_state.clone()).is_end_of_game() {
+            break;
+        }
+    }
+
+    let game_state = game_state.lock().unwrap();
+    GameOutcome {
+        home_score: game_state.home_points,
+        away_score: game_state.away_points,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use rapier3d::prelude::Vec3;
+    use dys_world::arena::Arena;
+    use dys_world::arena::environment::{EnvironmentModifier, GameEnvironment};
+    use dys_world::attribute::attribute_type::AttributeType;
+
+    use crate::ai::test_utils::make_generated_game;
+    use crate::game_objects::combatant::TeamAlignment;
+    use crate::game_state::GameState;
+    use crate::simulation::config::SimulationConfig;
+    use crate::simulation::simulate_tick;
+    use crate::simulation::simulation_event::SimulationEvent;
+    use super::{play_out, CounterfactualChange, CounterfactualError, GameFork};
+
+    #[test]
+    fn test_changes_only_affect_the_branch() {
+        let fork = GameFork::at_tick(GameState::from_game_seeded(make_generated_game(), &[0; 32]), 10).unwrap();
+        let original = fork.branch(&[], None).unwrap();
+        let (home_id, away_id, shover_id, world_strength) = {
+            let original = original.lock().unwrap();
+            let home_object = original.team_combatants(TeamAlignment::Home)[0].clone();
+            let away_team = original.team_combatants(TeamAlignment::Away);
+            let world_strength = home_object.combatant.lock().unwrap().get_attribute_value(&AttributeType::Strength).unwrap();
+            (home_object.id, away_team[0].id, away_team[1].id, world_strength)
+        };
+
+        let branch = fork.branch(&[
+            CounterfactualChange::AttributeChange { combatant_id: home_id, attribute_type: AttributeType::Strength, delta: 20.0 },
+            CounterfactualChange::RemoveCombatant(away_id),
+            CounterfactualChange::InjectEvent(SimulationEvent::CombatantShoveForceApplied {
+                shover_combatant_id: shover_id,
+                recipient_target_id: home_id,
+                force_magnitude: 10.0,
+                force_direction: Vec3::X,
+            }),
+        ], None).unwrap();
+
+        {
+            let branch = branch.lock().unwrap();
+            let home_object = &branch.combatants[&home_id];
+            let strength = home_object.combatant.lock().unwrap().get_attribute_value(&AttributeType::Strength).unwrap();
+            assert!((strength - world_strength - 20.0).abs() < 0.001);
+            assert!(home_object.is_stunned());
+            assert!(!branch.combatants.contains_key(&away_id));
+        }
+
+        // The original game, and the combatant in the world, are untouched
+        let original = original.lock().unwrap();
+        let home_object = &original.combatants[&home_id];
+        assert!((home_object.combatant.lock().unwrap().get_attribute_value(&AttributeType::Strength).unwrap() - world_strength).abs() < 0.001);
+        assert!(!home_object.is_stunned());
+        assert!(original.combatants.contains_key(&away_id));
+
+        // The branch can carry on without the removed combatant
+        for _ in 0..30 {
+            simulate_tick(branch.clone());
+        }
+
+        let result = fork.branch(&[CounterfactualChange::RemoveCombatant(away_id), CounterfactualChange::RemoveCombatant(away_id)], None);
+        assert!(matches!(result, Err(CounterfactualError::UnknownCombatant(id)) if id == away_id));
+    }
+
+    #[test]
+    fn test_forks_carry_on_from_the_source_game() {
+        let mut game = make_generated_game();
+        game.game_instance.arena = Arc::new(Mutex::new(Arena::new_with_testing_defaults().with_environment_modifier(EnvironmentModifier::LOW_GRAVITY)));
+
+        let mut game_state = GameState::from_game_seeded(game, &[0; 32]);
+        let combatant_id = *game_state.combatants.keys().next().unwrap();
+        assert!(game_state.adjust_attribute(combatant_id, &AttributeType::Strength, 20.0));
+        let strength = game_state.combatants[&combatant_id].combatant.lock().unwrap().get_attribute_value(&AttributeType::Strength).unwrap();
+        let gravity_y = game_state.physics_sim.gravity_y();
+
+        let fork = GameFork::at_tick(game_state, 10).unwrap();
+        assert_eq!(fork.fork_tick(), 10);
+
+        let branch = fork.branch(&[], None).unwrap();
+        let branch = branch.lock().unwrap();
+        let branch_strength = branch.combatants[&combatant_id].combatant.lock().unwrap().get_attribute_value(&AttributeType::Strength).unwrap();
+        assert!((branch_strength - strength).abs() < 0.001);
+        assert!((branch.physics_sim.gravity_y() - gravity_y).abs() < 0.001);
+        assert_eq!(branch.environment, GameEnvironment::default().with_modifier(EnvironmentModifier::LOW_GRAVITY));
+    }
+
+    #[test]
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn test_reports_compare_changes_against_unchanged_replays() {
+        let mut game_state = GameState::from_game_seeded(make_generated_game(), &[0; 32]);
+        game_state.simulation_config = SimulationConfig::default().with_seconds_per_period(5);
+        let game_state = Arc::new(Mutex::new(game_state));
+        for _ in 0..20 {
+            simulate_tick(game_state.clone());
+        }
+
+        let fork = GameFork::from_game_state(&game_state.lock().unwrap()).unwrap();
+        let home_points_at_fork = game_state.lock().unwrap().home_points;
+
+        let home_team = game_state.lock().unwrap().team_combatants(TeamAlignment::Home);
+        let changes = home_team
+            .iter()
+            .map(|combatant_object| CounterfactualChange::RemoveCombatant(combatant_object.id))
+            .collect::<Vec<_>>();
+
+        let report = fork.simulate(&changes, &[[1; 32], [2; 32]]).unwrap();
+        assert_eq!(report.fork_tick, 20);
+        assert_eq!(report.original, play_out(&game_state));
+        assert_eq!(report.baseline.outcomes().len(), 2);
+
+        // Without anyone left to play, the home team can't score again
+        assert!(report.counterfactual.outcomes().iter().all(|outcome| outcome.home_score == home_points_at_fork));
+        assert!(report.mean_score_change(TeamAlignment::Home) <= 0.0);
+    }
+}
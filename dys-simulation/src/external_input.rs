@@ -0,0 +1,127 @@
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+use rapier3d::prelude::Vec3;
+use serde::{Deserialize, Serialize};
+use dys_world::arena::plate::PlateId;
+use dys_world::arena::ArenaFeatureIndex;
+use dys_world::attribute::attribute_type::AttributeType;
+use dys_world::attribute::instance::AttributeValueT;
+use dys_world::combatant::instance::CombatantInstanceId;
+use crate::game_state::GameState;
+use crate::game_tick::GameTickNumber;
+use crate::simulation::event_queue::SimulationEventQueue;
+use crate::simulation::simulation_event::{PendingSimulationEvent, SimulationEvent, SimulationEventCause};
+
+/// Something from outside the simulation that influences a game while it's being played,
+/// such as fans voting to buff a combatant, or a scripted experiment.
+///
+/// Inputs are queued on a [`GameRunner`](crate::game_runner::GameRunner) for a tick,
+/// and applied just before that tick is simulated.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ExternalInput {
+    /// Adjusts one of a combatant's attributes for the rest of the game, eg Strength +20.
+    /// Only the game is affected, not the combatant in the world.
+    ApplyAttributeEffect { combatant_id: CombatantInstanceId, attribute_type: AttributeType, delta: AttributeValueT },
+
+    /// Takes a combatant off the field, and puts a teammate who isn't playing on in their place.
+    Substitute { combatant_id: CombatantInstanceId, substitute_id: CombatantInstanceId },
+
+    /// Turns a plate on or off for the rest of the game, overriding its schedule.
+    SetPlateActive { plate_id: PlateId, is_active: bool },
+
+    /// Moves an arena feature, such as a moving wall, and holds it there for the rest of the game.
+    MoveArenaFeature { feature_index: ArenaFeatureIndex, position: Vec3 },
+
+    /// Pauses the game runner, so that the game can be inspected before the tick is simulated.
+    Pause,
+}
+
+/// Why an external input couldn't be queued, or couldn't be applied to the game.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ExternalInputError {
+    /// The input was queued for a tick that has already been simulated.
+    TickAlreadySimulated { tick_number: GameTickNumber, next_tick_number: GameTickNumber },
+
+    /// The input concerns a combatant who isn't on the field.
+    UnknownCombatant(CombatantInstanceId),
+
+    /// The substitute isn't on the combatant's team, or is already playing.
+    SubstituteUnavailable(CombatantInstanceId),
+
+    UnknownPlate(PlateId),
+
+    /// The arena has no feature at the index, or the feature can't be moved.
+    UnmovableArenaFeature(ArenaFeatureIndex),
+}
+
+impl Display for ExternalInputError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExternalInputError::TickAlreadySimulated { tick_number, next_tick_number } => {
+                write!(f, "tick {tick_number} has already been simulated, the next tick is {next_tick_number}")
+            },
+            ExternalInputError::UnknownCombatant(combatant_id) => write!(f, "combatant {combatant_id} isn't on the field"),
+            ExternalInputError::SubstituteUnavailable(combatant_id) => write!(f, "combatant {combatant_id} can't be substituted on"),
+            ExternalInputError::UnknownPlate(plate_id) => write!(f, "arena has no plate {plate_id}"),
+            ExternalInputError::UnmovableArenaFeature(feature_index) => write!(f, "arena feature {feature_index} can't be moved"),
+        }
+    }
+}
+
+impl std::error::Error for ExternalInputError {}
+
+/// Events the simulation committed as the result of an input, and which of them caused which.
+type CommittedInputEvents = (Vec<SimulationEvent>, Vec<SimulationEventCause>);
+
+/// Applies the input to the game, returning any events the simulation committed as a result.
+/// Pausing is left to the game runner, so it doesn't affect the game state.
+pub(crate) fn apply_input(game_state: &Arc<Mutex<GameState>>, input: &ExternalInput) -> Result<CommittedInputEvents, ExternalInputError> {
+    match input {
+        ExternalInput::ApplyAttributeEffect { combatant_id, attribute_type, delta } => {
+            if !game_state.lock().unwrap().adjust_attribute(*combatant_id, attribute_type, *delta) {
+                return Err(ExternalInputError::UnknownCombatant(*combatant_id));
+            }
+
+            Ok((vec![], vec![]))
+        },
+        ExternalInput::Substitute { combatant_id, substitute_id } => {
+            let mut game_state = game_state.lock().unwrap();
+            if !game_state.combatants.contains_key(combatant_id) {
+                return Err(ExternalInputError::UnknownCombatant(*combatant_id));
+            }
+
+            if !game_state.substitute_combatant(*combatant_id, *substitute_id) {
+                return Err(ExternalInputError::SubstituteUnavailable(*substitute_id));
+            }
+
+            Ok((vec![], vec![]))
+        },
+        ExternalInput::SetPlateActive { plate_id, is_active } => {
+            let event = SimulationEvent::PlateActivityChanged { plate_id: *plate_id, is_active: *is_active };
+            let committed_input_events = commit_input_event(game_state, &event).ok_or(ExternalInputError::UnknownPlate(*plate_id))?;
+            game_state.lock().unwrap().plates.get_mut(plate_id).unwrap().override_schedule();
+            Ok(committed_input_events)
+        },
+        ExternalInput::MoveArenaFeature { feature_index, position } => {
+            let event = SimulationEvent::ArenaFeatureMoved { feature_index: *feature_index, position: *position };
+            let committed_input_events = commit_input_event(game_state, &event).ok_or(ExternalInputError::UnmovableArenaFeature(*feature_index))?;
+            game_state.lock().unwrap().kinematic_features.get_mut(feature_index).unwrap().override_kinematics();
+            Ok(committed_input_events)
+        },
+        ExternalInput::Pause => Ok((vec![], vec![])),
+    }
+}
+
+/// Commits the event as though the simulation had generated it, or returns None if the simulation rejected it.
+fn commit_input_event(game_state: &Arc<Mutex<GameState>>, event: &SimulationEvent) -> Option<CommittedInputEvents> {
+    let event_budget_per_tick = game_state.lock().unwrap().simulation_config.event_budget_per_tick();
+    let mut event_queue = SimulationEventQueue::new(event_budget_per_tick);
+    event_queue.commit(game_state, vec![PendingSimulationEvent(event.clone())]);
+    let (committed_simulation_events, event_causes, _) = event_queue.into_parts();
+    if committed_simulation_events.first() != Some(event) {
+        return None;
+    }
+
+    game_state.lock().unwrap().update_team_roles(&committed_simulation_events);
+    Some((committed_simulation_events, event_causes))
+}
@@ -1,15 +1,10 @@
 use std::sync::{Arc, Mutex};
-use rapier3d::glamx::vec3;
-use dys_world::{arena::{ball_spawn::ArenaBallSpawn, barrier::ArenaBarrier, feature::ArenaFeature, plate::ArenaPlate}, games::instance::GameInstance};
-use rapier3d::prelude::*;
-use dys_world::arena::Arena;
+use dys_world::{arena::Arena, games::instance::GameInstance, schedule::calendar::{Date, Month}, team::instance::TeamInstance};
 use crate::{
     game_log::GameLog,
-    game_objects::game_object::GameObject,
+    game_runner::GameRunner,
     game_state::GameState,
-    game_tick::{GameTick, TickPerformance},
-    simulation::simulate_tick,
-    simulation::simulation_event::SimulationEvent};
+    simulation::config::SimulationConfig};
 
 #[derive(Clone)]
 pub struct Game {
@@ -17,98 +12,43 @@ pub struct Game {
 }
 
 impl Game {
-    fn simulate_internal(&self, game_state: GameState) -> GameLog {
-        let mut ticks = vec![];
-
-        // Add a "tick 0" for initial state
-        // ZJ-TODO: there should probably be a SimulationEvent::InitialState, rather than a bunch of updates
-        {
-            let (rigid_body_set, _) = game_state.physics_sim.sets();
-            let mut simulation_events = vec![];
-            for (combatant_id, combatant_object) in &game_state.combatants {
-                let combatant_rb = rigid_body_set.get(combatant_object.rigid_body_handle().unwrap()).unwrap();
-                simulation_events.push(SimulationEvent::CombatantPositionUpdate { 
-                    combatant_id: *combatant_id,
-                    position: combatant_rb.translation(),
-                });
-            }
-            
-            for (ball_id, ball_object) in &game_state.balls {
-                let ball_rb = rigid_body_set.get(ball_object.rigid_body_handle().unwrap()).unwrap();
-                simulation_events.push(SimulationEvent::BallPositionUpdate { 
-                    ball_id: *ball_id,
-                    position: ball_rb.translation(),
-                    charge: ball_object.charge,
-                });
-            }
-
-            let arena = Arena::new_with_testing_defaults();
-            // let arena = self.game_instance.arena.lock().unwrap();
-            let arena_features = arena.all_features();
-            for feature in arena_features.iter().filter(|feature| feature.shape().is_some()) {
-                let shape = feature.shape().unwrap();
-                let object_type_id: u32 = {
-                    if let Some(barrier) = feature.as_any().downcast_ref::<ArenaBarrier>() {
-                        match barrier.pathing_type() {
-                            dys_world::arena::feature::NavmeshPathingType::Generate => 1,
-                            dys_world::arena::feature::NavmeshPathingType::Skip => 0,
-                            dys_world::arena::feature::NavmeshPathingType::Block => 2,
-                        }
-                    } else if feature.as_any().downcast_ref::<ArenaBallSpawn>().is_some() {
-                        3
-                    } else if feature.as_any().downcast_ref::<ArenaPlate>().is_some() {
-                        4
-                    } else {
-                        0
-                    }
-                };
-                simulation_events.push(SimulationEvent::ArenaObjectPositionUpdate {
-                    object_type_id,  
-                    position: *feature.origin(),
-                    scale: match shape.shape_type() {
-                        ShapeType::Ball => vec3(shape.as_ball().unwrap().radius, shape.as_ball().unwrap().radius, shape.as_ball().unwrap().radius),
-                        ShapeType::Cuboid => shape.as_cuboid().unwrap().half_extents * 2.0,
-                        ShapeType::Capsule => vec3(shape.as_capsule().unwrap().radius, shape.as_capsule().unwrap().height(), shape.as_capsule().unwrap().radius),
-                        ShapeType::Cylinder => vec3(shape.as_cylinder().unwrap().radius, shape.as_cylinder().unwrap().half_height * 2.0, shape.as_cylinder().unwrap().radius),
-                        _ => panic!("shape unsupported")
-                    },
-                    rotation: *feature.rotation(),
-                })
-            }
-
-            let tick_zero = GameTick {
-                tick_number: 0,
-                tick_performance: TickPerformance::default(),
-                simulation_events,
-                is_end_of_game: false,
-            };
-
-            ticks.push(tick_zero);
+    /// A game between two teams in the testing arena, for tests that need whole games to simulate.
+    #[must_use]
+    pub fn new_with_testing_defaults(away_team: Arc<Mutex<TeamInstance>>, home_team: Arc<Mutex<TeamInstance>>) -> Game {
+        Game {
+            game_instance: GameInstance {
+                game_id: 0,
+                away_team,
+                home_team,
+                arena: Arc::new(Mutex::new(Arena::new_with_testing_defaults())),
+                arena_id: 0,
+                date: Date::new(Month::Arguscorp, 1, 10000),
+            },
         }
+    }
 
-        let game_state = Arc::new(Mutex::new(game_state));
-        loop {
-            let new_tick = simulate_tick(game_state.clone());
-            let is_end_of_game = new_tick.is_end_of_game();
-
-            ticks.push(new_tick);
-
-            if is_end_of_game {
-                break;
-            }
-        }
+    fn simulate_internal(game_state: GameState) -> GameLog {
+        let mut game_runner = GameRunner::new(game_state);
+        let ticks = game_runner.by_ref().collect();
 
-        GameLog::from_ticks(ticks, game_state)
+        GameLog::from_ticks(ticks, game_runner.game_state().clone())
     }
 
     pub fn simulate(&self) -> GameLog {
         let game_state = GameState::from_game(self.clone());
-        self.simulate_internal(game_state)
+        Game::simulate_internal(game_state)
     }
 
     pub fn simulate_seeded(&self, seed: &[u8; 32]) -> GameLog {
         let game_state = GameState::from_game_seeded(self.clone(), seed);
-        self.simulate_internal(game_state)
+        Game::simulate_internal(game_state)
+    }
+
+    #[must_use]
+    pub fn simulate_seeded_with_config(&self, seed: &[u8; 32], simulation_config: SimulationConfig) -> GameLog {
+        let mut game_state = GameState::from_game_seeded(self.clone(), seed);
+        game_state.simulation_config = simulation_config;
+        Game::simulate_internal(game_state)
     }
 }
 
@@ -118,9 +58,14 @@ impl Game {
 mod tests {
     use rand::prelude::StdRng;
     use rand::SeedableRng;
-    use dys_world::{schedule::calendar::{Date, Month}, generator::Generator, games::instance::GameInstance};
+    use std::sync::{Arc, Mutex};
+    use dys_world::{arena::Arena, schedule::calendar::{Date, Month}, generator::Generator, games::instance::GameInstance};
 
+    use crate::ai::test_utils::make_generated_game;
     use crate::game::Game;
+    use crate::simulation::config::SimulationConfig;
+    use crate::simulation::simulation_event::EventRejectionReason;
+    use crate::state_hash::verify_replay;
 
     #[test]
     fn test_deterministic_simulations() {
@@ -137,15 +82,29 @@ mod tests {
                 game_id: 0,
                 away_team: teams.next().unwrap().to_owned(),
                 home_team: teams.next().unwrap().to_owned(),
-                // arena: Arc::new(Mutex::new(Arena::new_with_testing_defaults())),
+                arena: Arc::new(Mutex::new(Arena::new_with_testing_defaults())),
                 arena_id: 0,
                 date: Date::new(Month::Arguscorp, 1, 10000),
             },
         };
-        let game_1 = game.simulate_seeded(seed);
-        let game_2 = game.simulate_seeded(seed);
+        let game_log = game.simulate_seeded_with_config(seed, SimulationConfig::default().with_state_hashes(true));
 
-        assert_eq!(game_1.home_score(), game_2.home_score());
-        assert_eq!(game_1.away_score(), game_2.away_score());
+        // Every tick of a replay must match the recording, not just the final score
+        assert_eq!(verify_replay(&game, SimulationConfig::default(), &game_log), Ok(()));
+    }
+
+    #[test]
+    fn test_rejected_events_are_only_logged_when_enabled() {
+        // Every tick has more position updates than this, so some are always rejected
+        let game = make_generated_game();
+        let simulation_config = SimulationConfig::default().with_seconds_per_period(3).with_event_budget_per_tick(4);
+
+        let game_log = game.simulate_seeded_with_config(&[0; 32], simulation_config.clone());
+        assert!(game_log.ticks().iter().all(|game_tick| game_tick.rejected_events.is_empty()));
+
+        let game_log = game.simulate_seeded_with_config(&[0; 32], simulation_config.with_rejected_events(true));
+        let rejected_events = game_log.ticks().iter().flat_map(|game_tick| &game_tick.rejected_events).collect::<Vec<_>>();
+        assert!(!rejected_events.is_empty());
+        assert!(rejected_events.iter().all(|rejected| rejected.reason == EventRejectionReason::EventBudgetExceeded));
     }
 }
\ No newline at end of file
@@ -0,0 +1,298 @@
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+use indexmap::IndexMap;
+use rand_pcg::Pcg64;
+use rapier3d::prelude::{ColliderHandle, RigidBodyHandle};
+use serde::{Deserialize, Serialize};
+use dys_world::arena::environment::GameEnvironment;
+use dys_world::arena::navmesh::NavmeshPathCache;
+use dys_world::combatant::instance::{CombatantInstance, CombatantInstanceId};
+use dys_world::games::instance::GameInstanceId;
+use crate::ai::coordinator::TeamCoordinator;
+use crate::game::Game;
+use crate::game_objects::combatant::{CombatantObject, CombatantState, TeamAlignment};
+use crate::game_state::{BallsMapT, CollidersMapT, CombatantsMapT, GameState, HazardsMapT, KinematicFeaturesMapT, PlatesMapT, SeedT};
+use crate::game_tick::GameTickNumber;
+use crate::physics_sim::PhysicsSim;
+use crate::simulation::config::SimulationConfig;
+
+/// Why a game couldn't be checkpointed, or restored from a checkpoint.
+#[derive(Debug)]
+pub enum GameCheckpointError {
+    /// The checkpoint couldn't be written or read, eg because the bytes aren't a checkpoint.
+    Serialization(postcard::Error),
+
+    /// The checkpoint was taken from a different game than the one it's being restored into.
+    WrongGame { expected: GameInstanceId, found: GameInstanceId },
+
+    /// A combatant in the checkpoint doesn't play for either team in the game.
+    UnknownCombatant(CombatantInstanceId),
+}
+
+impl Display for GameCheckpointError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameCheckpointError::Serialization(error) => write!(f, "failed to serialize game checkpoint: {error}"),
+            GameCheckpointError::WrongGame { expected, found } => write!(f, "checkpoint is from game {found}, not game {expected}"),
+            GameCheckpointError::UnknownCombatant(combatant_id) => write!(f, "combatant {combatant_id} in checkpoint doesn't play in the game"),
+        }
+    }
+}
+
+impl std::error::Error for GameCheckpointError {}
+
+impl From<postcard::Error> for GameCheckpointError {
+    fn from(error: postcard::Error) -> Self {
+        GameCheckpointError::Serialization(error)
+    }
+}
+
+/// A combatant partway through a game.
+/// The combatant is kept as the game sees them, since the game changes its own copy when their attributes are adjusted.
+#[derive(Serialize, Deserialize)]
+struct CombatantCheckpoint {
+    combatant: CombatantInstance,
+    team: TeamAlignment,
+    rigid_body_handle: RigidBodyHandle,
+    collider_handle: ColliderHandle,
+    state: CombatantState,
+}
+
+/// A game partway through being simulated, from which the simulation can carry on exactly as it would have.
+///
+/// The game being played and the arena's navmesh are shared with the world rather than owned by the game,
+/// so they aren't part of the checkpoint, and must be given back when restoring it.
+#[derive(Serialize, Deserialize)]
+pub struct GameCheckpoint {
+    game_id: GameInstanceId,
+    seed: SeedT,
+    rng: Pcg64,
+    physics_sim: PhysicsSim,
+    combatants: IndexMap<CombatantInstanceId, CombatantCheckpoint>,
+    balls: BallsMapT,
+    plates: PlatesMapT,
+    hazards: HazardsMapT,
+    kinematic_features: KinematicFeaturesMapT,
+    active_colliders: CollidersMapT,
+    home_points: u16,
+    away_points: u16,
+    current_tick: GameTickNumber,
+    simulation_config: SimulationConfig,
+    environment: GameEnvironment,
+    team_coordinators: IndexMap<TeamAlignment, TeamCoordinator>,
+}
+
+impl GameCheckpoint {
+    /// # Panics
+    /// Will panic if a combatant or their state cannot be locked.
+    #[must_use]
+    pub fn from_game_state(game_state: &GameState) -> GameCheckpoint {
+        let combatants = game_state.combatants
+            .iter()
+            .map(|(combatant_id, combatant_object)| (*combatant_id, CombatantCheckpoint {
+                combatant: combatant_object.combatant.lock().unwrap().clone(),
+                team: combatant_object.team,
+                rigid_body_handle: combatant_object.rigid_body_handle,
+                collider_handle: combatant_object.collider_handle,
+                state: combatant_object.combatant_state.lock().unwrap().clone(),
+            }))
+            .collect();
+
+        GameCheckpoint {
+            game_id: game_state.game.game_instance.game_id,
+            seed: game_state.seed,
+            rng: game_state.rng.clone(),
+            physics_sim: game_state.physics_sim.clone(),
+            combatants,
+            balls: game_state.balls.clone(),
+            plates: game_state.plates.clone(),
+            hazards: game_state.hazards.clone(),
+            kinematic_features: game_state.kinematic_features.clone(),
+            active_colliders: game_state.active_colliders.clone(),
+            home_points: game_state.home_points,
+            away_points: game_state.away_points,
+            current_tick: game_state.current_tick,
+            simulation_config: game_state.simulation_config.clone(),
+            environment: game_state.environment.clone(),
+            team_coordinators: game_state.team_coordinators.clone(),
+        }
+    }
+
+    /// # Errors
+    /// Will error if a combatant is partway through an action that can't be serialized.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, GameCheckpointError> {
+        Ok(postcard::to_allocvec(self)?)
+    }
+
+    /// # Errors
+    /// Will error if the bytes aren't a checkpoint.
+    pub fn from_bytes(bytes: &[u8]) -> Result<GameCheckpoint, GameCheckpointError> {
+        Ok(postcard::from_bytes(bytes)?)
+    }
+
+    #[must_use]
+    pub fn game_id(&self) -> GameInstanceId {
+        self.game_id
+    }
+
+    /// The tick the game had just finished simulating when the checkpoint was taken.
+    #[must_use]
+    pub fn current_tick(&self) -> GameTickNumber {
+        self.current_tick
+    }
+
+    /// Picks the game back up from the checkpoint, in the game's own arena.
+    /// `game` must be the game the checkpoint was taken from.
+    ///
+    /// # Errors
+    /// Will error if the checkpoint is from a different game, or has combatants that don't play in the game.
+    ///
+    /// # Panics
+    /// Will panic if either team or the arena in the game cannot be locked.
+    pub fn restore(self, game: Game) -> Result<GameState, GameCheckpointError> {
+        if self.game_id != game.game_instance.game_id {
+            return Err(GameCheckpointError::WrongGame { expected: game.game_instance.game_id, found: self.game_id });
+        }
+
+        let team_combatant_ids = {
+            let home_team = game.game_instance.home_team.lock().unwrap();
+            let away_team = game.game_instance.away_team.lock().unwrap();
+            home_team.combatants
+                .iter()
+                .chain(away_team.combatants.iter())
+                .map(|combatant| combatant.lock().unwrap().id)
+                .collect::<Vec<_>>()
+        };
+
+        let mut combatants = CombatantsMapT::new();
+        for (combatant_id, combatant_checkpoint) in self.combatants {
+            if !team_combatant_ids.contains(&combatant_id) {
+                return Err(GameCheckpointError::UnknownCombatant(combatant_id));
+            }
+
+            combatants.insert(combatant_id, CombatantObject {
+                id: combatant_id,
+                combatant: Arc::new(Mutex::new(combatant_checkpoint.combatant)),
+                combatant_state: Arc::new(Mutex::new(combatant_checkpoint.state)),
+                team: combatant_checkpoint.team,
+                rigid_body_handle: combatant_checkpoint.rigid_body_handle,
+                collider_handle: combatant_checkpoint.collider_handle,
+            });
+        }
+
+        let arena_navmesh = game.game_instance.arena.lock().unwrap().navmesh();
+        Ok(GameState {
+            game,
+            seed: self.seed,
+            rng: self.rng,
+            physics_sim: self.physics_sim,
+            combatants,
+            balls: self.balls,
+            plates: self.plates,
+            hazards: self.hazards,
+            kinematic_features: self.kinematic_features,
+            active_colliders: self.active_colliders,
+            home_points: self.home_points,
+            away_points: self.away_points,
+            current_tick: self.current_tick,
+            simulation_config: self.simulation_config,
+            arena_navmesh,
+            path_cache: NavmeshPathCache::new(),
+            environment: self.environment,
+            team_coordinators: self.team_coordinators,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use dys_world::attribute::attribute_type::AttributeType;
+
+    use crate::ai::test_utils::make_generated_game;
+    use crate::game_state::GameState;
+    use crate::simulation::simulate_tick;
+    use crate::simulation::simulation_event::SimulationEvent;
+    use crate::state_hash::TickStateHash;
+    use super::GameCheckpointError;
+
+    fn simulate_ticks(game_state: &Arc<Mutex<GameState>>, ticks: u32) -> Vec<SimulationEvent> {
+        (0..ticks)
+            .flat_map(|_| simulate_tick(game_state.clone()).simulation_events)
+            .collect()
+    }
+
+    #[test]
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn test_restored_games_carry_on_identically() {
+        let game = make_generated_game();
+        let game_state = Arc::new(Mutex::new(GameState::from_game_seeded(game.clone(), &[0; 32])));
+
+        // Far enough in that combatants are partway through plans, with beliefs about the world
+        simulate_ticks(&game_state, 40);
+        let checkpoint = game_state.lock().unwrap().checkpoint().unwrap();
+
+        let restored_game_state = GameState::from_checkpoint(game, &checkpoint).unwrap();
+        assert_eq!(restored_game_state.current_tick, 40);
+        let restored_game_state = Arc::new(Mutex::new(restored_game_state));
+
+        let events = simulate_ticks(&game_state, 30);
+        let restored_events = simulate_ticks(&restored_game_state, 30);
+
+        assert!(!events.is_empty());
+        assert_eq!(postcard::to_allocvec(&events).unwrap(), postcard::to_allocvec(&restored_events).unwrap());
+
+        // Everything else about the games, down to the physics sim and the RNG, should have stayed in step too
+        let game_state = game_state.lock().unwrap();
+        let restored_game_state = restored_game_state.lock().unwrap();
+        assert_eq!(game_state.checkpoint().unwrap(), restored_game_state.checkpoint().unwrap());
+    }
+
+    #[test]
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn test_restored_games_keep_adjusted_attributes() {
+        let game = make_generated_game();
+        let game_state = Arc::new(Mutex::new(GameState::from_game_seeded(game.clone(), &[0; 32])));
+        simulate_ticks(&game_state, 10);
+
+        // Much faster than before, which only the game's own copy of the combatant knows about
+        let combatant_id = *game_state.lock().unwrap().combatants.keys().next().unwrap();
+        assert!(game_state.lock().unwrap().adjust_attribute(combatant_id, &AttributeType::Dexterity, 50.0));
+        let checkpoint = game_state.lock().unwrap().checkpoint().unwrap();
+
+        let restored_game_state = GameState::from_checkpoint(game, &checkpoint).unwrap();
+        let move_speed = |game_state: &GameState| game_state.combatants[&combatant_id].combatant.lock().unwrap().move_speed();
+        assert!((move_speed(&restored_game_state) - move_speed(&game_state.lock().unwrap())).abs() < f32::EPSILON);
+        assert_eq!(
+            TickStateHash::from_game_state(&restored_game_state).combined(),
+            TickStateHash::from_game_state(&game_state.lock().unwrap()).combined(),
+        );
+
+        let restored_game_state = Arc::new(Mutex::new(restored_game_state));
+        for _ in 0..30 {
+            let tick = simulate_tick(game_state.clone());
+            let restored_tick = simulate_tick(restored_game_state.clone());
+            assert_eq!(postcard::to_allocvec(&tick.simulation_events).unwrap(), postcard::to_allocvec(&restored_tick.simulation_events).unwrap());
+            assert_eq!(
+                TickStateHash::from_game_state(&restored_game_state.lock().unwrap()).combined(),
+                TickStateHash::from_game_state(&game_state.lock().unwrap()).combined(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_checkpoints_only_restore_into_their_own_game() {
+        let game = make_generated_game();
+        let game_state = GameState::from_game_seeded(game.clone(), &[0; 32]);
+        let checkpoint = game_state.checkpoint().unwrap();
+
+        let mut other_game = game;
+        other_game.game_instance.game_id += 1;
+
+        let result = GameState::from_checkpoint(other_game, &checkpoint);
+        assert!(matches!(result, Err(GameCheckpointError::WrongGame { expected: 1, found: 0 })));
+
+        let result = GameState::from_checkpoint(make_generated_game(), &checkpoint[..10]);
+        assert!(matches!(result, Err(GameCheckpointError::Serialization(_))));
+    }
+}
@@ -1,5 +1,6 @@
 use std::sync::{Arc, Mutex};
 use serde::{Deserialize, Serialize};
+use dys_world::arena::environment::GameEnvironment;
 use dys_world::combatant::instance::CombatantInstanceId;
 use dys_world::games::instance::GameInstanceId;
 use crate::game_state::{GameState, SeedT};
@@ -14,6 +15,7 @@ pub struct GameLog {
     ticks: Vec<GameTick>,
     performance: TickPerformance,
     combatants: Vec<CombatantInstanceId>,
+    environment: GameEnvironment,
 }
 
 impl GameLog {
@@ -35,9 +37,13 @@ impl GameLog {
             ticks,
             performance: perf,
             combatants,
+            environment: game_state.environment.clone(),
         }
     }
 
+    #[must_use]
+    pub fn game_id(&self) -> GameInstanceId { self.game_id }
+
     pub fn home_score(&self) -> u16 { self.home_score }
 
     pub fn away_score(&self) -> u16 { self.away_score }
@@ -57,4 +63,10 @@ impl GameLog {
     pub fn combatants(&self) -> &Vec<CombatantInstanceId> {
         &self.combatants
     }
+
+    /// Conditions the game was played in, such as low gravity or fog.
+    #[must_use]
+    pub fn environment(&self) -> &GameEnvironment {
+        &self.environment
+    }
 }
\ No newline at end of file
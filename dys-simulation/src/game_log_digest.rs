@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+use crate::game_log::GameLog;
+use crate::game_objects::combatant::TeamAlignment;
+use crate::state_hash::hash_serialized;
+
+/// A summary of a game log, small enough to be checked in as the expected result of simulating a game.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameLogDigest {
+    pub home_score: u16,
+    pub away_score: u16,
+    pub tick_count: usize,
+    /// How many of each variant of simulation event happened over the game.
+    pub event_counts: BTreeMap<String, u32>,
+    /// Hash of every event in the game, so that any change in behaviour is noticed,
+    /// even one that doesn't change the score or how often things happen.
+    pub events_hash: u64,
+}
+
+impl GameLogDigest {
+    #[must_use]
+    pub fn from_game_log(game_log: &GameLog) -> GameLogDigest {
+        let mut event_counts = BTreeMap::new();
+        let mut tick_hashes = vec![];
+        for game_tick in game_log.ticks() {
+            for simulation_event in &game_tick.simulation_events {
+                *event_counts.entry(simulation_event.variant_name().to_string()).or_default() += 1;
+            }
+
+            tick_hashes.push(hash_serialized(&game_tick.simulation_events));
+        }
+
+        GameLogDigest {
+            home_score: game_log.home_score(),
+            away_score: game_log.away_score(),
+            tick_count: game_log.ticks().len(),
+            event_counts,
+            events_hash: hash_serialized(&tick_hashes),
+        }
+    }
+
+    /// How a game played out differently to what was expected of it.
+    /// Empty if the game played out exactly as expected.
+    #[must_use]
+    pub fn changes_from(&self, expected: &GameLogDigest) -> Vec<GameLogChange> {
+        let mut changes = vec![];
+
+        for (team, expected_score, actual_score) in [
+            (TeamAlignment::Home, expected.home_score, self.home_score),
+            (TeamAlignment::Away, expected.away_score, self.away_score),
+        ] {
+            if expected_score != actual_score {
+                changes.push(GameLogChange::Score { team, expected: expected_score, actual: actual_score });
+            }
+        }
+
+        if expected.tick_count != self.tick_count {
+            changes.push(GameLogChange::TickCount { expected: expected.tick_count, actual: self.tick_count });
+        }
+
+        let mut variants = expected.event_counts.keys().chain(self.event_counts.keys()).collect::<Vec<_>>();
+        variants.sort();
+        variants.dedup();
+        for variant in variants {
+            let expected_count = expected.event_counts.get(variant).copied().unwrap_or_default();
+            let actual_count = self.event_counts.get(variant).copied().unwrap_or_default();
+            if expected_count != actual_count {
+                changes.push(GameLogChange::EventCount { variant: variant.clone(), expected: expected_count, actual: actual_count });
+            }
+        }
+
+        if changes.is_empty() && expected.events_hash != self.events_hash {
+            changes.push(GameLogChange::EventDetails);
+        }
+
+        changes
+    }
+}
+
+/// A way in which a game played out differently to what was expected of it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameLogChange {
+    Score { team: TeamAlignment, expected: u16, actual: u16 },
+    TickCount { expected: usize, actual: usize },
+    EventCount { variant: String, expected: u32, actual: u32 },
+    /// The same events happened as often as expected, but not exactly as expected, eg combatants took different paths.
+    EventDetails,
+}
+
+impl Display for GameLogChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameLogChange::Score { team, expected, actual } => write!(f, "{team:?} score {expected} -> {actual}"),
+            GameLogChange::TickCount { expected, actual } => write!(f, "game length {expected} -> {actual} ticks"),
+            GameLogChange::EventCount { variant, expected, actual } => {
+                write!(f, "{variant} events {expected} -> {actual} ({:+})", i64::from(*actual) - i64::from(*expected))
+            },
+            GameLogChange::EventDetails => write!(f, "event details changed, without changing scores or event counts"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ai::test_utils::make_generated_game;
+    use crate::game_objects::combatant::TeamAlignment;
+    use crate::simulation::config::SimulationConfig;
+    use super::{GameLogChange, GameLogDigest};
+
+    #[test]
+    fn test_digests_report_what_changed() {
+        let game = make_generated_game();
+        let game_log = game.simulate_seeded_with_config(&[0; 32], SimulationConfig::default().with_seconds_per_period(3));
+        let digest = GameLogDigest::from_game_log(&game_log);
+        assert_eq!(digest.tick_count, 61);
+        assert!(digest.changes_from(&digest).is_empty());
+
+        let mut expected = digest.clone();
+        expected.home_score += 3;
+        *expected.event_counts.get_mut("CombatantPositionUpdate").unwrap() += 2;
+        expected.event_counts.insert("RetiredEvent".to_string(), 1);
+
+        let position_updates = digest.event_counts["CombatantPositionUpdate"];
+        assert_eq!(digest.changes_from(&expected), vec![
+            GameLogChange::Score { team: TeamAlignment::Home, expected: digest.home_score + 3, actual: digest.home_score },
+            GameLogChange::EventCount { variant: "CombatantPositionUpdate".to_string(), expected: position_updates + 2, actual: position_updates },
+            GameLogChange::EventCount { variant: "RetiredEvent".to_string(), expected: 1, actual: 0 },
+        ]);
+
+        let mut expected = digest.clone();
+        expected.events_hash += 1;
+        assert_eq!(digest.changes_from(&expected), vec![GameLogChange::EventDetails]);
+    }
+}
@@ -1,5 +1,7 @@
+use serde::{Deserialize, Serialize};
 use rapier3d::{dynamics::{RigidBodyBuilder, RigidBodyHandle, RigidBodySet}, geometry::{ColliderBuilder, ColliderHandle, ColliderSet}, pipeline::ActiveEvents};
 use rapier3d::prelude::{Pose3, Vec3};
+use dys_world::arena::environment::GameEnvironment;
 use dys_world::combatant::instance::CombatantInstanceId;
 use crate::game_tick::GameTickNumber;
 
@@ -10,8 +12,9 @@ pub type BallId = u16;
 const BALL_RADIUS: f32 = 0.5;
 const BALL_RESTITUTION: f32 = 0.2;
 const BALL_MASS: f32 = 2.0;
+const EXPLOSION_RADIUS_PER_CHARGE: f32 = 0.3; // ZJ-TODO: figure out explosion radius as compared to charge
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum BallState {
     Idle,
     Held { 
@@ -25,7 +28,7 @@ pub enum BallState {
     Explode,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct BallObject {
     pub id: BallId,
     rigid_body_handle: RigidBodyHandle,
@@ -38,7 +41,7 @@ pub struct BallObject {
 }
 
 impl BallObject {
-    pub fn new(id: BallId, creation_tick: GameTickNumber, position: Vec3, rigid_body_set: &mut RigidBodySet, collider_set: &mut ColliderSet) -> BallObject {
+    pub fn new(id: BallId, creation_tick: GameTickNumber, position: Vec3, environment: &GameEnvironment, rigid_body_set: &mut RigidBodySet, collider_set: &mut ColliderSet) -> BallObject {
         let rigid_body = RigidBodyBuilder::dynamic()
             .translation(position)
             .lock_rotations()
@@ -47,7 +50,8 @@ impl BallObject {
         
         let collider = ColliderBuilder::ball(BALL_RADIUS)
             .active_events(ActiveEvents::COLLISION_EVENTS)
-            .restitution(BALL_RESTITUTION)
+            // Restitution above 1 would have balls gain speed with every bounce
+            .restitution((BALL_RESTITUTION * environment.ball_restitution_multiplier).min(1.0))
             .density(BALL_MASS)
             .position(Pose3::translation(0.0, BALL_RADIUS, 0.0))
             .build();
@@ -71,6 +75,12 @@ impl BallObject {
         BALL_RADIUS
     }
 
+    /// How far from the ball its explosion would reach, were it to explode now.
+    #[must_use]
+    pub fn explosion_radius(&self) -> f32 {
+        self.charge * EXPLOSION_RADIUS_PER_CHARGE
+    }
+
     pub fn set_held_by(&mut self, combatant_id: Option<CombatantInstanceId>, current_tick: GameTickNumber) {
         self.held_by = combatant_id;
 
@@ -1,6 +1,7 @@
 use std::{fmt::Debug, sync::{Arc, Mutex}};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use rand::Rng;
-use dys_world::{arena::plate::PlateId, combatant::instance::CombatantInstance};
+use dys_world::{arena::{environment::GameEnvironment, plate::PlateId, Arena}, combatant::instance::CombatantInstance};
 use rapier3d::{dynamics::{RigidBodyBuilder, RigidBodyHandle, RigidBodySet}, geometry::{ActiveCollisionTypes, ColliderBuilder, ColliderHandle, ColliderSet}, pipeline::ActiveEvents};
 use rapier3d::glamx::vec3;
 use rapier3d::prelude::*;
@@ -10,7 +11,9 @@ use dys_world::combatant::instance::CombatantInstanceId;
 use crate::{ai::{action::Action, agent::Agent, belief::Belief, planner}, game_state::GameState, game_tick::GameTickNumber, simulation::simulation_event::SimulationEvent};
 use crate::ai::belief::SatisfiableBelief;
 use crate::ai::beliefs::belief_set::BeliefSet;
-use crate::ai::sensor::Sensor;
+use crate::ai::personality::Personality;
+use crate::ai::planner_trace::PlannerTrace;
+use crate::ai::sensor::{Sensor, SensorSnapshot};
 use crate::ai::sensors::field_of_view::FieldOfViewSensor;
 use crate::ai::sensors::proximity::ProximitySensor;
 use crate::simulation::simulation_event::PendingSimulationEvent;
@@ -20,13 +23,54 @@ use super::{ball::BallId, game_object::GameObject};
 const COMBATANT_HALF_HEIGHT: f32 = 1.0; // ZJ-TODO: this should be derived from the character's limbs
 const COMBATANT_RADIUS: f32 = 0.5; // ZJ-TODO: this should be derived from the character's limbs
 const COMBATANT_MASS: f32 = 100.0;
+const COMBATANT_FRICTION: f32 = 0.5;
+const SIGHT_DISTANCE: f32 = 70.0;
 
-#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+/// Stamina spent per unit a combatant moves.
+pub(crate) const MOVE_STAMINA_COST_PER_UNIT: f32 = 0.1;
+
+/// Stamina spent throwing a ball.
+pub(crate) const THROW_STAMINA_COST: f32 = 5.0;
+
+/// Stamina spent shoving another combatant.
+pub(crate) const SHOVE_STAMINA_COST: f32 = 10.0;
+
+/// Stamina recovered each tick a combatant rests, as a fraction of their max stamina.
+/// Combatants recover less the harder they exerted themselves the tick before.
+const STAMINA_RECOVERY_PER_TICK: f32 = 0.005;
+
+/// Fully exhausted combatants move, throw and shove at this fraction of their rested ability.
+const EXHAUSTED_FATIGUE_MODIFIER: f32 = 0.5;
+
+/// A combatant's sensors, each with the ID it was registered under.
+pub type SensorsT = Vec<(u32, Box<dyn Sensor>)>;
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum TeamAlignment {
     Home,
     Away,
 }
 
+impl TeamAlignment {
+    /// Direction towards the enemy side of the arena, from where each team starts.
+    /// Arenas without starts for both teams are played along the x axis, with the home team facing +x.
+    #[must_use]
+    pub fn forward(&self, arena: &Arena) -> Vec3 {
+        let home_forward = arena.home_forward().unwrap_or(Vec3::X);
+        match self {
+            TeamAlignment::Home => home_forward,
+            TeamAlignment::Away => -home_forward,
+        }
+    }
+
+    /// Rotation that faces a combatant towards the enemy side of the arena.
+    #[must_use]
+    pub fn facing(&self, arena: &Arena) -> AngVector {
+        let forward = self.forward(arena);
+        AngVector::new(0.0, forward.x.atan2(forward.z), 0.0)
+    }
+}
+
 #[derive(Clone)]
 pub struct CombatantObject {
     pub id: CombatantInstanceId,
@@ -37,31 +81,59 @@ pub struct CombatantObject {
     pub collider_handle: ColliderHandle,
 }
 
-#[derive(Clone, Default, Debug)]
+/// Who a new combatant object is, and where they start in the arena.
+pub struct CombatantSpawn {
+    pub id: CombatantInstanceId,
+    pub combatant: Arc<Mutex<CombatantInstance>>,
+    pub position: Vec3,
+    pub rotation: Vec3,
+    pub team: TeamAlignment,
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct CombatantState {
     pub completed_action: Option<Action>,
     pub current_action: Option<Action>,
     pub plan: Vec<Action>,
+    /// The decisions made the last time this combatant planned.
+    /// Only recorded if planner traces are enabled in the simulation config.
+    pub planner_trace: Option<PlannerTrace>,
     pub beliefs: BeliefSet,
-    pub sensors: Vec<(u32, Box<dyn Sensor>)>,
+    #[serde(serialize_with = "serialize_sensors", deserialize_with = "deserialize_sensors")]
+    pub sensors: SensorsT,
 
     // ZJ-TODO: this should instead be a set of temporary limb modifiers
     pub damage: f32,
+    /// The damage this combatant had taken when they last planned.
+    pub damage_when_planned: f32,
+    /// Stamina spent and not yet recovered. Zero when fully rested.
+    pub fatigue: f32,
+    /// The last tick this combatant spent stamina.
+    pub exerted_on_tick: GameTickNumber,
+    /// The stamina this combatant spent on the tick they last exerted themselves.
+    pub exertion: f32,
     pub on_plate: Option<PlateId>,
     pub holding_ball: Option<BallId>,
     pub stunned: bool,
 }
 
+fn serialize_sensors<S: Serializer>(sensors: &[(u32, Box<dyn Sensor>)], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(sensors.iter().map(|(sensor_id, sensor)| (sensor_id, sensor.snapshot())))
+}
+
+fn deserialize_sensors<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SensorsT, D::Error> {
+    let sensors = Vec::<(u32, SensorSnapshot)>::deserialize(deserializer)?;
+    Ok(sensors.into_iter().map(|(sensor_id, sensor)| (sensor_id, sensor.restore())).collect())
+}
+
 impl CombatantObject {
     pub fn new(
-        id: CombatantInstanceId,
-        combatant: Arc<Mutex<CombatantInstance>>,
-        position: Vec3,
-        rotation: Vec3,
-        team: TeamAlignment,
+        spawn: CombatantSpawn,
+        environment: &GameEnvironment,
         rigid_body_set: &mut RigidBodySet,
         collider_set: &mut ColliderSet
     ) -> CombatantObject {
+        let CombatantSpawn { id, combatant, position, rotation, team } = spawn;
         let rigid_body = RigidBodyBuilder::dynamic()
             .translation(position)
             .rotation(rotation)
@@ -73,15 +145,15 @@ impl CombatantObject {
             .active_events(ActiveEvents::COLLISION_EVENTS)
             .active_collision_types(ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_FIXED | ActiveCollisionTypes::KINEMATIC_KINEMATIC)
             .mass(COMBATANT_MASS)
+            .friction(COMBATANT_FRICTION * environment.friction_multiplier)
             .position(Pose3::translation(0.0, COMBATANT_HALF_HEIGHT, 0.0))
             .build();
 
         let rigid_body_handle = rigid_body_set.insert(rigid_body);
         let collider_handle = collider_set.insert_with_parent(collider, rigid_body_handle, rigid_body_set);
 
-        const SIGHT_DISTANCE: f32 = 70.0;
         let field_of_view_sensor = FieldOfViewSensor::new(
-            SIGHT_DISTANCE, id, collider_handle
+            SIGHT_DISTANCE * environment.visibility_multiplier, id, collider_handle
         );
 
         // ZJ-TODO: determine this based on limbs
@@ -107,6 +179,7 @@ impl CombatantObject {
                 current_action: None,
                 completed_action: None,
                 plan: vec![],
+                planner_trace: None,
                 beliefs: BeliefSet::empty(),
                 sensors: vec![
                     (1, Box::new(field_of_view_sensor)),
@@ -114,6 +187,10 @@ impl CombatantObject {
                     (3, Box::new(ball_danger_proximity_sensor)),
                 ],
                 damage: 0.0,
+                damage_when_planned: 0.0,
+                fatigue: 0.0,
+                exerted_on_tick: 0,
+                exertion: 0.0,
                 stunned: false,
             })),
             team,
@@ -216,6 +293,76 @@ impl CombatantObject {
         let mut combatant_state = self.combatant_state.lock().unwrap();
         combatant_state.damage += damage;
     }
+
+    /// Remaining stamina, from 0.0 (exhausted) to the combatant's max stamina.
+    ///
+    /// # Panics
+    /// Will panic if the combatant or their state cannot be locked.
+    #[must_use]
+    pub fn stamina(&self) -> f32 {
+        let max_stamina = self.combatant.lock().unwrap().max_stamina();
+        (max_stamina - self.combatant_state.lock().unwrap().fatigue).max(0.0)
+    }
+
+    /// Multiplier for the combatant's speed, throwing accuracy and shove force.
+    /// 1.0 when fully rested, decreasing as the combatant tires.
+    ///
+    /// # Panics
+    /// Will panic if the combatant or their state cannot be locked.
+    #[must_use]
+    pub fn fatigue_modifier(&self) -> f32 {
+        let max_stamina = self.combatant.lock().unwrap().max_stamina();
+        let stamina_fraction = self.stamina() / max_stamina;
+        EXHAUSTED_FATIGUE_MODIFIER + (1.0 - EXHAUSTED_FATIGUE_MODIFIER) * stamina_fraction
+    }
+
+    /// Spends stamina, slowing how much recovers next tick.
+    ///
+    /// # Panics
+    /// Will panic if the combatant or their state cannot be locked.
+    pub fn exert(&mut self, stamina: f32, current_tick: GameTickNumber) {
+        if stamina <= 0.0 {
+            return;
+        }
+
+        let max_stamina = self.combatant.lock().unwrap().max_stamina();
+        let mut combatant_state = self.combatant_state.lock().unwrap();
+        combatant_state.fatigue = (combatant_state.fatigue + stamina).min(max_stamina);
+        if combatant_state.exerted_on_tick != current_tick {
+            combatant_state.exertion = 0.0;
+        }
+        combatant_state.exertion += stamina;
+        combatant_state.exerted_on_tick = current_tick;
+    }
+
+    /// Recovers stamina, scaled by how hard the combatant exerted themselves last tick.
+    /// Running flat out (or throwing, or shoving) recovers nothing, while walking at half pace recovers half as much as resting.
+    ///
+    /// # Panics
+    /// Will panic if the combatant or their state cannot be locked.
+    pub fn recover_stamina(&mut self, current_tick: GameTickNumber) {
+        let (max_stamina, full_exertion) = {
+            let combatant = self.combatant.lock().unwrap();
+            (combatant.max_stamina(), combatant.move_speed() * MOVE_STAMINA_COST_PER_UNIT)
+        };
+
+        let mut combatant_state = self.combatant_state.lock().unwrap();
+        let exertion = if current_tick.saturating_sub(combatant_state.exerted_on_tick) <= 1 {
+            combatant_state.exertion
+        } else {
+            0.0
+        };
+
+        let rest = if exertion <= 0.0 {
+            1.0
+        } else if full_exertion <= 0.0 {
+            0.0
+        } else {
+            (1.0 - exertion / full_exertion).clamp(0.0, 1.0)
+        };
+
+        combatant_state.fatigue = (combatant_state.fatigue - max_stamina * STAMINA_RECOVERY_PER_TICK * rest).max(0.0);
+    }
 }
 
 impl GameObject for CombatantObject {
@@ -318,6 +465,21 @@ impl Agent for CombatantObject {
             }
         }
 
+        // Combatants that value their own safety over their job give up on plans that are getting them hurt
+        let should_abandon_plan = {
+            let personality = Personality::from_combatant(&self.combatant.lock().unwrap());
+            let combatant_state = self.combatant_state.lock().unwrap();
+            let has_plan = combatant_state.current_action.is_some() || !combatant_state.plan.is_empty();
+            has_plan && personality.should_abandon_plan(combatant_state.damage - combatant_state.damage_when_planned)
+        };
+
+        if should_abandon_plan {
+            tracing::debug!("Abandoning plan after taking damage");
+            let mut combatant_state = self.combatant_state.lock().unwrap();
+            combatant_state.plan.clear();
+            combatant_state.current_action = None;
+        }
+
         let (current_action_is_none, plan_is_empty) = {
             let combatant_state = self.combatant_state.lock().unwrap();
             (combatant_state.current_action.is_none(), combatant_state.plan.is_empty())
@@ -325,9 +487,18 @@ impl Agent for CombatantObject {
 
         if current_action_is_none {
             if plan_is_empty {
-                let new_plan = planner::plan(self, game_state.clone());
+                let planner_traces_enabled = game_state.lock().unwrap().simulation_config.planner_traces_enabled();
+                let (new_plan, planner_trace) = if planner_traces_enabled {
+                    let (new_plan, planner_trace) = planner::plan_with_trace(self, game_state.clone());
+                    (new_plan, Some(planner_trace))
+                } else {
+                    (planner::plan(self, game_state.clone()), None)
+                };
+
                 let mut combatant_state = self.combatant_state.lock().unwrap();
                 combatant_state.plan = new_plan;
+                combatant_state.planner_trace = planner_trace;
+                combatant_state.damage_when_planned = combatant_state.damage;
             }
 
             {
@@ -417,3 +588,60 @@ impl Debug for CombatantObject {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use dys_world::attribute::attribute_type::AttributeType;
+    use dys_world::attribute::instance::AttributeInstance;
+    use crate::ai::agent::Agent;
+    use crate::ai::test_utils::TestAgent;
+
+    fn make_combatant() -> TestAgent {
+        TestAgent::new_with_attributes(&[
+            AttributeInstance::new(AttributeType::Constitution, 20.0),
+            AttributeInstance::new(AttributeType::Commitment, 10.0),
+            AttributeInstance::new(AttributeType::Dexterity, 50.0),
+        ])
+    }
+
+    #[test]
+    fn test_rested_combatants_have_full_stamina() {
+        let agent = make_combatant();
+        let combatant = agent.combatant();
+
+        assert!((combatant.stamina() - 100.0).abs() < 0.0001);
+        assert!((combatant.fatigue_modifier() - 1.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_exertion_lowers_fatigue_modifier() {
+        let agent = make_combatant();
+        let mut combatant = agent.combatant().to_owned();
+
+        combatant.exert(50.0, 10);
+        assert!((combatant.stamina() - 50.0).abs() < 0.0001);
+        assert!((combatant.fatigue_modifier() - 0.75).abs() < 0.0001);
+
+        combatant.exert(500.0, 11);
+        assert!(combatant.stamina().abs() < 0.0001);
+        assert!((combatant.fatigue_modifier() - 0.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_stamina_recovers_less_after_harder_exertion() {
+        let agent = make_combatant();
+        let mut combatant = agent.combatant().to_owned();
+
+        // Running flat out moves a unit each tick, costing 0.1 stamina
+        combatant.exert(50.0, 10);
+        combatant.recover_stamina(11);
+        assert!((combatant.stamina() - 50.0).abs() < 0.0001);
+
+        combatant.exert(0.05, 11);
+        combatant.recover_stamina(12);
+        assert!((combatant.stamina() - 50.2).abs() < 0.0001);
+
+        combatant.recover_stamina(13);
+        assert!((combatant.stamina() - 50.7).abs() < 0.0001);
+    }
+}
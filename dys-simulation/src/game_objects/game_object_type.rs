@@ -1,8 +1,10 @@
+use serde::{Deserialize, Serialize};
+use dys_world::arena::hazard::HazardId;
 use dys_world::arena::plate::PlateId;
 use dys_world::combatant::instance::CombatantInstanceId;
 use super::ball::BallId;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GameObjectType {
     Invalid,
     Barrier,
@@ -10,4 +12,5 @@ pub enum GameObjectType {
     Combatant(CombatantInstanceId),
     BallSpawn,
     Plate(PlateId),
+    Hazard(HazardId),
 }
\ No newline at end of file
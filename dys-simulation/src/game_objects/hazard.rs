@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use dys_world::arena::hazard::HazardId;
+use rapier3d::{dynamics::RigidBodyHandle, geometry::ColliderHandle};
+
+use super::game_object::GameObject;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HazardObject {
+    id: HazardId,
+    collider_handle: ColliderHandle,
+    stamina_drain_per_second: f32,
+}
+
+impl HazardObject {
+    #[must_use]
+    pub fn new(id: HazardId, collider_handle: ColliderHandle, stamina_drain_per_second: f32) -> HazardObject {
+        HazardObject {
+            id,
+            collider_handle,
+            stamina_drain_per_second,
+        }
+    }
+
+    #[must_use]
+    pub fn stamina_drain_per_second(&self) -> f32 {
+        self.stamina_drain_per_second
+    }
+}
+
+impl GameObject for HazardObject {
+    type GameObjectIdT = HazardId;
+    type GameStateT = ();
+
+    fn id(&self) -> Self::GameObjectIdT {
+        self.id
+    }
+
+    fn rigid_body_handle(&self) -> Option<RigidBodyHandle> {
+        None
+    }
+
+    fn collider_handle(&self) -> Option<ColliderHandle> {
+        Some(self.collider_handle)
+    }
+
+    fn change_state(&mut self, _current_tick: crate::game_tick::GameTickNumber, _new_state: Self::GameStateT) -> (Self::GameStateT, crate::game_tick::GameTickNumber) {
+        panic!("hazards cannot change state")
+    }
+
+    fn is_dirty(&self) -> bool {
+        panic!("hazards cannot be dirty")
+    }
+}
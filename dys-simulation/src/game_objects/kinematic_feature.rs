@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use dys_world::arena::feature::NavmeshAreaType;
+use dys_world::arena::kinematics::FeatureKinematics;
+use dys_world::arena::navmesh::{NavmeshAreaShape, NavmeshCostArea};
+use dys_world::arena::ArenaFeatureIndex;
+use rapier3d::{dynamics::RigidBodyHandle, geometry::ColliderHandle};
+use rapier3d::prelude::Vec3;
+
+use super::game_object::GameObject;
+
+/// An arena feature that moves during the game, such as a sliding wall, a sweeping hazard,
+/// or a plate that relocates between periods.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct KinematicFeatureObject {
+    feature_index: ArenaFeatureIndex,
+    kinematics: FeatureKinematics,
+    rigid_body_handle: Option<RigidBodyHandle>,
+    collider_handle: ColliderHandle,
+
+    /// The kind of area combatants path through when crossing this feature, if it's worth steering around.
+    navmesh_area: Option<NavmeshAreaType>,
+
+    /// Half the size of a box around the feature's position, lined up with the arena's axes, that covers the whole feature.
+    footprint_half_extents: Vec3,
+
+    /// Set once something outside the simulation has moved the feature, after which its kinematics no longer do.
+    is_kinematics_overridden: bool,
+
+    /// Where the feature was last moved to.
+    pub position: Vec3,
+}
+
+impl KinematicFeatureObject {
+    #[must_use]
+    pub fn new(
+        feature_index: ArenaFeatureIndex,
+        kinematics: FeatureKinematics,
+        rigid_body_handle: Option<RigidBodyHandle>,
+        collider_handle: ColliderHandle,
+        navmesh_area: Option<NavmeshAreaType>,
+        footprint_half_extents: Vec3,
+    ) -> KinematicFeatureObject {
+        KinematicFeatureObject {
+            feature_index,
+            position: kinematics.origin,
+            kinematics,
+            rigid_body_handle,
+            collider_handle,
+            navmesh_area,
+            footprint_half_extents,
+            is_kinematics_overridden: false,
+        }
+    }
+
+    #[must_use]
+    pub fn kinematics(&self) -> &FeatureKinematics {
+        &self.kinematics
+    }
+
+    #[must_use]
+    pub fn is_kinematics_overridden(&self) -> bool {
+        self.is_kinematics_overridden
+    }
+
+    pub fn override_kinematics(&mut self) {
+        self.is_kinematics_overridden = true;
+    }
+
+    /// The area pathing combatants should steer around (or towards) wherever the feature currently is.
+    #[must_use]
+    pub fn navmesh_cost_area(&self, agent_radius: f32) -> Option<NavmeshCostArea> {
+        Some(NavmeshCostArea {
+            area_type: self.navmesh_area?,
+            center: self.position,
+            shape: NavmeshAreaShape::Rectangle {
+                half_width: self.footprint_half_extents.x + agent_radius,
+                half_depth: self.footprint_half_extents.z + agent_radius,
+            },
+        })
+    }
+}
+
+impl GameObject for KinematicFeatureObject {
+    type GameObjectIdT = ArenaFeatureIndex;
+    type GameStateT = ();
+
+    fn id(&self) -> Self::GameObjectIdT {
+        self.feature_index
+    }
+
+    fn rigid_body_handle(&self) -> Option<RigidBodyHandle> {
+        self.rigid_body_handle
+    }
+
+    fn collider_handle(&self) -> Option<ColliderHandle> {
+        Some(self.collider_handle)
+    }
+
+    fn change_state(&mut self, _current_tick: crate::game_tick::GameTickNumber, _new_state: Self::GameStateT) -> (Self::GameStateT, crate::game_tick::GameTickNumber) {
+        panic!("kinematic features cannot change state")
+    }
+
+    fn is_dirty(&self) -> bool {
+        panic!("kinematic features cannot be dirty")
+    }
+}
@@ -2,4 +2,6 @@ pub mod ball;
 pub mod combatant;
 pub mod game_object_type;
 pub mod game_object;
+pub mod hazard;
+pub mod kinematic_feature;
 pub mod plate;
\ No newline at end of file
@@ -1,22 +1,57 @@
+use serde::{Deserialize, Serialize};
+use dys_world::arena::plate::PlateSchedule;
 use rapier3d::{dynamics::RigidBodyHandle, geometry::ColliderHandle};
 
 use super::game_object::GameObject;
 
 pub type PlateId = u8;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct PlateObject {
     id: PlateId,
     collider_handle: ColliderHandle,
+    schedule: PlateSchedule,
+    is_active: bool,
+
+    /// Set once something outside the simulation has turned the plate on or off, after which its schedule no longer does.
+    is_schedule_overridden: bool,
 }
 
 impl PlateObject {
-    pub fn new(id: PlateId, collider_handle: ColliderHandle) -> PlateObject {
+    #[must_use]
+    pub fn new(id: PlateId, collider_handle: ColliderHandle, schedule: PlateSchedule) -> PlateObject {
         PlateObject {
             id,
-            collider_handle
+            collider_handle,
+            is_active: schedule.is_active_at(0.0),
+            schedule,
+            is_schedule_overridden: false,
         }
     }
+
+    #[must_use]
+    pub fn schedule(&self) -> &PlateSchedule {
+        &self.schedule
+    }
+
+    /// Whether points can currently be scored on this plate.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    pub fn set_active(&mut self, is_active: bool) {
+        self.is_active = is_active;
+    }
+
+    #[must_use]
+    pub fn is_schedule_overridden(&self) -> bool {
+        self.is_schedule_overridden
+    }
+
+    pub fn override_schedule(&mut self) {
+        self.is_schedule_overridden = true;
+    }
 }
 
 impl GameObject for PlateObject {
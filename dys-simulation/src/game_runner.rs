@@ -0,0 +1,461 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use rapier3d::glamx::vec3;
+use rapier3d::prelude::*;
+use dys_world::arena::{ball_spawn::ArenaBallSpawn, barrier::ArenaBarrier, feature::ArenaFeature, hazard::ArenaHazard, plate::ArenaPlate};
+use crate::external_input::{apply_input, ExternalInput, ExternalInputError};
+use crate::game_objects::game_object::GameObject;
+use crate::game_state::GameState;
+use crate::game_tick::{GameTick, GameTickNumber, TickPerformance};
+use crate::simulation::simulate_tick;
+use crate::simulation::simulation_event::{SimulationEvent, SimulationEventCause};
+use crate::state_hash::TickStateHash;
+
+/// Simulates a game a tick at a time, yielding each tick as it's produced,
+/// so that the game can be watched (or broadcast) as it plays out rather than once it's over.
+///
+/// A game that hasn't kicked off yet starts with a "tick 0" describing its initial state.
+/// The game's state can be inspected between ticks with [`GameRunner::game_state`],
+/// and influenced by queueing [`ExternalInput`]s to be applied before a tick is simulated.
+///
+/// As an iterator, the runner yields ticks until the game ends or it's paused,
+/// and can carry on yielding ticks once it's resumed.
+pub struct GameRunner {
+    game_state: Arc<Mutex<GameState>>,
+    initial_tick_pending: bool,
+    is_finished: bool,
+    is_paused: bool,
+    pause_after_tick: Option<GameTickNumber>,
+    queued_inputs: BTreeMap<GameTickNumber, Vec<ExternalInput>>,
+    /// Events recording inputs applied since the last tick, to be logged with the next tick.
+    input_events: Vec<SimulationEvent>,
+    /// Which of the input events were caused by others, such as an applied input causing the events it committed.
+    input_event_causes: Vec<SimulationEventCause>,
+}
+
+impl GameRunner {
+    #[must_use]
+    pub fn new(game_state: GameState) -> GameRunner {
+        GameRunner {
+            initial_tick_pending: game_state.current_tick == 0,
+            game_state: Arc::new(Mutex::new(game_state)),
+            is_finished: false,
+            is_paused: false,
+            pause_after_tick: None,
+            queued_inputs: BTreeMap::new(),
+            input_events: vec![],
+            input_event_causes: vec![],
+        }
+    }
+
+    /// Simulates and yields the next tick of the game, once any inputs queued for it have been applied.
+    /// Yields nothing once the game has ended, or while the runner is paused.
+    ///
+    /// # Panics
+    /// Will panic if the game state cannot be locked.
+    pub fn next_tick(&mut self) -> Option<GameTick> {
+        if self.is_finished || self.is_paused {
+            return None;
+        }
+
+        self.apply_queued_inputs();
+        if self.is_paused {
+            return None;
+        }
+
+        let mut game_tick = if self.initial_tick_pending {
+            self.initial_tick_pending = false;
+            initial_tick(&self.game_state.lock().unwrap())
+        } else {
+            simulate_tick(self.game_state.clone())
+        };
+
+        // Events from inputs happened before the tick's own events, which their causes need to account for
+        for event_cause in &mut game_tick.event_causes {
+            event_cause.event_index += self.input_events.len();
+            event_cause.cause_index += self.input_events.len();
+        }
+        game_tick.simulation_events.splice(0..0, self.input_events.drain(..));
+        game_tick.event_causes.splice(0..0, self.input_event_causes.drain(..));
+
+        self.is_finished = game_tick.is_end_of_game();
+        if self.pause_after_tick.is_some_and(|tick_number| game_tick.tick_number >= tick_number) {
+            self.pause_after_tick = None;
+            self.is_paused = true;
+        }
+
+        Some(game_tick)
+    }
+
+    /// Queues an input to be applied just before the given tick is simulated.
+    /// Inputs for the same tick are applied in the order they were queued.
+    ///
+    /// Whether the input could be applied is only known once it has been, so it's logged with the tick
+    /// as either a [`SimulationEvent::ExternalInputApplied`] or a [`SimulationEvent::ExternalInputRejected`].
+    ///
+    /// # Errors
+    /// Will error if the tick has already been simulated.
+    pub fn queue_input(&mut self, tick_number: GameTickNumber, input: ExternalInput) -> Result<(), ExternalInputError> {
+        let next_tick_number = self.next_tick_number();
+        if tick_number < next_tick_number {
+            return Err(ExternalInputError::TickAlreadySimulated { tick_number, next_tick_number });
+        }
+
+        self.queued_inputs.entry(tick_number).or_default().push(input);
+        Ok(())
+    }
+
+    fn apply_queued_inputs(&mut self) {
+        let next_tick_number = self.next_tick_number();
+        while let Some(entry) = self.queued_inputs.first_entry() {
+            if *entry.key() > next_tick_number {
+                break;
+            }
+
+            for input in entry.remove() {
+                match apply_input(&self.game_state, &input) {
+                    Ok((committed_simulation_events, event_causes)) => {
+                        self.is_paused |= input == ExternalInput::Pause;
+                        let applied_index = self.input_events.len();
+                        self.input_events.push(SimulationEvent::ExternalInputApplied { input });
+
+                        // The input caused the first event it committed, which caused the rest,
+                        // and their causes are indexed from where they're inserted after the input
+                        let first_committed_index = self.input_events.len();
+                        if !committed_simulation_events.is_empty() {
+                            self.input_event_causes.push(SimulationEventCause { event_index: first_committed_index, cause_index: applied_index });
+                        }
+                        self.input_event_causes.extend(event_causes.into_iter().map(|event_cause| SimulationEventCause {
+                            event_index: event_cause.event_index + first_committed_index,
+                            cause_index: event_cause.cause_index + first_committed_index,
+                        }));
+                        self.input_events.extend(committed_simulation_events);
+                    },
+                    Err(error) => {
+                        tracing::debug!(?input, %error, "rejected external input");
+                        self.input_events.push(SimulationEvent::ExternalInputRejected { input, error });
+                    },
+                }
+            }
+        }
+    }
+
+    /// The number of the tick that will be yielded next.
+    #[must_use]
+    pub fn next_tick_number(&self) -> GameTickNumber {
+        if self.initial_tick_pending { 0 } else { self.current_tick() + 1 }
+    }
+
+    /// The state of the game as of the last tick yielded.
+    /// Changes made to it between ticks carry through to the rest of the game.
+    #[must_use]
+    pub fn game_state(&self) -> &Arc<Mutex<GameState>> {
+        &self.game_state
+    }
+
+    /// # Panics
+    /// Will panic if the game state cannot be locked.
+    #[must_use]
+    pub fn current_tick(&self) -> GameTickNumber {
+        self.game_state.lock().unwrap().current_tick
+    }
+
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.is_finished
+    }
+
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    pub fn pause(&mut self) {
+        self.is_paused = true;
+    }
+
+    /// Pauses the runner once it has yielded the given tick (or immediately, if it already has).
+    pub fn pause_after_tick(&mut self, tick_number: GameTickNumber) {
+        if !self.initial_tick_pending && self.current_tick() >= tick_number {
+            self.is_paused = true;
+        } else {
+            self.pause_after_tick = Some(tick_number);
+        }
+    }
+
+    pub fn resume(&mut self) {
+        self.is_paused = false;
+    }
+}
+
+impl Iterator for GameRunner {
+    type Item = GameTick;
+
+    fn next(&mut self) -> Option<GameTick> {
+        self.next_tick()
+    }
+}
+
+fn initial_tick(game_state: &GameState) -> GameTick {
+    // ZJ-TODO: there should probably be a SimulationEvent::InitialState, rather than a bunch of updates
+    let (rigid_body_set, _) = game_state.physics_sim.sets();
+    let mut simulation_events = vec![];
+    for (combatant_id, combatant_object) in &game_state.combatants {
+        let combatant_rb = rigid_body_set.get(combatant_object.rigid_body_handle().unwrap()).unwrap();
+        simulation_events.push(SimulationEvent::CombatantPositionUpdate {
+            combatant_id: *combatant_id,
+            position: combatant_rb.translation(),
+        });
+    }
+
+    for (ball_id, ball_object) in &game_state.balls {
+        let ball_rb = rigid_body_set.get(ball_object.rigid_body_handle().unwrap()).unwrap();
+        simulation_events.push(SimulationEvent::BallPositionUpdate {
+            ball_id: *ball_id,
+            position: ball_rb.translation(),
+            charge: ball_object.charge,
+        });
+    }
+
+    let arena = game_state.game.game_instance.arena.lock().unwrap();
+    let arena_features = arena.all_features();
+    for (feature_index, feature) in arena_features.iter().enumerate().filter(|(_, feature)| feature.shape().is_some()) {
+        let shape = feature.shape().unwrap();
+        let object_type_id: u32 = {
+            if let Some(barrier) = feature.as_any().downcast_ref::<ArenaBarrier>() {
+                match barrier.pathing_type() {
+                    dys_world::arena::feature::NavmeshPathingType::Generate => 1,
+                    dys_world::arena::feature::NavmeshPathingType::Skip => 0,
+                    dys_world::arena::feature::NavmeshPathingType::Block => 2,
+                }
+            } else if feature.as_any().downcast_ref::<ArenaBallSpawn>().is_some() {
+                3
+            } else if feature.as_any().downcast_ref::<ArenaPlate>().is_some() {
+                4
+            } else if feature.as_any().downcast_ref::<ArenaHazard>().is_some() {
+                5
+            } else {
+                0
+            }
+        };
+        simulation_events.push(SimulationEvent::ArenaObjectPositionUpdate {
+            feature_index,
+            object_type_id,
+            position: *feature.origin(),
+            scale: match shape.shape_type() {
+                ShapeType::Ball => vec3(shape.as_ball().unwrap().radius, shape.as_ball().unwrap().radius, shape.as_ball().unwrap().radius),
+                ShapeType::Cuboid => shape.as_cuboid().unwrap().half_extents * 2.0,
+                ShapeType::Capsule => vec3(shape.as_capsule().unwrap().radius, shape.as_capsule().unwrap().height(), shape.as_capsule().unwrap().radius),
+                ShapeType::Cylinder => vec3(shape.as_cylinder().unwrap().radius, shape.as_cylinder().unwrap().half_height * 2.0, shape.as_cylinder().unwrap().radius),
+                _ => panic!("shape unsupported")
+            },
+            rotation: *feature.rotation(),
+        });
+    }
+
+    GameTick {
+        tick_number: 0,
+        tick_performance: TickPerformance::default(),
+        simulation_events,
+        event_causes: vec![],
+        is_end_of_game: false,
+        rejected_events: vec![],
+        state_hash: game_state.simulation_config.state_hashes_enabled().then(|| TickStateHash::from_game_state(game_state)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use rapier3d::glamx::vec3;
+    use rapier3d::na::Quaternion;
+    use dys_world::arena::Arena;
+    use dys_world::arena::barrier::{ArenaBarrier, BarrierPathing};
+    use dys_world::arena::kinematics::{FeatureMotionPath, MotionLooping};
+    use dys_world::attribute::attribute_type::AttributeType;
+    use crate::ai::test_utils::make_generated_game;
+    use dys_world::generator::Generator;
+    use crate::external_input::{ExternalInput, ExternalInputError};
+    use crate::game::Game;
+    use crate::game_objects::combatant::TeamAlignment;
+    use crate::game_objects::game_object::GameObject;
+    use crate::game_state::GameState;
+    use crate::simulation::config::SimulationConfig;
+    use crate::simulation::simulation_event::SimulationEvent;
+    use super::GameRunner;
+
+    fn short_game_config() -> SimulationConfig {
+        SimulationConfig::default().with_seconds_per_period(3)
+    }
+
+    fn short_game_runner(game: Game) -> GameRunner {
+        let mut game_state = GameState::from_game_seeded(game, &[0; 32]);
+        game_state.simulation_config = short_game_config();
+        GameRunner::new(game_state)
+    }
+
+    #[test]
+    fn test_runner_yields_the_same_ticks_as_a_whole_simulation() {
+        let game = make_generated_game();
+        let game_log = game.simulate_seeded_with_config(&[0; 32], short_game_config());
+        let ticks = short_game_runner(game).collect::<Vec<_>>();
+
+        assert_eq!(ticks.len(), game_log.ticks().len());
+        for (tick, logged_tick) in ticks.iter().zip(game_log.ticks()) {
+            assert_eq!(tick.tick_number, logged_tick.tick_number);
+            assert_eq!(tick.simulation_events, logged_tick.simulation_events);
+        }
+
+        assert!(ticks.last().unwrap().is_end_of_game());
+    }
+
+    #[test]
+    fn test_runner_pauses_for_inspection_between_ticks() {
+        let mut game_runner = short_game_runner(make_generated_game());
+        game_runner.pause_after_tick(10);
+
+        let tick_numbers = game_runner.by_ref().map(|game_tick| game_tick.tick_number).collect::<Vec<_>>();
+        assert_eq!(tick_numbers, (0..=10).collect::<Vec<_>>());
+        assert!(game_runner.is_paused());
+        assert!(!game_runner.is_finished());
+        assert_eq!(game_runner.game_state().lock().unwrap().current_tick, 10);
+        assert!(game_runner.next_tick().is_none());
+
+        game_runner.resume();
+        assert_eq!(game_runner.next_tick().map(|game_tick| game_tick.tick_number), Some(11));
+
+        let last_tick = game_runner.by_ref().last().unwrap();
+        assert!(last_tick.is_end_of_game());
+        assert!(game_runner.is_finished());
+        assert!(game_runner.next_tick().is_none());
+    }
+
+    #[test]
+    fn test_initial_tick_lays_out_the_games_arena() {
+        let arena = Generator::new().generate_arena(&mut StdRng::seed_from_u64(0));
+        let feature_positions = arena.all_features()
+            .into_iter()
+            .filter(|feature| feature.shape().is_some())
+            .map(|feature| *feature.origin())
+            .collect::<Vec<_>>();
+
+        let mut game = make_generated_game();
+        game.game_instance.arena = Arc::new(Mutex::new(arena));
+        let initial_tick = short_game_runner(game).next_tick().unwrap();
+
+        let object_positions = initial_tick.simulation_events
+            .iter()
+            .filter_map(|event| match event {
+                SimulationEvent::ArenaObjectPositionUpdate { position, .. } => Some(*position),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(object_positions, feature_positions);
+    }
+
+    #[test]
+    fn test_runner_applies_queued_inputs_between_ticks() {
+        // Give the home team a combatant on the bench, who doesn't start the game
+        let mut game = make_generated_game();
+        let substitute_id = 1000;
+        {
+            let mut substitute = game.game_instance.away_team.lock().unwrap().combatants[0].lock().unwrap().clone();
+            substitute.id = substitute_id;
+            game.game_instance.home_team.lock().unwrap().combatants.insert(0, Arc::new(Mutex::new(substitute)));
+        }
+
+        // Add a wall that slides east along the south end of the arena
+        let moving_wall_index = Arena::new_with_testing_defaults().all_features().len();
+        game.game_instance.arena = Arc::new(Mutex::new(
+            Arena::new_with_testing_defaults().with_feature(Box::new(
+                ArenaBarrier::new(vec3(30.0, 0.0, 10.0), vec3(2.0, 10.0, 10.0), Quaternion::identity(), BarrierPathing::Disabled)
+                    .with_motion(FeatureMotionPath::new(vec![vec3(40.0, 0.0, 0.0)], 5.0, MotionLooping::Stop))
+            ))
+        ));
+        let wall_position = vec3(20.0, 0.0, 10.0);
+
+        let mut game_runner = short_game_runner(game);
+        let (combatant_id, combatant_role, plate_id) = {
+            let game_state = game_runner.game_state().lock().unwrap();
+            assert!(!game_state.combatants.contains_key(&substitute_id));
+
+            let combatant_id = game_state.team_combatants(TeamAlignment::Home)[0].id;
+            let combatant_role = game_state.team_coordinators[&TeamAlignment::Home].role(combatant_id);
+            (combatant_id, combatant_role, *game_state.plates.keys().next().unwrap())
+        };
+
+        let inputs = [
+            ExternalInput::Substitute { combatant_id, substitute_id },
+            ExternalInput::SetPlateActive { plate_id, is_active: false },
+            ExternalInput::MoveArenaFeature { feature_index: moving_wall_index, position: wall_position },
+            ExternalInput::ApplyAttributeEffect { combatant_id, attribute_type: AttributeType::Strength, delta: 20.0 },
+            ExternalInput::Pause,
+        ];
+        for input in inputs.clone() {
+            game_runner.queue_input(5, input).unwrap();
+        }
+
+        // Inputs are applied before their tick, so the runner pauses with tick 4 the last simulated
+        let tick_numbers = game_runner.by_ref().map(|game_tick| game_tick.tick_number).collect::<Vec<_>>();
+        assert_eq!(tick_numbers, (0..5).collect::<Vec<_>>());
+        assert!(game_runner.is_paused());
+        assert_eq!(
+            game_runner.queue_input(4, ExternalInput::Pause),
+            Err(ExternalInputError::TickAlreadySimulated { tick_number: 4, next_tick_number: 5 }),
+        );
+
+        {
+            let game_state = game_runner.game_state().lock().unwrap();
+            assert!(!game_state.combatants.contains_key(&combatant_id));
+            assert!(game_state.combatants.contains_key(&substitute_id));
+            assert_eq!(game_state.team_coordinators[&TeamAlignment::Home].role(substitute_id), combatant_role);
+            assert!(!game_state.plates[&plate_id].is_active());
+        }
+
+        game_runner.resume();
+        let game_tick = game_runner.next_tick().unwrap();
+        assert_eq!(game_tick.tick_number, 5);
+
+        // Events committed by inputs are caused by them
+        assert_eq!(game_tick.cause_of(2), Some(&SimulationEvent::ExternalInputApplied { input: inputs[1].clone() }));
+        assert_eq!(game_tick.cause_of(4), Some(&SimulationEvent::ExternalInputApplied { input: inputs[2].clone() }));
+        assert!((0..7).filter(|event_index| ![2, 4].contains(event_index)).all(|event_index| game_tick.cause_of(event_index).is_none()));
+
+        // The combatant had already been substituted off by the time their attribute was to be changed
+        let [substitute, set_plate_active, move_arena_feature, attribute_effect, pause] = inputs;
+        assert_eq!(game_tick.simulation_events[..7], [
+            SimulationEvent::ExternalInputApplied { input: substitute },
+            SimulationEvent::ExternalInputApplied { input: set_plate_active },
+            SimulationEvent::PlateActivityChanged { plate_id, is_active: false },
+            SimulationEvent::ExternalInputApplied { input: move_arena_feature },
+            SimulationEvent::ArenaFeatureMoved { feature_index: moving_wall_index, position: wall_position },
+            SimulationEvent::ExternalInputRejected { input: attribute_effect, error: ExternalInputError::UnknownCombatant(combatant_id) },
+            SimulationEvent::ExternalInputApplied { input: pause },
+        ]);
+
+        // Neither the plate's schedule nor the wall's path undo the inputs, this tick or any after it
+        let later_ticks = game_runner.by_ref().collect::<Vec<_>>();
+        assert!(later_ticks.last().unwrap().is_end_of_game());
+        let simulation_events = std::iter::once(&game_tick)
+            .chain(&later_ticks)
+            .flat_map(|game_tick| &game_tick.simulation_events)
+            .skip(7);
+        for simulation_event in simulation_events {
+            match simulation_event {
+                SimulationEvent::PlateActivityChanged { plate_id: changed_plate_id, .. } => assert_ne!(*changed_plate_id, plate_id),
+                SimulationEvent::PointsScoredByCombatant { plate_id: scored_plate_id, .. } => assert_ne!(*scored_plate_id, plate_id),
+                SimulationEvent::ArenaFeatureMoved { feature_index, .. } => assert_ne!(*feature_index, moving_wall_index),
+                _ => {}
+            }
+        }
+
+        let game_state = game_runner.game_state().lock().unwrap();
+        assert!(!game_state.plates[&plate_id].is_active());
+        let moving_wall = &game_state.kinematic_features[&moving_wall_index];
+        assert_eq!(moving_wall.position, wall_position);
+        let (rigid_body_set, _) = game_state.physics_sim.sets();
+        assert_eq!(rigid_body_set.get(moving_wall.rigid_body_handle().unwrap()).unwrap().translation(), wall_position);
+    }
+}
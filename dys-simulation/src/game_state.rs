@@ -1,13 +1,17 @@
-use std::f32::consts::FRAC_PI_2;
 use std::sync::{Arc, Mutex};
 use indexmap::IndexMap;
-use dys_world::{arena::{ball_spawn::ArenaBallSpawn, barrier::ArenaBarrier, combatant_start::ArenaCombatantStart, feature::ArenaFeature, navmesh::{ArenaNavmesh, ArenaNavmeshConfig}, plate::{ArenaPlate, PlateId}}};
+use dys_world::{arena::{environment::GameEnvironment, ball_spawn::ArenaBallSpawn, barrier::ArenaBarrier, combatant_start::ArenaCombatantStart, feature::{ArenaFeature, NavmeshAreaType, NavmeshPathingType}, hazard::{ArenaHazard, HazardId}, navmesh::{ArenaNavmesh, NavmeshCostArea, NavmeshPathCache}, plate::{ArenaPlate, PlateId}, ArenaFeatureIndex}};
 use rand::{random, SeedableRng};
 use rand_pcg::Pcg64;
 use rapier3d::prelude::*;
-use dys_world::arena::Arena;
-use dys_world::combatant::instance::CombatantInstanceId;
-use crate::{game::Game, game_objects::{ball::{BallId, BallObject}, combatant::{CombatantObject, TeamAlignment}, game_object::GameObject, game_object_type::GameObjectType, plate::PlateObject}, game_tick::GameTickNumber, physics_sim::PhysicsSim, simulation::config::SimulationConfig};
+use dys_world::attribute::attribute_type::AttributeType;
+use dys_world::attribute::instance::{AttributeInstance, AttributeValueT};
+use dys_world::combatant::instance::{CombatantInstanceId, EffectDuration};
+use dys_world::team::tactics::TeamTactics;
+use crate::ai::coordinator::{RoleAssignment, TeamCoordinator};
+use crate::game_checkpoint::{GameCheckpoint, GameCheckpointError};
+use crate::simulation::simulation_event::SimulationEvent;
+use crate::{game::Game, game_objects::{ball::{BallId, BallObject, BallState}, combatant::{CombatantObject, CombatantSpawn, TeamAlignment}, game_object::GameObject, game_object_type::GameObjectType, hazard::HazardObject, kinematic_feature::KinematicFeatureObject, plate::PlateObject}, game_tick::GameTickNumber, physics_sim::PhysicsSim, simulation::config::SimulationConfig};
 
 pub type SeedT = [u8; 32];
 
@@ -17,6 +21,8 @@ pub type SeedT = [u8; 32];
 pub type CombatantsMapT = IndexMap<CombatantInstanceId, CombatantObject>;
 pub type BallsMapT = IndexMap<BallId, BallObject>;
 pub type PlatesMapT = IndexMap<PlateId, PlateObject>;
+pub type HazardsMapT = IndexMap<HazardId, HazardObject>;
+pub type KinematicFeaturesMapT = IndexMap<ArenaFeatureIndex, KinematicFeatureObject>;
 pub type CollidersMapT = IndexMap<ColliderHandle, GameObjectType>;
 
 pub struct GameState {
@@ -27,12 +33,19 @@ pub struct GameState {
     pub combatants: CombatantsMapT,
     pub balls: BallsMapT,
     pub plates: PlatesMapT,
+    pub hazards: HazardsMapT,
+    pub kinematic_features: KinematicFeaturesMapT,
     pub active_colliders: CollidersMapT,
     pub home_points: u16,
     pub away_points: u16,
     pub current_tick: GameTickNumber,
     pub simulation_config: SimulationConfig,
-    pub arena_navmesh: ArenaNavmesh,
+    pub arena_navmesh: Arc<ArenaNavmesh>,
+    /// Paths found on the navmesh this tick.
+    pub path_cache: NavmeshPathCache,
+    /// Conditions the game is played in, such as low gravity or fog.
+    pub environment: GameEnvironment,
+    pub team_coordinators: IndexMap<TeamAlignment, TeamCoordinator>,
 }
 
 fn get_game_object_type_from_feature(feature: &dyn ArenaFeature) -> GameObjectType {
@@ -48,6 +61,10 @@ fn get_game_object_type_from_feature(feature: &dyn ArenaFeature) -> GameObjectTy
         return GameObjectType::Plate(plate.id);
     }
 
+    if let Some(hazard) = feature.as_any().downcast_ref::<ArenaHazard>() {
+        return GameObjectType::Hazard(hazard.id);
+    }
+
     panic!("unknown game object type for feature");
 }
 
@@ -57,50 +74,79 @@ impl GameState {
         GameState::from_game_seeded(game, &seed)
     }
 
+    /// Sets up a game to be played in its arena, subject to the arena's environment.
+    ///
+    /// # Panics
+    /// Will panic if the arena, either team, or one of their combatants cannot be locked.
+    #[allow(clippy::too_many_lines)]
     pub fn from_game_seeded(game: Game, seed: &SeedT) -> GameState {
+        let arena = game.game_instance.arena.clone();
+        let arena_guard = arena.lock().unwrap();
+        let arena = &*arena_guard;
+        let arena_navmesh = arena.navmesh();
         let current_tick = 0;
 
         let simulation_config = SimulationConfig::default();
-        let mut physics_sim = PhysicsSim::new(simulation_config.ticks_per_second());
+        let environment = arena.environment();
+        let mut physics_sim = PhysicsSim::new(simulation_config.ticks_per_second(), &environment);
         let (rigid_body_set, collider_set) = physics_sim.sets_mut();
 
         let mut active_colliders = CollidersMapT::new();
         let mut balls = BallsMapT::new();
         let mut combatants = CombatantsMapT::new();
         let mut plates = PlatesMapT::new();
+        let mut hazards = HazardsMapT::new();
+        let mut kinematic_features = KinematicFeaturesMapT::new();
 
         {
-            // let arena = game.game_instance.arena.lock().unwrap();
-            let arena = Arena::new_with_testing_defaults();
-            for feature in arena.all_features() {
-                if let Some(rigid_body) = feature.build_rigid_body() {
-                    let rigid_body_handle = rigid_body_set.insert(rigid_body);
-                    if let Some(collider) = feature.build_collider() {
-                        let collider_handle = collider_set.insert_with_parent(collider, rigid_body_handle, rigid_body_set);
-                        let game_object_type = get_game_object_type_from_feature(feature);
-                        if let GameObjectType::Plate(plate_id) = game_object_type {
-                            plates.insert(plate_id, PlateObject::new(plate_id, collider_handle));
-                        };
-
-                        active_colliders.insert(collider_handle, game_object_type);
-                    }
-                } else if let Some(collider) = feature.build_collider() {
-                    let collider_handle = collider_set.insert(collider);
-                    let game_object_type = get_game_object_type_from_feature(feature);
-                    match game_object_type {
-                        GameObjectType::Plate(plate_id) => plates.insert(plate_id, PlateObject::new(plate_id, collider_handle)),
-                        _ => None,
-                    };
-
-                    active_colliders.insert(collider_handle, game_object_type);
+            for (feature_index, feature) in arena.all_features().into_iter().enumerate() {
+                let rigid_body_handle = feature.build_rigid_body().map(|rigid_body| rigid_body_set.insert(rigid_body));
+                let Some(mut collider) = feature.build_collider() else {
+                    continue;
+                };
+
+                collider.set_friction(collider.friction() * environment.friction_multiplier);
+
+                let collider_handle = match rigid_body_handle {
+                    Some(rigid_body_handle) => collider_set.insert_with_parent(collider, rigid_body_handle, rigid_body_set),
+                    None => collider_set.insert(collider),
+                };
+
+                let game_object_type = get_game_object_type_from_feature(feature);
+                match game_object_type {
+                    GameObjectType::Plate(plate_id) => {
+                        let plate = feature.as_any().downcast_ref::<ArenaPlate>().unwrap();
+                        plates.insert(plate_id, PlateObject::new(plate_id, collider_handle, plate.schedule.clone()));
+                    },
+                    GameObjectType::Hazard(hazard_id) => {
+                        let hazard = feature.as_any().downcast_ref::<ArenaHazard>().unwrap();
+                        hazards.insert(hazard_id, HazardObject::new(hazard_id, collider_handle, hazard.stamina_drain_per_second));
+                    },
+                    _ => {},
+                }
+
+                if let Some(kinematics) = feature.kinematics() {
+                    // Moving walls aren't baked into the navmesh, so wherever they currently are is blocked for pathing instead
+                    let navmesh_area = feature.navmesh_area().or(
+                        (feature.pathing_type() == NavmeshPathingType::Block).then_some(NavmeshAreaType::Blocked)
+                    );
+
+                    let footprint = feature.shape().map_or(Vec3::ZERO, |shape| shape.compute_aabb(&feature.pose()).half_extents());
+
+                    kinematic_features.insert(
+                        feature_index,
+                        KinematicFeatureObject::new(feature_index, kinematics, rigid_body_handle, collider_handle, navmesh_area, footprint),
+                    );
                 }
+
+                active_colliders.insert(collider_handle, game_object_type);
             }
 
             let mut ball_id = 0;
 
             for ball_spawn in arena.features::<ArenaBallSpawn>() {
                 ball_id += 1;
-                let ball_object = BallObject::new(ball_id, current_tick, *ball_spawn.origin(), rigid_body_set, collider_set);
+                let ball_object = BallObject::new(ball_id, current_tick, *ball_spawn.origin(), &environment, rigid_body_set, collider_set);
 
                 active_colliders.insert(ball_object.collider_handle().expect("ball game objects must have collider handles"), GameObjectType::Ball(ball_id));
 
@@ -108,13 +154,13 @@ impl GameState {
             }
         }
 
+        let mut team_coordinators = IndexMap::new();
         {
             let mut home_combatants = { game.game_instance.home_team.lock().unwrap().combatants.clone() };
             let mut away_combatants = { game.game_instance.away_team.lock().unwrap().combatants.clone() };
 
-            // let arena = game.game_instance.arena.lock().unwrap();
-            let arena = Arena::new_with_testing_defaults();
             let combatant_starts = arena.features::<ArenaCombatantStart>();
+            let mut used_starts: IndexMap<TeamAlignment, Vec<Vec3>> = IndexMap::new();
 
             for player_start in combatant_starts {
                 let team_combatants = if player_start.is_home_team { &mut home_combatants } else { &mut away_combatants };
@@ -128,25 +174,41 @@ impl GameState {
 
                 let combatant_id = combatant.lock().unwrap().id.to_owned();
                 let combatant_object = CombatantObject::new(
-                    combatant_id,
-                    combatant,
-                    *player_start.origin(),
-                    AngVector::new(0.0, if team_alignment == TeamAlignment::Home { FRAC_PI_2 } else { -FRAC_PI_2 }, 0.0),
-                    team_alignment,
+                    CombatantSpawn {
+                        id: combatant_id,
+                        combatant,
+                        position: *player_start.origin(),
+                        rotation: team_alignment.facing(arena),
+                        team: team_alignment,
+                    },
+                    &environment,
                     rigid_body_set,
                     collider_set);
                 active_colliders.insert(combatant_object.collider_handle().expect("combatant game objects must have collider handles"), GameObjectType::Combatant(combatant_id));
                 combatants.insert(combatant_id, combatant_object);
+                used_starts.entry(team_alignment).or_default().push(*player_start.origin());
             }
-        }
 
-        let arena_navmesh = ArenaNavmesh::new_from(
-            Arc::new(Mutex::new(Arena::new_with_testing_defaults())),
-            // game.game_instance.arena.clone(),
-            ArenaNavmeshConfig {
-                unit_resolution: 1.0
+            for team_alignment in [TeamAlignment::Home, TeamAlignment::Away] {
+                let formation = match team_alignment {
+                    TeamAlignment::Home => game.game_instance.home_team.lock().unwrap().tactics.formation.clone(),
+                    TeamAlignment::Away => game.game_instance.away_team.lock().unwrap().tactics.formation.clone(),
+                };
+
+                let team_combatants = combatants
+                    .values()
+                    .filter(|combatant_object| combatant_object.team == team_alignment)
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                let team_starts = used_starts.get(&team_alignment).cloned().unwrap_or_default();
+
+                team_coordinators.insert(
+                    team_alignment,
+                    TeamCoordinator::kickoff(&formation, &team_starts, &team_combatants, team_alignment.forward(arena)),
+                );
             }
-        );
+        }
 
         GameState {
             game,
@@ -157,12 +219,158 @@ impl GameState {
             active_colliders,
             balls,
             plates,
+            hazards,
+            kinematic_features,
             home_points: 0,
             away_points: 0,
             current_tick,
             simulation_config,
-            arena_navmesh
+            arena_navmesh,
+            path_cache: NavmeshPathCache::new(),
+            environment,
+            team_coordinators,
+        }
+    }
+
+    /// Saves the game partway through, so that it can be picked back up with [`GameState::from_checkpoint`].
+    ///
+    /// # Errors
+    /// Will error if the game cannot be serialized.
+    pub fn checkpoint(&self) -> Result<Vec<u8>, GameCheckpointError> {
+        GameCheckpoint::from_game_state(self).to_bytes()
+    }
+
+    /// Picks a game back up from a checkpoint, to carry on simulating exactly as it would have.
+    /// `game` must be the game the checkpoint was taken from.
+    ///
+    /// # Errors
+    /// Will error if the bytes aren't a checkpoint, or the checkpoint is from a different game.
+    ///
+    /// # Panics
+    /// Will panic if either team or the arena in the game cannot be locked.
+    pub fn from_checkpoint(game: Game, checkpoint: &[u8]) -> Result<GameState, GameCheckpointError> {
+        GameCheckpoint::from_bytes(checkpoint)?.restore(game)
+    }
+
+    /// Takes a combatant off the field for the rest of the game.
+    /// Any ball they're holding is dropped, balls thrown by or at them are no longer in play,
+    /// and their role is handed to a teammate.
+    /// Everyone else forgets what they believed about the combatant, and plans afresh.
+    /// Returns false if the combatant isn't playing in the game.
+    ///
+    /// # Panics
+    /// Will panic if another combatant's state cannot be locked.
+    pub fn remove_combatant(&mut self, combatant_id: CombatantInstanceId) -> bool {
+        let Some(combatant_object) = self.combatants.shift_remove(&combatant_id) else {
+            return false;
+        };
+
+        self.active_colliders.shift_remove(&combatant_object.collider_handle);
+        self.physics_sim.remove_rigid_body(combatant_object.rigid_body_handle);
+
+        let current_tick = self.current_tick;
+        for ball_object in self.balls.values_mut() {
+            let involves_combatant = match ball_object.state {
+                BallState::Held { holder_id } => holder_id == combatant_id,
+                BallState::ThrownAtTarget { thrower_id, target_id, .. } => thrower_id == combatant_id || target_id == combatant_id,
+                BallState::Idle | BallState::Explode => false,
+            };
+
+            if involves_combatant {
+                ball_object.set_held_by(None, current_tick);
+                ball_object.change_state(current_tick, BallState::Idle);
+            }
+        }
+
+        if let Some(coordinator) = self.team_coordinators.get_mut(&combatant_object.team) {
+            coordinator.release_combatant(combatant_id);
+        }
+
+        for other_combatant_object in self.combatants.values() {
+            let mut combatant_state = other_combatant_object.combatant_state.lock().unwrap();
+            combatant_state.beliefs.forget_combatant(combatant_id);
+            combatant_state.plan.clear();
+            combatant_state.current_action = None;
         }
+
+        true
+    }
+
+    /// Puts a teammate who isn't playing on the field in place of a combatant, to play the rest of the game in their role.
+    /// The substitute comes on where the combatant was, facing the same way, and the combatant is taken off as with [`GameState::remove_combatant`].
+    /// Returns false if the combatant isn't playing, or the substitute isn't on their team or is already playing.
+    ///
+    /// # Panics
+    /// Will panic if the combatant's team or the substitute cannot be locked.
+    pub fn substitute_combatant(&mut self, combatant_id: CombatantInstanceId, substitute_id: CombatantInstanceId) -> bool {
+        let Some(combatant_object) = self.combatants.get(&combatant_id) else {
+            return false;
+        };
+
+        if self.combatants.contains_key(&substitute_id) {
+            return false;
+        }
+
+        let team_alignment = combatant_object.team;
+        let team = match team_alignment {
+            TeamAlignment::Home => &self.game.game_instance.home_team,
+            TeamAlignment::Away => &self.game.game_instance.away_team,
+        };
+
+        let substitute = team
+            .lock()
+            .unwrap()
+            .combatants
+            .iter()
+            .find(|combatant| combatant.lock().unwrap().id == substitute_id)
+            .cloned();
+
+        let Some(substitute) = substitute else {
+            return false;
+        };
+
+        let combatant_rigid_body_handle = combatant_object.rigid_body_handle;
+        let (rigid_body_set, collider_set) = self.physics_sim.sets_mut();
+        let combatant_rigid_body = rigid_body_set.get(combatant_rigid_body_handle).unwrap();
+        let (position, rotation) = (combatant_rigid_body.translation(), combatant_rigid_body.rotation().to_scaled_axis());
+        let substitute_object = CombatantObject::new(
+            CombatantSpawn {
+                id: substitute_id,
+                combatant: substitute,
+                position,
+                rotation,
+                team: team_alignment,
+            },
+            &self.environment,
+            rigid_body_set,
+            collider_set);
+
+        self.active_colliders.insert(substitute_object.collider_handle().expect("combatant game objects must have collider handles"), GameObjectType::Combatant(substitute_id));
+        self.combatants.insert(substitute_id, substitute_object);
+
+        // Hand over the combatant's role before they're taken off, so that it isn't given to a teammate instead
+        if let Some(coordinator) = self.team_coordinators.get_mut(&team_alignment) {
+            coordinator.substitute_combatant(combatant_id, substitute_id);
+        }
+
+        self.remove_combatant(combatant_id)
+    }
+
+    /// Adjusts one of a combatant's attributes for the rest of the game, eg Strength +20.
+    /// Combatants are shared with the world, so the game changes its own copy of the combatant.
+    /// Returns false if the combatant isn't playing in the game.
+    ///
+    /// # Panics
+    /// Will panic if the combatant cannot be locked.
+    pub fn adjust_attribute(&mut self, combatant_id: CombatantInstanceId, attribute_type: &AttributeType, delta: AttributeValueT) -> bool {
+        let Some(combatant_object) = self.combatants.get_mut(&combatant_id) else {
+            return false;
+        };
+
+        let mut combatant = combatant_object.combatant.lock().unwrap().clone();
+        combatant.apply_effect(AttributeInstance::new(attribute_type.clone(), delta), EffectDuration::NumberOfMatches(1));
+        combatant_object.combatant = Arc::new(Mutex::new(combatant));
+        true
     }
 
     pub fn home_and_away_combatants(&self) -> [Vec<CombatantObject>; 2] {
@@ -191,7 +399,88 @@ impl GameState {
         }
     }
 
+    /// # Panics
+    /// Will panic if the team cannot be locked.
+    #[must_use]
+    pub fn team_tactics(&self, team: TeamAlignment) -> TeamTactics {
+        let team_instance = match team {
+            TeamAlignment::Home => &self.game.game_instance.home_team,
+            TeamAlignment::Away => &self.game.game_instance.away_team,
+        };
+
+        team_instance.lock().unwrap().tactics.clone()
+    }
+
+    #[must_use]
+    pub fn role_assignment(&self, combatant_object: &CombatantObject) -> Option<RoleAssignment> {
+        self.team_coordinators
+            .get(&combatant_object.team)
+            .and_then(|coordinator| coordinator.assignment(combatant_object.id))
+            .cloned()
+    }
+
+    /// Lets each team's coordinator react to the events committed this tick.
+    pub fn update_team_roles(&mut self, committed_simulation_events: &[SimulationEvent]) {
+        for coordinator in self.team_coordinators.values_mut() {
+            coordinator.handle_events(committed_simulation_events, &self.combatants);
+        }
+    }
+
+    /// Areas pathing combatants should take into account wherever moving arena features currently are.
+    #[must_use]
+    pub fn kinematic_navmesh_areas(&self) -> Vec<NavmeshCostArea> {
+        let agent_radius = self.arena_navmesh.config().agent_radius;
+        self.kinematic_features
+            .values()
+            .filter_map(|kinematic_feature| kinematic_feature.navmesh_cost_area(agent_radius))
+            .collect()
+    }
+
     pub fn is_scoring_tick(&self) -> bool {
         self.current_tick % self.simulation_config.ticks_per_second() == 0
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use dys_world::arena::environment::EnvironmentModifier;
+    use dys_world::arena::Arena;
+
+    use crate::ai::test_utils::make_generated_game;
+    use crate::game_log::GameLog;
+    use crate::game_objects::game_object::GameObject;
+    use super::GameState;
+
+    #[test]
+    fn test_arena_environment_is_applied_to_the_game() {
+        let arena = Arena::new_with_testing_defaults()
+            .with_environment_modifier(EnvironmentModifier::Gravity(0.5))
+            .with_environment_modifier(EnvironmentModifier::Friction(0.2))
+            .with_environment_modifier(EnvironmentModifier::BallRestitution(2.0));
+        let default_game_state = GameState::from_game_seeded(make_generated_game(), &[0; 32]);
+        let mut game = make_generated_game();
+        game.game_instance.arena = Arc::new(Mutex::new(arena));
+        let game_state = GameState::from_game_seeded(game.clone(), &[0; 32]);
+
+        assert!((game_state.physics_sim.gravity_y() - default_game_state.physics_sim.gravity_y() * 0.5).abs() < 0.001);
+
+        let friction_and_restitution = |game_state: &GameState| {
+            let (_, collider_set) = game_state.physics_sim.sets();
+            let combatant = game_state.combatants.values().next().unwrap();
+            let ball = game_state.balls.values().next().unwrap();
+            (
+                collider_set.get(combatant.collider_handle().unwrap()).unwrap().friction(),
+                collider_set.get(ball.collider_handle().unwrap()).unwrap().restitution(),
+            )
+        };
+
+        let (default_friction, default_restitution) = friction_and_restitution(&default_game_state);
+        let (friction, restitution) = friction_and_restitution(&game_state);
+        assert!((friction - default_friction * 0.2).abs() < 0.001);
+        assert!((restitution - default_restitution * 2.0).abs() < 0.001);
+
+        let game_log = GameLog::from_ticks(vec![], Arc::new(Mutex::new(game_state)));
+        assert_eq!(*game_log.environment(), game.game_instance.arena.lock().unwrap().environment());
+    }
+}
@@ -2,7 +2,8 @@ use std::{ops::Add, time::Duration};
 
 use serde::{Deserialize, Serialize};
 
-use crate::simulation::simulation_event::SimulationEvent;
+use crate::simulation::simulation_event::{RejectedSimulationEvent, SimulationEvent, SimulationEventCause};
+use crate::state_hash::TickStateHash;
 
 pub type GameTickNumber = u32;
 
@@ -11,13 +12,30 @@ pub struct GameTick {
     pub tick_number: u32,
     pub tick_performance: TickPerformance,
     pub simulation_events: Vec<SimulationEvent>,
+    /// Which of the tick's simulation events were caused by other events on the tick, such as a stun causing a ball to be dropped.
+    pub event_causes: Vec<SimulationEventCause>,
     pub(crate) is_end_of_game: bool,
+    /// Hash of the game's state at the end of the tick.
+    /// Only recorded if state hashes are enabled in the simulation config.
+    pub state_hash: Option<TickStateHash>,
+    /// Events the simulation rejected during the tick, such as a combatant picking up a ball out of their reach.
+    /// Only recorded if rejected events are enabled in the simulation config.
+    pub rejected_events: Vec<RejectedSimulationEvent>,
 }
 
 impl GameTick {
     pub fn is_end_of_game(&self) -> bool { self.is_end_of_game }
     pub fn tick_performance(&self) -> &TickPerformance { &self.tick_performance }
 
+    /// The event on this tick that caused the event at the index, if any.
+    #[must_use]
+    pub fn cause_of(&self, event_index: usize) -> Option<&SimulationEvent> {
+        self.event_causes
+            .iter()
+            .find(|event_cause| event_cause.event_index == event_index)
+            .map(|event_cause| &self.simulation_events[event_cause.cause_index])
+    }
+
     pub fn perf_string(&self) -> String {
         self.tick_performance.perf_string()
     }
@@ -1,9 +1,23 @@
+pub mod counterfactual;
+pub mod external_input;
 pub mod game;
+pub mod game_checkpoint;
 pub mod game_log;
+pub mod game_log_digest;
+pub mod game_runner;
 pub mod game_objects;
 pub mod game_state;
 pub mod game_tick;
 pub mod simulation;
+pub mod state_hash;
 pub mod physics_sim;
 
-mod ai;
\ No newline at end of file
+pub(crate) mod ai;
+
+/// The parts of the planner that benches and tools need, without exposing the rest of the AI.
+pub mod planning {
+    pub use crate::ai::heuristic::PlannerHeuristic;
+    pub use crate::ai::heuristics::{cheapest_satisfier::CheapestSatisfierHeuristic, zero::ZeroHeuristic};
+    pub use crate::ai::planner::plan_with_heuristic;
+    pub use crate::ai::planner_trace::PlannerTrace;
+}
\ No newline at end of file
@@ -2,10 +2,16 @@ use std::sync::mpsc::Receiver;
 use rapier3d::glamx::vec3;
 use rapier3d::prelude::*;
 use rapier3d::parry::query::DefaultQueryDispatcher;
+use serde::{Deserialize, Serialize};
+use dys_world::arena::environment::GameEnvironment;
 
+/// Physics sims are serialized with everything needed to step on exactly as they would have,
+/// apart from the pipeline and event channels, which hold nothing between steps and are made afresh.
+#[derive(Serialize, Deserialize)]
 pub struct PhysicsSim {
     gravity: Vec3,
     integration_params: IntegrationParameters,
+    #[serde(skip)]
     pipeline: PhysicsPipeline,
     island_manager: IslandManager,
     broad_phase: DefaultBroadPhase,
@@ -16,25 +22,62 @@ pub struct PhysicsSim {
     multibody_joint_set: MultibodyJointSet,
     ccd_solver: CCDSolver,
     physics_hooks: (),
+    #[serde(skip)]
+    events: PhysicsEvents,
+}
+
+/// Clones start without any of the original's uncollected events.
+impl Clone for PhysicsSim {
+    fn clone(&self) -> Self {
+        PhysicsSim {
+            gravity: self.gravity,
+            integration_params: self.integration_params,
+            pipeline: PhysicsPipeline::new(),
+            island_manager: self.island_manager.clone(),
+            broad_phase: self.broad_phase.clone(),
+            narrow_phase: self.narrow_phase.clone(),
+            rigid_body_set: self.rigid_body_set.clone(),
+            collider_set: self.collider_set.clone(),
+            impulse_joint_set: self.impulse_joint_set.clone(),
+            multibody_joint_set: self.multibody_joint_set.clone(),
+            ccd_solver: self.ccd_solver.clone(),
+            physics_hooks: (),
+            events: PhysicsEvents::default(),
+        }
+    }
+}
+
+/// Collects the events raised by each physics step.
+struct PhysicsEvents {
     event_handler: ChannelEventCollector,
     collision_event_recv: Receiver<CollisionEvent>,
     contact_force_event_recv: Receiver<ContactForceEvent>,
 }
 
+impl Default for PhysicsEvents {
+    fn default() -> Self {
+        let (collision_send, collision_recv) = std::sync::mpsc::channel();
+        let (contact_force_send, contact_force_recv) = std::sync::mpsc::channel();
+
+        PhysicsEvents {
+            event_handler: ChannelEventCollector::new(collision_send, contact_force_send),
+            collision_event_recv: collision_recv,
+            contact_force_event_recv: contact_force_recv,
+        }
+    }
+}
+
 impl PhysicsSim {
-    pub fn new(ticks_per_second: u32) -> PhysicsSim {
+    #[must_use]
+    pub fn new(ticks_per_second: u32, environment: &GameEnvironment) -> PhysicsSim {
         let integration_params = IntegrationParameters {
             dt: 1.0 / (ticks_per_second as f32),
             max_ccd_substeps: 10,
             ..Default::default()
         };
 
-        let (collision_send, collision_recv) = std::sync::mpsc::channel();
-        let (contact_force_send, contact_force_recv) = std::sync::mpsc::channel();
-        let event_handler = ChannelEventCollector::new(collision_send, contact_force_send);
-
         PhysicsSim {
-            gravity: vec3(0.0, -9.81, 0.0),
+            gravity: vec3(0.0, -9.81 * environment.gravity_multiplier, 0.0),
             integration_params,
             pipeline: PhysicsPipeline::new(),
             island_manager: IslandManager::new(),
@@ -46,9 +89,7 @@ impl PhysicsSim {
             multibody_joint_set: MultibodyJointSet::new(),
             ccd_solver: CCDSolver::new(),
             physics_hooks: (),
-            event_handler,
-            collision_event_recv: collision_recv,
-            contact_force_event_recv: contact_force_recv,
+            events: PhysicsEvents::default(),
         }
     }
 
@@ -61,11 +102,11 @@ impl PhysicsSim {
     }
 
     pub fn collision_events(&mut self) -> &mut Receiver<CollisionEvent> {
-        &mut self.collision_event_recv
+        &mut self.events.collision_event_recv
     }
 
     pub fn contact_force_events(&mut self) -> &mut Receiver<ContactForceEvent> {
-        &mut self.contact_force_event_recv
+        &mut self.events.contact_force_event_recv
     }
 
     pub fn query_pipeline<'s>(&'s mut self, query_filter: QueryFilter<'s>) -> QueryPipeline<'s> {
@@ -90,10 +131,22 @@ impl PhysicsSim {
             &mut self.multibody_joint_set,
             &mut self.ccd_solver,
             &self.physics_hooks,
-            &self.event_handler,
+            &self.events.event_handler,
         )
     }
 
+    /// Takes a rigid body out of the sim, along with its colliders.
+    pub fn remove_rigid_body(&mut self, rigid_body_handle: RigidBodyHandle) {
+        self.rigid_body_set.remove(
+            rigid_body_handle,
+            &mut self.island_manager,
+            &mut self.collider_set,
+            &mut self.impulse_joint_set,
+            &mut self.multibody_joint_set,
+            true,
+        );
+    }
+
     pub fn gravity_y(&self) -> f32 {
         self.gravity.y
     }
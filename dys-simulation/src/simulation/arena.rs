@@ -0,0 +1,240 @@
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use rapier3d::pipeline::QueryFilter;
+
+use crate::game_objects::game_object::GameObject;
+use crate::game_objects::game_object_type::GameObjectType;
+use crate::game_state::GameState;
+use crate::simulation::simulation_stage::SimulationStage;
+use super::simulation_event::{PendingSimulationEvent, SimulationEvent};
+
+/// Moving features only report having moved once they've moved at least this far.
+/// Otherwise, features that have stopped would fill the game log with updates that don't go anywhere.
+const MIN_REPORTED_MOVEMENT: f32 = 0.001;
+
+/// Moves arena features along their paths, switches plates on and off on their schedules,
+/// and wears down combatants standing in hazards.
+///
+/// Features and plates that external inputs have moved or switched are left where the inputs put them.
+///
+/// This must happen before the physics sim steps, so that moving features push aside anything in their way this tick.
+#[tracing::instrument(skip_all, level = "trace")]
+#[allow(clippy::cast_precision_loss)]
+pub fn simulate_arena(
+    game_state: &Arc<Mutex<GameState>>,
+) -> SimulationStage {
+    let start_time = Instant::now();
+    let mut simulation_events = vec![];
+
+    let mut game_state = game_state.lock().unwrap();
+    let game_state = &mut *game_state;
+
+    let ticks_per_second = game_state.simulation_config.ticks_per_second();
+    let ticks_per_period = game_state.simulation_config.ticks_per_period();
+    let game_seconds = game_state.current_tick as f32 / ticks_per_second as f32;
+
+    // Tick N covers the moment just after tick N-1, up until tick N, so the last tick of a period still belongs to it
+    let period = game_state.current_tick.saturating_sub(1).checked_div(ticks_per_period).unwrap_or(0);
+
+    for (feature_index, kinematic_feature) in &game_state.kinematic_features {
+        if kinematic_feature.is_kinematics_overridden() {
+            continue;
+        }
+
+        let position = kinematic_feature.kinematics().position_at(period, game_seconds);
+        if (position - kinematic_feature.position).length() < MIN_REPORTED_MOVEMENT {
+            continue;
+        }
+
+        simulation_events.push(PendingSimulationEvent(
+            SimulationEvent::ArenaFeatureMoved { feature_index: *feature_index, position }
+        ));
+    }
+
+    for (plate_id, plate_object) in &game_state.plates {
+        if plate_object.is_schedule_overridden() {
+            continue;
+        }
+
+        let is_active = plate_object.schedule().is_active_at(game_seconds);
+        if is_active == plate_object.is_active() {
+            continue;
+        }
+
+        simulation_events.push(PendingSimulationEvent(
+            SimulationEvent::PlateActivityChanged { plate_id: *plate_id, is_active }
+        ));
+    }
+
+    let query_filter = QueryFilter::only_dynamic().exclude_sensors();
+    let query_pipeline = game_state.physics_sim.query_pipeline(query_filter);
+    for (hazard_id, hazard_object) in &game_state.hazards {
+        let hazard_collider = query_pipeline.colliders
+            .get(hazard_object.collider_handle().unwrap())
+            .expect("failed to find hazard with collider handle");
+
+        let stamina_cost = hazard_object.stamina_drain_per_second() / ticks_per_second as f32;
+
+        for (collider_handle, _) in query_pipeline.intersect_shape(*hazard_collider.position(), hazard_collider.shape()) {
+            let Some(GameObjectType::Combatant(combatant_id)) = game_state.active_colliders.get(&collider_handle) else {
+                continue;
+            };
+
+            simulation_events.push(PendingSimulationEvent(
+                SimulationEvent::CombatantInHazard { combatant_id: *combatant_id, hazard_id: *hazard_id, stamina_cost }
+            ));
+        }
+    }
+
+    SimulationStage {
+        execution_duration: start_time.elapsed(),
+        pending_events: simulation_events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use rapier3d::glamx::vec3;
+    use rapier3d::na::Quaternion;
+    use rapier3d::prelude::SharedShape;
+    use dys_world::arena::barrier::{ArenaBarrier, BarrierPathing};
+    use dys_world::arena::combatant_start::ArenaCombatantStart;
+    use dys_world::arena::feature::{ArenaFeature, NavmeshAreaType};
+    use dys_world::arena::navmesh::NavmeshCostProfile;
+    use dys_world::arena::hazard::ArenaHazard;
+    use dys_world::arena::kinematics::{FeatureMotionPath, MotionLooping};
+    use dys_world::arena::plate::{ArenaPlate, PlateSchedule};
+    use dys_world::arena::Arena;
+
+    use crate::ai::test_utils::make_generated_game;
+    use crate::game_objects::combatant::TeamAlignment;
+    use crate::game_objects::game_object::GameObject;
+    use crate::game_state::GameState;
+    use crate::simulation::simulate_tick;
+    use crate::simulation::simulation_event::SimulationEvent;
+
+    const MOVING_WALL_INDEX: usize = 3;
+
+    fn make_dynamic_game_state() -> Arc<Mutex<GameState>> {
+        let features: Vec<Box<dyn ArenaFeature>> = vec![
+            Box::new(ArenaBarrier::new(vec3(50.0, -5.0, 50.0), vec3(100.0, 10.0, 100.0), Quaternion::identity(), BarrierPathing::Enabled)),
+            Box::new(ArenaCombatantStart { origin: vec3(15.0, 0.0, 80.0), rotation: Quaternion::identity(), is_home_team: true }),
+            Box::new(ArenaCombatantStart { origin: vec3(85.0, 0.0, 20.0), rotation: Quaternion::identity(), is_home_team: false }),
+            // Slides 10 units east over 2 seconds, then stays put
+            Box::new(
+                ArenaBarrier::new(vec3(50.0, 0.0, 50.0), vec3(2.0, 10.0, 10.0), Quaternion::identity(), BarrierPathing::Disabled)
+                    .with_motion(FeatureMotionPath::new(vec![vec3(10.0, 0.0, 0.0)], 5.0, MotionLooping::Stop))
+            ),
+            // Never active, under the home combatant
+            Box::new(
+                ArenaPlate::new(1, vec3(15.0, 0.0, 80.0), SharedShape::cylinder(5.0, 5.0), Quaternion::identity())
+                    .with_schedule(PlateSchedule::Cycle { active_seconds: 0.0, inactive_seconds: 10.0, offset_seconds: 0.0 })
+            ),
+            // Always active, under the away combatant
+            Box::new(ArenaPlate::new(2, vec3(85.0, 0.0, 20.0), SharedShape::cylinder(5.0, 5.0), Quaternion::identity())),
+            // Active for the first second, then inactive for a second
+            Box::new(
+                ArenaPlate::new(3, vec3(50.0, 0.0, 80.0), SharedShape::cylinder(5.0, 3.0), Quaternion::identity())
+                    .with_schedule(PlateSchedule::Cycle { active_seconds: 1.0, inactive_seconds: 1.0, offset_seconds: 0.0 })
+            ),
+            Box::new(ArenaHazard::new(1, vec3(15.0, 0.0, 80.0), SharedShape::cylinder(5.0, 5.0), Quaternion::identity(), 10.0)),
+        ];
+
+        let mut game = make_generated_game();
+        game.game_instance.arena = Arc::new(Mutex::new(Arena::new(features)));
+
+        Arc::new(Mutex::new(GameState::from_game_seeded(game, &[0; 32])))
+    }
+
+    fn simulate_ticks(game_state: &Arc<Mutex<GameState>>, ticks: u32) -> Vec<SimulationEvent> {
+        (0..ticks)
+            .flat_map(|_| simulate_tick(game_state.clone()).simulation_events)
+            .collect()
+    }
+
+    #[test]
+    fn test_moving_walls_follow_their_path() {
+        let game_state = make_dynamic_game_state();
+        let events = simulate_ticks(&game_state, 30);
+
+        let moves = events
+            .iter()
+            .filter(|event| matches!(event, SimulationEvent::ArenaFeatureMoved { feature_index: MOVING_WALL_INDEX, .. }))
+            .count();
+
+        // The wall moves every tick for 2 seconds, then stops
+        assert_eq!(moves, 20);
+
+        let game_state = game_state.lock().unwrap();
+        let wall = game_state.kinematic_features.get(&MOVING_WALL_INDEX).unwrap();
+        let (rigid_body_set, _) = game_state.physics_sim.sets();
+        let wall_position = rigid_body_set.get(wall.rigid_body_handle().unwrap()).unwrap().translation();
+        assert!((wall_position - vec3(60.0, 0.0, 50.0)).length() < 0.01, "wall ended up at {wall_position:?}");
+
+        // Combatants can't path through the wall wherever it's got to, but can right up to its side
+        let areas = game_state.kinematic_navmesh_areas();
+        assert_eq!(areas.len(), 1);
+        assert_eq!(areas[0].area_type, NavmeshAreaType::Blocked);
+        assert!((areas[0].center - vec3(60.0, 0.0, 50.0)).length() < 0.01);
+
+        let cost_profile = NavmeshCostProfile { areas, ..NavmeshCostProfile::default() };
+        let mut path = game_state.arena_navmesh
+            .create_path_with_profile(vec3(55.0, 0.0, 50.0), vec3(65.0, 0.0, 50.0), &cost_profile)
+            .expect("failed to create path");
+        assert!(!path.is_empty());
+        while let Some(node) = path.next_node() {
+            let node = node.as_vector();
+            assert!((node.x - 60.0).abs() > 1.0 || (node.z - 50.0).abs() > 5.0, "path went through the wall at {node:?}");
+        }
+    }
+
+    #[test]
+    fn test_plates_switch_on_and_off_on_their_schedule() {
+        let game_state = make_dynamic_game_state();
+        let events = simulate_ticks(&game_state, 20);
+
+        let plate_changes = events
+            .iter()
+            .filter_map(|event| match event {
+                SimulationEvent::PlateActivityChanged { plate_id, is_active } => Some((*plate_id, *is_active)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(plate_changes, vec![(3, false), (3, true)]);
+
+        let scored_plates = events
+            .iter()
+            .filter_map(|event| match event {
+                SimulationEvent::PointsScoredByCombatant { plate_id, .. } => Some(*plate_id),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert!(scored_plates.contains(&2));
+        assert!(!scored_plates.contains(&1), "points were scored on an inactive plate");
+    }
+
+    #[test]
+    fn test_hazards_wear_down_combatants_in_them() {
+        let game_state = make_dynamic_game_state();
+        let events = simulate_ticks(&game_state, 5);
+
+        let home_combatant_id = {
+            let game_state = game_state.lock().unwrap();
+            game_state.combatants.values().find(|combatant| combatant.team == TeamAlignment::Home).unwrap().id
+        };
+
+        let hazard_costs = events
+            .iter()
+            .filter_map(|event| match event {
+                SimulationEvent::CombatantInHazard { combatant_id, hazard_id: 1, stamina_cost } => Some((*combatant_id, *stamina_cost)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert!(!hazard_costs.is_empty());
+        assert!(hazard_costs.iter().all(|(combatant_id, stamina_cost)| *combatant_id == home_combatant_id && (*stamina_cost - 1.0).abs() < 0.001));
+    }
+}
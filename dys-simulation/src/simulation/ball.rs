@@ -116,7 +116,7 @@ fn explode(
     };
     
     const EXPLOSION_CYLINDER_HEIGHT: f32 = 30.0;
-    let explosion_radius = ball.charge * 0.3; // ZJ-TODO: figure out explosion radius as compared to charge
+    let explosion_radius = ball.explosion_radius();
     let explosion_shape = Cylinder::new(EXPLOSION_CYLINDER_HEIGHT, explosion_radius);
     let explosion_pos = Pose3::new(ball_pos, Vec3::ZERO);
 
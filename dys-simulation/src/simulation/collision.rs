@@ -67,6 +67,8 @@ pub(crate) fn handle_collision_events(game_state: Arc<Mutex<GameState>>) -> Simu
             },
             (GameObjectType::Plate(_), _) | (_, GameObjectType::Plate(_)) => continue,
             (GameObjectType::BallSpawn, _) | (_, GameObjectType::BallSpawn) => continue,
+            // Hazards wear down whoever's in them each tick, rather than when they're entered
+            (GameObjectType::Hazard(_), _) | (_, GameObjectType::Hazard(_)) => continue,
             (GameObjectType::Barrier, GameObjectType::Combatant(combatant_id)) | (GameObjectType::Combatant(combatant_id), GameObjectType::Barrier) => {
                 if !evt.started() {
                     continue;
@@ -2,7 +2,7 @@ use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use dys_satisfiable::SatisfiableField;
 use crate::ai::agent::Agent;
-use crate::ai::belief::SatisfiableBelief;
+use crate::ai::belief::{Belief, SatisfiableBelief};
 use crate::game_state::GameState;
 use crate::simulation::simulation_event::{PendingSimulationEvent, SimulationEvent};
 use crate::simulation::simulation_stage::SimulationStage;
@@ -22,6 +22,13 @@ pub(crate) fn simulate_combatants(
 
     // Update combatants' sensors
     for (combatant_id, combatant_object) in &mut combatants {
+        // Rest before acting, so that combatants know how much stamina they have to work with
+        combatant_object.recover_stamina(current_tick);
+        let stamina = combatant_object.stamina();
+        combatant_object.combatant_state.lock().unwrap().beliefs.add_belief(
+            Belief::CombatantStamina { combatant_id: *combatant_id, stamina }
+        );
+
         {
             let sensors = {
                 // ZJ-TODO: refactor yuck
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use crate::ai::utility::UtilityProfile;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SimulationConfig {
@@ -38,6 +39,53 @@ pub struct SimulationConfig {
     /// If set to zero, the game will only end after time expires.
     /// Cannot be set to zero if periods_per_game is zero.
     game_conclusion_score: u16,
+
+    /// The maximum number of planner states a combatant may expand while planning in a single tick.
+    /// Once exhausted, the combatant uses the best plan found so far (if any).
+    /// Setting this to zero prevents combatants from planning at all.
+    #[serde(default = "default_planner_node_budget")]
+    planner_node_budget: u32,
+
+    /// The maximum number of actions in a single plan.
+    #[serde(default = "default_planner_max_plan_length")]
+    planner_max_plan_length: u8,
+
+    /// Should combatants record a trace of the planner's decisions each time they plan?
+    /// Useful for debugging, but slows down planning considerably.
+    #[serde(default)]
+    planner_traces_enabled: bool,
+
+    /// Should each tick record a hash of the game's state?
+    /// Lets replays be checked for determinism, at the cost of larger game logs.
+    #[serde(default)]
+    state_hashes_enabled: bool,
+
+    /// Should each tick record the events the simulation rejected, and why?
+    /// Useful for spotting combatants repeatedly trying things they can't do, at the cost of larger game logs.
+    #[serde(default)]
+    rejected_events_enabled: bool,
+
+    /// The maximum number of events the simulation will try to commit in a single tick, including those events cause.
+    /// Once spent, any further events that tick are rejected, so that a runaway chain of events can't stall the game.
+    #[serde(default = "default_event_budget_per_tick")]
+    event_budget_per_tick: u32,
+
+    /// Response curves that scale each goal's priority based on the game's context
+    /// (score, time remaining, the combatant's health, etc).
+    #[serde(default)]
+    utility_profile: UtilityProfile,
+}
+
+fn default_planner_node_budget() -> u32 {
+    256
+}
+
+fn default_planner_max_plan_length() -> u8 {
+    5
+}
+
+fn default_event_budget_per_tick() -> u32 {
+    4096
 }
 
 impl Default for SimulationConfig {
@@ -49,6 +97,13 @@ impl Default for SimulationConfig {
             ball_charge_increase_per_tick: 5.0,
             ball_charge_maximum: 100.0,
             game_conclusion_score: 150,
+            planner_node_budget: default_planner_node_budget(),
+            planner_max_plan_length: default_planner_max_plan_length(),
+            planner_traces_enabled: false,
+            state_hashes_enabled: false,
+            rejected_events_enabled: false,
+            event_budget_per_tick: default_event_budget_per_tick(),
+            utility_profile: UtilityProfile::default(),
         }
     }
 }
@@ -85,6 +140,68 @@ impl SimulationConfig {
     pub fn ball_charge_increase_per_tick(&self) -> f32 { self.ball_charge_increase_per_tick }
     pub fn ball_charge_maximum(&self) -> f32 { self.ball_charge_maximum }
     pub fn game_conclusion_score(&self) -> u16 { self.game_conclusion_score }
+    #[must_use]
+    pub fn planner_node_budget(&self) -> u32 { self.planner_node_budget }
+    #[must_use]
+    pub fn planner_max_plan_length(&self) -> u8 { self.planner_max_plan_length }
+    #[must_use]
+    pub fn planner_traces_enabled(&self) -> bool { self.planner_traces_enabled }
+    #[must_use]
+    pub fn state_hashes_enabled(&self) -> bool { self.state_hashes_enabled }
+    #[must_use]
+    pub fn rejected_events_enabled(&self) -> bool { self.rejected_events_enabled }
+    #[must_use]
+    pub fn event_budget_per_tick(&self) -> u32 { self.event_budget_per_tick }
+    #[must_use]
+    pub fn utility_profile(&self) -> &UtilityProfile { &self.utility_profile }
+
+    #[must_use]
+    pub fn with_seconds_per_period(mut self, seconds_per_period: u32) -> Self {
+        self.seconds_per_period = seconds_per_period;
+        self
+    }
+
+    #[must_use]
+    pub fn with_planner_node_budget(mut self, planner_node_budget: u32) -> Self {
+        self.planner_node_budget = planner_node_budget;
+        self
+    }
+
+    #[must_use]
+    pub fn with_planner_max_plan_length(mut self, planner_max_plan_length: u8) -> Self {
+        self.planner_max_plan_length = planner_max_plan_length;
+        self
+    }
+
+    #[must_use]
+    pub fn with_planner_traces(mut self, planner_traces_enabled: bool) -> Self {
+        self.planner_traces_enabled = planner_traces_enabled;
+        self
+    }
+
+    #[must_use]
+    pub fn with_state_hashes(mut self, state_hashes_enabled: bool) -> Self {
+        self.state_hashes_enabled = state_hashes_enabled;
+        self
+    }
+
+    #[must_use]
+    pub fn with_rejected_events(mut self, rejected_events_enabled: bool) -> Self {
+        self.rejected_events_enabled = rejected_events_enabled;
+        self
+    }
+
+    #[must_use]
+    pub fn with_event_budget_per_tick(mut self, event_budget_per_tick: u32) -> Self {
+        self.event_budget_per_tick = event_budget_per_tick;
+        self
+    }
+
+    #[must_use]
+    pub fn with_utility_profile(mut self, utility_profile: UtilityProfile) -> Self {
+        self.utility_profile = utility_profile;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -95,4 +212,22 @@ mod tests {
     fn test_default_config_is_valid() {
         assert!(SimulationConfig::default().is_valid());
     }
+
+    #[test]
+    fn test_config_without_newer_fields_uses_their_defaults() {
+        let simulation_config: SimulationConfig = serde_json::from_str(r#"{
+            "ticks_per_second": 10,
+            "periods_per_game": 2,
+            "seconds_per_period": 60,
+            "ball_charge_increase_per_tick": 5.0,
+            "ball_charge_maximum": 100.0,
+            "game_conclusion_score": 150
+        }"#).unwrap();
+
+        let default_config = SimulationConfig::default();
+        assert_eq!(simulation_config.planner_node_budget(), default_config.planner_node_budget());
+        assert_eq!(simulation_config.planner_max_plan_length(), default_config.planner_max_plan_length());
+        assert!(!simulation_config.planner_traces_enabled());
+        assert_eq!(simulation_config.event_budget_per_tick(), default_config.event_budget_per_tick());
+    }
 }
\ No newline at end of file
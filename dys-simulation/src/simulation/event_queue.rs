@@ -0,0 +1,228 @@
+use std::sync::{Arc, Mutex};
+use crate::game_state::GameState;
+use crate::simulation::simulation_event::{EventOutcome, EventRejectionReason, PendingSimulationEvent, RejectedSimulationEvent, SimulationEvent, SimulationEventCause};
+
+/// A pending event waiting to be simulated, along with the committed event that caused it (if any).
+struct QueuedEvent {
+    pending_event: PendingSimulationEvent,
+    cause_index: Option<usize>,
+}
+
+/// Commits pending simulation events, along with any further events they cause.
+///
+/// Events are worked through depth first, so everything an event causes is committed before the next pending event.
+/// An event that's the same variant as one of its causes is rejected rather than simulated, since it would likely
+/// keep causing itself forever, and once the budget is spent any further events are rejected too.
+pub(crate) struct SimulationEventQueue {
+    event_budget: u32,
+    events_simulated: u32,
+    committed_events: Vec<SimulationEvent>,
+    /// The index of the committed event that caused each committed event, if any.
+    committed_event_causes: Vec<Option<usize>>,
+    rejected_events: Vec<RejectedSimulationEvent>,
+}
+
+impl SimulationEventQueue {
+    pub fn new(event_budget: u32) -> SimulationEventQueue {
+        SimulationEventQueue {
+            event_budget,
+            events_simulated: 0,
+            committed_events: vec![],
+            committed_event_causes: vec![],
+            rejected_events: vec![],
+        }
+    }
+
+    /// Commits the pending events, and everything they cause, returning the events committed as a result.
+    #[tracing::instrument(skip_all, level = "trace")]
+    pub fn commit(&mut self, game_state: &Arc<Mutex<GameState>>, pending_events: Vec<PendingSimulationEvent>) -> &[SimulationEvent] {
+        let first_committed_index = self.committed_events.len();
+        let mut queued_events = pending_events
+            .into_iter()
+            .rev()
+            .map(|pending_event| QueuedEvent { pending_event, cause_index: None })
+            .collect::<Vec<_>>();
+
+        while let Some(QueuedEvent { pending_event, cause_index }) = queued_events.pop() {
+            tracing::trace!(?pending_event);
+            if let Some(reason) = self.rejection_before_simulating(&pending_event, cause_index) {
+                self.reject(pending_event, reason);
+                continue;
+            }
+
+            self.events_simulated += 1;
+            let new_pending_events = match SimulationEvent::simulate_event(game_state.clone(), &pending_event) {
+                EventOutcome::Committed(new_pending_events) => new_pending_events,
+                EventOutcome::Rejected(reason) => {
+                    self.reject(pending_event, reason);
+                    continue;
+                },
+            };
+
+            let committed_index = self.committed_events.len();
+            self.committed_events.push(pending_event.0);
+            self.committed_event_causes.push(cause_index);
+
+            // Queued in reverse, so that the new events are committed in the order they were caused
+            queued_events.extend(
+                new_pending_events
+                    .into_iter()
+                    .rev()
+                    .map(|pending_event| QueuedEvent { pending_event, cause_index: Some(committed_index) })
+            );
+        }
+
+        &self.committed_events[first_committed_index..]
+    }
+
+    /// Consumes the queue, returning every event it committed, which committed events caused which,
+    /// and every event it rejected.
+    pub fn into_parts(self) -> (Vec<SimulationEvent>, Vec<SimulationEventCause>, Vec<RejectedSimulationEvent>) {
+        let event_causes = self.committed_event_causes
+            .into_iter()
+            .enumerate()
+            .filter_map(|(event_index, cause_index)| Some(SimulationEventCause { event_index, cause_index: cause_index? }))
+            .collect();
+
+        (self.committed_events, event_causes, self.rejected_events)
+    }
+
+    fn rejection_before_simulating(&self, pending_event: &PendingSimulationEvent, cause_index: Option<usize>) -> Option<EventRejectionReason> {
+        if self.events_simulated >= self.event_budget {
+            return Some(EventRejectionReason::EventBudgetExceeded);
+        }
+
+        let mut causal_chain = vec![];
+        let mut next_cause_index = cause_index;
+        while let Some(cause_index) = next_cause_index {
+            let cause = &self.committed_events[cause_index];
+            causal_chain.push(cause.clone());
+
+            if pending_event.is_same_variant(cause) {
+                causal_chain.reverse();
+                return Some(EventRejectionReason::CausalCycle { causal_chain });
+            }
+
+            next_cause_index = self.committed_event_causes[cause_index];
+        }
+
+        None
+    }
+
+    fn reject(&mut self, pending_event: PendingSimulationEvent, reason: EventRejectionReason) {
+        match reason {
+            EventRejectionReason::CausalCycle { .. } | EventRejectionReason::EventBudgetExceeded => {
+                tracing::warn!(?pending_event, ?reason, "rejected simulation event");
+            },
+            _ => tracing::debug!(?pending_event, ?reason, "rejected simulation event"),
+        }
+
+        self.rejected_events.push(RejectedSimulationEvent { event: pending_event.0, reason });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use crate::ai::test_utils::make_generated_game;
+    use crate::game_state::GameState;
+    use crate::simulation::simulation_event::{EventRejectionReason, PendingSimulationEvent, RejectedSimulationEvent, SimulationEvent, SimulationEventCause};
+    use super::SimulationEventQueue;
+
+    #[test]
+    fn test_rejected_events_are_kept_with_their_reason() {
+        let game_state = Arc::new(Mutex::new(GameState::from_game_seeded(make_generated_game(), &[0; 32])));
+        let (combatant_id, ball_id) = {
+            let game_state = game_state.lock().unwrap();
+            (*game_state.combatants.keys().next().unwrap(), *game_state.balls.keys().next().unwrap())
+        };
+
+        // Combatants start the game well out of reach of the balls
+        let out_of_reach_pickup = SimulationEvent::CombatantPickedUpBall { combatant_id, ball_id };
+        let unknown_plate_change = SimulationEvent::PlateActivityChanged { plate_id: 99, is_active: false };
+        let stun = SimulationEvent::CombatantStunned { combatant_id, start: true };
+
+        let mut event_queue = SimulationEventQueue::new(64);
+        event_queue.commit(&game_state, vec![
+            PendingSimulationEvent(out_of_reach_pickup.clone()),
+            PendingSimulationEvent(unknown_plate_change.clone()),
+            PendingSimulationEvent(stun.clone()),
+        ]);
+
+        let (committed_simulation_events, _, rejected_simulation_events) = event_queue.into_parts();
+        assert_eq!(committed_simulation_events, vec![stun]);
+        assert!(matches!(
+            rejected_simulation_events[0],
+            RejectedSimulationEvent { reason: EventRejectionReason::BallOutOfReach { distance }, .. } if distance > 2.0
+        ));
+        assert_eq!(rejected_simulation_events[0].event, out_of_reach_pickup);
+        assert_eq!(rejected_simulation_events[1], RejectedSimulationEvent { event: unknown_plate_change, reason: EventRejectionReason::UnknownPlate });
+    }
+
+    #[test]
+    fn test_caused_events_are_committed_after_their_cause() {
+        let game_state = Arc::new(Mutex::new(GameState::from_game_seeded(make_generated_game(), &[0; 32])));
+        let (combatant_id, other_combatant_id, ball_id) = {
+            let mut game_state = game_state.lock().unwrap();
+            let mut combatant_ids = game_state.combatants.keys().copied();
+            let (combatant_id, other_combatant_id) = (combatant_ids.next().unwrap(), combatant_ids.next().unwrap());
+            let ball_id = *game_state.balls.keys().next().unwrap();
+
+            game_state.combatants.get_mut(&combatant_id).unwrap().pickup_ball(ball_id);
+            game_state.balls.get_mut(&ball_id).unwrap().set_held_by(Some(combatant_id), 0);
+            (combatant_id, other_combatant_id, ball_id)
+        };
+
+        // Stunning a combatant holding a ball causes them to drop it, before the next pending event is committed
+        let mut event_queue = SimulationEventQueue::new(64);
+        let committed_simulation_events = event_queue.commit(&game_state, vec![
+            PendingSimulationEvent(SimulationEvent::CombatantStunned { combatant_id, start: true }),
+            PendingSimulationEvent(SimulationEvent::CombatantStunned { combatant_id: other_combatant_id, start: true }),
+        ]);
+
+        assert_eq!(committed_simulation_events, [
+            SimulationEvent::CombatantStunned { combatant_id, start: true },
+            SimulationEvent::CombatantDroppedBall { combatant_id, ball_id },
+            SimulationEvent::CombatantStunned { combatant_id: other_combatant_id, start: true },
+        ]);
+
+        let (_, event_causes, _) = event_queue.into_parts();
+        assert_eq!(event_causes, vec![SimulationEventCause { event_index: 1, cause_index: 0 }]);
+    }
+
+    #[test]
+    fn test_events_repeating_their_causes_are_rejected() {
+        let stun = SimulationEvent::CombatantStunned { combatant_id: 1, start: true };
+        let drop = SimulationEvent::CombatantDroppedBall { combatant_id: 1, ball_id: 1 };
+        let mut event_queue = SimulationEventQueue::new(64);
+        event_queue.committed_events = vec![stun.clone(), drop.clone()];
+        event_queue.committed_event_causes = vec![None, Some(0)];
+
+        // Stunning another combatant as a result of the drop would be an indirect cycle
+        let restun = PendingSimulationEvent(SimulationEvent::CombatantStunned { combatant_id: 2, start: true });
+        assert_eq!(
+            event_queue.rejection_before_simulating(&restun, Some(1)),
+            Some(EventRejectionReason::CausalCycle { causal_chain: vec![stun, drop] }),
+        );
+        assert_eq!(event_queue.rejection_before_simulating(&restun, None), None);
+    }
+
+    #[test]
+    fn test_events_beyond_the_budget_are_rejected() {
+        let game_state = Arc::new(Mutex::new(GameState::from_game_seeded(make_generated_game(), &[0; 32])));
+        let combatant_ids = game_state.lock().unwrap().combatants.keys().copied().take(3).collect::<Vec<_>>();
+        let stuns = combatant_ids
+            .iter()
+            .map(|combatant_id| SimulationEvent::CombatantStunned { combatant_id: *combatant_id, start: true })
+            .collect::<Vec<_>>();
+
+        let mut event_queue = SimulationEventQueue::new(2);
+        event_queue.commit(&game_state, stuns.iter().cloned().map(PendingSimulationEvent).collect());
+
+        let (committed_simulation_events, _, rejected_simulation_events) = event_queue.into_parts();
+        assert_eq!(committed_simulation_events, stuns[..2]);
+        assert_eq!(rejected_simulation_events, vec![
+            RejectedSimulationEvent { event: stuns[2].clone(), reason: EventRejectionReason::EventBudgetExceeded },
+        ]);
+    }
+}
@@ -3,10 +3,12 @@ use std::time::{Duration, Instant};
 
 use crate::{game_state::GameState, game_tick::{GameTick, TickPerformance}};
 use crate::simulation::collision::handle_collision_events;
-use crate::simulation::simulation_event::{PendingSimulationEvent, SimulationEvent};
+use crate::simulation::event_queue::SimulationEventQueue;
 use crate::simulation::simulation_stage::SimulationStage;
-use self::{ball::simulate_balls, combatant::simulate_combatants, scoring::simulate_scoring};
+use crate::state_hash::TickStateHash;
+use self::{arena::simulate_arena, ball::simulate_balls, combatant::simulate_combatants, scoring::simulate_scoring};
 
+mod arena;
 mod ball;
 mod collision;
 mod combatant;
@@ -14,16 +16,32 @@ mod scoring;
 mod simulation_stage;
 
 pub mod config;
+pub(crate) mod event_queue;
 pub mod simulation_event;
 
 #[tracing::instrument(skip_all, level = "trace")]
 pub fn simulate_tick(game_state: Arc<Mutex<GameState>>) -> GameTick {
     let pre_tick_timestamp = Instant::now();
 
-    let (current_tick, simulation_config, phys_duration, highest_score) = {
+    {
         let mut game_state = game_state.lock().unwrap();
-
         game_state.current_tick += 1;
+        // Combatants and balls have moved since paths were last found, so last tick's paths may no longer be the best
+        game_state.path_cache.clear();
+    }
+
+    let event_budget_per_tick = game_state.lock().unwrap().simulation_config.event_budget_per_tick();
+    let mut event_queue = SimulationEventQueue::new(event_budget_per_tick);
+
+    // Moving arena features must be moved before the physics sim steps, so that they push aside anything in their way
+    tracing::debug!("starting arena simulation");
+    let arena_stage = simulate_arena(&game_state);
+    tracing::debug!("arena stage prepared");
+    event_queue.commit(&game_state, arena_stage.pending_events);
+    tracing::debug!("arena stage committed");
+
+    let (current_tick, simulation_config, phys_duration, highest_score) = {
+        let mut game_state = game_state.lock().unwrap();
 
         let pre_tick_timestamp = Instant::now();
         game_state.physics_sim.tick();
@@ -37,18 +55,16 @@ pub fn simulate_tick(game_state: Arc<Mutex<GameState>>) -> GameTick {
     let is_end_of_game = current_tick == simulation_config.ticks_per_game() || highest_score >= simulation_config.game_conclusion_score();
     let is_scoring_tick = current_tick % simulation_config.ticks_per_second() == 0;
 
-    let mut committed_simulation_events = vec![];
-
     tracing::debug!("starting balls simulation");
     let balls_stage = simulate_balls(game_state.clone());
     tracing::debug!("balls stage prepared");
-    committed_simulation_events.extend(commit_simulation_events(game_state.clone(), balls_stage.pending_events));
+    event_queue.commit(&game_state, balls_stage.pending_events);
     tracing::debug!("balls stage committed");
 
     tracing::debug!("starting combatants simulation");
     let combatants_stage = simulate_combatants(game_state.clone());
     tracing::debug!("combatants stage prepared");
-    committed_simulation_events.extend(commit_simulation_events(game_state.clone(), combatants_stage.pending_events));
+    event_queue.commit(&game_state, combatants_stage.pending_events);
     tracing::debug!("combatants stage committed");
 
     // Anything that may cause movement **must** occur before simulating collisions
@@ -56,7 +72,7 @@ pub fn simulate_tick(game_state: Arc<Mutex<GameState>>) -> GameTick {
     tracing::debug!("starting collision simulation");
     let collision_stage = handle_collision_events(game_state.clone());
     tracing::debug!("collision stage prepared");
-    committed_simulation_events.extend(commit_simulation_events(game_state.clone(), collision_stage.pending_events));
+    event_queue.commit(&game_state, collision_stage.pending_events);
     tracing::debug!("collision stage committed");
 
     let scoring_stage = if is_scoring_tick {
@@ -66,9 +82,18 @@ pub fn simulate_tick(game_state: Arc<Mutex<GameState>>) -> GameTick {
         SimulationStage { execution_duration: Duration::new(0, 0), pending_events: vec![] }
     };
     tracing::debug!("scoring stage prepared");
-    committed_simulation_events.extend(commit_simulation_events(game_state.clone(), scoring_stage.pending_events));
+    event_queue.commit(&game_state, scoring_stage.pending_events);
     tracing::debug!("scoring stage committed");
 
+    let (committed_simulation_events, event_causes, rejected_simulation_events) = event_queue.into_parts();
+
+    // Coordinators react to this tick's events, so that roles are up to date when combatants next plan
+    let state_hash = {
+        let mut game_state = game_state.lock().unwrap();
+        game_state.update_team_roles(&committed_simulation_events);
+        simulation_config.state_hashes_enabled().then(|| TickStateHash::from_game_state(&game_state))
+    };
+
     let post_tick_timestamp = Instant::now();
 
     GameTick {
@@ -81,46 +106,9 @@ pub fn simulate_tick(game_state: Arc<Mutex<GameState>>) -> GameTick {
             post_tick_timestamp - pre_tick_timestamp
         ),
         simulation_events: committed_simulation_events,
-        is_end_of_game
+        event_causes,
+        rejected_events: if simulation_config.rejected_events_enabled() { rejected_simulation_events } else { vec![] },
+        is_end_of_game,
+        state_hash,
     }
 }
-
-#[tracing::instrument(skip_all, level = "trace")]
-fn commit_simulation_events(
-    game_state: Arc<Mutex<GameState>>,
-    pending_events: Vec<PendingSimulationEvent>
-) -> Vec<SimulationEvent> {
-    let mut committed_simulation_events = vec![];
-    for pending_event in pending_events {
-        tracing::trace!(?pending_event);
-        let (successful_simulation, new_pending_events) = SimulationEvent::simulate_event(
-            game_state.clone(),
-            &pending_event
-        );
-
-        // If a newly generated pending event is the same variant as the currently evaluated pending event,
-        // we risk infinite recursion. There's likely a better way to handle this, but for simplicity's
-        // sake in the interim, just panic and force a different approach.
-        // Note that this won't catch cases where A creates a B, then B creates an A, and so on.
-        let potentially_infinite_recursion =  new_pending_events
-            .iter()
-            .any(|new_pending| new_pending.is_same_variant(&pending_event));
-
-        if potentially_infinite_recursion {
-            panic!("pending simulation event {pending_event:?} generated more pending events of same variant");
-        }
-
-        if !successful_simulation {
-            continue;
-        }
-
-        committed_simulation_events.push(pending_event.0);
-
-        // Attempt to commit the new events we just generated
-        committed_simulation_events.extend(
-            commit_simulation_events(game_state.clone(), new_pending_events)
-        );
-    }
-
-    committed_simulation_events
-}
\ No newline at end of file
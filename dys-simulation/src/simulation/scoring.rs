@@ -2,7 +2,7 @@ use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use rapier3d::{geometry::ColliderHandle, pipeline::QueryFilter};
 
-use crate::{game_objects::{combatant::TeamAlignment, game_object_type::GameObjectType}, game_state::GameState};
+use crate::{game_objects::{combatant::TeamAlignment, game_object_type::GameObjectType, plate::PlateObject}, game_state::GameState};
 use crate::simulation::simulation_stage::SimulationStage;
 use super::simulation_event::{PendingSimulationEvent, SimulationEvent};
 
@@ -31,6 +31,11 @@ pub fn simulate_scoring(
             panic!("non-plate game object returned as game object type");
         };
 
+        let is_plate_active = game_state.lock().unwrap().plates.get(plate_id).is_some_and(PlateObject::is_active);
+        if !is_plate_active {
+            continue;
+        }
+
         let mut affected_colliders = vec![];
         {
             let mut game_state = game_state.lock().unwrap();
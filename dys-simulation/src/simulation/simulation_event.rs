@@ -1,16 +1,19 @@
 use std::ops::{Deref, DerefMut};
 use std::sync::{Arc, Mutex};
 use rapier3d::glamx::vec3;
+use dys_world::arena::hazard::HazardId;
 use dys_world::arena::plate::PlateId;
+use dys_world::arena::ArenaFeatureIndex;
 use rapier3d::prelude::*;
 use rapier3d::na::{Quaternion, UnitQuaternion, Vector3};
 use serde::{Deserialize, Serialize};
 use dys_satisfiable::SatisfiableField;
 use dys_world::combatant::instance::CombatantInstanceId;
 use crate::ai::belief::{Belief, ExpiringBelief, SatisfiableBelief};
+use crate::external_input::{ExternalInput, ExternalInputError};
 use crate::game_objects::ball::BallId;
 use crate::game_objects::ball::BallState;
-use crate::game_objects::combatant::TeamAlignment;
+use crate::game_objects::combatant::{TeamAlignment, MOVE_STAMINA_COST_PER_UNIT, SHOVE_STAMINA_COST, THROW_STAMINA_COST};
 use crate::game_objects::game_object::GameObject;
 use crate::game_state::GameState;
 
@@ -38,12 +41,65 @@ impl DerefMut for PendingSimulationEvent {
 }
 
 impl PendingSimulationEvent {
-    /// Returns true if both pending simulation events are the same variant of SimulationEvent
-    pub fn is_same_variant(&self, other: &PendingSimulationEvent) -> bool {
-        std::mem::discriminant(&self.0) == std::mem::discriminant(&other.0)
+    /// Returns true if the pending event is the same variant of SimulationEvent as the other event
+    #[must_use]
+    pub fn is_same_variant(&self, other: &SimulationEvent) -> bool {
+        std::mem::discriminant(&self.0) == std::mem::discriminant(other)
     }
 }
 
+/// What came of the simulation trying to commit a pending event.
+#[derive(Debug)]
+pub enum EventOutcome {
+    /// The event happened, and may have caused more events that are yet to be committed.
+    Committed(Vec<PendingSimulationEvent>),
+
+    /// The event couldn't happen, and was discarded.
+    Rejected(EventRejectionReason),
+}
+
+/// Why the simulation refused to commit a pending event.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum EventRejectionReason {
+    /// The arena feature doesn't move, or doesn't exist.
+    UnmovableArenaFeature,
+
+    UnknownPlate,
+
+    /// The plate was switched off before points could be scored on it.
+    PlateInactive,
+
+    /// The combatant was too far from the ball to pick it up, eg because they believed it was somewhere else.
+    BallOutOfReach { distance: f32 },
+
+    /// The combatant was stunned before they could act.
+    CombatantStunned,
+
+    /// The event is the same variant as an event that caused it, directly or indirectly,
+    /// so would likely keep causing itself forever.
+    /// The causal chain runs from the earlier event of the same variant to the event that caused this one.
+    CausalCycle { causal_chain: Vec<SimulationEvent> },
+
+    /// So many events were simulated this tick that the simulation stopped simulating any more.
+    EventBudgetExceeded,
+}
+
+/// A pending event the simulation refused to commit, and why.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct RejectedSimulationEvent {
+    pub event: SimulationEvent,
+    pub reason: EventRejectionReason,
+}
+
+/// Records that a committed event was caused by another event committed on the same tick,
+/// such as a ball collision causing a combatant to be stunned.
+/// Both are indices into the tick's simulation events.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SimulationEventCause {
+    pub event_index: usize,
+    pub cause_index: usize,
+}
+
 /// SimulationEvents are any notable action that happens during a simulation.
 /// These events will be collected to form a recap of the game.
 /// 
@@ -52,11 +108,20 @@ impl PendingSimulationEvent {
 /// such as the last position of a combatant or ball. These will be used in the
 /// [GameLog](crate::game_log::GameLog) to allow clients to visually recreate 
 /// an entire game, whereas just discrete events may be confusing to see.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub enum SimulationEvent {
     // ZJ-TODO: keep?
     // This is currently only being used for tick zero initial state (eg where are there plates? where are there walls?)
-    ArenaObjectPositionUpdate { object_type_id: u32, position: Vec3, scale: Vec3, rotation: Quaternion<f32> },
+    ArenaObjectPositionUpdate { feature_index: ArenaFeatureIndex, object_type_id: u32, position: Vec3, scale: Vec3, rotation: Quaternion<f32> },
+
+    /// A moving arena feature (such as a sliding wall, or a plate relocating between periods) has moved through the world
+    ArenaFeatureMoved { feature_index: ArenaFeatureIndex, position: Vec3 },
+
+    /// A plate has started or stopped being able to be scored on
+    PlateActivityChanged { plate_id: PlateId, is_active: bool },
+
+    /// A combatant standing in a hazard has been worn down by it
+    CombatantInHazard { combatant_id: CombatantInstanceId, hazard_id: HazardId, stamina_cost: f32 },
 
     /// A ball has moved through the world
     BallPositionUpdate {
@@ -131,18 +196,104 @@ pub enum SimulationEvent {
     BroadcastBelief {
         from_combatant_id: CombatantInstanceId,
         belief: Belief,
-    }
+    },
+
+    /// An input from outside the simulation was applied to the game before this tick.
+    ExternalInputApplied { input: ExternalInput },
+
+    /// An input from outside the simulation couldn't be applied to the game, and was discarded.
+    ExternalInputRejected { input: ExternalInput, error: ExternalInputError },
 }
 
 impl SimulationEvent {
+    /// Name of the event's variant, eg for counting how often each kind of event happens.
+    #[must_use]
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            SimulationEvent::ArenaObjectPositionUpdate { .. } => "ArenaObjectPositionUpdate",
+            SimulationEvent::ArenaFeatureMoved { .. } => "ArenaFeatureMoved",
+            SimulationEvent::PlateActivityChanged { .. } => "PlateActivityChanged",
+            SimulationEvent::CombatantInHazard { .. } => "CombatantInHazard",
+            SimulationEvent::BallPositionUpdate { .. } => "BallPositionUpdate",
+            SimulationEvent::CombatantPositionUpdate { .. } => "CombatantPositionUpdate",
+            SimulationEvent::CombatantOnPlate { .. } => "CombatantOnPlate",
+            SimulationEvent::CombatantOffPlate { .. } => "CombatantOffPlate",
+            SimulationEvent::CombatantPickedUpBall { .. } => "CombatantPickedUpBall",
+            SimulationEvent::CombatantDroppedBall { .. } => "CombatantDroppedBall",
+            SimulationEvent::BallThrownAtEnemy { .. } => "BallThrownAtEnemy",
+            SimulationEvent::BallThrownAtTeammate { .. } => "BallThrownAtTeammate",
+            SimulationEvent::ThrownBallCaught { .. } => "ThrownBallCaught",
+            SimulationEvent::BallCollisionEnemy { .. } => "BallCollisionEnemy",
+            SimulationEvent::BallCollisionArena { .. } => "BallCollisionArena",
+            SimulationEvent::BallExplosion { .. } => "BallExplosion",
+            SimulationEvent::BallExplosionForceApplied { .. } => "BallExplosionForceApplied",
+            SimulationEvent::PointsScoredByCombatant { .. } => "PointsScoredByCombatant",
+            SimulationEvent::CombatantStunned { .. } => "CombatantStunned",
+            SimulationEvent::CombatantShoveForceApplied { .. } => "CombatantShoveForceApplied",
+            SimulationEvent::BroadcastBelief { .. } => "BroadcastBelief",
+            SimulationEvent::ExternalInputApplied { .. } => "ExternalInputApplied",
+            SimulationEvent::ExternalInputRejected { .. } => "ExternalInputRejected",
+        }
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
     pub fn simulate_event(
         game_state: Arc<Mutex<GameState>>,
         event: &PendingSimulationEvent,
-    ) -> (bool, Vec<PendingSimulationEvent>) {
+    ) -> EventOutcome {
         match **event {
             SimulationEvent::ArenaObjectPositionUpdate { .. } => {}
 
+            // External inputs are applied by the game runner, and only recorded as events
+            SimulationEvent::ExternalInputApplied { .. } | SimulationEvent::ExternalInputRejected { .. } => {}
+
+            SimulationEvent::ArenaFeatureMoved { feature_index, position } => {
+                let mut game_state = game_state.lock().unwrap();
+
+                let Some(kinematic_feature) = game_state.kinematic_features.get_mut(&feature_index) else {
+                    return EventOutcome::Rejected(EventRejectionReason::UnmovableArenaFeature);
+                };
+
+                kinematic_feature.position = position;
+                let rigid_body_handle = kinematic_feature.rigid_body_handle();
+                let collider_handle = kinematic_feature.collider_handle().unwrap();
+
+                let (rigid_body_set, collider_set) = game_state.physics_sim.sets_mut();
+                if let Some(rigid_body_handle) = rigid_body_handle {
+                    // Kinematic bodies are moved during the next physics step, pushing aside anything in the way
+                    rigid_body_set
+                        .get_mut(rigid_body_handle)
+                        .unwrap()
+                        .set_next_kinematic_translation(position);
+                } else {
+                    // Sensors (such as plates) don't push anything, so can be moved directly
+                    collider_set
+                        .get_mut(collider_handle)
+                        .unwrap()
+                        .set_translation(position);
+                }
+            }
+            SimulationEvent::PlateActivityChanged { plate_id, is_active } => {
+                let mut game_state = game_state.lock().unwrap();
+
+                let Some(plate_object) = game_state.plates.get_mut(&plate_id) else {
+                    return EventOutcome::Rejected(EventRejectionReason::UnknownPlate);
+                };
+
+                plate_object.set_active(is_active);
+            }
+            SimulationEvent::CombatantInHazard { combatant_id, hazard_id: _, stamina_cost } => {
+                let mut game_state = game_state.lock().unwrap();
+                let current_tick = game_state.current_tick;
+
+                let combatant_object = game_state
+                    .combatants
+                    .get_mut(&combatant_id)
+                    .unwrap();
+
+                combatant_object.exert(stamina_cost, current_tick);
+            }
+
             SimulationEvent::BallPositionUpdate { ball_id, position, charge: _ } => {
                 let mut game_state = game_state.lock().unwrap();
 
@@ -187,6 +338,10 @@ impl SimulationEvent {
                 if rotation.axis_angle().is_some() {
                     combatant_rb.set_rotation(rotation.into(), true);
                 }
+
+                let current_tick = game_state.current_tick;
+                let combatant_object = game_state.combatants.get_mut(&combatant_id).unwrap();
+                combatant_object.exert(difference_vector.length() * MOVE_STAMINA_COST_PER_UNIT, current_tick);
             }
             SimulationEvent::CombatantOnPlate { combatant_id, plate_id } => {
                 let mut game_state = game_state.lock().unwrap();
@@ -237,7 +392,7 @@ impl SimulationEvent {
                 // ZJ-TODO: read this from combatant stats
                 //          might have some long ass arms (if arms at all)
                 if distance.length() > 2.0 {
-                    return (false, vec![]);
+                    return EventOutcome::Rejected(EventRejectionReason::BallOutOfReach { distance: distance.length() });
                 }
 
                 {
@@ -248,7 +403,7 @@ impl SimulationEvent {
 
                     // Our combatant may have been stunned since initially trying this
                     if combatant_object.is_stunned() {
-                        return (false, vec![]);
+                        return EventOutcome::Rejected(EventRejectionReason::CombatantStunned);
                     }
                     combatant_object.pickup_ball(ball_id);
                 }
@@ -293,6 +448,7 @@ impl SimulationEvent {
                     .unwrap();
 
                 combatant_object.drop_ball();
+                combatant_object.exert(THROW_STAMINA_COST, current_tick);
 
                 let ball_object = game_state
                     .balls
@@ -315,6 +471,7 @@ impl SimulationEvent {
                     .unwrap();
 
                 combatant_object.drop_ball();
+                combatant_object.exert(THROW_STAMINA_COST, current_tick);
 
                 let ball_object = game_state
                     .balls
@@ -378,12 +535,17 @@ impl SimulationEvent {
                     combatant_object.apply_damage(force_magnitude);
                 }
             }
-            SimulationEvent::PointsScoredByCombatant { plate_id: _, combatant_id, points } => {
+            SimulationEvent::PointsScoredByCombatant { plate_id, combatant_id, points } => {
                 // ZJ-TODO: double points if no other combatants are on the plate
 
                 let mut game_state = game_state.lock().unwrap();
                 assert!(game_state.is_scoring_tick());
 
+                // The plate may have been switched off since
+                if game_state.plates.get(&plate_id).is_some_and(|plate_object| !plate_object.is_active()) {
+                    return EventOutcome::Rejected(EventRejectionReason::PlateInactive);
+                }
+
                 let combatant_team = game_state.combatants.get_mut(&combatant_id).unwrap();
 
                 if combatant_team.team == TeamAlignment::Home {
@@ -398,7 +560,7 @@ impl SimulationEvent {
                 if !is_stunned {
                     let combatant_object = game_state.combatants.get_mut(&combatant_id).unwrap();
                     combatant_object.set_stunned(false);
-                    return (true, vec![]);
+                    return EventOutcome::Committed(vec![]);
                 }
 
                 let current_tick = game_state.current_tick.to_owned();
@@ -424,7 +586,7 @@ impl SimulationEvent {
                             .combatant_id(SatisfiableField::Exactly(combatant_id))
                     );
 
-                    return (true, vec![
+                    return EventOutcome::Committed(vec![
                         PendingSimulationEvent(SimulationEvent::CombatantDroppedBall {
                             combatant_id,
                             ball_id,
@@ -432,8 +594,11 @@ impl SimulationEvent {
                     ])
                 }
             }
-            SimulationEvent::CombatantShoveForceApplied { shover_combatant_id: _, recipient_target_id, force_magnitude, force_direction } => {
+            SimulationEvent::CombatantShoveForceApplied { shover_combatant_id, recipient_target_id, force_magnitude, force_direction } => {
                 let mut game_state = game_state.lock().unwrap();
+                let current_tick = game_state.current_tick;
+
+                game_state.combatants.get_mut(&shover_combatant_id).unwrap().exert(SHOVE_STAMINA_COST, current_tick);
 
                 let combatant_rigid_body_handle = {
                     let combatant_object = game_state.combatants.get_mut(&recipient_target_id).unwrap();
@@ -469,6 +634,58 @@ impl SimulationEvent {
             }
         };
 
-        (true, vec![])
+        EventOutcome::Committed(vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use crate::ai::test_utils::make_generated_game;
+    use crate::game_objects::combatant::TeamAlignment;
+    use crate::game_objects::game_object::GameObject;
+    use crate::game_state::GameState;
+    use super::{EventOutcome, EventRejectionReason, PendingSimulationEvent, SimulationEvent};
+
+    #[test]
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn test_pickups_and_catches_are_rejected_with_their_reason() {
+        let game_state = Arc::new(Mutex::new(GameState::from_game_seeded(make_generated_game(), &[0; 32])));
+        let (catcher_id, thrower_id, ball_id) = {
+            let game_state = game_state.lock().unwrap();
+            (
+                game_state.team_combatants(TeamAlignment::Home)[0].id,
+                game_state.team_combatants(TeamAlignment::Away)[0].id,
+                *game_state.balls.keys().next().unwrap(),
+            )
+        };
+
+        // Combatants start the game well out of reach of the balls
+        let pickup = PendingSimulationEvent(SimulationEvent::CombatantPickedUpBall { combatant_id: catcher_id, ball_id });
+        assert!(matches!(
+            SimulationEvent::simulate_event(game_state.clone(), &pickup),
+            EventOutcome::Rejected(EventRejectionReason::BallOutOfReach { distance }) if distance > 2.0
+        ));
+
+        // Within reach, but stunned before the catch
+        {
+            let mut game_state = game_state.lock().unwrap();
+            let catcher_rigid_body_handle = game_state.combatants[&catcher_id].rigid_body_handle().unwrap();
+            let ball_rigid_body_handle = game_state.balls[&ball_id].rigid_body_handle().unwrap();
+            let (rigid_body_set, _) = game_state.physics_sim.sets_mut();
+            let catcher_position = rigid_body_set.get(catcher_rigid_body_handle).unwrap().translation();
+            rigid_body_set.get_mut(ball_rigid_body_handle).unwrap().set_translation(catcher_position, true);
+            game_state.combatants.get_mut(&catcher_id).unwrap().set_stunned(true);
+        }
+
+        let catch = PendingSimulationEvent(SimulationEvent::ThrownBallCaught { thrower_id, catcher_id, ball_id });
+        assert!(matches!(
+            SimulationEvent::simulate_event(game_state.clone(), &catch),
+            EventOutcome::Rejected(EventRejectionReason::CombatantStunned)
+        ));
+        assert!(matches!(
+            SimulationEvent::simulate_event(game_state, &pickup),
+            EventOutcome::Rejected(EventRejectionReason::CombatantStunned)
+        ));
     }
 }
\ No newline at end of file
@@ -0,0 +1,258 @@
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use dys_world::arena::ArenaFeatureIndex;
+use dys_world::combatant::instance::CombatantInstanceId;
+use dys_world::games::instance::GameInstanceId;
+use crate::game::Game;
+use crate::game_log::GameLog;
+use crate::game_objects::ball::BallId;
+use crate::game_objects::game_object::GameObject;
+use crate::game_objects::plate::PlateId;
+use crate::game_state::GameState;
+use crate::game_tick::GameTickNumber;
+use crate::simulation::config::SimulationConfig;
+use crate::simulation::simulate_tick;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// A part of a game's authoritative state that's hashed separately,
+/// so that a divergence can be traced back to whatever diverged first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HashedObject {
+    Scores,
+    Rng,
+    Combatant(CombatantInstanceId),
+    Ball(BallId),
+    Plate(PlateId),
+    KinematicFeature(ArenaFeatureIndex),
+}
+
+/// Hashes of a game's authoritative state at the end of a tick.
+///
+/// Everything is hashed from its postcard serialization with FNV-1a, rather than with std's hashers,
+/// so that hashes are the same on every platform (native and wasm alike) and every version of Rust.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TickStateHash {
+    objects: IndexMap<HashedObject, u64>,
+}
+
+impl TickStateHash {
+    /// # Panics
+    /// Will panic if a combatant's state cannot be locked.
+    #[must_use]
+    pub fn from_game_state(game_state: &GameState) -> TickStateHash {
+        let (rigid_body_set, _) = game_state.physics_sim.sets();
+        let rigid_body_hash = |rigid_body_handle| {
+            let rigid_body = rigid_body_set.get(rigid_body_handle).unwrap();
+            hash_serialized(&(rigid_body.position(), rigid_body.linvel(), rigid_body.angvel()))
+        };
+
+        let mut objects = IndexMap::new();
+        objects.insert(HashedObject::Scores, hash_serialized(&(game_state.home_points, game_state.away_points)));
+        objects.insert(HashedObject::Rng, hash_serialized(&game_state.rng));
+
+        for (combatant_id, combatant_object) in &game_state.combatants {
+            let combatant_state = combatant_object.combatant_state.lock().unwrap();
+            let state_hash = hash_serialized(&(
+                combatant_state.holding_ball,
+                combatant_state.on_plate,
+                combatant_state.stunned,
+                combatant_state.damage,
+                combatant_state.fatigue,
+            ));
+
+            objects.insert(HashedObject::Combatant(*combatant_id), combine(&[rigid_body_hash(combatant_object.rigid_body_handle), state_hash]));
+        }
+
+        for (ball_id, ball_object) in &game_state.balls {
+            let state_hash = hash_serialized(ball_object);
+            objects.insert(HashedObject::Ball(*ball_id), combine(&[rigid_body_hash(ball_object.rigid_body_handle().unwrap()), state_hash]));
+        }
+
+        for (plate_id, plate_object) in &game_state.plates {
+            objects.insert(HashedObject::Plate(*plate_id), hash_serialized(plate_object));
+        }
+
+        for (feature_index, kinematic_feature) in &game_state.kinematic_features {
+            objects.insert(HashedObject::KinematicFeature(*feature_index), hash_serialized(kinematic_feature));
+        }
+
+        TickStateHash { objects }
+    }
+
+    /// A single hash of all of the game's state.
+    #[must_use]
+    pub fn combined(&self) -> u64 {
+        let mut hashes = vec![];
+        for (object, hash) in &self.objects {
+            hashes.push(hash_serialized(object));
+            hashes.push(*hash);
+        }
+
+        combine(&hashes)
+    }
+
+    #[must_use]
+    pub fn object_hash(&self, object: HashedObject) -> Option<u64> {
+        self.objects.get(&object).copied()
+    }
+
+    /// The objects whose state differs between the hashes, including objects only one of them has.
+    #[must_use]
+    pub fn diverged_objects(&self, other: &TickStateHash) -> Vec<HashedObject> {
+        let missing_from_self = other.objects.keys().filter(|object| !self.objects.contains_key(*object));
+
+        self.objects
+            .iter()
+            .filter(|(object, hash)| other.objects.get(*object) != Some(hash))
+            .map(|(object, _)| object)
+            .chain(missing_from_self)
+            .copied()
+            .collect()
+    }
+}
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME))
+}
+
+pub(crate) fn hash_serialized(value: &impl Serialize) -> u64 {
+    let bytes = postcard::to_allocvec(value).expect("hashed state must be serializable");
+    fnv1a(FNV_OFFSET_BASIS, &bytes)
+}
+
+fn combine(hashes: &[u64]) -> u64 {
+    hashes.iter().fold(FNV_OFFSET_BASIS, |combined, hash| fnv1a(combined, &hash.to_le_bytes()))
+}
+
+/// Where a replayed game first stopped matching its recording.
+#[derive(Debug, PartialEq)]
+pub struct StateDivergence {
+    pub tick_number: GameTickNumber,
+    pub diverged_objects: Vec<HashedObject>,
+}
+
+/// Why a recorded game couldn't be verified against a replay of it.
+#[derive(Debug, PartialEq)]
+pub enum ReplayVerificationError {
+    /// The recording is of a different game than the one being replayed.
+    WrongGame { expected: GameInstanceId, found: GameInstanceId },
+
+    /// The recording was simulated without state hashes, so there's nothing to check the replay against.
+    MissingStateHash { tick_number: GameTickNumber },
+
+    /// The replay stopped matching the recording.
+    Diverged(StateDivergence),
+}
+
+impl Display for ReplayVerificationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayVerificationError::WrongGame { expected, found } => write!(f, "recording is of game {found}, not game {expected}"),
+            ReplayVerificationError::MissingStateHash { tick_number } => write!(f, "recording has no state hash for tick {tick_number}"),
+            ReplayVerificationError::Diverged(StateDivergence { tick_number, diverged_objects }) => {
+                write!(f, "replay diverged from recording on tick {tick_number}: {diverged_objects:?}")
+            },
+        }
+    }
+}
+
+impl std::error::Error for ReplayVerificationError {}
+
+/// Replays a recorded game from its seed, checking the state after every recorded tick against the recording's hashes.
+/// `simulation_config` must be the config the game was recorded with.
+///
+/// # Errors
+/// Will error on the first tick the replay diverges from the recording, or if the recording can't be checked.
+///
+/// # Panics
+/// Will panic if the game state cannot be locked.
+pub fn verify_replay(game: &Game, simulation_config: SimulationConfig, game_log: &GameLog) -> Result<(), ReplayVerificationError> {
+    if game.game_instance.game_id != game_log.game_id() {
+        return Err(ReplayVerificationError::WrongGame { expected: game.game_instance.game_id, found: game_log.game_id() });
+    }
+
+    let mut game_state = GameState::from_game_seeded(game.clone(), &game_log.seed());
+    game_state.simulation_config = simulation_config;
+    let game_state = Arc::new(Mutex::new(game_state));
+
+    for recorded_tick in game_log.ticks() {
+        let tick_number = recorded_tick.tick_number;
+        let Some(recorded_hash) = &recorded_tick.state_hash else {
+            return Err(ReplayVerificationError::MissingStateHash { tick_number });
+        };
+
+        while game_state.lock().unwrap().current_tick < tick_number {
+            simulate_tick(game_state.clone());
+        }
+
+        let replayed_hash = TickStateHash::from_game_state(&game_state.lock().unwrap());
+        if replayed_hash.combined() != recorded_hash.combined() {
+            return Err(ReplayVerificationError::Diverged(StateDivergence {
+                tick_number,
+                diverged_objects: replayed_hash.diverged_objects(recorded_hash),
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rapier3d::prelude::Vec3;
+
+    use crate::ai::test_utils::make_generated_game;
+    use crate::game_objects::game_object::GameObject;
+    use crate::game_state::GameState;
+    use crate::simulation::config::SimulationConfig;
+    use super::{verify_replay, HashedObject, ReplayVerificationError, StateDivergence, TickStateHash};
+
+    fn short_game_config() -> SimulationConfig {
+        SimulationConfig::default().with_seconds_per_period(3)
+    }
+
+    #[test]
+    fn test_hashes_pinpoint_diverged_objects() {
+        let mut game_state = GameState::from_game_seeded(make_generated_game(), &[0; 32]);
+        let state_hash = TickStateHash::from_game_state(&game_state);
+        assert_eq!(state_hash, TickStateHash::from_game_state(&game_state));
+
+        let (ball_id, ball_handle) = game_state.balls
+            .iter()
+            .map(|(ball_id, ball_object)| (*ball_id, ball_object.rigid_body_handle().unwrap()))
+            .next()
+            .unwrap();
+        let (rigid_body_set, _) = game_state.physics_sim.sets_mut();
+        rigid_body_set.get_mut(ball_handle).unwrap().set_linvel(Vec3::new(0.0, 0.001, 0.0), true);
+
+        let nudged_state_hash = TickStateHash::from_game_state(&game_state);
+        assert_ne!(state_hash.combined(), nudged_state_hash.combined());
+        assert_eq!(state_hash.diverged_objects(&nudged_state_hash), vec![HashedObject::Ball(ball_id)]);
+    }
+
+    #[test]
+    fn test_recordings_verify_against_replays() {
+        let game = make_generated_game();
+        let game_log = game.simulate_seeded_with_config(&[0; 32], short_game_config().with_state_hashes(true));
+        assert!(game_log.ticks().iter().all(|tick| tick.state_hash.is_some()));
+        assert_eq!(verify_replay(&game, short_game_config(), &game_log), Ok(()));
+
+        // Replaying with the wrong config starts out the same, but combatants soon act differently
+        let result = verify_replay(&game, short_game_config().with_planner_node_budget(0), &game_log);
+        assert!(matches!(
+            result,
+            Err(ReplayVerificationError::Diverged(StateDivergence { tick_number, diverged_objects }))
+                if tick_number > 0 && diverged_objects.iter().any(|object| matches!(object, HashedObject::Combatant(_)))
+        ));
+
+        let unhashed_game_log = game.simulate_seeded_with_config(&[0; 32], short_game_config());
+        assert_eq!(
+            verify_replay(&game, short_game_config(), &unhashed_game_log),
+            Err(ReplayVerificationError::MissingStateHash { tick_number: 0 }),
+        );
+    }
+}
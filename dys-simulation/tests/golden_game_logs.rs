@@ -0,0 +1,136 @@
+//! Re-simulates a corpus of games with known outcomes, to catch any change in how games play out.
+//!
+//! Each game in `golden/corpus.json` is played between two teams of a world fixture in `golden/worlds`,
+//! and its game log is digested and compared to the digest expected of it.
+//! When behaviour changes on purpose, re-run with `DYS_BLESS=1` to accept the new digests:
+//!
+//! ```sh
+//! DYS_BLESS=1 cargo test -p dys-simulation --test golden_game_logs
+//! ```
+//!
+//! Blessing also generates any missing world fixtures from their entry's `world_seed`.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use dys_simulation::game::Game;
+use dys_simulation::game_log_digest::GameLogDigest;
+use dys_world::generator::Generator;
+use dys_world::team::instance::TeamInstanceId;
+use dys_world::world::World;
+
+const BLESS_ENV_VAR: &str = "DYS_BLESS";
+
+#[derive(Serialize, Deserialize)]
+struct GoldenGame {
+    name: String,
+    /// Path of the world fixture, relative to the golden directory.
+    world: String,
+    /// Seed the world fixture was generated from, should it need generating again.
+    world_seed: u64,
+    home_team_id: TeamInstanceId,
+    away_team_id: TeamInstanceId,
+    /// Seed the game is simulated with, as hex.
+    seed: String,
+    expected: Option<GameLogDigest>,
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+fn parse_seed(seed: &str) -> [u8; 32] {
+    assert_eq!(seed.len(), 64, "seed {seed} must be 64 hex digits");
+
+    let mut parsed_seed = [0; 32];
+    for (index, byte) in parsed_seed.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&seed[index * 2..index * 2 + 2], 16).expect("seed must be hex");
+    }
+
+    parsed_seed
+}
+
+fn generate_missing_world(golden_game: &GoldenGame, bless: bool) {
+    let world_path = golden_dir().join(&golden_game.world);
+    if world_path.exists() {
+        return;
+    }
+
+    assert!(bless, "world fixture {} is missing, run with {BLESS_ENV_VAR}=1 to generate it", world_path.display());
+
+    let world = Generator::new().generate_world(&mut StdRng::seed_from_u64(golden_game.world_seed));
+    let world_json = serde_json::to_string(&world).expect("failed to serialize world fixture");
+    std::fs::write(&world_path, world_json).expect("failed to write world fixture");
+}
+
+fn load_world(golden_game: &GoldenGame) -> World {
+    let world_json = std::fs::read_to_string(golden_dir().join(&golden_game.world)).expect("failed to read world fixture");
+    serde_json::from_str(&world_json).expect("failed to parse world fixture")
+}
+
+fn simulate(golden_game: &GoldenGame) -> GameLogDigest {
+    let world = load_world(golden_game);
+    let team = |team_id| world.teams
+        .get(&team_id)
+        .unwrap_or_else(|| panic!("{}: world fixture has no team {team_id}", golden_game.name))
+        .clone();
+
+    let game = Game::new_with_testing_defaults(team(golden_game.away_team_id), team(golden_game.home_team_id));
+
+    GameLogDigest::from_game_log(&game.simulate_seeded(&parse_seed(&golden_game.seed)))
+}
+
+#[test]
+fn test_golden_game_logs() {
+    let bless = std::env::var(BLESS_ENV_VAR).is_ok_and(|bless| bless == "1");
+    let corpus_path = golden_dir().join("corpus.json");
+    let corpus_json = std::fs::read_to_string(&corpus_path).expect("failed to read golden corpus");
+    let mut corpus: Vec<GoldenGame> = serde_json::from_str(&corpus_json).expect("failed to parse golden corpus");
+
+    for golden_game in &corpus {
+        generate_missing_world(golden_game, bless);
+    }
+
+    // Full games are slow to simulate without optimizations, so play them all at once
+    let digests = std::thread::scope(|scope| {
+        let handles = corpus
+            .iter()
+            .map(|golden_game| scope.spawn(move || simulate(golden_game)))
+            .collect::<Vec<_>>();
+
+        handles.into_iter().map(|handle| handle.join().expect("golden game panicked")).collect::<Vec<_>>()
+    });
+
+    let mut report = String::new();
+    for (golden_game, digest) in corpus.iter_mut().zip(digests) {
+        let Some(expected) = &golden_game.expected else {
+            writeln!(report, "{}: no expected digest", golden_game.name).unwrap();
+            golden_game.expected = Some(digest);
+            continue;
+        };
+
+        let changes = digest.changes_from(expected);
+        if changes.is_empty() {
+            continue;
+        }
+
+        writeln!(report, "{}:", golden_game.name).unwrap();
+        for change in changes {
+            writeln!(report, "\t{change}").unwrap();
+        }
+
+        golden_game.expected = Some(digest);
+    }
+
+    if report.is_empty() {
+        return;
+    }
+
+    assert!(bless, "golden games played out differently than expected, run with {BLESS_ENV_VAR}=1 if this is intended:\n{report}");
+
+    let corpus_json = serde_json::to_string_pretty(&corpus).expect("failed to serialize golden corpus");
+    std::fs::write(&corpus_path, corpus_json + "\n").expect("failed to write golden corpus");
+    println!("blessed golden games:\n{report}");
+}
@@ -5,13 +5,19 @@ use dys_simulation::simulation::simulation_event::SimulationEvent;
 use dys_world::combatant::instance::CombatantInstanceId;
 use crate::game_stat::{GameStat, GameStatPointsScored};
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct CombatantStatline {
     pub combatant_id: CombatantInstanceId,
     pub points_scored: u8,
     pub balls_thrown: u16,
     pub throws_hit: u16,
     pub combatants_shoved: u16,
+    /// Pickups the simulation rejected, eg because the ball was out of reach.
+    /// Only counted if the game was simulated with rejected events enabled.
+    pub rejected_pickups: u16,
+    /// Catches the simulation rejected, eg because the catcher had been stunned.
+    /// Only counted if the game was simulated with rejected events enabled.
+    pub rejected_catches: u16,
 }
 
 impl CombatantStatline {
@@ -20,15 +26,33 @@ impl CombatantStatline {
         combatant_id: CombatantInstanceId,
         through_tick: Option<GameTickNumber>,
     ) -> CombatantStatline {
-        let events = game_log
+        let ticks = game_log
             .ticks()
             .iter()
             .filter(|tick| tick.tick_number <= through_tick.unwrap_or(GameTickNumber::MAX))
+            .collect::<Vec<_>>();
+
+        let events = ticks
+            .iter()
             .flat_map(|tick| tick.simulation_events.clone())
             .collect::<Vec<_>>();
 
+        let rejected_events = ticks
+            .iter()
+            .flat_map(|tick| &tick.rejected_events)
+            .map(|rejected| &rejected.event)
+            .collect::<Vec<_>>();
+
+        CombatantStatline::combatant_statline_from_events(combatant_id, &events, &rejected_events)
+    }
+
+    fn combatant_statline_from_events(
+        combatant_id: CombatantInstanceId,
+        events: &[SimulationEvent],
+        rejected_events: &[&SimulationEvent],
+    ) -> CombatantStatline {
         let points_scored = GameStatPointsScored
-            .calculate(combatant_id, &events)
+            .calculate(combatant_id, events)
             .unwrap_or_default() as u8;
 
         // ZJ-TODO: balls thrown at teammates may hit enemies and explode
@@ -48,12 +72,24 @@ impl CombatantStatline {
             .filter(|evt| matches!(evt, SimulationEvent::CombatantShoveForceApplied { shover_combatant_id: cid, .. } if *cid == combatant_id ))
             .count() as u16;
 
+        let rejected_pickups = rejected_events
+            .iter()
+            .filter(|evt| matches!(evt, SimulationEvent::CombatantPickedUpBall { combatant_id: cid, .. } if *cid == combatant_id))
+            .count() as u16;
+
+        let rejected_catches = rejected_events
+            .iter()
+            .filter(|evt| matches!(evt, SimulationEvent::ThrownBallCaught { catcher_id: cid, .. } if *cid == combatant_id))
+            .count() as u16;
+
         CombatantStatline {
             combatant_id,
             points_scored,
             balls_thrown,
             throws_hit,
             combatants_shoved,
+            rejected_pickups,
+            rejected_catches,
         }
     }
 
@@ -91,4 +127,27 @@ impl CombatantStatline {
             })
             .collect::<Vec<_>>()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use rapier3d::prelude::Vec3;
+    use dys_simulation::simulation::simulation_event::SimulationEvent;
+    use super::CombatantStatline;
+
+    #[test]
+    fn test_rejected_pickups_and_catches_are_counted_separately() {
+        let pickup = SimulationEvent::CombatantPickedUpBall { combatant_id: 1, ball_id: 1 };
+        let catch = SimulationEvent::ThrownBallCaught { thrower_id: 2, catcher_id: 1, ball_id: 1 };
+        let other_catch = SimulationEvent::ThrownBallCaught { thrower_id: 1, catcher_id: 2, ball_id: 1 };
+        let events = [SimulationEvent::CombatantPositionUpdate { combatant_id: 1, position: Vec3::ZERO }];
+
+        let statline = CombatantStatline::combatant_statline_from_events(1, &events, &[&pickup, &catch, &catch, &other_catch]);
+        assert_eq!(statline, CombatantStatline {
+            combatant_id: 1,
+            rejected_pickups: 1,
+            rejected_catches: 2,
+            ..CombatantStatline::default()
+        });
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use rapier3d::prelude::Vec3;
+use dys_simulation::game_log::GameLog;
+use dys_simulation::game_tick::GameTickNumber;
+use dys_simulation::simulation::simulation_event::SimulationEvent;
+use dys_world::combatant::instance::CombatantInstanceId;
+use crate::combatant_statline::CombatantStatline;
+
+/// Where a combatant was on a tick in each of two game logs, if they were in different places.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CombatantPositionDelta {
+    pub combatant_id: CombatantInstanceId,
+    pub left_position: Vec3,
+    pub right_position: Vec3,
+}
+
+impl CombatantPositionDelta {
+    #[must_use]
+    pub fn distance(&self) -> f32 {
+        (self.right_position - self.left_position).length()
+    }
+}
+
+/// How a tick differs between two game logs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TickDiff {
+    pub tick_number: GameTickNumber,
+    /// Events only in the right game log.
+    pub added_events: Vec<SimulationEvent>,
+    /// Events only in the left game log.
+    pub removed_events: Vec<SimulationEvent>,
+    /// Combatants who were somewhere else in the right game log.
+    /// Their position updates aren't also counted as added or removed events.
+    pub position_deltas: Vec<CombatantPositionDelta>,
+}
+
+impl TickDiff {
+    fn between(tick_number: GameTickNumber, left_events: &[SimulationEvent], right_events: &[SimulationEvent]) -> TickDiff {
+        let mut unmatched_right_events = right_events.iter().collect::<Vec<_>>();
+        let mut removed_events = vec![];
+        let mut position_deltas = vec![];
+
+        for left_event in left_events {
+            if let Some(index) = unmatched_right_events.iter().position(|right_event| *right_event == left_event) {
+                unmatched_right_events.remove(index);
+                continue;
+            }
+
+            if let SimulationEvent::CombatantPositionUpdate { combatant_id, position: left_position } = left_event {
+                let moved_elsewhere = unmatched_right_events.iter().position(|right_event| matches!(
+                    right_event,
+                    SimulationEvent::CombatantPositionUpdate { combatant_id: right_combatant_id, .. } if right_combatant_id == combatant_id
+                ));
+
+                if let Some(index) = moved_elsewhere {
+                    let SimulationEvent::CombatantPositionUpdate { position: right_position, .. } = unmatched_right_events.remove(index) else {
+                        unreachable!("only position updates are matched");
+                    };
+
+                    position_deltas.push(CombatantPositionDelta {
+                        combatant_id: *combatant_id,
+                        left_position: *left_position,
+                        right_position: *right_position,
+                    });
+                    continue;
+                }
+            }
+
+            removed_events.push(left_event.clone());
+        }
+
+        TickDiff {
+            tick_number,
+            added_events: unmatched_right_events.into_iter().cloned().collect(),
+            removed_events,
+            position_deltas,
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added_events.is_empty() && self.removed_events.is_empty() && self.position_deltas.is_empty()
+    }
+}
+
+fn tick_events(game_log: &GameLog, tick_number: GameTickNumber) -> &[SimulationEvent] {
+    game_log
+        .ticks()
+        .iter()
+        .find(|game_tick| game_tick.tick_number == tick_number)
+        .map(|game_tick| game_tick.simulation_events.as_slice())
+        .unwrap_or_default()
+}
+
+/// A combatant whose stats differ between two game logs.
+/// A statline is missing if the combatant only played in one of the games.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatlineDiff {
+    pub combatant_id: CombatantInstanceId,
+    pub left: Option<CombatantStatline>,
+    pub right: Option<CombatantStatline>,
+}
+
+/// The differences between two game logs, such as two simulations of a game from the same seed.
+/// Ticks are aligned by tick number, so ticks only one of the logs has count as entirely added or removed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameLogDiff {
+    pub left_score: (u16, u16),
+    pub right_score: (u16, u16),
+    /// Only the ticks that differ, in tick order.
+    pub ticks: Vec<TickDiff>,
+    pub statlines: Vec<StatlineDiff>,
+}
+
+impl GameLogDiff {
+    #[must_use]
+    pub fn between(left: &GameLog, right: &GameLog) -> GameLogDiff {
+        let tick_numbers = left.ticks()
+            .iter()
+            .chain(right.ticks())
+            .map(|game_tick| game_tick.tick_number)
+            .collect::<BTreeSet<_>>();
+
+        let ticks = tick_numbers
+            .into_iter()
+            .map(|tick_number| TickDiff::between(tick_number, tick_events(left, tick_number), tick_events(right, tick_number)))
+            .filter(|tick_diff| !tick_diff.is_empty())
+            .collect();
+
+        let left_statlines = CombatantStatline::from_game_log(left);
+        let right_statlines = CombatantStatline::from_game_log(right);
+        let combatant_ids = left_statlines
+            .iter()
+            .chain(&right_statlines)
+            .map(|statline| statline.combatant_id)
+            .collect::<BTreeSet<_>>();
+
+        let find_statline = |statlines: &[CombatantStatline], combatant_id| statlines
+            .iter()
+            .find(|statline| statline.combatant_id == combatant_id)
+            .cloned();
+
+        let statlines = combatant_ids
+            .into_iter()
+            .map(|combatant_id| StatlineDiff {
+                combatant_id,
+                left: find_statline(&left_statlines, combatant_id),
+                right: find_statline(&right_statlines, combatant_id),
+            })
+            .filter(|statline_diff| statline_diff.left != statline_diff.right)
+            .collect();
+
+        GameLogDiff {
+            left_score: (left.home_score(), left.away_score()),
+            right_score: (right.home_score(), right.away_score()),
+            ticks,
+            statlines,
+        }
+    }
+
+    /// The first tick on which the game logs differ, if any.
+    #[must_use]
+    pub fn first_divergence(&self) -> Option<GameTickNumber> {
+        self.ticks.first().map(|tick_diff| tick_diff.tick_number)
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.left_score == self.right_score && self.ticks.is_empty() && self.statlines.is_empty()
+    }
+}
+
+/// Summarizes the diff, detailing at most the first few divergent ticks (if the formatter's precision is set).
+impl Display for GameLogDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "game logs are identical");
+        }
+
+        let (left_home, left_away) = self.left_score;
+        let (right_home, right_away) = self.right_score;
+        writeln!(f, "score: H {left_home} - {left_away} A -> H {right_home} - {right_away} A")?;
+        match self.first_divergence() {
+            Some(first_divergence) => writeln!(f, "first divergence on tick {first_divergence}, {} divergent ticks", self.ticks.len())?,
+            None => writeln!(f, "no divergent ticks")?,
+        }
+
+        for tick_diff in self.ticks.iter().take(f.precision().unwrap_or(usize::MAX)) {
+            writeln!(f, "tick {}:", tick_diff.tick_number)?;
+            for position_delta in &tick_diff.position_deltas {
+                writeln!(
+                    f,
+                    "\tcombatant {} is {:.3} away ({:?} -> {:?})",
+                    position_delta.combatant_id,
+                    position_delta.distance(),
+                    position_delta.left_position,
+                    position_delta.right_position,
+                )?;
+            }
+
+            for removed_event in &tick_diff.removed_events {
+                writeln!(f, "\t- {removed_event:?}")?;
+            }
+
+            for added_event in &tick_diff.added_events {
+                writeln!(f, "\t+ {added_event:?}")?;
+            }
+        }
+
+        for statline_diff in &self.statlines {
+            writeln!(f, "combatant {} stats: {:?} -> {:?}", statline_diff.combatant_id, statline_diff.left, statline_diff.right)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use rapier3d::prelude::Vec3;
+    use dys_simulation::game::Game;
+    use dys_simulation::game_log::GameLog;
+    use dys_simulation::simulation::config::SimulationConfig;
+    use dys_simulation::simulation::simulation_event::SimulationEvent;
+    use dys_world::generator::Generator;
+    use crate::combatant_statline::CombatantStatline;
+    use super::{CombatantPositionDelta, GameLogDiff, StatlineDiff, TickDiff};
+
+    fn simulate_short_game(simulation_config: SimulationConfig) -> GameLog {
+        let world = Generator::new().generate_world(&mut StdRng::seed_from_u64(0));
+        let game = Game::new_with_testing_defaults(world.teams[&1].clone(), world.teams[&2].clone());
+
+        game.simulate_seeded_with_config(&[0; 32], simulation_config.with_seconds_per_period(3))
+    }
+
+    #[test]
+    fn test_ticks_diff_events_and_positions() {
+        let stunned = SimulationEvent::CombatantStunned { combatant_id: 3, start: true };
+        let left_events = [
+            SimulationEvent::CombatantPositionUpdate { combatant_id: 1, position: Vec3::ZERO },
+            SimulationEvent::CombatantPositionUpdate { combatant_id: 2, position: Vec3::ZERO },
+            stunned.clone(),
+        ];
+        let right_events = [
+            SimulationEvent::CombatantPositionUpdate { combatant_id: 2, position: Vec3::X },
+            SimulationEvent::CombatantPositionUpdate { combatant_id: 1, position: Vec3::ZERO },
+            SimulationEvent::CombatantPickedUpBall { combatant_id: 3, ball_id: 1 },
+        ];
+
+        assert_eq!(TickDiff::between(5, &left_events, &right_events), TickDiff {
+            tick_number: 5,
+            added_events: vec![SimulationEvent::CombatantPickedUpBall { combatant_id: 3, ball_id: 1 }],
+            removed_events: vec![stunned],
+            position_deltas: vec![CombatantPositionDelta { combatant_id: 2, left_position: Vec3::ZERO, right_position: Vec3::X }],
+        });
+
+        assert!(TickDiff::between(5, &left_events, &left_events).is_empty());
+    }
+
+    #[test]
+    fn test_game_logs_diverge_with_their_config() {
+        let game_log = simulate_short_game(SimulationConfig::default());
+        let diff = GameLogDiff::between(&game_log, &simulate_short_game(SimulationConfig::default()));
+        assert!(diff.is_empty());
+        assert_eq!(diff.first_divergence(), None);
+
+        // Without planning, combatants start out the same but soon act differently
+        let diff = GameLogDiff::between(&game_log, &simulate_short_game(SimulationConfig::default().with_planner_node_budget(0)));
+        assert!(diff.first_divergence().is_some_and(|tick_number| tick_number > 0));
+        // Combatants that don't plan never shove, throw or score, so only those who did with planning differ
+        let idle = |combatant_id| CombatantStatline { combatant_id, ..CombatantStatline::default() };
+        assert_eq!(diff.statlines, vec![
+            StatlineDiff { combatant_id: 0, left: Some(CombatantStatline { combatants_shoved: 1, ..idle(0) }), right: Some(idle(0)) },
+            StatlineDiff { combatant_id: 2, left: Some(CombatantStatline { points_scored: 7, ..idle(2) }), right: Some(idle(2)) },
+            StatlineDiff { combatant_id: 7, left: Some(CombatantStatline { points_scored: 1, ..idle(7) }), right: Some(idle(7)) },
+            StatlineDiff { combatant_id: 8, left: Some(CombatantStatline { balls_thrown: 1, ..idle(8) }), right: Some(idle(8)) },
+        ]);
+        assert!(format!("{diff:.1}").contains(&format!("first divergence on tick {}", diff.first_divergence().unwrap())));
+    }
+
+    #[test]
+    fn test_game_logs_only_display_as_identical_without_differences() {
+        let diff = GameLogDiff { left_score: (1, 2), right_score: (1, 2), ticks: vec![], statlines: vec![] };
+        assert_eq!(diff.to_string(), "game logs are identical\n");
+
+        // Statlines can differ without any tick differing, eg if the logs only keep some of their ticks
+        let diff = GameLogDiff {
+            statlines: vec![StatlineDiff { combatant_id: 1, left: None, right: Some(CombatantStatline::default()) }],
+            ..diff
+        };
+        assert!(!diff.is_empty());
+        assert!(!diff.to_string().contains("identical"));
+        assert!(diff.to_string().contains("no divergent ticks"));
+    }
+}
@@ -1,7 +1,7 @@
 use dys_simulation::simulation::simulation_event::SimulationEvent;
 use dys_world::combatant::instance::CombatantInstanceId;
 
-type ValueFnT<ValueT> = fn(CombatantInstanceId, &Vec<SimulationEvent>) -> Option<ValueT>;
+type ValueFnT<ValueT> = fn(CombatantInstanceId, &[SimulationEvent]) -> Option<ValueT>;
 
 pub trait GameStat {
     type ValueT;
@@ -9,7 +9,7 @@ pub trait GameStat {
     fn name(&self) -> impl Into<String>;
     fn value_fn(&self) -> ValueFnT<Self::ValueT>;
 
-    fn calculate(&self, combatant_id: CombatantInstanceId, events: &Vec<SimulationEvent>) -> Option<Self::ValueT> {
+    fn calculate(&self, combatant_id: CombatantInstanceId, events: &[SimulationEvent]) -> Option<Self::ValueT> {
         self.value_fn()(combatant_id, events)
     }
 }
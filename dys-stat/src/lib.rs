@@ -1,2 +1,3 @@
 pub mod combatant_statline;
+pub mod game_log_diff;
 pub mod game_stat;
\ No newline at end of file
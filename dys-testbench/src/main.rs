@@ -1,8 +1,6 @@
-use std::sync::{Arc, Mutex};
 use dys_observability::logger::LoggerOptions;
 use dys_simulation::{game::Game, game_log::GameLog};
 use dys_world::{
-    arena::Arena,
     generator::Generator,
     schedule::{calendar::{Date, Month}},
 };
@@ -39,15 +37,16 @@ fn main() {
     let mut teams = world.teams.values();
     let away_team = teams.next().expect("failed to get away team from generated world").to_owned();
     let home_team = teams.next().expect("failed to get home team from generated world").to_owned();
-    let _arena = Arc::new(Mutex::new(Arena::new_with_testing_defaults()));
+    let arena_id = home_team.lock().unwrap().home_arena_id;
+    let arena = world.arenas[&arena_id].clone();
     let date = Date::new(Month::Arguscorp, 1, 10000);
 
     let game_instance = GameInstance {
         game_id: 0,
         away_team,
         home_team,
-        // arena,
-        arena_id: 0,
+        arena,
+        arena_id,
         date,
     };
     let game = Game { game_instance };
@@ -1,15 +1,16 @@
 use std::collections::btree_map::Entry;
 use std::collections::BTreeMap;
-use std::sync::{Arc, Mutex};
 use eframe::Frame;
 use egui::Ui;
 use rand::SeedableRng;
 use rand_pcg::Pcg64;
 use dys_simulation::game::Game;
+use dys_simulation::game_runner::GameRunner;
 use dys_simulation::game_objects::combatant::CombatantState;
 use dys_simulation::game_state::GameState;
+use dys_simulation::planning::PlannerTrace;
+use dys_simulation::simulation::config::SimulationConfig;
 use dys_simulation::game_tick::{GameTick, GameTickNumber};
-use dys_simulation::simulation::simulate_tick;
 use dys_world::combatant::instance::CombatantInstanceId;
 use dys_world::schedule::calendar::Date;
 use dys_world::schedule::calendar::Month::Arguscorp;
@@ -17,7 +18,7 @@ use dys_world::games::instance::GameInstance;
 use dys_world::generator::Generator;
 
 struct GamePeekApp {
-    game_state: Arc<Mutex<GameState>>,
+    game_runner: GameRunner,
     simmed_ticks: Vec<GameTick>,
     combatant_states_by_tick: BTreeMap<GameTickNumber, BTreeMap<CombatantInstanceId, CombatantState>>,
     combatant_filter: Option<CombatantInstanceId>,
@@ -25,11 +26,14 @@ struct GamePeekApp {
 
 impl GamePeekApp {
     fn tick(&mut self) {
-        let tick = simulate_tick(self.game_state.clone());
+        let Some(tick) = self.game_runner.next_tick() else {
+            return;
+        };
+
         let tick_number = tick.tick_number;
         self.simmed_ticks.push(tick);
 
-        let game_state = self.game_state.lock().unwrap();
+        let game_state = self.game_runner.game_state().lock().unwrap();
         for (combatant_id, combatant_object) in &game_state.combatants {
             let combatant_state_clone = combatant_object.combatant_state.lock().unwrap().to_owned();
             match self.combatant_states_by_tick.entry(tick_number) {
@@ -46,6 +50,41 @@ impl GamePeekApp {
     }
 }
 
+fn show_planner_trace(ui: &mut Ui, planner_trace: &PlannerTrace) {
+    match &planner_trace.chosen_plan {
+        Some(chosen_plan) => {
+            ui.label(format!(
+                "Chose {} for goal {} (cost {}, prioritized cost {})",
+                chosen_plan.actions.join(" -> "),
+                chosen_plan.goal_name,
+                chosen_plan.cost,
+                chosen_plan.prioritized_cost,
+            ));
+        },
+        None => {
+            ui.label("Chose no plan");
+        },
+    }
+
+    ui.label(format!(
+        "Expanded {} nodes{}",
+        planner_trace.nodes_expanded,
+        if planner_trace.node_budget_exhausted { " (budget exhausted)" } else { "" }
+    ));
+
+    for goal_trace in &planner_trace.goals {
+        ui.label(format!("Goal {} (priority {}): {}", goal_trace.goal_name, goal_trace.priority, goal_trace.outcome));
+        for candidate_plan in &goal_trace.candidate_plans {
+            let verdict = match &candidate_plan.rejection_reason {
+                Some(rejection_reason) => format!("rejected: {rejection_reason}"),
+                None => String::from("accepted"),
+            };
+
+            ui.label(format!("    [{}] {} - {verdict}", candidate_plan.cost, candidate_plan.actions.join(" -> ")));
+        }
+    }
+}
+
 impl eframe::App for GamePeekApp {
     fn ui(&mut self, ui: &mut Ui, _frame: &mut Frame) {
         egui::CentralPanel::default().show(ui, |ui| {
@@ -54,7 +93,7 @@ impl eframe::App for GamePeekApp {
                 .show_ui(ui, |ui| {
                     ui.selectable_value(&mut self.combatant_filter, None, "(none)");
 
-                    let game_state = self.game_state.lock().unwrap();
+                    let game_state = self.game_runner.game_state().lock().unwrap();
                     for (combatant_id, _) in &game_state.combatants {
                         ui.selectable_value(
                             &mut self.combatant_filter,
@@ -65,16 +104,7 @@ impl eframe::App for GamePeekApp {
                 });
 
             if ui.button("Simulate All").clicked() {
-                let total_tick_count = {
-                    let game_state = self.game_state.lock().unwrap();
-                    game_state.simulation_config.ticks_per_game()
-                };
-
-                let Ok(manually_ticked_count) = u32::try_from(self.simmed_ticks.len()) else {
-                    panic!("shocking large amount of ticks in simulation (> u32::MAX)");
-                };
-
-                for _ in manually_ticked_count..total_tick_count {
+                while !self.game_runner.is_finished() {
                     self.tick();
                 }
             }
@@ -95,8 +125,11 @@ impl eframe::App for GamePeekApp {
                     ui.collapsing(format!("Tick {}", tick.tick_number), |ui| {
                         make_collapseable("Simulation Events".to_string(), tick.tick_number).show(ui, |ui| {
                             if self.combatant_filter.is_none() {
-                                for evt in &tick.simulation_events {
-                                    ui.label(format!("{evt:?}"));
+                                for (event_index, evt) in tick.simulation_events.iter().enumerate() {
+                                    match tick.cause_of(event_index) {
+                                        Some(cause) => ui.label(format!("{evt:?} (caused by {})", cause.variant_name())),
+                                        None => ui.label(format!("{evt:?}")),
+                                    };
                                 }
                             } else {
                                 // ZJ-TODO: fix this
@@ -112,6 +145,15 @@ impl eframe::App for GamePeekApp {
                             }
                         });
 
+                        make_collapseable("Rejected Events".to_string(), tick.tick_number).show(ui, |ui| {
+                            for rejected in &tick.rejected_events {
+                                let rejected_str = format!("{:?}: {:?}", rejected.reason, rejected.event);
+                                if self.combatant_filter.is_none_or(|combatant_id| rejected_str.contains(&format!("combatant_id: {combatant_id}"))) {
+                                    ui.label(rejected_str);
+                                }
+                            }
+                        });
+
                         make_collapseable("Combatants".to_string(), tick.tick_number).show(ui, |ui| {
                             let states = if self.combatant_filter.is_some() {
                                 let combatant_id = self.combatant_filter.unwrap();
@@ -151,6 +193,11 @@ impl eframe::App for GamePeekApp {
                                         for action in combatant_state.plan.iter().rev() {
                                             ui.label(format!("Planned Action: {}", action.name()));
                                         }
+                                        if let Some(planner_trace) = &combatant_state.planner_trace {
+                                            make_collapseable("Planner Trace".to_string(), tick.tick_number).show(ui, |ui| {
+                                                show_planner_trace(ui, planner_trace);
+                                            });
+                                        }
                                         make_collapseable("Beliefs".to_string(), tick.tick_number).show(ui, |ui| {
                                             let sourced_beliefs = combatant_state.beliefs.sourced_beliefs().to_owned();
                                             let sorted_map = BTreeMap::from_iter(sourced_beliefs.iter());
@@ -185,20 +232,25 @@ fn main() -> eframe::Result {
     let generator = Generator::new();
     let world = generator.generate_world(&mut Pcg64::from_seed(seed.to_owned()));
 
-    let app = GamePeekApp {
-        game_state: Arc::new(Mutex::new(GameState::from_game_seeded(
-            Game {
-                game_instance: GameInstance {
-                    game_id: 0,
-                    away_team: world.teams[&0].clone(),
-                    home_team: world.teams[&1].clone(),
-                    // arena: Arc::new(Mutex::new(Arena::new_with_testing_defaults())),
-                    arena_id: 0,
-                    date: Date::new(Arguscorp, 1, 1000),
-                },
+    let home_team = world.teams[&2].clone();
+    let arena_id = home_team.lock().unwrap().home_arena_id;
+    let mut game_state = GameState::from_game_seeded(
+        Game {
+            game_instance: GameInstance {
+                game_id: 0,
+                away_team: world.teams[&1].clone(),
+                home_team,
+                arena: world.arenas[&arena_id].clone(),
+                arena_id,
+                date: Date::new(Arguscorp, 1, 1000),
             },
-            &seed
-        ))),
+        },
+        &seed
+    );
+    game_state.simulation_config = SimulationConfig::default().with_planner_traces(true).with_rejected_events(true);
+
+    let app = GamePeekApp {
+        game_runner: GameRunner::new(game_state),
         simmed_ticks: vec![],
         combatant_states_by_tick: BTreeMap::new(),
         combatant_filter: None,
@@ -0,0 +1,56 @@
+//! Compares two game logs written by `dys-testbench`, eg from before and after a change to the simulation.
+//!
+//! ```sh
+//! cargo run -p dys-tool-logdiff -- before/game_log.bin after/game_log.bin [--max-ticks N]
+//! ```
+//!
+//! Exits with status 1 if the game logs differ.
+
+use std::process::ExitCode;
+use dys_simulation::game_log::GameLog;
+use dys_stat::game_log_diff::GameLogDiff;
+
+const USAGE: &str = "usage: dys-tool-logdiff <left game log> <right game log> [--max-ticks N]";
+
+fn read_game_log(path: &str) -> Result<GameLog, String> {
+    let bytes = std::fs::read(path).map_err(|err| format!("failed to read {path}: {err}"))?;
+    postcard::from_bytes(&bytes).map_err(|err| format!("failed to deserialize game log {path}: {err}"))
+}
+
+fn run(args: &[String]) -> Result<bool, String> {
+    let mut paths = vec![];
+    let mut max_ticks = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--max-ticks" {
+            let ticks = args.next().ok_or(USAGE)?;
+            max_ticks = Some(ticks.parse::<usize>().map_err(|_| format!("--max-ticks must be a number, not {ticks}"))?);
+        } else {
+            paths.push(arg.as_str());
+        }
+    }
+
+    let [left_path, right_path] = paths[..] else {
+        return Err(USAGE.to_string());
+    };
+
+    let diff = GameLogDiff::between(&read_game_log(left_path)?, &read_game_log(right_path)?);
+    match max_ticks {
+        Some(max_ticks) => print!("{diff:.max_ticks$}"),
+        None => print!("{diff}"),
+    }
+
+    Ok(diff.is_empty())
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::from(2)
+        },
+    }
+}
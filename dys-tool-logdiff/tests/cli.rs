@@ -0,0 +1,58 @@
+//! Runs the logdiff tool on game logs written the same way `dys-testbench` writes them.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use dys_simulation::game::Game;
+use dys_simulation::simulation::config::SimulationConfig;
+use dys_world::generator::Generator;
+
+fn write_short_game_log(file_name: &str, simulation_config: SimulationConfig) -> PathBuf {
+    let world = Generator::new().generate_world(&mut StdRng::seed_from_u64(0));
+    let game = Game::new_with_testing_defaults(world.teams[&1].clone(), world.teams[&2].clone());
+
+    let game_log = game.simulate_seeded_with_config(&[0; 32], simulation_config.with_seconds_per_period(3));
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(file_name);
+    std::fs::write(&path, postcard::to_allocvec(&game_log).unwrap()).unwrap();
+    path
+}
+
+fn logdiff(args: &[&Path]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_dys-tool-logdiff"))
+        .args(args)
+        .output()
+        .expect("failed to run dys-tool-logdiff")
+}
+
+#[test]
+fn test_logdiff_exits_with_whether_game_logs_differ() {
+    let game_log = write_short_game_log("logdiff_left.bin", SimulationConfig::default());
+    let same_game_log = write_short_game_log("logdiff_same.bin", SimulationConfig::default());
+    let unplanned_game_log = write_short_game_log("logdiff_unplanned.bin", SimulationConfig::default().with_planner_node_budget(0));
+
+    let output = logdiff(&[&game_log, &same_game_log]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "game logs are identical\n");
+
+    let output = logdiff(&[&game_log, &unplanned_game_log, Path::new("--max-ticks"), Path::new("1")]);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("first divergence on tick"), "unexpected output: {stdout}");
+    assert_eq!(stdout.lines().filter(|line| line.starts_with("tick ")).count(), 1);
+}
+
+#[test]
+fn test_logdiff_rejects_bad_arguments() {
+    let output = logdiff(&[Path::new("only_one_game_log.bin")]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("usage:"));
+
+    let output = logdiff(&[Path::new("missing_left.bin"), Path::new("missing_right.bin")]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("failed to read missing_left.bin"));
+
+    let output = logdiff(&[Path::new("left.bin"), Path::new("right.bin"), Path::new("--max-ticks"), Path::new("many")]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--max-ticks must be a number"));
+}
@@ -1,18 +1,52 @@
 use std::sync::{Arc, Mutex};
 
 use criterion::{criterion_group, criterion_main, Criterion};
-use dys_world::arena::{navmesh::{ArenaNavmesh, ArenaNavmeshConfig}, Arena};
+use dys_world::arena::{feature::NavmeshAreaType, navmesh::{ArenaNavmesh, ArenaNavmeshConfig, NavmeshCostProfile, NavmeshPathCache}, Arena};
+use rapier3d::glamx::vec3;
 
 fn navmesh_simulation_benchmark(c: &mut Criterion) {    
     let arena = Arc::new(Mutex::new(Arena::new_with_testing_defaults()));
     let config = ArenaNavmeshConfig {
         unit_resolution: 1.0,
+        ..ArenaNavmeshConfig::default()
     };
     
     c.bench_function("navmesh_generation", |b| b.iter(|| {
         ArenaNavmesh::new_from(arena.clone(), config.clone());
     }));
+
+    let navmesh = ArenaNavmesh::new_from(arena.clone(), config.clone());
+    let baked_navmesh = serde_json::to_vec(&navmesh).expect("failed to bake navmesh");
+
+    c.bench_function("navmesh_load_baked", |b| b.iter(|| {
+        serde_json::from_slice::<ArenaNavmesh>(&baked_navmesh).expect("failed to load baked navmesh");
+    }));
+}
+
+fn navmesh_path_query_benchmark(c: &mut Criterion) {
+    let arena = Arc::new(Mutex::new(Arena::new_with_testing_defaults()));
+    let navmesh = ArenaNavmesh::new_from(arena, ArenaNavmeshConfig::default());
+
+    // Corner to corner, around the plate walls
+    let from = vec3(15.0, 0.0, 10.0);
+    let to = vec3(85.0, 0.0, 90.0);
+    let cost_profile = NavmeshCostProfile::default()
+        .with_area(NavmeshAreaType::Danger, vec3(50.0, 0.0, 50.0), 5.0)
+        .with_area(NavmeshAreaType::Obstacle, vec3(30.0, 0.0, 30.0), 1.0);
+
+    c.bench_function("navmesh_path_query", |b| b.iter(|| {
+        navmesh.create_path(from, to)
+    }));
+
+    c.bench_function("navmesh_path_query_with_profile", |b| b.iter(|| {
+        navmesh.create_path_with_profile(from, to, &cost_profile)
+    }));
+
+    let mut path_cache = NavmeshPathCache::new();
+    c.bench_function("navmesh_path_query_cached", |b| b.iter(|| {
+        navmesh.create_path_with_cache(from, to, &cost_profile, &mut path_cache)
+    }));
 }
 
-criterion_group!(benches, navmesh_simulation_benchmark);
-criterion_main!(benches);
\ No newline at end of file
+criterion_group!(benches, navmesh_simulation_benchmark, navmesh_path_query_benchmark);
+criterion_main!(benches);
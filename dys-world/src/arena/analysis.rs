@@ -0,0 +1,429 @@
+use rapier3d::glamx::vec3;
+use rapier3d::parry::query;
+use rapier3d::prelude::*;
+
+use super::ball_spawn::ArenaBallSpawn;
+use super::combatant_start::ArenaCombatantStart;
+use super::feature::{ArenaFeature, NavmeshPathingType};
+use super::navmesh::ArenaNavmesh;
+use super::plate::{ArenaPlate, PlateId};
+use super::Arena;
+
+/// How far apart the home and away teams' distances to their nth closest landmark of a kind may be,
+/// as a fraction of the longer distance, before the arena is considered to favor one team.
+const MAX_DISTANCE_ASYMMETRY: f32 = 0.1;
+
+/// How far above a point feature (eg a combatant start) must be clear of solid geometry.
+const POINT_FEATURE_CLEARANCE: f32 = 0.1;
+
+/// Something in the arena that combatants need to get to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArenaLandmark {
+    Plate(PlateId),
+    BallSpawn { origin: Vec3 },
+}
+
+/// A problem that makes an arena unplayable or unfair.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArenaIssue {
+    /// A team has no combatant starts at all.
+    MissingCombatantStarts { is_home_team: bool },
+
+    /// The teams have different numbers of combatant starts.
+    UnevenCombatantStarts { home_starts: usize, away_starts: usize },
+
+    /// A landmark can't be pathed to from a combatant start.
+    UnreachableLandmark { landmark: ArenaLandmark, start: Vec3 },
+
+    /// One team's nth closest landmark of a kind (eg their closest plate) is much closer than the other team's.
+    /// Landmarks don't need to be equally far from both teams; in a mirrored arena, each team has its own mirror image.
+    AsymmetricLandmarks { home_landmark: ArenaLandmark, away_landmark: ArenaLandmark, home_distance: f32, away_distance: f32 },
+
+    /// Two features occupy the same space in a way that's likely a mistake, such as a start inside a wall.
+    /// Features are identified by their index in [`Arena::all_features`].
+    OverlappingFeatures { first_feature: usize, second_feature: usize },
+}
+
+impl ArenaIssue {
+    /// Does this issue stop games from being played in the arena, rather than only making them unfair?
+    #[must_use]
+    pub fn is_unplayable(&self) -> bool {
+        matches!(self, ArenaIssue::MissingCombatantStarts { .. } | ArenaIssue::UnreachableLandmark { .. })
+    }
+}
+
+/// How far each team has to travel to a landmark, averaged over the team's combatant starts that can reach it.
+/// Distances are `None` if the landmark can't be reached from any of the team's starts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LandmarkDistances {
+    pub landmark: ArenaLandmark,
+    pub home_distance: Option<f32>,
+    pub away_distance: Option<f32>,
+
+    /// How many of each team's starts can't reach the landmark at all, and so aren't part of the team's distance.
+    pub home_unreachable_starts: u16,
+    pub away_unreachable_starts: u16,
+}
+
+/// 1.0 if both teams are equally far from their landmarks, approaching 0.0 as one team is favored.
+/// Landmarks a team can't reach are as unfair as can be.
+fn distance_fairness(home_distance: Option<f32>, away_distance: Option<f32>) -> f32 {
+    match (home_distance, away_distance) {
+        (Some(home_distance), Some(away_distance)) => {
+            let longer_distance = home_distance.max(away_distance);
+            if longer_distance <= f32::EPSILON {
+                1.0
+            } else {
+                home_distance.min(away_distance) / longer_distance
+            }
+        },
+        _ => 0.0,
+    }
+}
+
+/// The fraction of a team's starts that can reach a landmark.
+#[allow(clippy::cast_precision_loss)]
+fn reachable_fraction(unreachable_starts: u16, team_starts: usize) -> f32 {
+    if team_starts == 0 {
+        return 0.0;
+    }
+
+    1.0 - f32::from(unreachable_starts) / team_starts as f32
+}
+
+/// Pairs each team's nth closest landmark with the other team's nth closest landmark,
+/// so that arenas mirrored across the center line compare each landmark with its mirror image.
+fn ranked_landmark_matchups(landmark_distances: &[LandmarkDistances]) -> Vec<(&LandmarkDistances, &LandmarkDistances)> {
+    let ranked = |team_distance: fn(&LandmarkDistances) -> Option<f32>| {
+        let mut ranked = landmark_distances.iter().collect::<Vec<_>>();
+        ranked.sort_by(|a, b| {
+            let a = team_distance(a).unwrap_or(f32::INFINITY);
+            let b = team_distance(b).unwrap_or(f32::INFINITY);
+            a.total_cmp(&b)
+        });
+        ranked
+    };
+
+    ranked(|distances| distances.home_distance)
+        .into_iter()
+        .zip(ranked(|distances| distances.away_distance))
+        .collect()
+}
+
+/// Whether an arena is playable, and how fair it is to both teams.
+#[derive(Clone, Debug)]
+pub struct ArenaAnalysis {
+    pub issues: Vec<ArenaIssue>,
+    pub plate_distances: Vec<LandmarkDistances>,
+    pub ball_spawn_distances: Vec<LandmarkDistances>,
+
+    /// 1.0 for a perfectly fair arena, approaching 0.0 as the arena favors one team.
+    pub fairness: f32,
+}
+
+impl ArenaAnalysis {
+    #[must_use]
+    pub fn analyze(arena: &Arena, navmesh: &ArenaNavmesh) -> ArenaAnalysis {
+        let mut issues = find_overlapping_features(arena, navmesh.config().agent_radius);
+
+        let starts = arena.features::<ArenaCombatantStart>();
+        let home_starts = starts.iter().filter(|start| start.is_home_team).map(|start| start.origin).collect::<Vec<_>>();
+        let away_starts = starts.iter().filter(|start| !start.is_home_team).map(|start| start.origin).collect::<Vec<_>>();
+
+        for (team_starts, is_home_team) in [(&home_starts, true), (&away_starts, false)] {
+            if team_starts.is_empty() {
+                issues.push(ArenaIssue::MissingCombatantStarts { is_home_team });
+            }
+        }
+
+        if home_starts.len() != away_starts.len() {
+            issues.push(ArenaIssue::UnevenCombatantStarts { home_starts: home_starts.len(), away_starts: away_starts.len() });
+        }
+
+        let plates = arena
+            .features::<ArenaPlate>()
+            .into_iter()
+            .map(|plate| (ArenaLandmark::Plate(plate.id), plate.origin))
+            .collect::<Vec<_>>();
+
+        let ball_spawns = arena
+            .features::<ArenaBallSpawn>()
+            .into_iter()
+            .map(|ball_spawn| (ArenaLandmark::BallSpawn { origin: ball_spawn.origin }, ball_spawn.origin))
+            .collect::<Vec<_>>();
+
+        let plate_distances = landmark_distances(navmesh, &plates, &home_starts, &away_starts, &mut issues);
+        let ball_spawn_distances = landmark_distances(navmesh, &ball_spawns, &home_starts, &away_starts, &mut issues);
+
+        let mut matchup_fairness = vec![];
+        for (home_landmark, away_landmark) in ranked_landmark_matchups(&plate_distances)
+            .into_iter()
+            .chain(ranked_landmark_matchups(&ball_spawn_distances)) {
+            let fairness = distance_fairness(home_landmark.home_distance, away_landmark.away_distance);
+
+            // A landmark only some of a team's starts can reach favors the other team, however close it is to the rest
+            let reachability = reachable_fraction(home_landmark.home_unreachable_starts, home_starts.len())
+                .min(reachable_fraction(away_landmark.away_unreachable_starts, away_starts.len()));
+            matchup_fairness.push(fairness * reachability);
+
+            if let (Some(home_distance), Some(away_distance)) = (home_landmark.home_distance, away_landmark.away_distance)
+                && fairness < 1.0 - MAX_DISTANCE_ASYMMETRY {
+                issues.push(ArenaIssue::AsymmetricLandmarks {
+                    home_landmark: home_landmark.landmark.clone(),
+                    away_landmark: away_landmark.landmark.clone(),
+                    home_distance,
+                    away_distance,
+                });
+            }
+        }
+
+        let fairness = arena_fairness(&matchup_fairness, home_starts.len(), away_starts.len());
+
+        ArenaAnalysis {
+            issues,
+            plate_distances,
+            ball_spawn_distances,
+            fairness,
+        }
+    }
+
+    /// Can games be played in this arena? Arenas may be playable, but still unfair.
+    #[must_use]
+    pub fn is_playable(&self) -> bool {
+        !self.issues.iter().any(ArenaIssue::is_unplayable)
+    }
+}
+
+/// How far each team is from each landmark, noting any landmarks that can't be reached from a start.
+fn landmark_distances(
+    navmesh: &ArenaNavmesh,
+    landmarks: &[(ArenaLandmark, Vec3)],
+    home_starts: &[Vec3],
+    away_starts: &[Vec3],
+    issues: &mut Vec<ArenaIssue>,
+) -> Vec<LandmarkDistances> {
+    let mut landmark_distances = vec![];
+    for (landmark, landmark_position) in landmarks {
+        let mut team_distance = |team_starts: &[Vec3]| {
+            let mut total_distance = 0.0;
+            let mut reachable_starts = 0_u16;
+            let mut unreachable_starts = 0_u16;
+
+            for start in team_starts {
+                let path = navmesh.create_path(*start, *landmark_position).filter(|path| !path.is_empty());
+                let Some(path) = path else {
+                    issues.push(ArenaIssue::UnreachableLandmark { landmark: landmark.clone(), start: *start });
+                    unreachable_starts += 1;
+                    continue;
+                };
+
+                total_distance += path.length();
+                reachable_starts += 1;
+            }
+
+            ((reachable_starts > 0).then(|| total_distance / f32::from(reachable_starts)), unreachable_starts)
+        };
+
+        let (home_distance, home_unreachable_starts) = team_distance(home_starts);
+        let (away_distance, away_unreachable_starts) = team_distance(away_starts);
+        landmark_distances.push(LandmarkDistances {
+            landmark: landmark.clone(),
+            home_distance,
+            away_distance,
+            home_unreachable_starts,
+            away_unreachable_starts,
+        });
+    }
+
+    landmark_distances
+}
+
+/// Fairness of each landmark matchup, scaled down by how uneven the teams' numbers of starts are.
+#[allow(clippy::cast_precision_loss)]
+fn arena_fairness(matchup_fairness: &[f32], home_starts: usize, away_starts: usize) -> f32 {
+    let most_starts = home_starts.max(away_starts);
+    if most_starts == 0 {
+        return 0.0;
+    }
+
+    let start_fairness = home_starts.min(away_starts) as f32 / most_starts as f32;
+    if matchup_fairness.is_empty() {
+        return start_fairness;
+    }
+
+    let landmark_fairness = matchup_fairness.iter().sum::<f32>() / matchup_fairness.len() as f32;
+
+    start_fairness * landmark_fairness
+}
+
+/// Finds features that likely weren't meant to overlap:
+/// - combatant starts and ball spawns buried in solid geometry, or too close together for combatants to use both
+/// - plates overlapping each other, or walls
+///
+/// Solid features overlapping each other (eg walls meeting the floor) are how arenas are built, so aren't flagged.
+fn find_overlapping_features(arena: &Arena, agent_radius: f32) -> Vec<ArenaIssue> {
+    let features = arena.all_features();
+    let is_point_feature = |feature: &dyn ArenaFeature| {
+        feature.as_any().is::<ArenaCombatantStart>() || feature.as_any().is::<ArenaBallSpawn>()
+    };
+    let is_plate = |feature: &dyn ArenaFeature| feature.as_any().is::<ArenaPlate>();
+
+    let shapes_intersect = |first: &dyn ArenaFeature, second: &dyn ArenaFeature| {
+        let (Some(first_shape), Some(second_shape)) = (first.shape(), second.shape()) else {
+            return false;
+        };
+
+        query::intersection_test(&first.pose(), first_shape.as_ref(), &second.pose(), second_shape.as_ref()).unwrap_or(false)
+    };
+
+    let mut issues = vec![];
+    for (first_index, first) in features.iter().enumerate() {
+        for (second_index, second) in features.iter().enumerate().skip(first_index + 1) {
+            let overlaps = match (first, second) {
+                (first, second) if is_point_feature(*first) && is_point_feature(*second) => {
+                    (*first.origin() - *second.origin()).length() < agent_radius * 2.0
+                },
+                (point, solid) | (solid, point) if is_point_feature(*point) => {
+                    let clearance_point = *point.origin() + vec3(0.0, POINT_FEATURE_CLEARANCE, 0.0);
+                    solid.pathing_type() != NavmeshPathingType::Skip
+                        && solid.shape().is_some_and(|shape| shape.contains_point(&solid.pose(), clearance_point))
+                },
+                (first, second) if is_plate(*first) && is_plate(*second) => shapes_intersect(*first, *second),
+                (plate, wall) | (wall, plate) if is_plate(*plate) && wall.pathing_type() == NavmeshPathingType::Block => {
+                    shapes_intersect(*plate, *wall)
+                },
+                _ => false,
+            };
+
+            if overlaps {
+                issues.push(ArenaIssue::OverlappingFeatures { first_feature: first_index, second_feature: second_index });
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use rapier3d::na::Quaternion;
+    use rapier3d::glamx::vec3;
+    use rapier3d::prelude::SharedShape;
+
+    use crate::arena::Arena;
+    use crate::arena::analysis::{ArenaAnalysis, ArenaIssue, ArenaLandmark};
+    use crate::arena::barrier::{ArenaBarrier, BarrierPathing};
+    use crate::arena::combatant_start::ArenaCombatantStart;
+    use crate::arena::feature::ArenaFeature;
+    use crate::arena::navmesh::{ArenaNavmesh, ArenaNavmeshConfig};
+    use crate::arena::plate::ArenaPlate;
+
+    fn analyze(arena: Arena) -> ArenaAnalysis {
+        let arena = Arc::new(Mutex::new(arena));
+        let navmesh = ArenaNavmesh::new_from(arena.clone(), ArenaNavmeshConfig::default());
+
+        ArenaAnalysis::analyze(&arena.lock().unwrap(), &navmesh)
+    }
+
+    fn testing_arena_with(features: Vec<Box<dyn ArenaFeature>>) -> Arena {
+        let mut arena = Arena::new_with_testing_defaults();
+        arena.all_features.extend(features);
+        arena
+    }
+
+    #[test]
+    fn test_testing_arena_is_playable_and_fair() {
+        let analysis = analyze(Arena::new_with_testing_defaults());
+
+        assert!(analysis.is_playable());
+        assert!(analysis.issues.is_empty(), "unexpected issues: {:?}", analysis.issues);
+        assert!(analysis.fairness > 0.95);
+    }
+
+    #[test]
+    fn test_walled_off_plate_is_unreachable() {
+        // Wall in the plate on its open sides, between the existing north and south plate walls
+        let analysis = analyze(testing_arena_with(vec![
+            Box::new(ArenaBarrier::new(vec3(33.0, 0.0, 50.0), vec3(2.0, 10.0, 45.0), Quaternion::identity(), BarrierPathing::Disabled)),
+            Box::new(ArenaBarrier::new(vec3(67.0, 0.0, 50.0), vec3(2.0, 10.0, 45.0), Quaternion::identity(), BarrierPathing::Disabled)),
+        ]));
+
+        assert!(!analysis.is_playable());
+        assert!(analysis.issues.iter().any(|issue| matches!(
+            issue,
+            ArenaIssue::UnreachableLandmark { landmark: ArenaLandmark::Plate(1), .. }
+        )));
+
+        let plate_distances = analysis.plate_distances
+            .iter()
+            .find(|distances| distances.landmark == ArenaLandmark::Plate(1))
+            .unwrap();
+        assert!(plate_distances.home_distance.is_none() && plate_distances.away_distance.is_none());
+        assert_eq!((plate_distances.home_unreachable_starts, plate_distances.away_unreachable_starts), (5, 5));
+        assert!(analysis.fairness < 0.95);
+    }
+
+    #[test]
+    fn test_starts_cut_off_from_landmarks_are_counted_separately() {
+        // Box in the northernmost home start
+        let analysis = analyze(testing_arena_with(vec![
+            Box::new(ArenaBarrier::new(vec3(12.0, 0.0, 80.0), vec3(1.0, 10.0, 7.0), Quaternion::identity(), BarrierPathing::Disabled)),
+            Box::new(ArenaBarrier::new(vec3(18.0, 0.0, 80.0), vec3(1.0, 10.0, 7.0), Quaternion::identity(), BarrierPathing::Disabled)),
+            Box::new(ArenaBarrier::new(vec3(15.0, 0.0, 77.0), vec3(7.0, 10.0, 1.0), Quaternion::identity(), BarrierPathing::Disabled)),
+            Box::new(ArenaBarrier::new(vec3(15.0, 0.0, 83.0), vec3(7.0, 10.0, 1.0), Quaternion::identity(), BarrierPathing::Disabled)),
+        ]));
+
+        let plate_distances = analysis.plate_distances
+            .iter()
+            .find(|distances| distances.landmark == ArenaLandmark::Plate(1))
+            .unwrap();
+        assert_eq!((plate_distances.home_unreachable_starts, plate_distances.away_unreachable_starts), (1, 0));
+        assert!(plate_distances.home_distance.is_some());
+
+        // The other home starts being as close as ever doesn't make up for one that can't play at all
+        assert!(analysis.fairness < 0.85);
+    }
+
+    #[test]
+    fn test_plates_closer_to_one_team_are_unfair() {
+        let analysis = analyze(testing_arena_with(vec![
+            Box::new(ArenaPlate::new(2, vec3(25.0, 0.0, 50.0), SharedShape::cylinder(5.0, 3.0), Quaternion::identity())),
+        ]));
+
+        assert!(analysis.is_playable());
+        assert!(analysis.issues.iter().any(|issue| matches!(
+            issue,
+            ArenaIssue::AsymmetricLandmarks { home_landmark: ArenaLandmark::Plate(2), home_distance, away_distance, .. } if home_distance < away_distance
+        )));
+        assert!(analysis.fairness < 0.95);
+    }
+
+    #[test]
+    fn test_mirrored_plates_are_fair() {
+        let analysis = analyze(testing_arena_with(vec![
+            Box::new(ArenaPlate::new(2, vec3(25.0, 0.0, 50.0), SharedShape::cylinder(5.0, 3.0), Quaternion::identity())),
+            Box::new(ArenaPlate::new(3, vec3(75.0, 0.0, 50.0), SharedShape::cylinder(5.0, 3.0), Quaternion::identity())),
+        ]));
+
+        assert!(analysis.issues.is_empty(), "unexpected issues: {:?}", analysis.issues);
+        assert!(analysis.fairness > 0.95);
+    }
+
+    #[test]
+    fn test_starts_inside_walls_overlap() {
+        let arena = testing_arena_with(vec![
+            Box::new(ArenaCombatantStart { origin: vec3(50.0, 0.0, 30.0), rotation: Quaternion::identity(), is_home_team: true }),
+        ]);
+        let start_index = arena.all_features().len() - 1;
+
+        let analysis = analyze(arena);
+
+        assert!(analysis.issues.iter().any(|issue| matches!(
+            issue,
+            ArenaIssue::OverlappingFeatures { second_feature, .. } if *second_feature == start_index
+        )));
+        assert!(analysis.issues.contains(&ArenaIssue::UnevenCombatantStarts { home_starts: 6, away_starts: 5 }));
+    }
+}
@@ -1,8 +1,10 @@
 use rapier3d::na::Quaternion;
 use rapier3d::prelude::Vec3;
+use serde::{Deserialize, Serialize};
 use super::feature::{ArenaFeature, NavmeshPathingType};
 
 /// Location where a ball can be spawned
+#[derive(Serialize, Deserialize)]
 pub struct ArenaBallSpawn {    
     /// Center point of the ball spawn spot
     pub origin: Vec3,
@@ -1,8 +1,9 @@
 use nalgebra::Quaternion;
+use serde::{Deserialize, Serialize};
 use rapier3d::prelude::*;
-use super::{feature::NavmeshPathingType, ArenaFeature};
+use super::{feature::NavmeshPathingType, kinematics::{FeatureKinematics, FeatureMotionPath}, ArenaFeature};
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum BarrierPathing {
     Disabled,
     Enabled
@@ -11,6 +12,7 @@ pub enum BarrierPathing {
 /// All barriers are rectangular prisms.
 /// Barriers can be used as walls (which aren't pathable by characters)
 /// or as floors (which are pathable by characters)
+#[derive(Serialize, Deserialize)]
 pub struct ArenaBarrier {
     /// Center point of the barrier
     origin: Vec3,
@@ -22,6 +24,9 @@ pub struct ArenaBarrier {
 
     /// Is this 
     pathing: BarrierPathing,
+
+    /// The path the barrier moves along, if it isn't fixed in place
+    motion: Option<FeatureMotionPath>,
 }
 
 impl ArenaBarrier {
@@ -38,16 +43,35 @@ impl ArenaBarrier {
             origin,
             shape,
             rotation,
-            pathing
+            pathing,
+            motion: None,
         }
     }
+
+    /// Makes the barrier move along a path, rather than being fixed in place.
+    #[must_use]
+    pub fn with_motion(mut self, motion: FeatureMotionPath) -> ArenaBarrier {
+        self.motion = Some(motion);
+        self
+    }
+
+    #[must_use]
+    pub fn motion(&self) -> Option<&FeatureMotionPath> {
+        self.motion.as_ref()
+    }
 }
 
 impl ArenaFeature for ArenaBarrier {
     fn build_rigid_body(&self) -> Option<RigidBody> {
-        let rigid_body = RigidBodyBuilder::fixed()
-            .translation(self.origin)
-            .rotation(self.rotation.vector().into())
+        // Moving barriers push whatever's in their way, but aren't pushed around themselves
+        let rigid_body_builder = if self.motion.is_some() {
+            RigidBodyBuilder::kinematic_position_based()
+        } else {
+            RigidBodyBuilder::fixed()
+        };
+
+        let rigid_body = rigid_body_builder
+            .pose(self.pose())
             .build();
 
         Some(rigid_body)
@@ -91,6 +115,14 @@ impl ArenaFeature for ArenaBarrier {
         if self.pathing == BarrierPathing::Enabled { NavmeshPathingType::Generate } else { NavmeshPathingType::Block }
     }
 
+    fn kinematics(&self) -> Option<FeatureKinematics> {
+        Some(FeatureKinematics {
+            origin: self.origin,
+            motion: Some(self.motion.clone()?),
+            period_origins: vec![],
+        })
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
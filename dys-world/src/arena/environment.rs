@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+/// A change to the conditions games are played in, such as low gravity or fog.
+/// Modifiers are multipliers, so a gravity modifier of 0.5 halves gravity.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum EnvironmentModifier {
+    /// How strongly everything is pulled back to the ground.
+    Gravity(f32),
+    /// How much grip the arena and combatants have, eg how far a shoved combatant slides.
+    Friction(f32),
+    /// How bouncy balls are.
+    BallRestitution(f32),
+    /// How far combatants can see.
+    Visibility(f32),
+}
+
+impl EnvironmentModifier {
+    pub const LOW_GRAVITY: EnvironmentModifier = EnvironmentModifier::Gravity(0.4);
+    pub const SLICK_FLOOR: EnvironmentModifier = EnvironmentModifier::Friction(0.2);
+    pub const BOUNCY_BALLS: EnvironmentModifier = EnvironmentModifier::BallRestitution(4.0);
+    pub const FOG: EnvironmentModifier = EnvironmentModifier::Visibility(0.35);
+}
+
+/// The conditions a game is played in, once all of its environment modifiers have been applied.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameEnvironment {
+    pub gravity_multiplier: f32,
+    pub friction_multiplier: f32,
+    pub ball_restitution_multiplier: f32,
+    pub visibility_multiplier: f32,
+}
+
+impl Default for GameEnvironment {
+    fn default() -> Self {
+        GameEnvironment {
+            gravity_multiplier: 1.0,
+            friction_multiplier: 1.0,
+            ball_restitution_multiplier: 1.0,
+            visibility_multiplier: 1.0,
+        }
+    }
+}
+
+impl GameEnvironment {
+    /// Applies each modifier in turn. Modifiers of the same kind stack, eg two low gravity modifiers make for very low gravity.
+    #[must_use]
+    pub fn from_modifiers(modifiers: &[EnvironmentModifier]) -> GameEnvironment {
+        modifiers
+            .iter()
+            .fold(GameEnvironment::default(), |environment, modifier| environment.with_modifier(*modifier))
+    }
+
+    #[must_use]
+    pub fn with_modifier(mut self, modifier: EnvironmentModifier) -> GameEnvironment {
+        // Negative multipliers don't make sense (eg sight reaching behind you), so they're treated as zero
+        match modifier {
+            EnvironmentModifier::Gravity(multiplier) => self.gravity_multiplier *= multiplier.max(0.0),
+            EnvironmentModifier::Friction(multiplier) => self.friction_multiplier *= multiplier.max(0.0),
+            EnvironmentModifier::BallRestitution(multiplier) => self.ball_restitution_multiplier *= multiplier.max(0.0),
+            EnvironmentModifier::Visibility(multiplier) => self.visibility_multiplier *= multiplier.max(0.0),
+        }
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EnvironmentModifier, GameEnvironment};
+
+    #[test]
+    fn test_no_modifiers_is_default_environment() {
+        assert_eq!(GameEnvironment::from_modifiers(&[]), GameEnvironment::default());
+    }
+
+    #[test]
+    fn test_modifiers_stack() {
+        let environment = GameEnvironment::from_modifiers(&[
+            EnvironmentModifier::Gravity(0.5),
+            EnvironmentModifier::FOG,
+            EnvironmentModifier::Gravity(0.5),
+        ]);
+
+        assert!((environment.gravity_multiplier - 0.25).abs() < f32::EPSILON);
+        assert!((environment.visibility_multiplier - 0.35).abs() < f32::EPSILON);
+        assert!((environment.friction_multiplier - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_negative_modifiers_are_treated_as_zero() {
+        let environment = GameEnvironment::from_modifiers(&[EnvironmentModifier::Visibility(-1.0)]);
+
+        assert!(environment.visibility_multiplier.abs() < f32::EPSILON);
+    }
+}
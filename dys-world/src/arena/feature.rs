@@ -1,7 +1,11 @@
 use std::any::Any;
 
 use rapier3d::{dynamics::RigidBody, geometry::{Collider, SharedShape}, na::Quaternion};
-use rapier3d::prelude::Vec3;
+use rapier3d::glamx::Quat;
+use rapier3d::prelude::{Pose3, Vec3};
+use serde::{Deserialize, Serialize};
+
+use super::kinematics::FeatureKinematics;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum NavmeshPathingType {
@@ -13,7 +17,23 @@ pub enum NavmeshPathingType {
     Block
 }
 
-pub trait ArenaFeature {
+/// Kinds of area that cost more (or less) than usual to path through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NavmeshAreaType {
+    /// Areas where points are scored, such as plates.
+    Plate,
+    /// Areas that hurt to move through.
+    Hazard,
+    /// Areas that are about to hurt to be in, such as where a ball is about to explode.
+    Danger,
+    /// Areas occupied by something in the way, such as another combatant.
+    Obstacle,
+    /// Areas nothing can move through, such as wherever a moving wall currently is.
+    Blocked,
+}
+
+/// Arenas are shared between every game played in them, so features must be safe to share across threads.
+pub trait ArenaFeature: Send + Sync {
     fn build_rigid_body(&self) -> Option<RigidBody> { None }
 
     fn build_collider(&self) -> Option<Collider> { None }
@@ -22,9 +42,33 @@ pub trait ArenaFeature {
 
     fn rotation(&self) -> &Quaternion<f32>;
 
+    /// Position and rotation of the feature in the world.
+    fn pose(&self) -> Pose3 {
+        Pose3::from_parts(*self.origin(), to_rotation(self.rotation()))
+    }
+
     fn shape(&self) -> Option<&SharedShape> { None }
 
     fn pathing_type(&self) -> NavmeshPathingType;
 
+    /// The kind of area covered by this feature's shape, if pathing through it should cost more (or less) than usual.
+    fn navmesh_area(&self) -> Option<NavmeshAreaType> { None }
+
+    /// How the feature moves during a game, if it moves at all.
+    /// Moving features aren't baked into the navmesh, since they won't stay where they start.
+    fn kinematics(&self) -> Option<FeatureKinematics> { None }
+
     fn as_any(&self) -> &dyn Any;
+}
+
+/// Converts a feature's rotation into the rotation used by physics and navmesh queries.
+/// Degenerate (zero-length) rotations are treated as no rotation.
+#[must_use]
+pub fn to_rotation(rotation: &Quaternion<f32>) -> Quat {
+    let rotation = Quat::from_xyzw(rotation.i, rotation.j, rotation.k, rotation.w);
+    if rotation.length_squared() > 0.0 {
+        rotation.normalize()
+    } else {
+        Quat::IDENTITY
+    }
 }
\ No newline at end of file
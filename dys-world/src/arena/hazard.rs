@@ -0,0 +1,95 @@
+use rapier3d::na::Quaternion;
+use rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::feature::{NavmeshAreaType, NavmeshPathingType};
+use super::kinematics::{FeatureKinematics, FeatureMotionPath};
+use super::ArenaFeature;
+
+pub type HazardId = u8;
+
+/// An area that wears down combatants standing in it.
+/// Hazards may sit still, or sweep across the arena along a path.
+#[derive(Serialize, Deserialize)]
+pub struct ArenaHazard {
+    pub id: HazardId,
+
+    /// Center point of the hazard
+    pub origin: Vec3,
+
+    /// Shape of the hazard, including size of that shape
+    pub shape: SharedShape,
+
+    /// Quaternion of the rotation
+    pub rotation: Quaternion<f32>,
+
+    /// How much stamina each combatant in the hazard loses per second
+    pub stamina_drain_per_second: f32,
+
+    /// The path the hazard moves along, if it isn't fixed in place
+    pub motion: Option<FeatureMotionPath>,
+}
+
+impl ArenaHazard {
+    #[must_use]
+    pub fn new(id: HazardId, origin: Vec3, shape: SharedShape, rotation: Quaternion<f32>, stamina_drain_per_second: f32) -> ArenaHazard {
+        ArenaHazard {
+            id,
+            origin,
+            shape,
+            rotation,
+            stamina_drain_per_second,
+            motion: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_motion(mut self, motion: FeatureMotionPath) -> ArenaHazard {
+        self.motion = Some(motion);
+        self
+    }
+}
+
+impl ArenaFeature for ArenaHazard {
+    fn build_collider(&self) -> Option<Collider> {
+        // Like plates, hazards only detect combatants in them, rather than getting in anyone's way
+        let collider = ColliderBuilder::new(self.shape.clone())
+            .position(self.pose())
+            .sensor(true)
+            .build();
+
+        Some(collider)
+    }
+
+    fn origin(&self) -> &Vec3 {
+        &self.origin
+    }
+
+    fn rotation(&self) -> &Quaternion<f32> {
+        &self.rotation
+    }
+
+    fn shape(&self) -> Option<&SharedShape> {
+        Some(&self.shape)
+    }
+
+    fn pathing_type(&self) -> NavmeshPathingType {
+        NavmeshPathingType::Skip
+    }
+
+    fn navmesh_area(&self) -> Option<NavmeshAreaType> {
+        Some(NavmeshAreaType::Hazard)
+    }
+
+    fn kinematics(&self) -> Option<FeatureKinematics> {
+        Some(FeatureKinematics {
+            origin: self.origin,
+            motion: Some(self.motion.clone()?),
+            period_origins: vec![],
+        })
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,180 @@
+use rapier3d::prelude::Vec3;
+use serde::{Deserialize, Serialize};
+
+/// What a moving feature does once it reaches the last waypoint of its path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MotionLooping {
+    /// Stay at the last waypoint.
+    Stop,
+    /// Head straight back to the start of the path, and go around again.
+    Loop,
+    /// Retrace the path backwards to the start, and go around again.
+    PingPong,
+}
+
+/// A path a feature moves along at a constant speed, starting from the feature's origin.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FeatureMotionPath {
+    /// Offsets from the feature's origin to move between, in order.
+    /// The feature's origin is the implicit first waypoint.
+    waypoints: Vec<Vec3>,
+
+    /// Units moved per second.
+    speed: f32,
+
+    looping: MotionLooping,
+}
+
+impl FeatureMotionPath {
+    #[must_use]
+    pub fn new(waypoints: Vec<Vec3>, speed: f32, looping: MotionLooping) -> FeatureMotionPath {
+        FeatureMotionPath {
+            waypoints,
+            speed,
+            looping,
+        }
+    }
+
+    #[must_use]
+    pub fn waypoints(&self) -> &[Vec3] {
+        &self.waypoints
+    }
+
+    /// All points along the path, in the order they're visited, including the return to the start when looping.
+    fn points(&self) -> Vec<Vec3> {
+        let mut points = vec![Vec3::ZERO];
+        points.extend(&self.waypoints);
+
+        match self.looping {
+            MotionLooping::Stop => {},
+            MotionLooping::Loop => points.push(Vec3::ZERO),
+            MotionLooping::PingPong => points.extend(self.waypoints.iter().rev().skip(1).chain([&Vec3::ZERO])),
+        }
+
+        points
+    }
+
+    /// How far the feature travels to go around the path once.
+    #[must_use]
+    pub fn length(&self) -> f32 {
+        self.points()
+            .windows(2)
+            .map(|segment| (segment[1] - segment[0]).length())
+            .sum()
+    }
+
+    /// How far from its origin the feature is after moving along the path for some time.
+    #[must_use]
+    pub fn offset_at(&self, seconds: f32) -> Vec3 {
+        let length = self.length();
+        if length <= 0.0 || self.speed <= 0.0 {
+            return Vec3::ZERO;
+        }
+
+        let distance = seconds.max(0.0) * self.speed;
+        let mut distance = match self.looping {
+            MotionLooping::Stop => distance.min(length),
+            MotionLooping::Loop | MotionLooping::PingPong => distance % length,
+        };
+
+        let points = self.points();
+        for segment in points.windows(2) {
+            let segment_length = (segment[1] - segment[0]).length();
+            // Waypoints on top of each other make for zero-length segments, which are skipped over
+            if distance <= segment_length && segment_length > 0.0 {
+                return segment[0].lerp(segment[1], distance / segment_length);
+            }
+
+            distance -= segment_length;
+        }
+
+        points.last().copied().unwrap_or(Vec3::ZERO)
+    }
+}
+
+/// How a feature moves during a game.
+/// Features without kinematics stay where the arena puts them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FeatureKinematics {
+    /// Where the feature starts the game.
+    pub origin: Vec3,
+
+    /// The path the feature moves along, if any.
+    pub motion: Option<FeatureMotionPath>,
+
+    /// Where the feature is moved to at the start of each period, in order, repeating if there are more periods than origins.
+    /// If empty, the feature stays at its origin between periods.
+    pub period_origins: Vec<Vec3>,
+}
+
+impl FeatureKinematics {
+    /// Where the feature is during a period, some time into the game.
+    #[must_use]
+    pub fn position_at(&self, period: u32, game_seconds: f32) -> Vec3 {
+        let period_origin = if self.period_origins.is_empty() {
+            self.origin
+        } else {
+            self.period_origins[period as usize % self.period_origins.len()]
+        };
+
+        let offset = self.motion
+            .as_ref()
+            .map_or(Vec3::ZERO, |motion| motion.offset_at(game_seconds));
+
+        period_origin + offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rapier3d::glamx::vec3;
+    use super::{FeatureKinematics, FeatureMotionPath, MotionLooping};
+
+    fn assert_near(actual: rapier3d::prelude::Vec3, expected: rapier3d::prelude::Vec3) {
+        assert!((actual - expected).length() < 0.001, "expected {expected:?}, got {actual:?}");
+    }
+
+    #[test]
+    fn test_stopping_path_stays_at_last_waypoint() {
+        let motion = FeatureMotionPath::new(vec![vec3(10.0, 0.0, 0.0), vec3(10.0, 0.0, 10.0)], 2.0, MotionLooping::Stop);
+
+        assert_near(motion.offset_at(0.0), vec3(0.0, 0.0, 0.0));
+        assert_near(motion.offset_at(2.5), vec3(5.0, 0.0, 0.0));
+        assert_near(motion.offset_at(7.5), vec3(10.0, 0.0, 5.0));
+        assert_near(motion.offset_at(100.0), vec3(10.0, 0.0, 10.0));
+    }
+
+    #[test]
+    fn test_looping_path_returns_to_start() {
+        let motion = FeatureMotionPath::new(vec![vec3(10.0, 0.0, 0.0), vec3(10.0, 0.0, 10.0)], 1.0, MotionLooping::Loop);
+
+        // Out 10, across 10, then ~14.1 back diagonally
+        assert!((motion.length() - (20.0 + 200.0_f32.sqrt())).abs() < 0.001);
+        assert_near(motion.offset_at(motion.length()), vec3(0.0, 0.0, 0.0));
+        assert_near(motion.offset_at(motion.length() + 5.0), vec3(5.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_ping_pong_path_retraces_its_steps() {
+        let motion = FeatureMotionPath::new(vec![vec3(10.0, 0.0, 0.0), vec3(10.0, 0.0, 10.0)], 1.0, MotionLooping::PingPong);
+
+        assert!((motion.length() - 40.0).abs() < 0.001);
+        assert_near(motion.offset_at(25.0), vec3(10.0, 0.0, 5.0));
+        assert_near(motion.offset_at(35.0), vec3(5.0, 0.0, 0.0));
+        assert_near(motion.offset_at(40.0), vec3(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_features_relocate_between_periods() {
+        let kinematics = FeatureKinematics {
+            origin: vec3(50.0, 0.0, 50.0),
+            motion: Some(FeatureMotionPath::new(vec![vec3(0.0, 0.0, 4.0)], 1.0, MotionLooping::Stop)),
+            period_origins: vec![vec3(50.0, 0.0, 50.0), vec3(20.0, 0.0, 50.0)],
+        };
+
+        assert_near(kinematics.position_at(0, 2.0), vec3(50.0, 0.0, 52.0));
+        assert_near(kinematics.position_at(1, 0.0), vec3(20.0, 0.0, 50.0));
+        assert_near(kinematics.position_at(1, 3.0), vec3(20.0, 0.0, 53.0));
+        assert_near(kinematics.position_at(2, 10.0), vec3(50.0, 0.0, 54.0));
+    }
+}
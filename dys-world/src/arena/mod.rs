@@ -1,8 +1,10 @@
 use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, OnceLock};
 use nalgebra::Quaternion;
 use rapier3d::prelude::*;
 use rapier3d::glamx::vec3;
-use self::{ball_spawn::ArenaBallSpawn, feature::ArenaFeature, plate::ArenaPlate, combatant_start::ArenaCombatantStart, barrier::ArenaBarrier, barrier::BarrierPathing};
+use self::navmesh::{ArenaNavmesh, ArenaNavmeshConfig};
+use self::{ball_spawn::ArenaBallSpawn, environment::{EnvironmentModifier, GameEnvironment}, feature::ArenaFeature, plate::ArenaPlate, combatant_start::ArenaCombatantStart, barrier::ArenaBarrier, barrier::BarrierPathing};
 
 pub mod feature;
 
@@ -10,13 +12,28 @@ pub mod barrier;
 pub mod plate;
 pub mod combatant_start;
 pub mod ball_spawn;
+pub mod environment;
+pub mod hazard;
+pub mod kinematics;
 pub mod navmesh;
+pub mod analysis;
 pub(crate) mod serde;
 
-const PLATE_VERTICAL_HEIGHT: f32 = 5.0;
+pub(crate) const PLATE_VERTICAL_HEIGHT: f32 = 5.0;
+
+pub type ArenaId = u32;
+
+/// Index of a feature in [`Arena::all_features`].
+pub type ArenaFeatureIndex = usize;
 
 pub struct Arena {
-    all_features: Vec<Box<dyn ArenaFeature>>
+    all_features: Vec<Box<dyn ArenaFeature>>,
+
+    /// Conditions every game played in this arena is subject to, such as low gravity.
+    environment_modifiers: Vec<EnvironmentModifier>,
+
+    /// Generating a navmesh is expensive, so it's baked the first time a game needs it and shared by every game after.
+    navmesh: OnceLock<Arc<ArenaNavmesh>>,
 }
 
 impl Debug for Arena {
@@ -26,6 +43,23 @@ impl Debug for Arena {
 }
 
 impl Arena {
+    #[must_use]
+    pub fn new(all_features: Vec<Box<dyn ArenaFeature>>) -> Arena {
+        Arena { all_features, environment_modifiers: vec![], navmesh: OnceLock::new() }
+    }
+
+    #[must_use]
+    pub fn with_feature(mut self, feature: Box<dyn ArenaFeature>) -> Arena {
+        self.all_features.push(feature);
+        self
+    }
+
+    #[must_use]
+    pub fn with_environment_modifier(mut self, modifier: EnvironmentModifier) -> Arena {
+        self.environment_modifiers.push(modifier);
+        self
+    }
+
     #[must_use]
     pub fn new_with_testing_defaults() -> Arena {
         Arena {
@@ -53,7 +87,7 @@ impl Arena {
                 ),
                 // Plate
                 Box::new(
-                    ArenaPlate { id: 1, origin: vec3(50.0, 0.0, 50.0), shape: SharedShape::cylinder(PLATE_VERTICAL_HEIGHT, 10.0), rotation: Quaternion::identity() }
+                    ArenaPlate::new(1, vec3(50.0, 0.0, 50.0), SharedShape::cylinder(PLATE_VERTICAL_HEIGHT, 10.0), Quaternion::identity())
                 ),
                 // South Ball Spawn
                 Box::new(
@@ -111,7 +145,9 @@ impl Arena {
                 Box::new(
                     ArenaCombatantStart { origin: vec3(85.0, 0.0, 20.0), rotation: Quaternion::identity(), is_home_team: false }
                 ),
-            ]
+            ],
+            environment_modifiers: vec![],
+            navmesh: OnceLock::new(),
         }
     }
 
@@ -132,4 +168,75 @@ impl Arena {
             .filter_map(|feature| feature.as_any().downcast_ref::<T>())
             .collect()
     }
+
+    #[must_use]
+    pub fn environment_modifiers(&self) -> &[EnvironmentModifier] {
+        &self.environment_modifiers
+    }
+
+    /// The navmesh games in this arena are pathed on.
+    /// Features can't be changed once an arena is built, so the navmesh is only generated once.
+    #[must_use]
+    pub fn navmesh(&self) -> Arc<ArenaNavmesh> {
+        self.navmesh
+            .get_or_init(|| Arc::new(ArenaNavmesh::from_arena(self, ArenaNavmeshConfig::default())))
+            .clone()
+    }
+
+    /// Subjects every game played in this arena from now on to another modifier, eg after a proposal passes.
+    pub fn add_environment_modifier(&mut self, modifier: EnvironmentModifier) {
+        self.environment_modifiers.push(modifier);
+    }
+
+    /// The conditions games in this arena are played in.
+    #[must_use]
+    pub fn environment(&self) -> GameEnvironment {
+        GameEnvironment::from_modifiers(&self.environment_modifiers)
+    }
+
+    /// Direction along the floor from the home team's side of the arena towards the away team's side,
+    /// taken from where each team's combatants start.
+    /// None if either team has no starts, or both teams' starts are centred on the same spot.
+    #[must_use]
+    // Arenas have a handful of starts, far too few to lose any precision as a float
+    #[allow(clippy::cast_precision_loss)]
+    pub fn home_forward(&self) -> Option<Vec3> {
+        let team_centre = |is_home_team: bool| {
+            let origins = self
+                .features::<ArenaCombatantStart>()
+                .into_iter()
+                .filter(|start| start.is_home_team == is_home_team)
+                .map(|start| start.origin)
+                .collect::<Vec<_>>();
+
+            (!origins.is_empty()).then(|| origins.iter().sum::<Vec3>() / origins.len() as f32)
+        };
+
+        let home_to_away = team_centre(false)? - team_centre(true)?;
+        vec3(home_to_away.x, 0.0, home_to_away.z).try_normalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rapier3d::na::Quaternion;
+    use rapier3d::glamx::vec3;
+    use rapier3d::prelude::Vec3;
+    use super::Arena;
+    use super::combatant_start::ArenaCombatantStart;
+    use super::feature::ArenaFeature;
+
+    fn start(x: f32, z: f32, is_home_team: bool) -> Box<dyn ArenaFeature> {
+        Box::new(ArenaCombatantStart { origin: vec3(x, 0.0, z), is_home_team, rotation: Quaternion::identity() })
+    }
+
+    #[test]
+    fn test_home_forward_points_from_home_starts_to_away_starts() {
+        assert!((Arena::new_with_testing_defaults().home_forward().unwrap() - Vec3::X).length() < 0.0001);
+
+        let mirrored = Arena::new(vec![start(10.0, 90.0, true), start(10.0, 70.0, true), start(10.0, 10.0, false)]);
+        assert!((mirrored.home_forward().unwrap() - Vec3::NEG_Z).length() < 0.0001);
+
+        assert!(Arena::new(vec![start(10.0, 90.0, true)]).home_forward().is_none());
+    }
 }
\ No newline at end of file
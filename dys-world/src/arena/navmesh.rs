@@ -1,26 +1,157 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
 use std::fmt::Display;
 use std::sync::{Arc, Mutex};
 
 use ordered_float::{self, OrderedFloat};
 
-use petgraph::algo;
-use petgraph::visit::EdgeRef;
-use petgraph::graphmap::UnGraphMap;
 use rapier3d::glamx::vec3;
 use rapier3d::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use super::feature::NavmeshPathingType;
+use super::feature::{NavmeshAreaType, NavmeshPathingType};
 use super::Arena;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ArenaNavmeshConfig {
     /// How granular of a navmesh should we generate?
     /// If =1, there will be one navmesh node per unit, so a square arena of 20x20 units would have 400 navmesh nodes.
     /// If <1, there will be more than one navmesh node per unit.
     pub unit_resolution: f32,
+
+    /// The tallest ledge a combatant can step up onto (or down from) without a ramp.
+    pub max_step_height: f32,
+
+    /// The steepest surface, in degrees from flat, that a combatant can walk on.
+    pub max_slope_degrees: f32,
+
+    /// How far from walls combatants keep. Nodes closer than this to blocking geometry aren't generated.
+    pub agent_radius: f32,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+impl Default for ArenaNavmeshConfig {
+    fn default() -> Self {
+        ArenaNavmeshConfig {
+            unit_resolution: 1.0,
+            max_step_height: 0.5,
+            max_slope_degrees: 35.0,
+            agent_radius: 0.5,
+        }
+    }
+}
+
+/// The part of the arena an area covers horizontally, around the area's center.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NavmeshAreaShape {
+    Circle { radius: f32 },
+    /// A rectangle lined up with the arena's x and z axes.
+    Rectangle { half_width: f32, half_depth: f32 },
+}
+
+/// An area that costs more (or less) than usual to path through, that only applies to a single path query.
+#[derive(Clone, Debug)]
+pub struct NavmeshCostArea {
+    pub area_type: NavmeshAreaType,
+    pub center: Vec3,
+    pub shape: NavmeshAreaShape,
+}
+
+impl NavmeshCostArea {
+    fn contains(&self, point: Vec3) -> bool {
+        let mut offset = point - self.center;
+        offset.y = 0.0;
+
+        match self.shape {
+            NavmeshAreaShape::Circle { radius } => offset.length() <= radius,
+            NavmeshAreaShape::Rectangle { half_width, half_depth } => offset.x.abs() <= half_width && offset.z.abs() <= half_depth,
+        }
+    }
+
+    /// Half the width (along x) and depth (along z) of a rectangle around the center that covers the whole area.
+    fn half_extents(&self) -> (f32, f32) {
+        match self.shape {
+            NavmeshAreaShape::Circle { radius } => (radius, radius),
+            NavmeshAreaShape::Rectangle { half_width, half_depth } => (half_width, half_depth),
+        }
+    }
+}
+
+/// How costly each kind of area is to path through.
+/// Costs are multipliers, so an area with a cost of 5.0 is as costly to cross as five times the distance on open ground.
+/// Areas are avoided where possible, but never made impassable, so that a path can still be made when the destination is in one.
+/// The exception is blocked areas, which can't be pathed through at any cost.
+#[derive(Clone, Debug)]
+pub struct NavmeshCostProfile {
+    pub plate_cost: f32,
+    pub hazard_cost: f32,
+    pub danger_cost: f32,
+    pub obstacle_cost: f32,
+
+    /// Areas that only apply to this query, such as other combatants or where a ball is about to explode.
+    pub areas: Vec<NavmeshCostArea>,
+}
+
+impl Default for NavmeshCostProfile {
+    fn default() -> Self {
+        NavmeshCostProfile {
+            plate_cost: 1.0,
+            hazard_cost: 5.0,
+            danger_cost: 8.0,
+            obstacle_cost: 10.0,
+            areas: vec![],
+        }
+    }
+}
+
+impl NavmeshCostProfile {
+    #[must_use]
+    pub fn cost(&self, area_type: NavmeshAreaType) -> f32 {
+        match area_type {
+            NavmeshAreaType::Plate => self.plate_cost,
+            NavmeshAreaType::Hazard => self.hazard_cost,
+            NavmeshAreaType::Danger => self.danger_cost,
+            NavmeshAreaType::Obstacle => self.obstacle_cost,
+            NavmeshAreaType::Blocked => f32::INFINITY,
+        }
+    }
+
+    #[must_use]
+    pub fn with_area(mut self, area_type: NavmeshAreaType, center: Vec3, radius: f32) -> NavmeshCostProfile {
+        self.areas.push(NavmeshCostArea { area_type, center, shape: NavmeshAreaShape::Circle { radius } });
+        self
+    }
+}
+
+/// Index of a vertical column of navmesh nodes, in units of the navmesh resolution along the x and z axes.
+/// A column has a node for each walkable level, eg the floor and a bridge over it.
+type ColumnKey = (i64, i64);
+
+/// How far above a walkable surface must be clear of other geometry for the surface to be walkable.
+const SURFACE_CLEARANCE: f32 = 0.01;
+
+/// Offsets to neighboring columns, and the base cost of moving to them.
+const NEIGHBOR_OFFSETS: [(i64, i64, f32); 8] = {
+    const CARDINAL_NEIGHBOR_WEIGHT: f32 = 1.0;
+    const DIAGONAL_NEIGHBOR_WEIGHT: f32 = 1.7;
+    [
+        (-1, 0, CARDINAL_NEIGHBOR_WEIGHT),
+        (1, 0, CARDINAL_NEIGHBOR_WEIGHT),
+        (0, -1, CARDINAL_NEIGHBOR_WEIGHT),
+        (0, 1, CARDINAL_NEIGHBOR_WEIGHT),
+        (-1, -1, DIAGONAL_NEIGHBOR_WEIGHT),
+        (-1, 1, DIAGONAL_NEIGHBOR_WEIGHT),
+        (1, -1, DIAGONAL_NEIGHBOR_WEIGHT),
+        (1, 1, DIAGONAL_NEIGHBOR_WEIGHT),
+    ]
+};
+
+#[allow(clippy::cast_possible_truncation)]
+fn column_key(point: Vec3, unit_resolution: f32) -> ColumnKey {
+    ((point.x / unit_resolution).round() as i64, (point.z / unit_resolution).round() as i64)
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct ArenaNavmeshNode {
     x: OrderedFloat<f32>,
     y: OrderedFloat<f32>,
@@ -49,6 +180,7 @@ impl Display for ArenaNavmeshNode {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ArenaNavmeshPath {
     path: Vec<ArenaNavmeshNode>
 }
@@ -70,6 +202,11 @@ impl ArenaNavmeshPath {
         self.path.pop()
     }
 
+    /// The nodes still to be visited, in the order they'll be visited, without using them up.
+    pub fn upcoming_nodes(&self) -> impl Iterator<Item = &ArenaNavmeshNode> {
+        self.path.iter().rev()
+    }
+
     #[must_use]
     pub fn len(&self) -> usize {
         self.path.len()
@@ -79,123 +216,409 @@ impl ArenaNavmeshPath {
     pub fn is_empty(&self) -> bool {
         self.path.is_empty()
     }
+
+    /// Distance travelled along the path, from its first node to its last.
+    #[must_use]
+    pub fn length(&self) -> f32 {
+        self.path
+            .windows(2)
+            .map(|nodes| (nodes[1].as_vector() - nodes[0].as_vector()).length())
+            .sum()
+    }
+}
+
+/// Index of a node in the navmesh.
+type NodeIndex = u32;
+
+/// Index of a cluster of navmesh columns.
+type ClusterIndex = u32;
+
+/// How many columns wide (and deep) each cluster of the navmesh is.
+const CLUSTER_WIDTH: i64 = 8;
+
+/// Everything that determines the result of a path query.
+/// Areas that only apply to the query are keyed by the nodes they cover, rather than where exactly they are,
+/// as two queries whose areas cover the same nodes will find the same path.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct PathQueryKey {
+    from: NodeIndex,
+    to: NodeIndex,
+    costs: [u32; 4],
+    area_nodes: Vec<(NodeIndex, NavmeshAreaType)>,
+}
+
+impl PathQueryKey {
+    fn new(from: NodeIndex, to: NodeIndex, cost_profile: &NavmeshCostProfile, area_nodes: Vec<(NodeIndex, NavmeshAreaType)>) -> PathQueryKey {
+        PathQueryKey {
+            from,
+            to,
+            costs: [
+                cost_profile.plate_cost.to_bits(),
+                cost_profile.hazard_cost.to_bits(),
+                cost_profile.danger_cost.to_bits(),
+                cost_profile.obstacle_cost.to_bits(),
+            ],
+            area_nodes,
+        }
+    }
+}
+
+/// Paths already found during a tick, so that combatants making the same path query don't search for it again.
+/// Whoever owns the cache clears it each tick, as the areas combatants steer around move from tick to tick.
+#[derive(Default)]
+pub struct NavmeshPathCache {
+    paths: HashMap<PathQueryKey, Vec<NodeIndex>>,
+}
+
+impl NavmeshPathCache {
+    #[must_use]
+    pub fn new() -> NavmeshPathCache {
+        NavmeshPathCache::default()
+    }
+
+    /// Forgets all previously found paths.
+    pub fn clear(&mut self) {
+        self.paths.clear();
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+}
+
+/// A coarser graph over the navmesh, where each cluster stands in for the nodes in a square of columns.
+/// Paths are planned from cluster to cluster first, so that the search node by node only has to consider
+/// the clusters along the way.
+struct NavmeshClusters {
+    /// Cluster each node is in.
+    node_clusters: Vec<ClusterIndex>,
+    /// Average position of each cluster's nodes.
+    centres: Vec<Vec3>,
+    /// Clusters that can be stepped into from each cluster.
+    neighbors: Vec<Vec<ClusterIndex>>,
+}
+
+impl NavmeshClusters {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    fn new(nodes: &[ArenaNavmeshNode], edges: &[Vec<(NodeIndex, f32)>], unit_resolution: f32) -> NavmeshClusters {
+        let mut cluster_indices = HashMap::<ColumnKey, ClusterIndex>::new();
+        let mut node_clusters = Vec::with_capacity(nodes.len());
+        let mut position_sums = Vec::<(Vec3, usize)>::new();
+
+        for node in nodes {
+            let (column_x, column_z) = column_key(node.as_vector(), unit_resolution);
+            let cluster_key = (column_x.div_euclid(CLUSTER_WIDTH), column_z.div_euclid(CLUSTER_WIDTH));
+            let cluster_index = *cluster_indices.entry(cluster_key).or_insert_with(|| {
+                position_sums.push((Vec3::ZERO, 0));
+                (position_sums.len() - 1) as ClusterIndex
+            });
+
+            let (position_sum, node_count) = &mut position_sums[cluster_index as usize];
+            *position_sum += node.as_vector();
+            *node_count += 1;
+            node_clusters.push(cluster_index);
+        }
+
+        let mut neighbors = vec![Vec::<ClusterIndex>::new(); position_sums.len()];
+        for (node_index, node_edges) in edges.iter().enumerate() {
+            let cluster_index = node_clusters[node_index];
+            for (neighbor_index, _) in node_edges {
+                let neighbor_cluster_index = node_clusters[*neighbor_index as usize];
+                if neighbor_cluster_index != cluster_index {
+                    neighbors[cluster_index as usize].push(neighbor_cluster_index);
+                }
+            }
+        }
+
+        for cluster_neighbors in &mut neighbors {
+            cluster_neighbors.sort_unstable();
+            cluster_neighbors.dedup();
+        }
+
+        NavmeshClusters {
+            node_clusters,
+            centres: position_sums.into_iter().map(|(position_sum, node_count)| position_sum / node_count as f32).collect(),
+            neighbors,
+        }
+    }
+
+    /// A* from cluster to cluster, as the crow flies between their centres.
+    /// None if no node in one cluster can reach the other, in which case neither can any of their nodes.
+    fn get_path_between_clusters(&self, from: ClusterIndex, to: ClusterIndex) -> Option<Vec<ClusterIndex>> {
+        let centre = |index: ClusterIndex| self.centres[index as usize];
+        let heuristic = |index: ClusterIndex| OrderedFloat((centre(to) - centre(index)).length());
+
+        let mut costs = vec![f32::INFINITY; self.centres.len()];
+        let mut came_from = vec![None::<ClusterIndex>; self.centres.len()];
+        let mut to_visit = BinaryHeap::new();
+
+        costs[from as usize] = 0.0;
+        to_visit.push(Reverse((heuristic(from), from)));
+
+        while let Some(Reverse((_, index))) = to_visit.pop() {
+            if index == to {
+                let mut path = vec![index];
+                while let Some(previous) = came_from[*path.last().unwrap() as usize] {
+                    path.push(previous);
+                }
+                return Some(path);
+            }
+
+            for neighbor_index in &self.neighbors[index as usize] {
+                let cost = costs[index as usize] + (centre(*neighbor_index) - centre(index)).length();
+                if cost < costs[*neighbor_index as usize] {
+                    costs[*neighbor_index as usize] = cost;
+                    came_from[*neighbor_index as usize] = Some(index);
+                    to_visit.push(Reverse((OrderedFloat(cost) + heuristic(*neighbor_index), *neighbor_index)));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Which clusters a search node by node may pass through: those on the path between clusters,
+    /// and the clusters around them, to leave room to detour around costly areas.
+    fn corridor(&self, cluster_path: &[ClusterIndex]) -> Vec<bool> {
+        let mut is_in_corridor = vec![false; self.centres.len()];
+        for cluster_index in cluster_path {
+            is_in_corridor[*cluster_index as usize] = true;
+            for neighbor_index in &self.neighbors[*cluster_index as usize] {
+                is_in_corridor[*neighbor_index as usize] = true;
+            }
+        }
+
+        is_in_corridor
+    }
+}
+
+/// The parts of a navmesh that are expensive to generate, so that a navmesh can be baked once and loaded later.
+#[derive(Serialize, Deserialize)]
+struct BakedArenaNavmesh {
+    config: ArenaNavmeshConfig,
+    nodes: Vec<[f32; 3]>,
+    edges: Vec<Vec<(NodeIndex, f32)>>,
+    node_areas: Vec<Vec<NavmeshAreaType>>,
 }
 
 pub struct ArenaNavmesh {
-    graph: UnGraphMap<ArenaNavmeshNode, f32>,
+    nodes: Vec<ArenaNavmeshNode>,
+    /// Neighbors of each node, and the cost of moving to them.
+    edges: Vec<Vec<(NodeIndex, f32)>>,
+    /// Areas of the arena each node is in.
+    node_areas: Vec<Vec<NavmeshAreaType>>,
+    columns: HashMap<ColumnKey, Vec<NodeIndex>>,
+    clusters: NavmeshClusters,
     config: ArenaNavmeshConfig,
 }
 
 impl ArenaNavmesh {
     /// # Panics
     /// Will panic if `arena` cannot be locked.
+    #[allow(clippy::needless_pass_by_value)]
     pub fn new_from(arena: Arc<Mutex<Arena>>, config: ArenaNavmeshConfig) -> ArenaNavmesh {
-        let arena = arena.lock().unwrap();
-        let arena_features = arena.all_features();
+        ArenaNavmesh::from_arena(&arena.lock().unwrap(), config)
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss, clippy::too_many_lines)]
+    #[must_use]
+    pub fn from_arena(arena: &Arena, config: ArenaNavmeshConfig) -> ArenaNavmesh {
+        // Moving features won't stay where they start, so only features that stay put are baked in.
+        // Paths steer around moving features with cost areas wherever they are at the time instead.
+        let arena_features = arena
+            .all_features()
+            .into_iter()
+            .filter(|feature| feature.kinematics().is_none())
+            .collect::<Vec<_>>();
         let pathable_arena_features = arena_features
             .iter()
             .filter(|filter| filter.pathing_type() == NavmeshPathingType::Generate);
 
-        let unpathable_arena_shapes = arena
-            .all_features()
+        // Both walls and other floors can bury a walkable surface, eg the floor underneath a raised platform
+        let solid_arena_shapes = arena_features
             .iter()
-            .filter(|filter| filter.pathing_type() == NavmeshPathingType::Block)
-            .map(|feature| {
-                (feature.shape().expect("failed to get unpathable feature shape"), Pose3::new(feature.origin().to_owned(), vec3(0., 0., 0.)))
-            })
+            .filter(|feature| feature.pathing_type() != NavmeshPathingType::Skip)
+            .filter_map(|feature| Some((feature.shape()?, feature.pose())))
+            .collect::<Vec<_>>();
+
+        let blocking_arena_shapes = arena_features
+            .iter()
+            .filter(|feature| feature.pathing_type() == NavmeshPathingType::Block)
+            .filter_map(|feature| Some((feature.shape()?, feature.pose())))
+            .collect::<Vec<_>>();
+
+        let area_arena_shapes = arena_features
+            .iter()
+            .filter_map(|feature| Some((feature.shape()?, feature.pose(), feature.navmesh_area()?)))
             .collect::<Vec<_>>();
 
-        let mut graph = UnGraphMap::<ArenaNavmeshNode, f32>::new();
+        let unit_resolution = config.unit_resolution;
+        let min_walkable_normal_y = config.max_slope_degrees.to_radians().cos();
+
+        let mut nodes = Vec::<ArenaNavmeshNode>::new();
+        let mut node_areas = Vec::<Vec<NavmeshAreaType>>::new();
+        let mut columns = HashMap::<ColumnKey, Vec<NodeIndex>>::new();
 
         // Add all pathable features
         for feature in pathable_arena_features {
-            let origin = feature.origin();
             let Some(shape) = feature.shape() else {
                 continue;
             };
 
-            let shape_isometry = Pose3::new(origin.to_owned(), vec3(0., 0., 0.));
-            let aabb = shape.compute_aabb(&shape_isometry);
-            let vertices = aabb.vertices();
-
-            let x_comparator_fn = |first: &&Vec3, second: &&Vec3| first.x.partial_cmp(&second.x).unwrap_or(std::cmp::Ordering::Equal);
-            let y_comparator_fn = |first: &&Vec3, second: &&Vec3| first.y.partial_cmp(&second.y).unwrap_or(std::cmp::Ordering::Equal);
-            let z_comparator_fn = |first: &&Vec3, second: &&Vec3| first.z.partial_cmp(&second.z).unwrap_or(std::cmp::Ordering::Equal);
-
-            let min_x = vertices.iter().min_by(x_comparator_fn).expect("failed to get min_x").x;
-            let max_x = vertices.iter().max_by(x_comparator_fn).expect("failed to get max_x").x;
-            let min_y = vertices.iter().min_by(y_comparator_fn).expect("failed to get min_y").y;
-            let max_y = vertices.iter().max_by(y_comparator_fn).expect("failed to get max_y").y;
-            let min_z = vertices.iter().min_by(z_comparator_fn).expect("failed to get min_z").z;
-            let max_z = vertices.iter().max_by(z_comparator_fn).expect("failed to get max_z").z;
-
-            let mut curr_z = min_z;
-            while curr_z <= max_z {
-                let mut curr_x = min_x;
-                while curr_x <= max_x {
-                    let mut curr_y = max_y;
-                    while curr_y >= min_y {
-                        let curr_point = vec3(curr_x, curr_y, curr_z);
-                        if shape.contains_point(&shape_isometry, curr_point) {
-                            // If any of our unpathable geometry is in the way, skip the potential node instead
-                            let mut is_unpathable = false;
-                            for (unpathable_shape, unpathable_isometry) in &unpathable_arena_shapes {
-                                if unpathable_shape.contains_point(unpathable_isometry, curr_point) {
-                                    is_unpathable = true;
-                                    break;
-                                }
-                            }
-
-                            if !is_unpathable {
-                                let node = ArenaNavmeshNode::from_point(curr_point);
-                                graph.add_node(node);
-
-                                break;
-                            }
-                        }
+            // Features may be rotated, so the AABB must be computed with the feature's full pose
+            let shape_pose = feature.pose();
+            let aabb = shape.compute_aabb(&shape_pose);
+            let ray_length = aabb.maxs.y - aabb.mins.y + 2.0;
+
+            let min_column_x = (aabb.mins.x / unit_resolution).ceil() as i64;
+            let max_column_x = (aabb.maxs.x / unit_resolution).floor() as i64;
+            let min_column_z = (aabb.mins.z / unit_resolution).ceil() as i64;
+            let max_column_z = (aabb.maxs.z / unit_resolution).floor() as i64;
+
+            for column_z in min_column_z..=max_column_z {
+                for column_x in min_column_x..=max_column_x {
+                    // Look down onto the top of the feature, which may be sloped
+                    let ray = Ray::new(
+                        vec3(column_x as f32 * unit_resolution, aabb.maxs.y + 1.0, column_z as f32 * unit_resolution),
+                        vec3(0.0, -1.0, 0.0),
+                    );
+
+                    let Some(hit) = shape.cast_ray_and_get_normal(&shape_pose, &ray, ray_length, true) else {
+                        continue;
+                    };
+
+                    if hit.normal.y < min_walkable_normal_y {
+                        continue;
+                    }
+
+                    let surface_point = ray.point_at(hit.time_of_impact);
+
+                    // If any other geometry is in the way, skip the potential node instead
+                    let clearance_point = surface_point + vec3(0.0, SURFACE_CLEARANCE, 0.0);
+                    let is_buried = solid_arena_shapes
+                        .iter()
+                        .any(|(solid_shape, solid_pose)| solid_shape.contains_point(solid_pose, clearance_point));
+
+                    if is_buried {
+                        continue;
+                    }
+
+                    // Keep combatants far enough from walls that they don't scrape along them
+                    let is_against_wall = blocking_arena_shapes
+                        .iter()
+                        .any(|(blocking_shape, blocking_pose)| {
+                            blocking_shape.distance_to_point(blocking_pose, clearance_point, true) < config.agent_radius
+                        });
 
-                        curr_y -= config.unit_resolution;
+                    if is_against_wall {
+                        continue;
                     }
-                    curr_x += config.unit_resolution;
+
+                    // Features that touch (eg a ramp meeting a platform) may both find the same surface
+                    let column = columns.entry((column_x, column_z)).or_default();
+                    if column.iter().any(|index| (nodes[*index as usize].as_vector().y - surface_point.y).abs() <= SURFACE_CLEARANCE) {
+                        continue;
+                    }
+
+                    column.push(nodes.len() as NodeIndex);
+                    nodes.push(ArenaNavmeshNode::from_point(surface_point));
+
+                    node_areas.push(area_arena_shapes
+                        .iter()
+                        .filter(|(area_shape, area_pose, _)| area_shape.contains_point(area_pose, clearance_point))
+                        .map(|(_, _, area_type)| *area_type)
+                        .collect());
                 }
-                curr_z += config.unit_resolution;
             }
         }
 
-        // Add edges between nodes
-        let mut new_edges = vec![];
-        for node in graph.nodes() {
-            const CARDINAL_NEIGHBOR_WEIGHT: f32 = 1.0;
-            const DIAGONAL_NEIGHBOR_WEIGHT: f32 = 1.7;
-            let neighbor_points: Vec<(Vec3, f32)> = vec![
-                (node.as_vector() + vec3(-config.unit_resolution, 0.0, 0.0), CARDINAL_NEIGHBOR_WEIGHT),
-                (node.as_vector() + vec3(config.unit_resolution, 0.0, 0.0), CARDINAL_NEIGHBOR_WEIGHT),
-                (node.as_vector() + vec3(0.0, 0.0, -config.unit_resolution), CARDINAL_NEIGHBOR_WEIGHT),
-                (node.as_vector() + vec3(0.0, 0.0, config.unit_resolution), CARDINAL_NEIGHBOR_WEIGHT),
-                (node.as_vector() + vec3(-config.unit_resolution, 0.0, -config.unit_resolution), DIAGONAL_NEIGHBOR_WEIGHT),
-                (node.as_vector() + vec3(-config.unit_resolution, 0.0, config.unit_resolution), DIAGONAL_NEIGHBOR_WEIGHT),
-                (node.as_vector() + vec3(config.unit_resolution, 0.0, -config.unit_resolution), DIAGONAL_NEIGHBOR_WEIGHT),
-                (node.as_vector() + vec3(config.unit_resolution, 0.0, config.unit_resolution), DIAGONAL_NEIGHBOR_WEIGHT),
-            ];
-
-            for (neighbor_point, weight) in neighbor_points {
-                let maybe_neighbor_node = ArenaNavmeshNode::from_point(neighbor_point);
-                if graph.contains_node(maybe_neighbor_node) {
-                    new_edges.push((node, maybe_neighbor_node, weight));
+        // Add edges between nodes, including between levels where they're close enough in height to step or walk between
+        let max_slope_rise = config.max_slope_degrees.to_radians().tan();
+        let edges = nodes
+            .iter()
+            .map(|node| {
+                let (column_x, column_z) = column_key(node.as_vector(), unit_resolution);
+                let mut node_edges = vec![];
+
+                for (offset_x, offset_z, weight) in NEIGHBOR_OFFSETS {
+                    let Some(neighbor_column) = columns.get(&(column_x + offset_x, column_z + offset_z)) else {
+                        continue;
+                    };
+
+                    let horizontal_distance = unit_resolution * ((offset_x * offset_x + offset_z * offset_z) as f32).sqrt();
+                    let max_rise = config.max_step_height.max(horizontal_distance * max_slope_rise);
+
+                    for neighbor_index in neighbor_column {
+                        let rise = (nodes[*neighbor_index as usize].as_vector().y - node.as_vector().y).abs();
+                        if rise > max_rise {
+                            continue;
+                        }
+
+                        // Climbing costs more than walking the same distance on the flat
+                        let climb_multiplier = horizontal_distance.hypot(rise) / horizontal_distance;
+                        node_edges.push((*neighbor_index, weight * climb_multiplier));
+                    }
                 }
-            }
-        }
 
-        for (node, neighbor, weight) in new_edges {
-            graph.add_edge(node, neighbor, weight);
-        }
+                node_edges
+            })
+            .collect::<Vec<_>>();
 
+        let clusters = NavmeshClusters::new(&nodes, &edges, unit_resolution);
         ArenaNavmesh {
-            graph,
+            nodes,
+            edges,
+            node_areas,
+            columns,
+            clusters,
             config,
         }
     }
 
+    fn from_baked(baked: BakedArenaNavmesh) -> Result<ArenaNavmesh, String> {
+        let node_count = baked.nodes.len();
+        if baked.edges.len() != node_count || baked.node_areas.len() != node_count {
+            return Err(format!(
+                "baked navmesh has {node_count} nodes, but edges for {} and areas for {}",
+                baked.edges.len(),
+                baked.node_areas.len(),
+            ));
+        }
+
+        if baked.edges.iter().flatten().any(|(neighbor_index, _)| *neighbor_index as usize >= node_count) {
+            return Err(String::from("baked navmesh has edges to nodes that don't exist"));
+        }
+
+        let nodes = baked.nodes
+            .into_iter()
+            .map(|[x, y, z]| ArenaNavmeshNode::from_point(vec3(x, y, z)))
+            .collect::<Vec<_>>();
+
+        let mut columns = HashMap::<ColumnKey, Vec<NodeIndex>>::new();
+        for (index, node) in nodes.iter().enumerate() {
+            columns
+                .entry(column_key(node.as_vector(), baked.config.unit_resolution))
+                .or_default()
+                .push(NodeIndex::try_from(index).map_err(|err| err.to_string())?);
+        }
+
+        let clusters = NavmeshClusters::new(&nodes, &baked.edges, baked.config.unit_resolution);
+        Ok(ArenaNavmesh {
+            nodes,
+            edges: baked.edges,
+            node_areas: baked.node_areas,
+            columns,
+            clusters,
+            config: baked.config,
+        })
+    }
+
     #[must_use]
     pub fn config(&self) -> &ArenaNavmeshConfig {
         &self.config
@@ -203,64 +626,240 @@ impl ArenaNavmesh {
 
     /// Attempts to create a path from one point to another point. Returns an empty vector if a path cannot be made.
     #[tracing::instrument(level = "trace", skip_all)]
-    pub fn create_path(&self, mut from: Vec3, mut to: Vec3) -> Option<ArenaNavmeshPath> {
-        // ZJ-TODO: HACK: grounding the coordinates to 0.0 is sad and bad
-        from.y = 0.0;
-        to.y = 0.0;
+    pub fn create_path(&self, from: Vec3, to: Vec3) -> Option<ArenaNavmeshPath> {
+        self.create_path_with_profile(from, to, &NavmeshCostProfile::default())
+    }
+
+    /// Attempts to create a path from one point to another point, steering clear of costly areas
+    /// (such as hazards, or other combatants) where the cost profile says it's worth the detour.
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn create_path_with_profile(&self, from: Vec3, to: Vec3, cost_profile: &NavmeshCostProfile) -> Option<ArenaNavmeshPath> {
+        self.create_path_with_cache(from, to, cost_profile, &mut NavmeshPathCache::new())
+    }
+
+    /// As [`ArenaNavmesh::create_path_with_profile`], but reusing the path from an identical query
+    /// that's already in the cache, and caching the path otherwise.
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn create_path_with_cache(
+        &self,
+        from: Vec3,
+        to: Vec3,
+        cost_profile: &NavmeshCostProfile,
+        path_cache: &mut NavmeshPathCache,
+    ) -> Option<ArenaNavmeshPath> {
+        let start_index = self.get_closest_node_index(from)?;
+        let end_index = self.get_closest_node_index(to)?;
+
+        let query_key = PathQueryKey::new(start_index, end_index, cost_profile, self.get_query_area_nodes(cost_profile));
+        let index_path = if let Some(index_path) = path_cache.paths.get(&query_key) {
+            index_path.clone()
+        } else {
+            let index_path = self.get_path_between_nodes(start_index, end_index, cost_profile, &query_key.area_nodes);
+            path_cache.paths.insert(query_key, index_path.clone());
+            index_path
+        };
+
+        Some(ArenaNavmeshPath::new(index_path.into_iter().map(|index| self.node(index)).collect()))
+    }
+
+    /// Moves a point horizontally onto the navmesh, where each node covers the square of the arena around it
+    /// (as wide as the navmesh's resolution). Points already on the navmesh are unchanged.
+    /// None if there's no navmesh near the point.
+    #[must_use]
+    pub fn clamp_to_navmesh(&self, point: Vec3) -> Option<Vec3> {
+        let node_position = self.node(self.get_closest_node_index(point)?).as_vector();
+        let half_resolution = self.config.unit_resolution / 2.0;
+
+        Some(vec3(
+            point.x.clamp(node_position.x - half_resolution, node_position.x + half_resolution),
+            point.y,
+            point.z.clamp(node_position.z - half_resolution, node_position.z + half_resolution),
+        ))
+    }
+
+    fn node(&self, index: NodeIndex) -> ArenaNavmeshNode {
+        self.nodes[index as usize]
+    }
+
+    /// Nodes covered by each of the areas that only apply to a query, in order of node.
+    fn get_query_area_nodes(&self, cost_profile: &NavmeshCostProfile) -> Vec<(NodeIndex, NavmeshAreaType)> {
+        let unit_resolution = self.config.unit_resolution;
+        let mut area_nodes = vec![];
+
+        for area in &cost_profile.areas {
+            let (half_width, half_depth) = area.half_extents();
+            let extents_offset = vec3(half_width, 0.0, half_depth);
+            let (min_column_x, min_column_z) = column_key(area.center - extents_offset, unit_resolution);
+            let (max_column_x, max_column_z) = column_key(area.center + extents_offset, unit_resolution);
+
+            for column_z in min_column_z..=max_column_z {
+                for column_x in min_column_x..=max_column_x {
+                    let Some(column) = self.columns.get(&(column_x, column_z)) else {
+                        continue;
+                    };
+
+                    area_nodes.extend(column
+                        .iter()
+                        .filter(|index| area.contains(self.node(**index).as_vector()))
+                        .map(|index| (*index, area.area_type)));
+                }
+            }
+        }
 
-        let start_node = ArenaNavmesh::get_closest_node(&self.graph, from, self.config.unit_resolution)?;
-        let end_node = ArenaNavmesh::get_closest_node(&self.graph, to, self.config.unit_resolution)?;
+        // Stable, so that each node's areas stay in the order they were given
+        area_nodes.sort_by_key(|(index, _)| *index);
+        area_nodes
+    }
 
-        let node_path = self.get_path_between_nodes(start_node, end_node);
+    /// How much more costly than usual it is to move onto the node, given the areas it's in.
+    fn node_cost_multiplier(&self, index: NodeIndex, cost_profile: &NavmeshCostProfile, query_node_areas: &HashMap<NodeIndex, Vec<NavmeshAreaType>>) -> f32 {
+        let arena_areas = self.node_areas[index as usize].iter();
+        let query_areas = query_node_areas.get(&index).into_iter().flatten();
 
-        Some(ArenaNavmeshPath::new(node_path))
+        arena_areas
+            .chain(query_areas)
+            .map(|area_type| cost_profile.cost(*area_type))
+            .product()
     }
 
+    /// Finds the cheapest path between two nodes, by first finding a path between the clusters they're in,
+    /// and then searching node by node through only the clusters along the way.
+    /// The clusters around the way are searched too, so the path can still detour around costly areas.
     #[tracing::instrument(level = "trace", skip_all)]
     fn get_path_between_nodes(
         &self,
-        from: ArenaNavmeshNode,
-        to: ArenaNavmeshNode
-    ) -> Vec<ArenaNavmeshNode> {
-        // To introduce suboptimal pathing for combatants,
-        // we'll add a random vector to A*'s normal heuristic algorithm of euclidean distance.
-
-        let astar_result = algo::astar(
-            &self.graph,
-            from,
-            |node| node.as_vector() == to.as_vector(),
-            |edge| *edge.weight(),
-            |node| (to.as_vector() - node.as_vector()).length(),
-        );
+        from: NodeIndex,
+        to: NodeIndex,
+        cost_profile: &NavmeshCostProfile,
+        query_area_nodes: &[(NodeIndex, NavmeshAreaType)],
+    ) -> Vec<NodeIndex> {
+        let mut query_node_areas = HashMap::<NodeIndex, Vec<NavmeshAreaType>>::new();
+        for (index, area_type) in query_area_nodes {
+            query_node_areas.entry(*index).or_default().push(*area_type);
+        }
 
-        let Some((_total_cost, path)) = astar_result else {
+        let from_cluster = self.clusters.node_clusters[from as usize];
+        let to_cluster = self.clusters.node_clusters[to as usize];
+        let Some(cluster_path) = self.clusters.get_path_between_clusters(from_cluster, to_cluster) else {
             return vec![];
         };
 
-        path
+        let corridor = self.clusters.corridor(&cluster_path);
+        let path = self.search_nodes(from, to, cost_profile, &query_node_areas, Some(&corridor));
+        if !path.is_empty() {
+            return path;
+        }
+
+        // The way through a cluster can be cut off even though the clusters themselves connect
+        self.search_nodes(from, to, cost_profile, &query_node_areas, None)
     }
 
-    /// This function is **expensive**. Should not be used when constructing the navmesh graph, and only for client requests (like [create_path](ArenaNavmesh::create_path)).
-    #[tracing::instrument(level = "trace", skip_all)]
-    fn get_closest_node(graph: &UnGraphMap<ArenaNavmeshNode, f32>, point: Vec3, unit_resolution: f32) -> Option<ArenaNavmeshNode> {
-        let scalar = 1.0 / unit_resolution;
-        let adjusted_point = vec3(
-            (point.x * scalar).round() / scalar,
-            (point.y * scalar).round() / scalar,
-            (point.z * scalar).round() / scalar,
-        );
-        let potential_node = ArenaNavmeshNode::from_point(adjusted_point);
+    /// A* over node indices, keeping per-node state in flat vectors rather than maps keyed by node.
+    /// If there's a corridor, only nodes in the corridor's clusters are searched.
+    fn search_nodes(
+        &self,
+        from: NodeIndex,
+        to: NodeIndex,
+        cost_profile: &NavmeshCostProfile,
+        query_node_areas: &HashMap<NodeIndex, Vec<NavmeshAreaType>>,
+        corridor: Option<&[bool]>,
+    ) -> Vec<NodeIndex> {
+        let is_in_corridor = |index: NodeIndex| corridor.is_none_or(|corridor| corridor[self.clusters.node_clusters[index as usize] as usize]);
+
+        // Straight-line distance to the goal
+        let goal = self.node(to).as_vector();
+        let heuristic = |index: NodeIndex| OrderedFloat((goal - self.node(index).as_vector()).length());
+
+        let mut costs = vec![f32::INFINITY; self.nodes.len()];
+        let mut came_from = vec![None::<NodeIndex>; self.nodes.len()];
+        let mut is_visited = vec![false; self.nodes.len()];
+        let mut to_visit = BinaryHeap::new();
+
+        costs[from as usize] = 0.0;
+        to_visit.push(Reverse((heuristic(from), from)));
+
+        while let Some(Reverse((_, index))) = to_visit.pop() {
+            if index == to {
+                let mut path = vec![index];
+                while let Some(previous) = came_from[*path.last().unwrap() as usize] {
+                    path.push(previous);
+                }
+                path.reverse();
+                return path;
+            }
 
-        if graph.contains_node(potential_node) {
-            Some(potential_node)
-        } else {
-            None
+            if std::mem::replace(&mut is_visited[index as usize], true) {
+                continue;
+            }
+
+            for (neighbor_index, weight) in &self.edges[index as usize] {
+                if !is_in_corridor(*neighbor_index) {
+                    continue;
+                }
+
+                let cost = costs[index as usize] + weight * self.node_cost_multiplier(*neighbor_index, cost_profile, query_node_areas);
+                if cost < costs[*neighbor_index as usize] {
+                    costs[*neighbor_index as usize] = cost;
+                    came_from[*neighbor_index as usize] = Some(index);
+                    to_visit.push(Reverse((OrderedFloat(cost) + heuristic(*neighbor_index), *neighbor_index)));
+                }
+            }
         }
-    
-        // graph
-        //     .nodes()
-        //     .filter(|node| (node.as_point() - point).magnitude() <= unit_resolution)
-        //     .min_by(|first, second| (first.as_point() - point).magnitude().partial_cmp(&(second.as_point() - point).magnitude()).unwrap_or(std::cmp::Ordering::Equal))
+
+        vec![]
+    }
+
+    /// Finds the node a point is standing on (or falling towards): the highest node in the point's column
+    /// that's no more than a step above the point.
+    /// Points hugging a wall may be in a column kept clear for the agent radius, in which case the nearest column is used.
+    #[tracing::instrument(level = "trace", skip_all)]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    fn get_closest_node_index(&self, point: Vec3) -> Option<NodeIndex> {
+        let unit_resolution = self.config.unit_resolution;
+        let (column_x, column_z) = column_key(point, unit_resolution);
+        let search_radius = (self.config.agent_radius / unit_resolution).ceil() as i64;
+
+        let column = (-search_radius..=search_radius)
+            .flat_map(|offset_z| (-search_radius..=search_radius).map(move |offset_x| (column_x + offset_x, column_z + offset_z)))
+            .filter_map(|key| Some((key, self.columns.get(&key)?)))
+            .min_by_key(|((key_x, key_z), _)| {
+                let offset = vec3(*key_x as f32 * unit_resolution - point.x, 0.0, *key_z as f32 * unit_resolution - point.z);
+                OrderedFloat(offset.length())
+            })
+            .map(|(_, column)| column)?;
+
+        let standing_on = column
+            .iter()
+            .filter(|index| self.node(**index).as_vector().y <= point.y + self.config.max_step_height)
+            .max_by_key(|index| self.node(**index).y);
+
+        // If the point is below every level (eg clipped into the floor), the lowest level is the best guess
+        standing_on
+            .or_else(|| column.iter().min_by_key(|index| self.node(**index).y))
+            .copied()
+    }
+
+    #[cfg(test)]
+    fn get_closest_node(&self, point: Vec3) -> Option<ArenaNavmeshNode> {
+        self.get_closest_node_index(point).map(|index| self.node(index))
+    }
+}
+
+impl Serialize for ArenaNavmesh {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BakedArenaNavmesh {
+            config: self.config.clone(),
+            nodes: self.nodes.iter().map(|node| node.as_vector().to_array()).collect(),
+            edges: self.edges.clone(),
+            node_areas: self.node_areas.clone(),
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ArenaNavmesh {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let baked = BakedArenaNavmesh::deserialize(deserializer)?;
+        ArenaNavmesh::from_baked(baked).map_err(serde::de::Error::custom)
     }
 }
 
@@ -268,7 +867,12 @@ impl ArenaNavmesh {
 mod tests {
     use nalgebra::Quaternion;
 
+    use std::f32::consts::FRAC_PI_2;
+    use std::sync::OnceLock;
+
     use crate::arena::barrier::{ArenaBarrier, BarrierPathing};
+    use crate::arena::feature::ArenaFeature;
+    use crate::arena::kinematics::{FeatureMotionPath, MotionLooping};
 
     use super::*;
 
@@ -281,7 +885,9 @@ mod tests {
                 Box::new(
                     ArenaBarrier::new(vec3(0.0, -5.0, 0.0), vec3(TEST_SQUARE_ARENA_SIZE, 10.0, TEST_SQUARE_ARENA_SIZE), Quaternion::identity(), BarrierPathing::Enabled)
                 ),
-            ]
+            ],
+            environment_modifiers: vec![],
+            navmesh: OnceLock::new(),
         }
     }
 
@@ -289,6 +895,7 @@ mod tests {
         let test_arena = get_test_square_arena_at_origin();
         let test_config = ArenaNavmeshConfig {
             unit_resolution: 1.0,
+            ..ArenaNavmeshConfig::default()
         };
 
         (Arc::new(Mutex::new(test_arena)), test_config)
@@ -301,13 +908,9 @@ mod tests {
     #[test]
     fn test_get_closest_node_valid_point() {        
         let (test_arena, test_config) = test_defaults();
-        let unit_resolution = test_config.unit_resolution;
 
         let navmesh = ArenaNavmesh::new_from(test_arena, test_config);
-        let closest_node = ArenaNavmesh::get_closest_node(
-            &navmesh.graph,
-            vec3(1.1, 0.0, 1.3),
-            unit_resolution);
+        let closest_node = navmesh.get_closest_node(vec3(1.1, 0.0, 1.3));
 
         assert!(closest_node.is_some());
 
@@ -322,23 +925,22 @@ mod tests {
         let unit_resolution = test_config.unit_resolution;
 
         let navmesh = ArenaNavmesh::new_from(test_arena, test_config);
-        assert!(ArenaNavmesh::get_closest_node(&navmesh.graph, vec3(-TEST_SQUARE_ARENA_SIZE - unit_resolution, 0.0, 0.0), unit_resolution).is_none());
-        assert!(ArenaNavmesh::get_closest_node(&navmesh.graph, vec3(TEST_SQUARE_ARENA_SIZE + unit_resolution, 0.0, 0.0), unit_resolution).is_none());
-        assert!(ArenaNavmesh::get_closest_node(&navmesh.graph, vec3(0.0, 0.0, -TEST_SQUARE_ARENA_SIZE -unit_resolution), unit_resolution).is_none());
-        assert!(ArenaNavmesh::get_closest_node(&navmesh.graph, vec3(0.0, 0.0, TEST_SQUARE_ARENA_SIZE + unit_resolution), unit_resolution).is_none());
+        assert!(navmesh.get_closest_node(vec3(-TEST_SQUARE_ARENA_SIZE - unit_resolution, 0.0, 0.0)).is_none());
+        assert!(navmesh.get_closest_node(vec3(TEST_SQUARE_ARENA_SIZE + unit_resolution, 0.0, 0.0)).is_none());
+        assert!(navmesh.get_closest_node(vec3(0.0, 0.0, -TEST_SQUARE_ARENA_SIZE -unit_resolution)).is_none());
+        assert!(navmesh.get_closest_node(vec3(0.0, 0.0, TEST_SQUARE_ARENA_SIZE + unit_resolution)).is_none());
     }
 
     #[test]
     fn test_get_path_between_nodes() {
         let (test_arena, test_config) = test_defaults();
-        let unit_resolution = test_config.unit_resolution;
 
         let navmesh = ArenaNavmesh::new_from(test_arena, test_config);
 
-        let start_node = ArenaNavmesh::get_closest_node(&navmesh.graph, vec3(1.0, 0.0, 1.0), unit_resolution).expect("failed to get start node");
-        let end_node = ArenaNavmesh::get_closest_node(&navmesh.graph, vec3(1.0, 0.0, 5.0), unit_resolution).expect("failed to get end node");
+        let start_node = navmesh.get_closest_node_index(vec3(1.0, 0.0, 1.0)).expect("failed to get start node");
+        let end_node = navmesh.get_closest_node_index(vec3(1.0, 0.0, 5.0)).expect("failed to get end node");
 
-        let path = navmesh.get_path_between_nodes(start_node, end_node);
+        let path = navmesh.get_path_between_nodes(start_node, end_node, &NavmeshCostProfile::default(), &[]);
 
         assert!(!path.is_empty());
         assert!(*path.first().unwrap() == start_node);
@@ -348,14 +950,13 @@ mod tests {
     #[test]
     fn test_get_path_between_nodes_prefers_diagonals() {        
         let (test_arena, test_config) = test_defaults();
-        let unit_resolution = test_config.unit_resolution;
 
         let navmesh = ArenaNavmesh::new_from(test_arena, test_config);
 
-        let start_node = ArenaNavmesh::get_closest_node(&navmesh.graph, vec3(1.0, 0.0, 1.0), unit_resolution).expect("failed to get start node");
-        let end_node = ArenaNavmesh::get_closest_node(&navmesh.graph, vec3(2.0, 0.0, 2.0), unit_resolution).expect("failed to get end node");
+        let start_node = navmesh.get_closest_node_index(vec3(1.0, 0.0, 1.0)).expect("failed to get start node");
+        let end_node = navmesh.get_closest_node_index(vec3(2.0, 0.0, 2.0)).expect("failed to get end node");
 
-        let path = navmesh.get_path_between_nodes(start_node, end_node);
+        let path = navmesh.get_path_between_nodes(start_node, end_node, &NavmeshCostProfile::default(), &[]);
 
         // An ideal path from (1.0, 0.0, 1.0) -> (2.0, 0.0, 2.0) with unit resolution of 1.0 should be exactly one node away (diagonally by (1.0, 0.0, 1.0)), 
         // meaning the total path should be only the start node and only the end node.
@@ -369,17 +970,347 @@ mod tests {
     fn test_get_path_between_nodes_with_smaller_resolution() {        
         let (test_arena, mut test_config) = test_defaults();
         test_config.unit_resolution = 0.5;
-        let unit_resolution = test_config.unit_resolution;
 
         let navmesh = ArenaNavmesh::new_from(test_arena, test_config);
 
-        let start_node = ArenaNavmesh::get_closest_node(&navmesh.graph, vec3(1.0, 0.0, 1.0), unit_resolution).expect("failed to get start node");
-        let end_node = ArenaNavmesh::get_closest_node(&navmesh.graph, vec3(1.0, 0.0, 5.0), unit_resolution).expect("failed to get end node");
+        let start_node = navmesh.get_closest_node_index(vec3(1.0, 0.0, 1.0)).expect("failed to get start node");
+        let end_node = navmesh.get_closest_node_index(vec3(1.0, 0.0, 5.0)).expect("failed to get end node");
 
-        let path = navmesh.get_path_between_nodes(start_node, end_node);
+        let path = navmesh.get_path_between_nodes(start_node, end_node, &NavmeshCostProfile::default(), &[]);
 
         assert!(path.len() == 9);
         assert!(*path.first().unwrap() == start_node);
         assert!(*path.last().unwrap() == end_node);
     }
-}
+
+    #[test]
+    fn test_create_path_avoiding_obstacles() {
+        let (test_arena, test_config) = test_defaults();
+        let navmesh = ArenaNavmesh::new_from(test_arena, test_config);
+
+        let obstacle = vec3(1.0, 0.0, 3.0);
+        let cost_profile = NavmeshCostProfile::default().with_area(NavmeshAreaType::Obstacle, obstacle, 0.5);
+        let mut path = navmesh
+            .create_path_with_profile(vec3(1.0, 0.0, 1.0), vec3(1.0, 0.0, 5.0), &cost_profile)
+            .expect("failed to create path");
+
+        assert!(!path.is_empty());
+        while let Some(node) = path.next_node() {
+            assert!((node.as_vector() - obstacle).length() > 0.5);
+        }
+    }
+
+    #[test]
+    fn test_paths_detour_around_danger_worth_avoiding() {
+        let (test_arena, test_config) = test_defaults();
+        let navmesh = ArenaNavmesh::new_from(test_arena, test_config);
+
+        let danger = vec3(10.0, 0.0, 0.0);
+        let path_length = |cost_profile: &NavmeshCostProfile| {
+            let mut path = navmesh
+                .create_path_with_profile(vec3(0.0, 0.0, 0.0), vec3(20.0, 0.0, 0.0), cost_profile)
+                .expect("failed to create path");
+
+            let mut entered_danger = false;
+            let mut length = 0.0;
+            let mut previous_node: Option<ArenaNavmeshNode> = None;
+            while let Some(node) = path.next_node() {
+                let mut offset = node.as_vector() - danger;
+                offset.y = 0.0;
+                entered_danger |= offset.length() <= 3.0;
+                length += previous_node.map_or(0.0, |previous| (node.as_vector() - previous.as_vector()).length());
+                previous_node = Some(node);
+            }
+
+            (length, entered_danger)
+        };
+
+        let (direct_length, direct_entered_danger) = path_length(&NavmeshCostProfile::default());
+        assert!(direct_entered_danger);
+
+        let cautious = NavmeshCostProfile::default().with_area(NavmeshAreaType::Danger, danger, 3.0);
+        let (cautious_length, cautious_entered_danger) = path_length(&cautious);
+        assert!(!cautious_entered_danger);
+        assert!(cautious_length > direct_length);
+
+        // Danger that's cheap to cross isn't worth the detour
+        let reckless = NavmeshCostProfile { danger_cost: 1.0, ..cautious };
+        let (reckless_length, reckless_entered_danger) = path_length(&reckless);
+        assert!(reckless_entered_danger);
+        assert!((reckless_length - direct_length).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_paths_never_cross_blocked_areas() {
+        let (test_arena, test_config) = test_defaults();
+        let navmesh = ArenaNavmesh::new_from(test_arena, test_config);
+
+        // A wall spanning x 9..11 and z -10..10, right across the direct path
+        let wall = NavmeshCostArea {
+            area_type: NavmeshAreaType::Blocked,
+            center: vec3(10.0, 0.0, 0.0),
+            shape: NavmeshAreaShape::Rectangle { half_width: 1.0, half_depth: 10.0 },
+        };
+        let cost_profile = NavmeshCostProfile { areas: vec![wall.clone()], ..NavmeshCostProfile::default() };
+
+        let mut path = navmesh
+            .create_path_with_profile(vec3(0.0, 0.0, 0.0), vec3(20.0, 0.0, 0.0), &cost_profile)
+            .expect("failed to create path");
+
+        assert!(!path.is_empty());
+        while let Some(node) = path.next_node() {
+            assert!(!wall.contains(node.as_vector()), "path crossed the wall at {:?}", node.as_vector());
+        }
+
+        // Nowhere inside a blocked area can be reached, however much of a detour it'd take
+        let path = navmesh
+            .create_path_with_profile(vec3(0.0, 0.0, 0.0), vec3(10.0, 0.0, 0.0), &cost_profile)
+            .expect("failed to create path");
+        assert!(path.is_empty());
+    }
+
+    #[test]
+    fn test_nodes_keep_agent_radius_from_walls() {
+        let (_, test_config) = test_defaults();
+        let test_config = ArenaNavmeshConfig { agent_radius: 1.5, ..test_config };
+
+        // A wall spanning x 9.5..10.5
+        let navmesh = get_test_navmesh_with_features(vec![
+            Box::new(ArenaBarrier::new(vec3(10.0, 0.0, 0.0), vec3(1.0, 10.0, 20.0), Quaternion::identity(), BarrierPathing::Disabled)),
+        ], test_config);
+
+        for column_x in 9..=11 {
+            assert!(!navmesh.columns.contains_key(&(column_x, 0)), "column {column_x} is too close to the wall");
+        }
+
+        assert!(navmesh.columns.contains_key(&(8, 0)));
+        assert!(navmesh.columns.contains_key(&(12, 0)));
+
+        // Points against the wall still find the nearest walkable node
+        let closest_node = navmesh.get_closest_node(vec3(9.2, 0.0, 0.0)).expect("failed to get node near wall");
+        assert!((closest_node.as_vector().x - 8.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_points_off_the_navmesh_are_clamped_onto_it() {
+        let (_, test_config) = test_defaults();
+        let test_config = ArenaNavmeshConfig { agent_radius: 1.5, ..test_config };
+
+        // A wall spanning x 9.5..10.5, keeping nodes back to x 8
+        let navmesh = get_test_navmesh_with_features(vec![
+            Box::new(ArenaBarrier::new(vec3(10.0, 0.0, 0.0), vec3(1.0, 10.0, 20.0), Quaternion::identity(), BarrierPathing::Disabled)),
+        ], test_config);
+
+        let open_ground = vec3(3.2, 0.0, 1.4);
+        assert_eq!(navmesh.clamp_to_navmesh(open_ground), Some(open_ground));
+
+        let against_wall = navmesh.clamp_to_navmesh(vec3(9.2, 0.0, 0.3)).expect("failed to clamp point near wall");
+        assert!((against_wall - vec3(8.5, 0.0, 0.3)).length() < 0.0001);
+
+        assert!(navmesh.clamp_to_navmesh(vec3(TEST_SQUARE_ARENA_SIZE + 10.0, 0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn test_moving_walls_are_not_baked_into_the_navmesh() {
+        let (_, test_config) = test_defaults();
+
+        let navmesh = get_test_navmesh_with_features(vec![
+            Box::new(
+                ArenaBarrier::new(vec3(10.0, 0.0, 0.0), vec3(1.0, 10.0, 20.0), Quaternion::identity(), BarrierPathing::Disabled)
+                    .with_motion(FeatureMotionPath::new(vec![vec3(0.0, 0.0, 20.0)], 1.0, MotionLooping::PingPong))
+            ),
+        ], test_config);
+
+        for column_x in 9..=11 {
+            assert!(navmesh.columns.contains_key(&(column_x, 0)), "column {column_x} was blocked by a moving wall");
+        }
+    }
+
+    fn path_points(mut path: ArenaNavmeshPath) -> Vec<Vec3> {
+        let mut points = vec![];
+        while let Some(node) = path.next_node() {
+            points.push(node.as_vector());
+        }
+
+        points
+    }
+
+    #[test]
+    fn test_cached_paths_match_computed_paths() {
+        let (test_arena, test_config) = test_defaults();
+        let navmesh = ArenaNavmesh::new_from(test_arena, test_config);
+
+        let mut path_cache = NavmeshPathCache::new();
+        let cost_profile = NavmeshCostProfile::default().with_area(NavmeshAreaType::Danger, vec3(10.0, 0.0, 0.0), 2.5);
+        let computed = path_points(navmesh.create_path_with_cache(vec3(0.0, 0.0, 0.0), vec3(20.0, 0.0, 0.0), &cost_profile, &mut path_cache).unwrap());
+        let cached = path_points(navmesh.create_path_with_cache(vec3(0.0, 0.0, 0.0), vec3(20.0, 0.0, 0.0), &cost_profile, &mut path_cache).unwrap());
+        assert_eq!(computed, cached);
+        assert_eq!(path_cache.len(), 1);
+
+        // Moving an area without it covering any other nodes is the same query
+        let nudged_profile = NavmeshCostProfile::default().with_area(NavmeshAreaType::Danger, vec3(10.0, 0.0, 0.05), 2.5);
+        navmesh.create_path_with_cache(vec3(0.0, 0.0, 0.0), vec3(20.0, 0.0, 0.0), &nudged_profile, &mut path_cache);
+        assert_eq!(path_cache.len(), 1);
+
+        // A different profile is a different query
+        let direct = path_points(navmesh.create_path_with_cache(vec3(0.0, 0.0, 0.0), vec3(20.0, 0.0, 0.0), &NavmeshCostProfile::default(), &mut path_cache).unwrap());
+        assert_ne!(computed, direct);
+        assert_eq!(path_cache.len(), 2);
+    }
+
+    #[test]
+    fn test_paths_through_clusters_are_as_cheap_as_searching_every_node() {
+        let (_, test_config) = test_defaults();
+
+        // A long wall spanning x -0.5..0.5 from one side of the arena to z 30, that the path must go all the way around
+        let navmesh = get_test_navmesh_with_features(vec![
+            Box::new(ArenaBarrier::new(vec3(0.0, 0.0, -10.0), vec3(1.0, 10.0, 80.0), Quaternion::identity(), BarrierPathing::Disabled)),
+        ], test_config);
+
+        let from = navmesh.get_closest_node_index(vec3(-10.0, 0.0, -20.0)).expect("failed to get start node");
+        let to = navmesh.get_closest_node_index(vec3(10.0, 0.0, -20.0)).expect("failed to get end node");
+        let cost_profile = NavmeshCostProfile::default().with_area(NavmeshAreaType::Danger, vec3(0.0, 0.0, 32.0), 2.0);
+        let query_area_nodes = navmesh.get_query_area_nodes(&cost_profile);
+        let mut query_node_areas = HashMap::<NodeIndex, Vec<NavmeshAreaType>>::new();
+        for (index, area_type) in &query_area_nodes {
+            query_node_areas.entry(*index).or_default().push(*area_type);
+        }
+
+        let path_cost = |path: &[NodeIndex]| path
+            .windows(2)
+            .map(|indices| {
+                let (_, weight) = navmesh.edges[indices[0] as usize].iter().find(|(index, _)| *index == indices[1]).unwrap();
+                weight * navmesh.node_cost_multiplier(indices[1], &cost_profile, &query_node_areas)
+            })
+            .sum::<f32>();
+
+        let clustered_path = navmesh.get_path_between_nodes(from, to, &cost_profile, &query_area_nodes);
+        let searched_path = navmesh.search_nodes(from, to, &cost_profile, &query_node_areas, None);
+
+        assert!(navmesh.node(*clustered_path.iter().max_by_key(|index| navmesh.node(**index).z).unwrap()).as_vector().z > 30.0);
+        assert!((path_cost(&clustered_path) - path_cost(&searched_path)).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_baked_navmesh_loads_with_same_paths() {
+        let (_, test_config) = test_defaults();
+        let navmesh = get_test_navmesh_with_features(get_test_platform_features(true), test_config);
+
+        let baked = serde_json::to_string(&navmesh).expect("failed to bake navmesh");
+        let loaded: ArenaNavmesh = serde_json::from_str(&baked).expect("failed to load baked navmesh");
+
+        assert_eq!(loaded.config(), navmesh.config());
+        assert_eq!(
+            path_points(loaded.create_path(vec3(5.0, 0.0, 0.0), vec3(25.0, 2.0, 0.0)).unwrap()),
+            path_points(navmesh.create_path(vec3(5.0, 0.0, 0.0), vec3(25.0, 2.0, 0.0)).unwrap()),
+        );
+    }
+
+    #[test]
+    fn test_baked_navmesh_with_dangling_edges_fails_to_load() {
+        let baked = r#"{"config":{},"nodes":[[0.0,0.0,0.0]],"edges":[[[1,1.0]]],"node_areas":[[]]}"#;
+        assert!(serde_json::from_str::<ArenaNavmesh>(baked).is_err());
+    }
+
+    fn get_test_navmesh_with_features(features: Vec<Box<dyn ArenaFeature>>, config: ArenaNavmeshConfig) -> ArenaNavmesh {
+        let mut arena = get_test_square_arena_at_origin();
+        arena.all_features.extend(features);
+
+        ArenaNavmesh::new_from(Arc::new(Mutex::new(arena)), config)
+    }
+
+    /// A rotation of `angle` radians around the given axis.
+    fn axis_angle(axis: Vec3, angle: f32) -> Quaternion<f32> {
+        let axis = axis.normalize() * (angle / 2.0).sin();
+        Quaternion::new((angle / 2.0).cos(), axis.x, axis.y, axis.z)
+    }
+
+    /// A platform 2 units high spanning x 20..30 and z -5..5, and optionally a ramp up to it from x 10.
+    fn get_test_platform_features(with_ramp: bool) -> Vec<Box<dyn ArenaFeature>> {
+        let mut features: Vec<Box<dyn ArenaFeature>> = vec![
+            Box::new(ArenaBarrier::new(vec3(25.0, 1.0, 0.0), vec3(10.0, 2.0, 10.0), Quaternion::identity(), BarrierPathing::Enabled)),
+        ];
+
+        if with_ramp {
+            let angle = (2.0_f32 / 10.0).atan();
+            let surface_normal = vec3(-angle.sin(), angle.cos(), 0.0);
+            let ramp_top_center = vec3(15.0, 1.0, 0.0);
+
+            features.push(Box::new(ArenaBarrier::new(
+                ramp_top_center - surface_normal * 0.5,
+                vec3(10.2, 1.0, 10.0),
+                axis_angle(vec3(0.0, 0.0, 1.0), angle),
+                BarrierPathing::Enabled,
+            )));
+        }
+
+        features
+    }
+
+    #[test]
+    fn test_rotated_features_block_their_rotated_footprint() {
+        let (_, test_config) = test_defaults();
+
+        // Unrotated, this wall would span x 0..20 along z 0; rotated, it spans z -10..10 along x 10
+        let navmesh = get_test_navmesh_with_features(vec![
+            Box::new(ArenaBarrier::new(vec3(10.0, 0.0, 0.0), vec3(20.0, 10.0, 4.0), axis_angle(vec3(0.0, 1.0, 0.0), FRAC_PI_2), BarrierPathing::Disabled)),
+        ], test_config);
+
+        assert!(navmesh.get_closest_node(vec3(10.0, 0.0, 5.0)).is_none());
+        assert!(navmesh.get_closest_node(vec3(5.0, 0.0, 0.0)).is_some());
+    }
+
+    #[test]
+    fn test_raised_platforms_need_a_ramp() {
+        let (_, test_config) = test_defaults();
+
+        let navmesh = get_test_navmesh_with_features(get_test_platform_features(false), test_config);
+        let path = navmesh.create_path(vec3(5.0, 0.0, 0.0), vec3(25.0, 2.0, 0.0)).expect("failed to create path");
+
+        assert!(path.is_empty());
+    }
+
+    #[test]
+    fn test_paths_climb_ramps_onto_platforms() {
+        let (_, test_config) = test_defaults();
+
+        let navmesh = get_test_navmesh_with_features(get_test_platform_features(true), test_config);
+        let mut path = navmesh.create_path(vec3(5.0, 0.0, 0.0), vec3(25.0, 2.0, 0.0)).expect("failed to create path");
+
+        assert!(!path.is_empty());
+
+        let mut last_node = None;
+        while let Some(node) = path.next_node() {
+            last_node = Some(node);
+        }
+
+        assert!((last_node.unwrap().as_vector().y - 2.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_ramps_steeper_than_max_slope_are_not_walkable() {
+        let (_, test_config) = test_defaults();
+        let test_config = ArenaNavmeshConfig { max_slope_degrees: 5.0, ..test_config };
+
+        let navmesh = get_test_navmesh_with_features(get_test_platform_features(true), test_config);
+        let path = navmesh.create_path(vec3(5.0, 0.0, 0.0), vec3(25.0, 2.0, 0.0)).expect("failed to create path");
+
+        assert!(path.is_empty());
+    }
+
+    #[test]
+    fn test_closest_node_picks_level_being_stood_on() {
+        let (_, test_config) = test_defaults();
+
+        // A bridge with its top at y 6, and room to walk underneath it
+        let navmesh = get_test_navmesh_with_features(vec![
+            Box::new(ArenaBarrier::new(vec3(-20.0, 5.5, 0.0), vec3(10.0, 1.0, 10.0), Quaternion::identity(), BarrierPathing::Enabled)),
+        ], test_config);
+
+        let on_bridge = navmesh.get_closest_node(vec3(-20.0, 6.5, 0.0)).expect("failed to get bridge node");
+        let under_bridge = navmesh.get_closest_node(vec3(-20.0, 0.5, 0.0)).expect("failed to get floor node");
+
+        assert!((on_bridge.as_vector().y - 6.0).abs() < 0.01);
+        assert!(under_bridge.as_vector().y.abs() < 0.01);
+    }
+}
\ No newline at end of file
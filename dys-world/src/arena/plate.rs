@@ -1,11 +1,42 @@
+use serde::{Deserialize, Serialize};
 use nalgebra::Quaternion;
 use rapier3d::prelude::*;
 
-use super::feature::NavmeshPathingType;
+use super::feature::{NavmeshAreaType, NavmeshPathingType};
+use super::kinematics::FeatureKinematics;
 use super::ArenaFeature;
 
 pub type PlateId = u8;
 
+/// When a plate can be scored on.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PlateSchedule {
+    /// The plate can always be scored on.
+    Always,
+
+    /// The plate is active for a while, then inactive for a while, over and over.
+    /// The offset shifts where in the cycle the plate is at the start of the game.
+    Cycle { active_seconds: f32, inactive_seconds: f32, offset_seconds: f32 },
+}
+
+impl PlateSchedule {
+    #[must_use]
+    pub fn is_active_at(&self, game_seconds: f32) -> bool {
+        match self {
+            PlateSchedule::Always => true,
+            PlateSchedule::Cycle { active_seconds, inactive_seconds, offset_seconds } => {
+                let cycle_seconds = active_seconds + inactive_seconds;
+                if cycle_seconds <= 0.0 {
+                    return true;
+                }
+
+                (game_seconds + offset_seconds).rem_euclid(cycle_seconds) < *active_seconds
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct ArenaPlate {
     pub id: PlateId,
 
@@ -17,12 +48,44 @@ pub struct ArenaPlate {
 
     /// Quaternion of the rotation
     pub rotation: Quaternion<f32>,
+
+    /// When the plate can be scored on
+    pub schedule: PlateSchedule,
+
+    /// Where the plate moves to at the start of each period. If empty, the plate stays at its origin.
+    pub period_origins: Vec<Vec3>,
+}
+
+impl ArenaPlate {
+    #[must_use]
+    pub fn new(id: PlateId, origin: Vec3, shape: SharedShape, rotation: Quaternion<f32>) -> ArenaPlate {
+        ArenaPlate {
+            id,
+            origin,
+            shape,
+            rotation,
+            schedule: PlateSchedule::Always,
+            period_origins: vec![],
+        }
+    }
+
+    #[must_use]
+    pub fn with_schedule(mut self, schedule: PlateSchedule) -> ArenaPlate {
+        self.schedule = schedule;
+        self
+    }
+
+    #[must_use]
+    pub fn with_period_origins(mut self, period_origins: Vec<Vec3>) -> ArenaPlate {
+        self.period_origins = period_origins;
+        self
+    }
 }
 
 impl ArenaFeature for ArenaPlate {
     fn build_rigid_body(&self) -> Option<RigidBody> {
         // Plates do not need rigid bodies (at least with current design)
-        // Maybe there's a world where plates move around but meh, feature creep
+        // Plates that relocate between periods have their colliders moved directly
         None
     }
 
@@ -32,8 +95,7 @@ impl ArenaFeature for ArenaPlate {
         // but not so large that players flying through the air are counted towards plate progress.
 
         let collider = ColliderBuilder::new(self.shape.clone())
-            .translation(self.origin)
-            .rotation(self.rotation.vector().into())
+            .position(self.pose())
             .sensor(true)
             .build();
 
@@ -56,7 +118,47 @@ impl ArenaFeature for ArenaPlate {
         NavmeshPathingType::Skip
     }
 
+    fn navmesh_area(&self) -> Option<NavmeshAreaType> {
+        Some(NavmeshAreaType::Plate)
+    }
+
+    fn kinematics(&self) -> Option<FeatureKinematics> {
+        if self.period_origins.is_empty() {
+            return None;
+        }
+
+        Some(FeatureKinematics {
+            origin: self.origin,
+            motion: None,
+            period_origins: self.period_origins.clone(),
+        })
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PlateSchedule;
+
+    #[test]
+    fn test_cycling_plates_alternate_active_and_inactive() {
+        let schedule = PlateSchedule::Cycle { active_seconds: 10.0, inactive_seconds: 5.0, offset_seconds: 0.0 };
+
+        assert!(schedule.is_active_at(0.0));
+        assert!(schedule.is_active_at(9.9));
+        assert!(!schedule.is_active_at(10.0));
+        assert!(!schedule.is_active_at(14.9));
+        assert!(schedule.is_active_at(15.0));
+    }
+
+    #[test]
+    fn test_offset_plates_start_partway_through_their_cycle() {
+        let schedule = PlateSchedule::Cycle { active_seconds: 10.0, inactive_seconds: 5.0, offset_seconds: 10.0 };
+
+        assert!(!schedule.is_active_at(0.0));
+        assert!(schedule.is_active_at(5.0));
+    }
+}
@@ -1,6 +1,13 @@
 use std::sync::{Arc, Mutex};
-use serde::Serializer;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use crate::arena::Arena;
+use super::ball_spawn::ArenaBallSpawn;
+use super::barrier::ArenaBarrier;
+use super::combatant_start::ArenaCombatantStart;
+use super::environment::EnvironmentModifier;
+use super::feature::ArenaFeature;
+use super::hazard::ArenaHazard;
+use super::plate::ArenaPlate;
 
 pub(crate) fn _serialize_arena_to_id<S>(
     _: &Arc<Mutex<Arena>>,
@@ -12,4 +19,75 @@ where
     s.serialize_u64(0)
     // ZJ-TODO
     // s.serialize_u64(arena.lock().unwrap().id)
-}
\ No newline at end of file
+}
+
+/// A feature of an arena being saved. Arenas hold their features as trait objects, so each kind is named here.
+#[derive(Serialize)]
+enum SavedArenaFeature<'a> {
+    Barrier(&'a ArenaBarrier),
+    Plate(&'a ArenaPlate),
+    Hazard(&'a ArenaHazard),
+    CombatantStart(&'a ArenaCombatantStart),
+    BallSpawn(&'a ArenaBallSpawn),
+}
+
+/// A feature of an arena being loaded, in the same shape as [`SavedArenaFeature`].
+#[derive(Deserialize)]
+enum LoadedArenaFeature {
+    Barrier(ArenaBarrier),
+    Plate(ArenaPlate),
+    Hazard(ArenaHazard),
+    CombatantStart(ArenaCombatantStart),
+    BallSpawn(ArenaBallSpawn),
+}
+
+#[derive(Serialize)]
+struct SavedArena<'a> {
+    features: Vec<SavedArenaFeature<'a>>,
+    environment_modifiers: &'a [EnvironmentModifier],
+}
+
+#[derive(Deserialize)]
+struct LoadedArena {
+    features: Vec<LoadedArenaFeature>,
+    environment_modifiers: Vec<EnvironmentModifier>,
+}
+
+impl Serialize for Arena {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let features = self.all_features
+            .iter()
+            .map(|feature| {
+                let feature = feature.as_any();
+                feature.downcast_ref().map(SavedArenaFeature::Barrier)
+                    .or_else(|| feature.downcast_ref().map(SavedArenaFeature::Plate))
+                    .or_else(|| feature.downcast_ref().map(SavedArenaFeature::Hazard))
+                    .or_else(|| feature.downcast_ref().map(SavedArenaFeature::CombatantStart))
+                    .or_else(|| feature.downcast_ref().map(SavedArenaFeature::BallSpawn))
+                    .ok_or_else(|| serde::ser::Error::custom("arena has a kind of feature that can't be saved"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        SavedArena { features, environment_modifiers: &self.environment_modifiers }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Arena {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let loaded = LoadedArena::deserialize(deserializer)?;
+        let all_features = loaded.features
+            .into_iter()
+            .map(|feature| -> Box<dyn ArenaFeature> {
+                match feature {
+                    LoadedArenaFeature::Barrier(barrier) => Box::new(barrier),
+                    LoadedArenaFeature::Plate(plate) => Box::new(plate),
+                    LoadedArenaFeature::Hazard(hazard) => Box::new(hazard),
+                    LoadedArenaFeature::CombatantStart(combatant_start) => Box::new(combatant_start),
+                    LoadedArenaFeature::BallSpawn(ball_spawn) => Box::new(ball_spawn),
+                }
+            })
+            .collect();
+
+        Ok(Arena { environment_modifiers: loaded.environment_modifiers, ..Arena::new(all_features) })
+    }
+}
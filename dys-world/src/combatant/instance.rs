@@ -6,7 +6,10 @@ use crate::combatant::limb::Limb;
 
 pub type CombatantInstanceId = u32;
 
-#[derive(Debug, Deserialize, Serialize)]
+/// Even the frailest, least committed combatants have some stamina.
+const MIN_STAMINA: f32 = 10.0;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct CombatantInstance {
     pub id: CombatantInstanceId,
     pub name: String,
@@ -14,12 +17,12 @@ pub struct CombatantInstance {
     pub effect_modifiers: Vec<EffectInstance>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum EffectDuration {
     NumberOfMatches(u32),
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct EffectInstance {
     pub attribute_modifier: Vec<AttributeInstance>,
     pub duration: EffectDuration,
@@ -87,12 +90,66 @@ impl CombatantInstance {
         // ZJ-TODO: factor in weight
         dexterity / 50.0
     }
+
+    /// The most stamina a combatant can have, spent by exerting themselves (moving, throwing, shoving).
+    #[must_use]
+    pub fn max_stamina(&self) -> f32 {
+        let constitution = self
+            .get_attribute_value(&AttributeType::Constitution)
+            .unwrap_or_default();
+        let commitment = self
+            .get_attribute_value(&AttributeType::Commitment)
+            .unwrap_or_default();
+
+        // ZJ-TODO: tune these values
+        (constitution * 2.5 + commitment * 5.0).max(MIN_STAMINA)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::attribute::attribute_type::AttributeType;
+    use crate::attribute::instance::AttributeInstance;
+    use crate::combatant::instance::CombatantInstance;
+    use crate::combatant::limb::{Limb, LimbModifier, LimbType};
+
+    fn make_combatant(attributes: &[AttributeInstance]) -> CombatantInstance {
+        CombatantInstance {
+            id: 1,
+            name: String::from("TestCombatant"),
+            limbs: vec![
+                Limb {
+                    limb_type: LimbType::Torso,
+                    modifiers: vec![LimbModifier::default_with_attributes(attributes)],
+                    child_limbs: vec![],
+                }
+            ],
+            effect_modifiers: vec![],
+        }
+    }
+
     #[test]
     fn test_effect() {
 
     }
+
+    #[test]
+    fn test_max_stamina_scales_with_constitution_and_commitment() {
+        let average = make_combatant(&[
+            AttributeInstance::new(AttributeType::Constitution, 20.0),
+            AttributeInstance::new(AttributeType::Commitment, 10.0),
+        ]);
+        let committed = make_combatant(&[
+            AttributeInstance::new(AttributeType::Constitution, 20.0),
+            AttributeInstance::new(AttributeType::Commitment, 20.0),
+        ]);
+        let hardy = make_combatant(&[
+            AttributeInstance::new(AttributeType::Constitution, 40.0),
+            AttributeInstance::new(AttributeType::Commitment, 10.0),
+        ]);
+
+        assert!(committed.max_stamina() > average.max_stamina());
+        assert!(hardy.max_stamina() > average.max_stamina());
+        assert!(make_combatant(&[]).max_stamina() > 0.0);
+    }
 }
\ No newline at end of file
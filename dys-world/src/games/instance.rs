@@ -1,5 +1,6 @@
 use std::sync::{Arc, Mutex};
 use serde::Serialize;
+use crate::arena::{Arena, ArenaId};
 use crate::games::serde::serialize_team_instance_to_id;
 use crate::team::instance::TeamInstance;
 use crate::schedule::calendar::Date;
@@ -15,7 +16,9 @@ pub struct GameInstance {
     pub away_team: Arc<Mutex<TeamInstance>>,
     #[serde(serialize_with = "serialize_team_instance_to_id")]
     pub home_team: Arc<Mutex<TeamInstance>>,
-    // #[serde(serialize_with = "serialize_arena_to_id")]
-    pub arena_id: u32,
+    /// The arena the game is played in. It's saved with the world, so games only save its id.
+    #[serde(skip_serializing)]
+    pub arena: Arc<Mutex<Arena>>,
+    pub arena_id: ArenaId,
     pub date: Date,
 }
\ No newline at end of file
@@ -0,0 +1,231 @@
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex};
+
+use rand::{Rng, RngExt};
+use rapier3d::glamx::vec3;
+use rapier3d::na::Quaternion;
+use rapier3d::prelude::{SharedShape, Vec3};
+
+use crate::arena::analysis::ArenaAnalysis;
+use crate::arena::ball_spawn::ArenaBallSpawn;
+use crate::arena::barrier::{ArenaBarrier, BarrierPathing};
+use crate::arena::combatant_start::ArenaCombatantStart;
+use crate::arena::feature::ArenaFeature;
+use crate::arena::navmesh::{ArenaNavmesh, ArenaNavmeshConfig};
+use crate::arena::plate::{ArenaPlate, PlateId};
+use crate::arena::{Arena, PLATE_VERTICAL_HEIGHT};
+use crate::generator::Generator;
+
+// ZJ-TODO: these should be config driven
+const ARENA_SIZE: f32 = 100.0;
+const WALL_THICKNESS: f32 = 3.0;
+const WALL_HEIGHT: f32 = 60.0;
+const FLOOR_THICKNESS: f32 = 10.0;
+
+/// Distance of each team's combatant starts from their end of the arena.
+const COMBATANT_START_INSET: f32 = 15.0;
+const COMBATANTS_PER_TEAM: u8 = 5;
+
+const PLATE_COUNT: RangeInclusive<u8> = 1..=3;
+const PLATE_RADIUS: RangeInclusive<f32> = 4.0..=10.0;
+
+/// Ball spawns are placed in mirrored pairs, one on each team's half.
+const BALL_SPAWN_PAIR_COUNT: RangeInclusive<u8> = 1..=2;
+
+/// Clusters of barriers combatants can take cover behind, on each team's half.
+const COVER_CLUSTER_COUNT: RangeInclusive<u8> = 1..=3;
+const COVER_BARRIERS_PER_CLUSTER: RangeInclusive<u8> = 2..=3;
+const COVER_CLUSTER_SPREAD: f32 = 4.0;
+const COVER_BARRIER_LENGTH: RangeInclusive<f32> = 3.0..=6.0;
+const COVER_BARRIER_HEIGHT: RangeInclusive<f32> = 2.0..=4.0;
+const COVER_BARRIER_THICKNESS: f32 = 1.0;
+
+/// How many layouts to try before giving up and using the testing arena, which is known to be valid.
+const MAX_LAYOUT_ATTEMPTS: u8 = 10;
+
+/// A barrier placed on the home team's half of the arena, and mirrored onto the away team's half.
+struct MirroredBarrier {
+    origin: Vec3,
+    size: Vec3,
+    yaw: f32,
+}
+
+impl Generator {
+    /// Generates an arena that's a mirror image across its center line, so neither team is favored.
+    /// Layouts are checked with [`ArenaAnalysis`], and regenerated until every plate and ball spawn is reachable
+    /// and no features overlap.
+    /// # Panics
+    /// Will panic if the arena being validated is still referenced after validation; this shouldn't happen.
+    #[must_use]
+    pub fn generate_arena(&self, rng: &mut impl Rng) -> Arena {
+        for attempt in 1..=MAX_LAYOUT_ATTEMPTS {
+            let arena = Arc::new(Mutex::new(self.generate_arena_layout(rng)));
+            let navmesh = ArenaNavmesh::new_from(arena.clone(), ArenaNavmeshConfig::default());
+
+            let analysis = ArenaAnalysis::analyze(&arena.lock().unwrap(), &navmesh);
+            if analysis.issues.is_empty() {
+                drop(navmesh);
+                return Arc::into_inner(arena)
+                    .expect("no other references to the generated arena should remain")
+                    .into_inner()
+                    .unwrap();
+            }
+
+            tracing::debug!(attempt, issues = ?analysis.issues, "discarding invalid arena layout");
+        }
+
+        tracing::warn!("failed to generate a valid arena layout; falling back to the testing arena");
+        Arena::new_with_testing_defaults()
+    }
+
+    #[allow(clippy::unused_self, clippy::cast_precision_loss)]
+    fn generate_arena_layout(&self, rng: &mut impl Rng) -> Arena {
+        let center_x = ARENA_SIZE / 2.0;
+        let mirror = |point: Vec3| vec3(ARENA_SIZE - point.x, point.y, point.z);
+
+        let mut features: Vec<Box<dyn ArenaFeature>> = vec![
+            // West Wall
+            Box::new(ArenaBarrier::new(vec3(0.0, 0.0, center_x), vec3(WALL_THICKNESS, WALL_HEIGHT, ARENA_SIZE), Quaternion::identity(), BarrierPathing::Disabled)),
+            // East Wall
+            Box::new(ArenaBarrier::new(vec3(ARENA_SIZE, 0.0, center_x), vec3(WALL_THICKNESS, WALL_HEIGHT, ARENA_SIZE), Quaternion::identity(), BarrierPathing::Disabled)),
+            // South Wall
+            Box::new(ArenaBarrier::new(vec3(center_x, 0.0, 0.0), vec3(ARENA_SIZE, WALL_HEIGHT, WALL_THICKNESS), Quaternion::identity(), BarrierPathing::Disabled)),
+            // North Wall
+            Box::new(ArenaBarrier::new(vec3(center_x, 0.0, ARENA_SIZE), vec3(ARENA_SIZE, WALL_HEIGHT, WALL_THICKNESS), Quaternion::identity(), BarrierPathing::Disabled)),
+            // Floor
+            Box::new(ArenaBarrier::new(vec3(center_x, -FLOOR_THICKNESS / 2.0, center_x), vec3(ARENA_SIZE, FLOOR_THICKNESS, ARENA_SIZE), Quaternion::identity(), BarrierPathing::Enabled)),
+        ];
+
+        // Combatant starts are spread evenly along each team's end of the arena
+        let start_spacing = ARENA_SIZE / f32::from(COMBATANTS_PER_TEAM + 1);
+        for start_index in 1..=COMBATANTS_PER_TEAM {
+            let home_start = vec3(COMBATANT_START_INSET, 0.0, start_spacing * f32::from(start_index));
+            for (origin, is_home_team) in [(home_start, true), (mirror(home_start), false)] {
+                features.push(Box::new(ArenaCombatantStart { origin, rotation: Quaternion::identity(), is_home_team }));
+            }
+        }
+
+        // An odd plate out sits on the center line, and the rest are mirrored pairs
+        let plate_count = rng.random_range(PLATE_COUNT);
+        let mut plates: Vec<(Vec3, f32)> = vec![];
+        if plate_count % 2 == 1 {
+            let radius = rng.random_range(PLATE_RADIUS);
+            let origin = vec3(center_x, 0.0, rng.random_range(30.0..=70.0));
+            plates.push((origin, radius));
+        }
+
+        while plates.len() < usize::from(plate_count) {
+            let radius = rng.random_range(PLATE_RADIUS);
+            let origin = vec3(
+                rng.random_range((COMBATANT_START_INSET + radius + 5.0)..=(center_x - radius - 1.0)),
+                0.0,
+                rng.random_range((WALL_THICKNESS + radius)..=(ARENA_SIZE - WALL_THICKNESS - radius)),
+            );
+
+            // Overlapping plates are flagged during validation, but it's cheaper to retry the plate than the whole layout
+            let overlaps_plate = plates
+                .iter()
+                .any(|(other_origin, other_radius)| (origin - *other_origin).length() < radius + other_radius);
+            if overlaps_plate {
+                continue;
+            }
+
+            plates.push((origin, radius));
+            plates.push((mirror(origin), radius));
+        }
+
+        for (plate_index, (origin, radius)) in plates.into_iter().enumerate() {
+            features.push(Box::new(ArenaPlate::new(
+                PlateId::try_from(plate_index + 1).unwrap(),
+                origin,
+                SharedShape::cylinder(PLATE_VERTICAL_HEIGHT, radius),
+                Quaternion::identity(),
+            )));
+        }
+
+        for _ in 0..rng.random_range(BALL_SPAWN_PAIR_COUNT) {
+            let origin = vec3(
+                rng.random_range((COMBATANT_START_INSET + 10.0)..=(center_x - 5.0)),
+                0.0,
+                rng.random_range(10.0..=(ARENA_SIZE - 10.0)),
+            );
+
+            for origin in [origin, mirror(origin)] {
+                features.push(Box::new(ArenaBallSpawn { origin, rotation: Quaternion::identity() }));
+            }
+        }
+
+        for _ in 0..rng.random_range(COVER_CLUSTER_COUNT) {
+            let cluster_center = vec3(
+                rng.random_range((COMBATANT_START_INSET + 5.0)..=(center_x - 5.0)),
+                0.0,
+                rng.random_range(15.0..=(ARENA_SIZE - 15.0)),
+            );
+
+            for _ in 0..rng.random_range(COVER_BARRIERS_PER_CLUSTER) {
+                let height = rng.random_range(COVER_BARRIER_HEIGHT);
+                let barrier = MirroredBarrier {
+                    origin: cluster_center + vec3(
+                        rng.random_range(-COVER_CLUSTER_SPREAD..=COVER_CLUSTER_SPREAD),
+                        height / 2.0,
+                        rng.random_range(-COVER_CLUSTER_SPREAD..=COVER_CLUSTER_SPREAD),
+                    ),
+                    size: vec3(rng.random_range(COVER_BARRIER_LENGTH), height, COVER_BARRIER_THICKNESS),
+                    yaw: rng.random_range(0.0..std::f32::consts::PI),
+                };
+
+                // Mirroring across the center line reverses the direction of the barrier's rotation
+                for (origin, yaw) in [(barrier.origin, barrier.yaw), (mirror(barrier.origin), -barrier.yaw)] {
+                    let rotation = Quaternion::new((yaw / 2.0).cos(), 0.0, (yaw / 2.0).sin(), 0.0);
+                    features.push(Box::new(ArenaBarrier::new(origin, barrier.size, rotation, BarrierPathing::Disabled)));
+                }
+            }
+        }
+
+        Arena::new(features)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::arena::analysis::ArenaAnalysis;
+    use crate::arena::combatant_start::ArenaCombatantStart;
+    use crate::arena::navmesh::{ArenaNavmesh, ArenaNavmeshConfig};
+    use crate::arena::plate::ArenaPlate;
+    use crate::generator::Generator;
+
+    #[test]
+    fn test_generated_arenas_are_valid() {
+        let generator = Generator::new();
+
+        for seed in 0..5 {
+            let arena = Arc::new(Mutex::new(generator.generate_arena(&mut StdRng::seed_from_u64(seed))));
+            let navmesh = ArenaNavmesh::new_from(arena.clone(), ArenaNavmeshConfig::default());
+            let analysis = ArenaAnalysis::analyze(&arena.lock().unwrap(), &navmesh);
+
+            assert!(analysis.issues.is_empty(), "seed {seed} generated an arena with issues: {:?}", analysis.issues);
+            assert!(analysis.fairness > 0.95);
+            assert_eq!(arena.lock().unwrap().features::<ArenaCombatantStart>().len(), 10);
+        }
+    }
+
+    #[test]
+    fn test_arena_generation_is_seeded() {
+        let generator = Generator::new();
+        let plates = |seed: u64| {
+            generator
+                .generate_arena(&mut StdRng::seed_from_u64(seed))
+                .features::<ArenaPlate>()
+                .iter()
+                .map(|plate| plate.origin)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(plates(7), plates(7));
+    }
+}
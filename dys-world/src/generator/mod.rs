@@ -1,21 +1,27 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::ops::RangeInclusive;
 use std::sync::{Arc, Mutex};
 use rand::prelude::IteratorRandom;
-use rand::Rng;
+use rand::{Rng, RngExt};
 use rand_distr::{Distribution, Normal};
 use crate::combatant::instance::{CombatantInstance, CombatantInstanceId};
 use crate::combatant::limb::{Limb, LimbModifier, LimbType};
 use crate::attribute::instance::AttributeInstance;
 use crate::attribute::attribute_type::AttributeType;
 use crate::team::instance::{TeamInstance, TeamInstanceId};
+use crate::team::formation::Formation;
+use crate::team::tactics::{ActionCostMultipliers, GoalWeights, TeamTactics};
 use crate::world::World;
 use crate::games::instance::GameInstance;
+use crate::arena::{Arena, ArenaId};
+use crate::arena::environment::EnvironmentModifier;
 use crate::proposal::{Proposal, ProposalEffect, ProposalOption};
 use crate::schedule::calendar::{Date, Month};
 use crate::schedule::season::{GamesMapT, ScheduleMapT, Season};
 use crate::schedule::series::{Series, SeriesType};
 
+mod arena;
+
 pub struct Generator {
     given_names: Vec<String>,
     surnames: Vec<String>,
@@ -81,7 +87,11 @@ impl Generator {
                     Limb { 
                         limb_type: LimbType::Head, 
                         modifiers: vec![ LimbModifier::default_with_attributes(&[
-                            AttributeInstance::new(AttributeType::Cognition, generate_value_around_fn(30.0))
+                            AttributeInstance::new(AttributeType::Cognition, generate_value_around_fn(30.0)),
+                            AttributeInstance::new(AttributeType::Ego, generate_value_around_fn(10.0)),
+                            AttributeInstance::new(AttributeType::Stoicism, generate_value_around_fn(10.0)),
+                            AttributeInstance::new(AttributeType::Commitment, generate_value_around_fn(10.0)),
+                            AttributeInstance::new(AttributeType::SelfPreservation, generate_value_around_fn(10.0)),
                         ])],
                         child_limbs: vec![
                             Limb {
@@ -367,6 +377,51 @@ impl Generator {
         }
     }
     
+    /// Generates tactics that vary around the default tactics, so that teams play differently.
+    ///
+    /// # Panics
+    /// Panics if a default tactic is negative or not finite.
+    #[must_use]
+    #[allow(clippy::unused_self)]
+    pub fn generate_team_tactics(&self, rng: &mut impl Rng) -> TeamTactics {
+        let default_goal_weights = GoalWeights::default();
+        let default_action_cost_multipliers = ActionCostMultipliers::default();
+
+        let mut vary_fn = |value: f32| {
+            let normal_distribution = Normal::new(value, value * 0.15).unwrap();
+            normal_distribution.sample(rng).max(0.0)
+        };
+
+        let goal_weights = GoalWeights {
+            score_points: vary_fn(default_goal_weights.score_points),
+            pass_ball: vary_fn(default_goal_weights.pass_ball),
+            throw_at_enemies: vary_fn(default_goal_weights.throw_at_enemies),
+            shove_enemies: vary_fn(default_goal_weights.shove_enemies),
+            catch_ball: vary_fn(default_goal_weights.catch_ball),
+            defend_plates: vary_fn(default_goal_weights.defend_plates),
+            screen_teammates: vary_fn(default_goal_weights.screen_teammates),
+            enemies_on_plate: vary_fn(default_goal_weights.enemies_on_plate),
+        };
+
+        let action_cost_multipliers = ActionCostMultipliers {
+            move_to_location: vary_fn(default_action_cost_multipliers.move_to_location),
+            move_to_ball: vary_fn(default_action_cost_multipliers.move_to_ball),
+            pick_up_ball: vary_fn(default_action_cost_multipliers.pick_up_ball),
+            catch_ball: vary_fn(default_action_cost_multipliers.catch_ball),
+            pass_ball: vary_fn(default_action_cost_multipliers.pass_ball),
+            throw_ball: vary_fn(default_action_cost_multipliers.throw_ball),
+            shove: vary_fn(default_action_cost_multipliers.shove),
+        };
+
+        TeamTactics {
+            goal_weights,
+            action_cost_multipliers,
+            aggression: rng.random_range(0.0..=1.0),
+            plate_focus: rng.random_range(0.0..=1.0),
+            formation: Formation::default(),
+        }
+    }
+
     pub fn generate_combatants(
         &self,
         count: u32,
@@ -391,32 +446,49 @@ impl Generator {
         let combatants = self.generate_combatants(total_combatants_to_generate, rng);
         let sorted_combatants = BTreeMap::from_iter(&combatants);
 
-        let teams = sorted_combatants
+        let teams: HashMap<_, _> = sorted_combatants
             .values()
             .collect::<Vec<_>>()
             .chunks(players_per_team as usize)
             .enumerate()
-            .map(|(id, combatants)| TeamInstance {
-                id: id as u32 + 1,
-                name: self.team_names[id].clone(),
-                combatants: combatants.iter().map(|arc| (**arc).to_owned()).collect::<Vec<_>>(),
+            .map(|(index, combatants)| {
+                let id = u32::try_from(index + 1).expect("team count should fit in a u32");
+                TeamInstance {
+                    id,
+                    name: self.team_names[index].clone(),
+                    combatants: combatants.iter().map(|arc| (**arc).to_owned()).collect::<Vec<_>>(),
+                    tactics: self.generate_team_tactics(rng),
+                    // Each team gets its own home venue
+                    home_arena_id: id,
+                }
             })
             .map(|team| (team.id, Arc::new(Mutex::new(team))))
             .collect();
 
-        let season = self.generate_season(rng, &teams);
-            
+        let arenas = teams
+            .values()
+            .map(|team| team.lock().unwrap().home_arena_id)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|arena_id| (arena_id, Arc::new(Mutex::new(self.generate_arena(rng)))))
+            .collect();
+
+        let season = self.generate_season(rng, &teams, &arenas);
+
         World {
             combatants,
             teams,
-            season
+            season,
+            arenas,
         }
     }
 
+    #[allow(clippy::too_many_lines)]
     pub fn generate_season(
         &self,
         rng: &mut impl Rng,
         teams: &HashMap<TeamInstanceId, Arc<Mutex<TeamInstance>>>,
+        arenas: &HashMap<ArenaId, Arc<Mutex<Arena>>>,
     ) -> Season {
         // ZJ-TODO: I'd love for this to be more interesting
         // For now, just do a simple round-robin of 3 game series
@@ -454,12 +526,14 @@ impl Generator {
             for _ in 0..series_len {
                 {
                     game_id += 1;
+                    let home_team = if swap_fixed_matchup { fixed_opponent.clone() } else { fixed_team.clone() };
+                    let arena_id = home_team.lock().unwrap().home_arena_id;
                     let game = Arc::new(Mutex::new(GameInstance {
                         game_id,
                         away_team: if swap_fixed_matchup { fixed_team.clone() } else { fixed_opponent.clone() },
-                        home_team: if swap_fixed_matchup { fixed_opponent.clone() } else { fixed_team.clone() },
-                        // arena: Arc::new(Mutex::new(Arena::new_with_testing_defaults())), // ZJ-TODO
-                        arena_id: 0,
+                        home_team,
+                        arena: arenas[&arena_id].clone(),
+                        arena_id,
                         date: date.clone(),
                     }));
 
@@ -471,12 +545,14 @@ impl Generator {
                 }
                 {
                     game_id += 1;
+                    let home_team = if swap_alt_matchup { alt_opponent_2.clone() } else { alt_opponent_1.clone() };
+                    let arena_id = home_team.lock().unwrap().home_arena_id;
                     let game = Arc::new(Mutex::new(GameInstance {
                         game_id,
                         away_team: if swap_alt_matchup { alt_opponent_1.clone() } else { alt_opponent_2.clone() },
-                        home_team: if swap_alt_matchup { alt_opponent_2.clone() } else { alt_opponent_1.clone() },
-                        // arena: Arc::new(Mutex::new(Arena::new_with_testing_defaults())), // ZJ-TODO
-                        arena_id: 0,
+                        home_team,
+                        arena: arenas[&arena_id].clone(),
+                        arena_id,
                         date: date.clone(),
                     }));
 
@@ -583,6 +659,43 @@ impl Generator {
             );
         }
 
+        // Teams may also vote to change the conditions at their own home arena
+        let environment_options = [
+            ("Low Gravity", "Everything floats back down a little slower.", EnvironmentModifier::LOW_GRAVITY),
+            ("Slick Floor", "Shoved combatants slide a long way.", EnvironmentModifier::SLICK_FLOOR),
+            ("Bouncy Balls", "Balls bounce off everything they hit.", EnvironmentModifier::BOUNCY_BALLS),
+            ("Fog", "Combatants can't see far in front of them.", EnvironmentModifier::FOG),
+        ];
+
+        let team_proposal_count = proposals.len() as u64;
+        for (proposal_index, (_, team)) in world.teams.iter().enumerate() {
+            let proposal_id = team_proposal_count + (proposal_index + 1) as u64;
+
+            let team_instance = team.lock().unwrap();
+            let team_name = team_instance.name.clone();
+            let arena_id = team_instance.home_arena_id;
+
+            proposals.push(
+                Proposal {
+                    id: proposal_id,
+                    name: format!("Change the Weather at {team_name}'s Arena"),
+                    description: "Pick the conditions every game at this arena is played in from now on.".to_string(),
+                    options: environment_options
+                        .iter()
+                        .enumerate()
+                        .map(|(option_index, (name, description, modifier))| ProposalOption {
+                            id: (option_index + 1) as u64,
+                            name: (*name).to_string(),
+                            description: (*description).to_string(),
+                            effects: vec![
+                                ProposalEffect::ArenaEnvironmentModifier { arena_id, modifier: *modifier }
+                            ],
+                        })
+                        .collect(),
+                }
+            );
+        }
+
         proposals
     }
 }
@@ -597,8 +710,53 @@ mod tests {
         let generator = Generator::new();
         let rng = &mut rng();
         let world = generator.generate_world(rng);
-        let season = generator.generate_season(rng, &world.teams);
+        let season = generator.generate_season(rng, &world.teams, &world.arenas);
 
         assert_ne!(season.games().len(), 0);
     }
+
+    #[test]
+    fn generate_world_gives_teams_home_arenas() {
+        let generator = Generator::new();
+        let world = generator.generate_world(&mut rng());
+
+        for team in world.teams.values() {
+            assert!(world.arenas.contains_key(&team.lock().unwrap().home_arena_id));
+        }
+
+        for game in world.season.games() {
+            let game = game.upgrade().unwrap();
+            let game = game.lock().unwrap();
+            assert_eq!(game.arena_id, game.home_team.lock().unwrap().home_arena_id);
+            assert!(Arc::ptr_eq(&game.arena, &world.arenas[&game.arena_id]));
+        }
+    }
+
+    #[test]
+    fn generate_proposals_lets_teams_change_their_home_arena_environment() {
+        let generator = Generator::new();
+        let rng = &mut rng();
+        let world = generator.generate_world(rng);
+        let proposals = generator.generate_proposals(rng, &world);
+
+        let environment_arena_ids = proposals
+            .iter()
+            .flat_map(|proposal| &proposal.options)
+            .flat_map(|option| &option.effects)
+            .filter_map(|effect| match effect {
+                ProposalEffect::ArenaEnvironmentModifier { arena_id, .. } => Some(*arena_id),
+                ProposalEffect::CombatantTemporaryAttributeBonus { .. } => None,
+            })
+            .collect::<Vec<_>>();
+
+        for team in world.teams.values() {
+            assert!(environment_arena_ids.contains(&team.lock().unwrap().home_arena_id));
+        }
+
+        // Proposal IDs must stay unique
+        let mut proposal_ids = proposals.iter().map(|proposal| proposal.id).collect::<Vec<_>>();
+        proposal_ids.sort_unstable();
+        proposal_ids.dedup();
+        assert_eq!(proposal_ids.len(), proposals.len());
+    }
 }
\ No newline at end of file
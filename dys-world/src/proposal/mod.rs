@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use crate::arena::environment::EnvironmentModifier;
+use crate::arena::ArenaId;
 use crate::attribute::instance::AttributeInstance;
 use crate::combatant::instance::CombatantInstanceId;
 
@@ -24,4 +26,9 @@ pub enum ProposalEffect {
         combatant_instance_id: CombatantInstanceId,
         attribute_instance_bonus: AttributeInstance
     },
+    /// Changes the conditions of every game played in an arena from then on, eg making it foggy
+    ArenaEnvironmentModifier {
+        arena_id: ArenaId,
+        modifier: EnvironmentModifier,
+    },
 }
\ No newline at end of file
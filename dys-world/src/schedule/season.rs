@@ -60,10 +60,12 @@ impl Season {
 
 #[cfg(test)]
 mod tests {
+    use crate::arena::Arena;
     use crate::games::instance::GameInstanceId;
     use crate::schedule::calendar::Month;
     use crate::schedule::series::SeriesType;
     use crate::team::instance::TeamInstance;
+    use crate::team::tactics::TeamTactics;
     use super::*;
 
     #[test]
@@ -75,13 +77,17 @@ mod tests {
                     id: 1,
                     name: String::new(),
                     combatants: vec![],
+                    tactics: TeamTactics::default(),
+                    home_arena_id: 0,
                 })),
                 home_team: Arc::new(Mutex::new(TeamInstance {
                     id: 2,
                     name: String::new(),
                     combatants: vec![],
+                    tactics: TeamTactics::default(),
+                    home_arena_id: 0,
                 })),
-                // arena: Arc::new(Mutex::new(Arena::new_with_testing_defaults())),
+                arena: Arc::new(Mutex::new(Arena::new_with_testing_defaults())),
                 arena_id: 0,
                 date: date.to_owned(),
             }))
@@ -1,7 +1,8 @@
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use serde::ser::SerializeSeq;
+use serde::ser::{SerializeMap, SerializeSeq};
 use serde::Serializer;
+use crate::arena::{Arena, ArenaId};
 use crate::combatant::instance::{CombatantInstance, CombatantInstanceId};
 use crate::team::instance::{TeamInstance, TeamInstanceId};
 
@@ -45,4 +46,21 @@ where
         seq.serialize_element(&*team.lock().unwrap())?;
     }
     seq.end()
-}
\ No newline at end of file
+}
+
+pub(crate) fn serialize_arenas<S>(
+    arenas: &HashMap<ArenaId, Arc<Mutex<Arena>>>,
+    s: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut arena_ids = arenas.keys().collect::<Vec<_>>();
+    arena_ids.sort_unstable();
+
+    let mut map = s.serialize_map(Some(arenas.len()))?;
+    for arena_id in arena_ids {
+        map.serialize_entry(arena_id, &*arenas[arena_id].lock().unwrap())?;
+    }
+    map.end()
+}
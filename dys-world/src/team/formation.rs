@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+use crate::attribute::attribute_type::AttributeType;
+use crate::combatant::instance::CombatantInstance;
+use crate::team::tactics::GoalWeights;
+
+/// The job a combatant has been given by their team.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CombatantRole {
+    /// Stands on plates to score points, and defends the plates their team holds.
+    PlateHolder,
+
+    /// Throws at and shoves enemies.
+    Striker,
+
+    /// Passes to and screens for teammates.
+    #[default]
+    Support,
+
+    /// Picks up loose balls and gets them to teammates.
+    BallCollector,
+}
+
+impl CombatantRole {
+    /// Roles ordered from most to least important to fill.
+    /// When a team has fewer available combatants than roles, the least important roles go unfilled first.
+    pub const BY_IMPORTANCE: [CombatantRole; 4] = [
+        CombatantRole::PlateHolder,
+        CombatantRole::Striker,
+        CombatantRole::BallCollector,
+        CombatantRole::Support,
+    ];
+
+    /// How important this role is to fill, where lower values are more important.
+    #[must_use]
+    pub fn importance(&self) -> usize {
+        CombatantRole::BY_IMPORTANCE
+            .iter()
+            .position(|role| role == self)
+            .unwrap_or(CombatantRole::BY_IMPORTANCE.len())
+    }
+
+    /// How well suited the combatant is to this role, based on their attributes.
+    #[must_use]
+    pub fn suitability(&self, combatant: &CombatantInstance) -> f32 {
+        let attr = |attribute_type: AttributeType| combatant.get_attribute_value(&attribute_type).unwrap_or_default();
+
+        match self {
+            CombatantRole::PlateHolder => attr(AttributeType::Constitution) + attr(AttributeType::Stability),
+            CombatantRole::Striker => attr(AttributeType::Coordination) + attr(AttributeType::Strength),
+            CombatantRole::Support => attr(AttributeType::Communication) + attr(AttributeType::Coordination),
+            CombatantRole::BallCollector => attr(AttributeType::Dexterity) + attr(AttributeType::Coordination),
+        }
+    }
+
+    /// Multipliers applied on top of the team's goal weights for combatants in this role.
+    #[must_use]
+    pub fn goal_weight_multipliers(&self) -> GoalWeights {
+        let unchanged = GoalWeights::uniform(1.0);
+
+        match self {
+            CombatantRole::PlateHolder => GoalWeights {
+                score_points: 2.0,
+                throw_at_enemies: 0.75,
+                shove_enemies: 0.75,
+                defend_plates: 1.5,
+                ..unchanged
+            },
+            CombatantRole::Striker => GoalWeights {
+                score_points: 0.5,
+                throw_at_enemies: 1.5,
+                shove_enemies: 1.5,
+                ..unchanged
+            },
+            CombatantRole::Support => GoalWeights {
+                pass_ball: 1.5,
+                catch_ball: 1.25,
+                screen_teammates: 2.0,
+                ..unchanged
+            },
+            CombatantRole::BallCollector => GoalWeights {
+                score_points: 0.5,
+                pass_ball: 1.25,
+                catch_ball: 1.5,
+                ..unchanged
+            },
+        }
+    }
+}
+
+/// A role and the position the combatant filling it lines up at.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormationSlot {
+    pub role: CombatantRole,
+
+    /// Distance from the combatant start towards the enemy side of the arena.
+    pub forward: f32,
+
+    /// Distance from the combatant start to the right, when facing the enemy side of the arena.
+    pub lateral: f32,
+}
+
+/// How a team lines up, relative to the arena's combatant starts.
+/// The first slot belongs to the team's first combatant start, the second slot to the second start, and so on.
+/// Combatants without a slot support their teammates from their start.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Formation {
+    pub slots: Vec<FormationSlot>,
+}
+
+impl Default for Formation {
+    fn default() -> Self {
+        Formation {
+            slots: vec![
+                FormationSlot { role: CombatantRole::Support, forward: 5.0, lateral: 0.0 },
+                FormationSlot { role: CombatantRole::Striker, forward: 15.0, lateral: 0.0 },
+                FormationSlot { role: CombatantRole::PlateHolder, forward: 20.0, lateral: 0.0 },
+                FormationSlot { role: CombatantRole::Striker, forward: 15.0, lateral: 0.0 },
+                FormationSlot { role: CombatantRole::BallCollector, forward: 10.0, lateral: 0.0 },
+            ],
+        }
+    }
+}
+
+impl Formation {
+    /// The slot lined up at the team's nth combatant start.
+    #[must_use]
+    pub fn slot(&self, start_index: usize) -> FormationSlot {
+        self.slots.get(start_index).cloned().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::attribute::attribute_type::AttributeType;
+    use crate::attribute::instance::AttributeInstance;
+    use crate::combatant::instance::CombatantInstance;
+    use crate::combatant::limb::{Limb, LimbModifier, LimbType};
+    use crate::team::formation::{CombatantRole, Formation};
+
+    #[test]
+    fn test_suitability_follows_attributes() {
+        let combatant = CombatantInstance {
+            id: 1,
+            name: String::from("TestCombatant"),
+            limbs: vec![
+                Limb {
+                    limb_type: LimbType::Torso,
+                    modifiers: vec![LimbModifier::default_with_attributes(&[
+                        AttributeInstance::new(AttributeType::Strength, 20.0),
+                        AttributeInstance::new(AttributeType::Coordination, 5.0),
+                    ])],
+                    child_limbs: vec![],
+                }
+            ],
+            effect_modifiers: vec![],
+        };
+
+        assert!(CombatantRole::Striker.suitability(&combatant) > CombatantRole::PlateHolder.suitability(&combatant));
+    }
+
+    #[test]
+    fn test_missing_slots_default_to_support() {
+        let formation = Formation::default();
+        let slot = formation.slot(formation.slots.len());
+
+        assert_eq!(slot.role, CombatantRole::Support);
+        assert!(slot.forward.abs() < f32::EPSILON);
+    }
+}
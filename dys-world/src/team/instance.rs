@@ -1,8 +1,10 @@
 use std::sync::{Arc, Mutex};
 use serde::Serialize;
 use crate::{
+    arena::ArenaId,
     combatant::instance::CombatantInstance,
     serde::serialize_combatants_to_ids,
+    team::tactics::TeamTactics,
 };
 
 pub type TeamInstanceId = u32;
@@ -13,5 +15,10 @@ pub struct TeamInstance {
     pub name: String,
 
     #[serde(serialize_with = "serialize_combatants_to_ids")]
-    pub combatants: Vec<Arc<Mutex<CombatantInstance>>>
+    pub combatants: Vec<Arc<Mutex<CombatantInstance>>>,
+
+    pub tactics: TeamTactics,
+
+    /// The arena this team plays its home games in.
+    pub home_arena_id: ArenaId,
 }
\ No newline at end of file
@@ -1,3 +1,5 @@
+pub mod formation;
 pub mod instance;
 pub mod record;
+pub mod tactics;
 pub(crate) mod serde;
\ No newline at end of file
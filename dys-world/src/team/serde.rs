@@ -3,9 +3,11 @@ use std::fmt::Formatter;
 use std::sync::{Arc, Mutex};
 use serde::de::{Error, IgnoredAny, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer};
+use crate::arena::{Arena, ArenaId};
 use crate::combatant::instance::{CombatantInstance, CombatantInstanceId};
 use crate::schedule::season::{GamesMapT, ScheduleMapT, Season};
 use crate::team::instance::TeamInstance;
+use crate::team::tactics::TeamTactics;
 use crate::world::World;
 
 impl<'de> Deserialize<'de> for World {
@@ -13,11 +15,12 @@ impl<'de> Deserialize<'de> for World {
     where
         D: Deserializer<'de>
     {
-        const FIELDS: &[&str] = &["combatants", "teams", "season"];
+        const FIELDS: &[&str] = &["combatants", "teams", "season", "arenas"];
         enum Field {
             Combatants,
             Teams,
             Season,
+            Arenas,
         }
 
         impl<'de> Deserialize<'de> for Field {
@@ -41,6 +44,7 @@ impl<'de> Deserialize<'de> for World {
                             "combatants" => Ok(Field::Combatants),
                             "teams" => Ok(Field::Teams),
                             "season" => Ok(Field::Season),
+                            "arenas" => Ok(Field::Arenas),
                             _ => Err(Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -73,7 +77,8 @@ impl<'de> Deserialize<'de> for World {
                     combatants: HashMap::new(),
                     teams: HashMap::new(),
                     // ZJ-TODO: below
-                    season: Season::new(GamesMapT::new(), ScheduleMapT::new(), vec![])
+                    season: Season::new(GamesMapT::new(), ScheduleMapT::new(), vec![]),
+                    arenas: HashMap::new(),
                 };
 
                 let mut combatants: Vec<CombatantInstance> = vec![];
@@ -87,6 +92,13 @@ impl<'de> Deserialize<'de> for World {
                             // ZJ-TODO
                             map.next_value::<IgnoredAny>()?;
                         },
+                        Field::Arenas => {
+                            let arenas: HashMap<ArenaId, Arena> = map.next_value()?;
+                            world_instance.arenas = arenas
+                                .into_iter()
+                                .map(|(arena_id, arena)| (arena_id, Arc::new(Mutex::new(arena))))
+                                .collect();
+                        },
                     }
                 }
 
@@ -137,11 +149,13 @@ impl<'de> Deserialize<'de> for PartialTeamInstance {
     where
         D: Deserializer<'de>
     {
-        const FIELDS: &[&str] = &["id", "name", "combatants"];
+        const FIELDS: &[&str] = &["id", "name", "combatants", "tactics", "home_arena_id"];
         enum Field {
             Id,
             Name,
             Combatants,
+            Tactics,
+            HomeArenaId,
         }
 
         impl<'de> Deserialize<'de> for Field {
@@ -166,6 +180,8 @@ impl<'de> Deserialize<'de> for PartialTeamInstance {
                             "id" => Ok(Field::Id),
                             "name" => Ok(Field::Name),
                             "combatants" => Ok(Field::Combatants),
+                            "tactics" => Ok(Field::Tactics),
+                            "home_arena_id" => Ok(Field::HomeArenaId),
                             _ => Err(Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -198,6 +214,8 @@ impl<'de> Deserialize<'de> for PartialTeamInstance {
                         id: 0,
                         name: String::new(),
                         combatants: vec![],
+                        tactics: TeamTactics::default(),
+                        home_arena_id: 0,
                     },
                     team_member_ids: vec![],
                 };
@@ -207,6 +225,8 @@ impl<'de> Deserialize<'de> for PartialTeamInstance {
                         Field::Id => partial_team_instance.team_instance.id = map.next_value()?,
                         Field::Name => partial_team_instance.team_instance.name = map.next_value()?,
                         Field::Combatants => partial_team_instance.team_member_ids = map.next_value()?,
+                        Field::Tactics => partial_team_instance.team_instance.tactics = map.next_value()?,
+                        Field::HomeArenaId => partial_team_instance.team_instance.home_arena_id = map.next_value()?,
                     }
                 }
 
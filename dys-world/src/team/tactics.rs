@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use crate::team::formation::Formation;
+
+/// A team's approach to games.
+/// Managers may change their team's tactics between games.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TeamTactics {
+    /// Multipliers applied to the priority of each goal combatants consider.
+    pub goal_weights: GoalWeights,
+
+    /// Multipliers applied to the cost of actions combatants may take.
+    pub action_cost_multipliers: ActionCostMultipliers,
+
+    /// How eager the team is to engage enemies, from 0.0 (passive) to 1.0 (reckless).
+    /// 0.5 is neutral.
+    pub aggression: f32,
+
+    /// How much the team prioritizes standing on and contesting plates, from 0.0 (ignore plates) to 1.0 (only plates).
+    /// 0.5 is neutral.
+    pub plate_focus: f32,
+
+    /// The roles the team's combatants play, and where they line up.
+    pub formation: Formation,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GoalWeights {
+    pub score_points: f32,
+    pub pass_ball: f32,
+    pub throw_at_enemies: f32,
+    pub shove_enemies: f32,
+    pub catch_ball: f32,
+
+    /// Guarding plates that teammates are standing on, and intercepting enemies running towards plates.
+    pub defend_plates: f32,
+
+    /// Standing between enemies and teammates carrying a ball.
+    pub screen_teammates: f32,
+
+    /// Additional multiplier for goals that target enemies standing on plates.
+    pub enemies_on_plate: f32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActionCostMultipliers {
+    /// Moving to plates and other combatants.
+    pub move_to_location: f32,
+    pub move_to_ball: f32,
+    pub pick_up_ball: f32,
+    pub catch_ball: f32,
+    pub pass_ball: f32,
+    pub throw_ball: f32,
+    pub shove: f32,
+}
+
+impl Default for TeamTactics {
+    fn default() -> Self {
+        TeamTactics {
+            goal_weights: GoalWeights::default(),
+            action_cost_multipliers: ActionCostMultipliers::default(),
+            aggression: 0.5,
+            plate_focus: 0.5,
+            formation: Formation::default(),
+        }
+    }
+}
+
+impl Default for GoalWeights {
+    fn default() -> Self {
+        GoalWeights {
+            score_points: 1.0,
+            pass_ball: 1.5,
+            throw_at_enemies: 1.0,
+            shove_enemies: 1.0,
+            catch_ball: 1.0,
+            defend_plates: 1.0,
+            screen_teammates: 1.0,
+            enemies_on_plate: 4.5,
+        }
+    }
+}
+
+impl Default for ActionCostMultipliers {
+    fn default() -> Self {
+        ActionCostMultipliers {
+            move_to_location: 0.8,
+            move_to_ball: 0.4,
+            pick_up_ball: 1.0,
+            catch_ball: 1.0,
+            pass_ball: 1.0,
+            throw_ball: 1.0,
+            shove: 1.0,
+        }
+    }
+}
+
+impl GoalWeights {
+    /// Goal weights with every weight set to the same value.
+    #[must_use]
+    pub fn uniform(weight: f32) -> GoalWeights {
+        GoalWeights {
+            score_points: weight,
+            pass_ball: weight,
+            throw_at_enemies: weight,
+            shove_enemies: weight,
+            catch_ball: weight,
+            defend_plates: weight,
+            screen_teammates: weight,
+            enemies_on_plate: weight,
+        }
+    }
+
+    /// Multiplies each weight by the corresponding weight in `multipliers`.
+    #[must_use]
+    pub fn scaled_by(&self, multipliers: &GoalWeights) -> GoalWeights {
+        GoalWeights {
+            score_points: self.score_points * multipliers.score_points,
+            pass_ball: self.pass_ball * multipliers.pass_ball,
+            throw_at_enemies: self.throw_at_enemies * multipliers.throw_at_enemies,
+            shove_enemies: self.shove_enemies * multipliers.shove_enemies,
+            catch_ball: self.catch_ball * multipliers.catch_ball,
+            defend_plates: self.defend_plates * multipliers.defend_plates,
+            screen_teammates: self.screen_teammates * multipliers.screen_teammates,
+            enemies_on_plate: self.enemies_on_plate * multipliers.enemies_on_plate,
+        }
+    }
+}
+
+impl TeamTactics {
+    /// Multiplier for goals that engage enemies, ranging from 0.5 (passive) to 1.5 (reckless).
+    #[must_use]
+    pub fn aggression_multiplier(&self) -> f32 {
+        0.5 + self.aggression.clamp(0.0, 1.0)
+    }
+
+    /// Multiplier for goals involving plates, ranging from 0.5 (ignore plates) to 1.5 (only plates).
+    #[must_use]
+    pub fn plate_focus_multiplier(&self) -> f32 {
+        0.5 + self.plate_focus.clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::team::tactics::TeamTactics;
+
+    #[test]
+    fn test_default_tactics_are_neutral() {
+        let tactics = TeamTactics::default();
+        assert!((tactics.aggression_multiplier() - 1.0).abs() < f32::EPSILON);
+        assert!((tactics.plate_focus_multiplier() - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_multipliers_are_clamped() {
+        let tactics = TeamTactics {
+            aggression: 5.0,
+            plate_focus: -5.0,
+            ..TeamTactics::default()
+        };
+
+        assert!((tactics.aggression_multiplier() - 1.5).abs() < f32::EPSILON);
+        assert!((tactics.plate_focus_multiplier() - 0.5).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let tactics: TeamTactics = serde_json::from_str(r#"{ "aggression": 0.9, "goal_weights": { "pass_ball": 3.0 } }"#).unwrap();
+
+        assert!((tactics.aggression - 0.9).abs() < f32::EPSILON);
+        assert!((tactics.plate_focus - 0.5).abs() < f32::EPSILON);
+        assert!((tactics.goal_weights.pass_ball - 3.0).abs() < f32::EPSILON);
+        assert!((tactics.goal_weights.enemies_on_plate - 4.5).abs() < f32::EPSILON);
+    }
+}
@@ -3,10 +3,12 @@ use std::sync::{Arc, Mutex};
 use serde::Serialize;
 use crate::{
     combatant::instance::CombatantInstance,
-    serde::{serialize_combatants, serialize_teams},
+    serde::{serialize_arenas, serialize_combatants, serialize_teams},
     team::instance::TeamInstance,
 };
+use crate::arena::{Arena, ArenaId};
 use crate::combatant::instance::CombatantInstanceId;
+use crate::proposal::{ProposalEffect, ProposalOption};
 use crate::schedule::season::Season;
 use crate::team::instance::TeamInstanceId;
 
@@ -20,14 +22,53 @@ pub struct World {
 
     #[serde(skip_serializing)]
     pub season: Season,
+
+    /// Arenas games are played in, including each team's home venue.
+    #[serde(serialize_with = "serialize_arenas")]
+    pub arenas: HashMap<ArenaId, Arc<Mutex<Arena>>>,
+}
+
+impl World {
+    /// Applies the effects of a proposal option that's passed.
+    /// Returns false if an effect targets an arena that isn't in the world, in which case that effect is skipped.
+    ///
+    /// # Panics
+    /// If another thread panicked while holding a targeted arena's lock.
+    #[must_use]
+    pub fn enact_proposal_option(&self, option: &ProposalOption) -> bool {
+        let mut all_enacted = true;
+        for effect in &option.effects {
+            match effect {
+                ProposalEffect::CombatantTemporaryAttributeBonus { .. } => {
+                    // ZJ-TODO: proposals don't say how many matches a bonus lasts for yet
+                },
+                ProposalEffect::ArenaEnvironmentModifier { arena_id, modifier } => {
+                    let Some(arena) = self.arenas.get(arena_id) else {
+                        all_enacted = false;
+                        continue;
+                    };
+
+                    // Games share their arena, so every game played there from now on is subject to the modifier
+                    arena.lock().unwrap().add_environment_modifier(*modifier);
+                },
+            }
+        }
+
+        all_enacted
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::arena::environment::EnvironmentModifier;
     use crate::attribute::attribute_type::AttributeType;
     use crate::attribute::instance::AttributeInstance;
     use crate::combatant::limb::{Limb, LimbModifier, LimbType};
+    use crate::generator::Generator;
     use crate::schedule::season::{GamesMapT, ScheduleMapT};
+    use crate::team::tactics::TeamTactics;
     use super::*;
 
     #[test]
@@ -85,6 +126,8 @@ mod tests {
                     combatants: vec![
                         combatants.get(&1).unwrap().clone(),
                     ],
+                    tactics: TeamTactics::default(),
+                    home_arena_id: 1,
                 }
             ))),
             (2, Arc::new(Mutex::new(
@@ -94,6 +137,11 @@ mod tests {
                     combatants: vec![
                         combatants.get(&2).unwrap().clone(),
                     ],
+                    tactics: TeamTactics {
+                        aggression: 0.8,
+                        ..TeamTactics::default()
+                    },
+                    home_arena_id: 2,
                 }
             ))),
         ]);
@@ -108,6 +156,9 @@ mod tests {
             combatants,
             teams,
             season,
+            arenas: HashMap::from([
+                (1, Arc::new(Mutex::new(Arena::new_with_testing_defaults().with_environment_modifier(EnvironmentModifier::LOW_GRAVITY)))),
+            ]),
         };
 
         let serialized = serde_json::to_string(&world).unwrap();
@@ -115,5 +166,62 @@ mod tests {
 
         assert_eq!(world.combatants.len(), deserialized.combatants.len());
         assert_eq!(world.teams.len(), deserialized.teams.len());
+        assert_eq!(
+            world.teams[&2].lock().unwrap().tactics,
+            deserialized.teams[&2].lock().unwrap().tactics,
+        );
+        assert_eq!(deserialized.teams[&2].lock().unwrap().home_arena_id, 2);
+
+        let arena = world.arenas[&1].lock().unwrap();
+        let deserialized_arena = deserialized.arenas[&1].lock().unwrap();
+        assert_eq!(deserialized_arena.environment(), arena.environment());
+        assert_eq!(
+            serde_json::to_string(&*deserialized_arena).unwrap(),
+            serde_json::to_string(&*arena).unwrap(),
+        );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn enacted_environment_proposals_change_their_arena() {
+        let generator = Generator::new();
+        let world = generator.generate_world(&mut StdRng::seed_from_u64(0));
+        let arena_id = world.teams[&1].lock().unwrap().home_arena_id;
+
+        let proposals = generator.generate_proposals(&mut StdRng::seed_from_u64(0), &world);
+        let low_gravity = proposals
+            .iter()
+            .flat_map(|proposal| &proposal.options)
+            .find(|option| option.effects.iter().any(|effect| matches!(
+                effect,
+                ProposalEffect::ArenaEnvironmentModifier { arena_id: effect_arena_id, modifier: EnvironmentModifier::LOW_GRAVITY }
+                    if *effect_arena_id == arena_id
+            )))
+            .unwrap();
+
+        let expected_environment = world.arenas[&arena_id].lock().unwrap().environment().with_modifier(EnvironmentModifier::LOW_GRAVITY);
+        assert!(world.enact_proposal_option(low_gravity));
+        assert_eq!(world.arenas[&arena_id].lock().unwrap().environment(), expected_environment);
+
+        // Games already scheduled in the arena are played in the new conditions
+        let game = world.season.games()
+            .into_iter()
+            .filter_map(|game| game.upgrade())
+            .find(|game| game.lock().unwrap().arena_id == arena_id)
+            .unwrap();
+        let game_environment = game.lock().unwrap().arena.lock().unwrap().environment();
+        assert_eq!(game_environment, expected_environment);
+    }
+
+    #[test]
+    fn enacting_proposals_for_missing_arenas_fails() {
+        let world = Generator::new().generate_world(&mut StdRng::seed_from_u64(0));
+        let option = ProposalOption {
+            id: 1,
+            name: String::new(),
+            description: String::new(),
+            effects: vec![ProposalEffect::ArenaEnvironmentModifier { arena_id: 99, modifier: EnvironmentModifier::FOG }],
+        };
+
+        assert!(!world.enact_proposal_option(&option));
+    }
+}